@@ -0,0 +1,360 @@
+//! Agent action events shared by ingestion, the action log, and the overlay.
+//!
+//! # Wire schema (version 1)
+//!
+//! Events travel as newline-delimited JSON objects:
+//!
+//! ```json
+//! {"version":1,"id":42,"timestamp_ms":1700000000000,"agent_id":"openclaw",
+//!  "kind":"write","target_path":"src/main.rs","payload":{"bytes":120}}
+//! ```
+//!
+//! - `version` (optional, default `1`): schema version; newer versions are rejected.
+//! - `id` (optional): sender-assigned identifier; a local one is assigned when missing or `0`.
+//! - `timestamp_ms` (optional): Unix time in milliseconds; defaults to the receive time.
+//! - `agent_id` (optional, default `"openclaw"`).
+//! - `kind` (required): one of [`ActionKind`] in snake_case; unknown kinds map to `other`.
+//! - `target_path` (required): absolute, or relative to the scanned root.
+//! - `payload` (optional): arbitrary JSON kept alongside the event.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const SCHEMA_VERSION: u32 = 1;
+pub const DEFAULT_AGENT_ID: &str = "openclaw";
+
+static NEXT_EVENT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    Inspect,
+    Read,
+    Write,
+    Create,
+    Delete,
+    Rename,
+    Execute,
+    Classify,
+    Correlate,
+    Trace,
+    Verify,
+    Highlight,
+    #[serde(other)]
+    Other,
+}
+
+impl ActionKind {
+    pub const ALL: [ActionKind; 13] = [
+        Self::Inspect,
+        Self::Read,
+        Self::Write,
+        Self::Create,
+        Self::Delete,
+        Self::Rename,
+        Self::Execute,
+        Self::Classify,
+        Self::Correlate,
+        Self::Trace,
+        Self::Verify,
+        Self::Highlight,
+        Self::Other,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Inspect => "inspect",
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Create => "create",
+            Self::Delete => "delete",
+            Self::Rename => "rename",
+            Self::Execute => "execute",
+            Self::Classify => "classify",
+            Self::Correlate => "correlate",
+            Self::Trace => "trace",
+            Self::Verify => "verify",
+            Self::Highlight => "highlight",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Self {
+        let name = name.trim();
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(name))
+            .unwrap_or(Self::Other)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionEvent {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub id: u64,
+    #[serde(
+        rename = "timestamp_ms",
+        default = "SystemTime::now",
+        serialize_with = "serialize_timestamp",
+        deserialize_with = "deserialize_timestamp"
+    )]
+    pub timestamp: SystemTime,
+    #[serde(default = "default_agent_id")]
+    pub agent_id: String,
+    pub kind: ActionKind,
+    pub target_path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+impl ActionEvent {
+    pub fn new(kind: ActionKind, target_path: PathBuf) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            id: next_event_id(),
+            timestamp: SystemTime::now(),
+            agent_id: DEFAULT_AGENT_ID.to_string(),
+            kind,
+            target_path,
+            payload: None,
+        }
+    }
+
+    /// Parses one wire line: a JSON event, or the legacy `<kind>\t<path>` form.
+    pub fn parse_line(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Err("Empty action line".to_string());
+        }
+
+        if line.starts_with('{') {
+            let mut event: ActionEvent = serde_json::from_str(line)
+                .map_err(|error| format!("Invalid action event: {error}"))?;
+            if event.version > SCHEMA_VERSION {
+                return Err(format!(
+                    "Unsupported action schema version {} (expected <= {SCHEMA_VERSION})",
+                    event.version
+                ));
+            }
+            if event.target_path.as_os_str().is_empty() {
+                return Err("Action event has an empty target_path".to_string());
+            }
+            if event.id == 0 {
+                event.id = next_event_id();
+            }
+            return Ok(event);
+        }
+
+        let (kind, path) = match line.split_once('\t') {
+            Some((kind, path)) => (ActionKind::from_name(kind), path.trim()),
+            None => (ActionKind::Other, line),
+        };
+        if path.is_empty() {
+            return Err("Action line has an empty path".to_string());
+        }
+
+        Ok(Self::new(kind, PathBuf::from(path)))
+    }
+}
+
+/// Action counts per fixed-width time bucket, oldest bucket first.
+#[derive(Debug, Clone, Default)]
+pub struct RateHistogram {
+    pub bucket: Duration,
+    pub total: Vec<u32>,
+    pub per_kind: HashMap<ActionKind, Vec<u32>>,
+}
+
+impl RateHistogram {
+    /// Buckets `events` into `bucket_count` buckets ending at `now`. Events
+    /// are placed by their own timestamps, which need not be in order.
+    pub fn from_events<'a>(
+        events: impl IntoIterator<Item = &'a ActionEvent>,
+        now: SystemTime,
+        bucket: Duration,
+        bucket_count: usize,
+    ) -> Self {
+        let mut histogram = Self {
+            bucket,
+            total: vec![0; bucket_count],
+            per_kind: HashMap::new(),
+        };
+        if bucket.is_zero() || bucket_count == 0 {
+            return histogram;
+        }
+
+        for event in events {
+            // Events from the future (clock skew) count towards the newest bucket.
+            let age = now
+                .duration_since(event.timestamp)
+                .unwrap_or(Duration::ZERO);
+            let buckets_ago = (age.as_secs_f64() / bucket.as_secs_f64()) as usize;
+            if buckets_ago >= bucket_count {
+                continue;
+            }
+
+            let index = bucket_count - 1 - buckets_ago;
+            histogram.total[index] = histogram.total[index].saturating_add(1);
+            let kind_buckets = histogram
+                .per_kind
+                .entry(event.kind)
+                .or_insert_with(|| vec![0; bucket_count]);
+            kind_buckets[index] = kind_buckets[index].saturating_add(1);
+        }
+
+        histogram
+    }
+
+    /// Average actions per minute over the newest buckets covering one minute.
+    pub fn per_minute(buckets: &[u32], bucket: Duration) -> f32 {
+        if bucket.is_zero() || buckets.is_empty() {
+            return 0.0;
+        }
+
+        let window = ((60.0 / bucket.as_secs_f32()).ceil() as usize).clamp(1, buckets.len());
+        let count: u32 = buckets[buckets.len() - window..].iter().sum();
+        count as f32 * 60.0 / (window as f32 * bucket.as_secs_f32())
+    }
+}
+
+fn next_event_id() -> u64 {
+    NEXT_EVENT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn default_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+fn default_agent_id() -> String {
+    DEFAULT_AGENT_ID.to_string()
+}
+
+fn serialize_timestamp<S: Serializer>(
+    value: &SystemTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let millis = value
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64;
+    serializer.serialize_u64(millis)
+}
+
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SystemTime, D::Error> {
+    let millis = u64::deserialize(deserializer)?;
+    UNIX_EPOCH
+        .checked_add(Duration::from_millis(millis))
+        .ok_or_else(|| serde::de::Error::custom(format!("timestamp_ms {millis} is out of range")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_json_event_with_defaults() {
+        let event =
+            ActionEvent::parse_line(r#"{"kind":"write","target_path":"logs/app.log"}"#).unwrap();
+
+        assert_eq!(event.kind, ActionKind::Write);
+        assert_eq!(event.target_path, PathBuf::from("logs/app.log"));
+        assert_eq!(event.agent_id, DEFAULT_AGENT_ID);
+        assert_eq!(event.version, SCHEMA_VERSION);
+        assert_ne!(event.id, 0);
+    }
+
+    #[test]
+    fn unknown_kind_and_legacy_lines_are_accepted() {
+        let event =
+            ActionEvent::parse_line(r#"{"kind":"teleport","target_path":"/tmp/a"}"#).unwrap();
+        assert_eq!(event.kind, ActionKind::Other);
+
+        let legacy = ActionEvent::parse_line("Delete\t/tmp/b").unwrap();
+        assert_eq!(legacy.kind, ActionKind::Delete);
+        assert_eq!(legacy.target_path, PathBuf::from("/tmp/b"));
+    }
+
+    #[test]
+    fn rejects_newer_schema_versions() {
+        let line = r#"{"version":99,"kind":"read","target_path":"a"}"#;
+        assert!(ActionEvent::parse_line(line).is_err());
+    }
+
+    #[test]
+    fn rate_histogram_buckets_recent_events_by_age() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut events = Vec::new();
+        for (seconds_ago, kind) in [
+            (125, ActionKind::Read),
+            (15, ActionKind::Write),
+            (3, ActionKind::Write),
+        ] {
+            let mut event = ActionEvent::new(kind, PathBuf::from("a"));
+            event.timestamp = now - Duration::from_secs(seconds_ago);
+            events.push(event);
+        }
+
+        let histogram = RateHistogram::from_events(events.iter(), now, Duration::from_secs(10), 6);
+
+        assert_eq!(histogram.total, vec![0, 0, 0, 0, 1, 1]);
+        assert_eq!(
+            histogram.per_kind[&ActionKind::Write],
+            vec![0, 0, 0, 0, 1, 1]
+        );
+        assert!(!histogram.per_kind.contains_key(&ActionKind::Read));
+        assert_eq!(
+            RateHistogram::per_minute(&histogram.total, histogram.bucket),
+            2.0
+        );
+    }
+
+    #[test]
+    fn rate_histogram_skips_stale_events_anywhere_in_the_log() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut events = Vec::new();
+        // A sender with a slow clock delivers the newest arrival.
+        for seconds_ago in [5, 12, 600] {
+            let mut event = ActionEvent::new(ActionKind::Write, PathBuf::from("a"));
+            event.timestamp = now - Duration::from_secs(seconds_ago);
+            events.push(event);
+        }
+
+        let histogram = RateHistogram::from_events(events.iter(), now, Duration::from_secs(10), 6);
+
+        assert_eq!(histogram.total, vec![0, 0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn timestamp_round_trips_as_millis() {
+        let mut event = ActionEvent::new(ActionKind::Trace, PathBuf::from("x"));
+        event.timestamp = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains(r#""timestamp_ms":1700000000123"#));
+
+        let parsed = ActionEvent::parse_line(&json).unwrap();
+        assert_eq!(parsed.timestamp, event.timestamp);
+        assert_eq!(parsed.id, event.id);
+
+        // Out of range for `SystemTime` on some platforms: an error there,
+        // never a panic.
+        let line = format!(
+            r#"{{"kind":"read","target_path":"a","timestamp_ms":{}}}"#,
+            u64::MAX
+        );
+        match ActionEvent::parse_line(&line) {
+            Ok(event) => assert_eq!(
+                event.timestamp,
+                UNIX_EPOCH + Duration::from_millis(u64::MAX)
+            ),
+            Err(message) => assert!(message.contains("out of range"), "{message}"),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::action::ActionEvent;
+use crate::exclude::expand_pattern;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// Default rules offered in the editor; one pattern per line.
+pub const DEFAULT_SENSITIVE_RULES: &str = "~/.ssh\n~/.aws\n~/.gnupg\n**/.env\n**/secrets/**\n";
+
+#[derive(Debug, Clone)]
+pub struct SensitiveAlert {
+    pub event: ActionEvent,
+    pub rule: String,
+}
+
+/// Compiled sensitive-path rules.
+///
+/// A rule without glob characters matches the path itself and everything
+/// beneath it; `~` expands to the home directory, and relative patterns are
+/// matched anywhere in the tree (`secrets/**` behaves like `**/secrets/**`).
+#[derive(Debug, Clone, Default)]
+pub struct SensitiveRules {
+    patterns: Vec<String>,
+    set: Option<GlobSet>,
+    /// Maps each compiled glob back to its index in `patterns`.
+    glob_owners: Vec<usize>,
+}
+
+impl SensitiveRules {
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut rules = Self::default();
+        let mut errors = Vec::new();
+        let mut builder = GlobSetBuilder::new();
+
+        for line in text.lines() {
+            let pattern = line.trim();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+
+            let mut compiled = Vec::new();
+            for glob_text in expand_pattern(pattern) {
+                match GlobBuilder::new(&glob_text)
+                    .case_insensitive(cfg!(windows))
+                    .literal_separator(true)
+                    .build()
+                {
+                    Ok(glob) => compiled.push(glob),
+                    Err(error) => {
+                        errors.push(format!("{pattern}: {error}"));
+                        compiled.clear();
+                        break;
+                    }
+                }
+            }
+
+            if compiled.is_empty() {
+                continue;
+            }
+
+            let owner = rules.patterns.len();
+            rules.patterns.push(pattern.to_string());
+            for glob in compiled {
+                builder.add(glob);
+                rules.glob_owners.push(owner);
+            }
+        }
+
+        match builder.build() {
+            Ok(set) => rules.set = Some(set),
+            Err(error) => errors.push(error.to_string()),
+        }
+
+        (rules, errors)
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Returns the first rule matching `path`, if any.
+    pub fn matching_rule(&self, path: &Path) -> Option<&str> {
+        let set = self.set.as_ref()?;
+        let normalized = path.to_string_lossy().replace('\\', "/");
+        let glob_index = set.matches(normalized.as_str()).into_iter().min()?;
+        let owner = *self.glob_owners.get(glob_index)?;
+        self.patterns.get(owner).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_rules_match_the_directory_and_its_contents() {
+        let (rules, errors) = SensitiveRules::parse("/srv/keys\n");
+        assert!(errors.is_empty());
+
+        assert_eq!(
+            rules.matching_rule(Path::new("/srv/keys")),
+            Some("/srv/keys")
+        );
+        assert_eq!(
+            rules.matching_rule(Path::new("/srv/keys/id_rsa")),
+            Some("/srv/keys")
+        );
+        assert_eq!(rules.matching_rule(Path::new("/srv/keys2/id_rsa")), None);
+    }
+
+    #[test]
+    fn glob_and_relative_rules_match_anywhere() {
+        let (rules, errors) = SensitiveRules::parse("**/secrets/**\n.env\n# comment\n");
+        assert!(errors.is_empty());
+        assert_eq!(rules.len(), 2);
+
+        assert_eq!(
+            rules.matching_rule(Path::new("/app/config/secrets/db.toml")),
+            Some("**/secrets/**")
+        );
+        assert_eq!(rules.matching_rule(Path::new("/app/.env")), Some(".env"));
+        assert_eq!(rules.matching_rule(Path::new("/app/src/main.rs")), None);
+    }
+
+    #[test]
+    fn invalid_rules_are_reported() {
+        let (rules, errors) = SensitiveRules::parse("/ok\n/bad[\n");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(errors.len(), 1);
+    }
+}
@@ -0,0 +1,426 @@
+//! Per-scan summaries derived from the finished tree: file-type totals and
+//! the stable aliases used by demo mode.
+
+use crate::memory::{total_size, HeapSize};
+use crate::model::Node;
+use ecolor::Color32;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Everything computed from a tree before it is shown, so the UI thread never
+/// walks a multi-million-node tree itself.
+#[derive(Debug, Clone, Default)]
+pub struct ScanAnalysis {
+    pub type_stats: Vec<TypeStat>,
+    pub total_file_bytes: u64,
+    pub size_histogram: SizeHistogram,
+    pub age_histogram: AgeHistogram,
+    pub zero_byte_files: ZeroByteFiles,
+    pub alias_map: HashMap<PathBuf, AliasEntry>,
+    /// Approximate memory held by the tree itself.
+    pub tree_bytes: usize,
+}
+
+impl ScanAnalysis {
+    pub fn of(root: &Node) -> Self {
+        let mut files = Vec::new();
+        collect_files(root, &mut files);
+        let size_histogram = SizeHistogram::of(
+            files
+                .iter()
+                .filter(|node| !node.is_dir)
+                .map(|node| node.size),
+        );
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let age_histogram = AgeHistogram::of(
+            files
+                .iter()
+                .filter(|node| !node.is_dir)
+                .map(|node| (node.modified, node.size)),
+            now,
+        );
+        let zero_byte_files = ZeroByteFiles::of(&files);
+        let (type_stats, total_file_bytes) = summarize_types(
+            files
+                .into_iter()
+                .map(|node| (file_type_key(&node.path), node.size)),
+        );
+        Self {
+            type_stats,
+            total_file_bytes,
+            size_histogram,
+            age_histogram,
+            zero_byte_files,
+            alias_map: build_alias_map(root),
+            tree_bytes: total_size(root),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AliasKind {
+    File,
+    Folder,
+}
+
+#[derive(Debug, Clone)]
+pub struct AliasEntry {
+    pub code: String,
+    pub kind: AliasKind,
+}
+
+impl HeapSize for AliasEntry {
+    fn heap_size(&self) -> usize {
+        self.code.heap_size()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeStat {
+    pub key: String,
+    pub bytes: u64,
+    pub files: u64,
+    pub color: Color32,
+}
+
+/// Exclusive upper bounds of the file size buckets, 16x apart from 4 KiB up
+/// to 1 GiB; larger files share one last bucket.
+pub const SIZE_BUCKET_BOUNDS: &[u64] = &[4 << 10, 64 << 10, 1 << 20, 16 << 20, 256 << 20, 1 << 30];
+
+/// Files and their bytes per [`SIZE_BUCKET_BOUNDS`] bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeHistogram {
+    pub files: Vec<u64>,
+    pub bytes: Vec<u64>,
+}
+
+impl SizeHistogram {
+    pub fn of(sizes: impl IntoIterator<Item = u64>) -> Self {
+        let buckets = SIZE_BUCKET_BOUNDS.len() + 1;
+        let mut histogram = Self {
+            files: vec![0; buckets],
+            bytes: vec![0; buckets],
+        };
+        for size in sizes {
+            let bucket = SIZE_BUCKET_BOUNDS
+                .iter()
+                .position(|&bound| size < bound)
+                .unwrap_or(SIZE_BUCKET_BOUNDS.len());
+            histogram.files[bucket] += 1;
+            histogram.bytes[bucket] = histogram.bytes[bucket].saturating_add(size);
+        }
+        histogram
+    }
+
+    /// The bucket's lower and exclusive upper bound (`None` for the last).
+    pub fn bounds(bucket: usize) -> (u64, Option<u64>) {
+        let low = bucket
+            .checked_sub(1)
+            .map_or(0, |previous| SIZE_BUCKET_BOUNDS[previous]);
+        (low, SIZE_BUCKET_BOUNDS.get(bucket).copied())
+    }
+}
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Exclusive upper bounds of the file age buckets: a month, six months, a
+/// year and two years. Older files share one bucket and files without a
+/// known modification time get the last one.
+pub const AGE_BUCKET_BOUNDS: &[u64] = &[
+    30 * DAY_SECS,
+    182 * DAY_SECS,
+    365 * DAY_SECS,
+    730 * DAY_SECS,
+];
+
+/// Files and their bytes per [`AGE_BUCKET_BOUNDS`] bucket, by last
+/// modification relative to `now`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgeHistogram {
+    /// Unix seconds the ages were measured from.
+    pub now: u64,
+    pub files: Vec<u64>,
+    pub bytes: Vec<u64>,
+}
+
+impl AgeHistogram {
+    /// Index of the bucket for files whose modification time is unknown.
+    pub const UNKNOWN: usize = AGE_BUCKET_BOUNDS.len() + 1;
+
+    /// Buckets `(modified, size)` pairs, with `modified` in Unix seconds.
+    pub fn of(files: impl IntoIterator<Item = (u64, u64)>, now: u64) -> Self {
+        let mut histogram = Self {
+            now,
+            files: vec![0; Self::UNKNOWN + 1],
+            bytes: vec![0; Self::UNKNOWN + 1],
+        };
+        for (modified, size) in files {
+            let bucket = histogram.bucket(modified);
+            histogram.files[bucket] += 1;
+            histogram.bytes[bucket] = histogram.bytes[bucket].saturating_add(size);
+        }
+        histogram
+    }
+
+    pub fn bucket(&self, modified: u64) -> usize {
+        if modified == 0 {
+            return Self::UNKNOWN;
+        }
+        let age = self.now.saturating_sub(modified);
+        AGE_BUCKET_BOUNDS
+            .iter()
+            .position(|&bound| age < bound)
+            .unwrap_or(AGE_BUCKET_BOUNDS.len())
+    }
+}
+
+/// Zero-byte files, which take no area in a size treemap.
+///
+/// Empty folders are zero-size leaves as well and are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZeroByteFiles {
+    /// Every zero-byte file, in tree order.
+    pub files: Vec<PathBuf>,
+    /// Folders directly holding zero-byte files and how many, most first.
+    pub folders: Vec<(PathBuf, u64)>,
+}
+
+impl ZeroByteFiles {
+    pub fn of(files: &[&Node]) -> Self {
+        let files: Vec<PathBuf> = files
+            .iter()
+            .filter(|node| node.size == 0 && !node.is_dir)
+            .map(|node| node.path.clone())
+            .collect();
+        let mut counts: HashMap<&Path, u64> = HashMap::new();
+        for path in &files {
+            if let Some(parent) = path.parent() {
+                *counts.entry(parent).or_default() += 1;
+            }
+        }
+        let mut folders: Vec<(PathBuf, u64)> = counts
+            .into_iter()
+            .map(|(folder, count)| (folder.to_path_buf(), count))
+            .collect();
+        folders.sort_by(|(a_path, a_count), (b_path, b_count)| {
+            b_count.cmp(a_count).then_with(|| a_path.cmp(b_path))
+        });
+        Self { files, folders }
+    }
+}
+
+/// Per-type totals over `(type key, bytes)` for every file, largest first,
+/// plus the bytes of all files.
+pub fn summarize_types(files: impl IntoIterator<Item = (String, u64)>) -> (Vec<TypeStat>, u64) {
+    let mut map: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut total_file_bytes = 0_u64;
+    for (key, size) in files {
+        let entry = map.entry(key).or_insert((0, 0));
+        entry.0 = entry.0.saturating_add(size);
+        entry.1 = entry.1.saturating_add(1);
+        total_file_bytes = total_file_bytes.saturating_add(size);
+    }
+
+    let mut stats: Vec<TypeStat> = map
+        .into_iter()
+        .map(|(key, (bytes, files))| TypeStat {
+            color: color_for_type_key(&key),
+            key,
+            bytes,
+            files,
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.key.cmp(&b.key)));
+    (stats, total_file_bytes)
+}
+
+/// Every file (leaf) below `node`, or `node` itself when it has no children.
+pub fn collect_files<'a>(node: &'a Node, files: &mut Vec<&'a Node>) {
+    if node.children.is_empty() {
+        files.push(node);
+        return;
+    }
+
+    for child in &node.children {
+        collect_files(child, files);
+    }
+}
+
+pub fn file_type_key(path: &Path) -> String {
+    path.extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "(no_ext)".to_string())
+}
+
+pub fn color_for_type_key(key: &str) -> Color32 {
+    if key == "(no_ext)" {
+        return Color32::from_rgb(122, 128, 136);
+    }
+
+    const PALETTE: [Color32; 24] = [
+        Color32::from_rgb(210, 96, 96),
+        Color32::from_rgb(214, 127, 78),
+        Color32::from_rgb(196, 151, 72),
+        Color32::from_rgb(153, 171, 72),
+        Color32::from_rgb(106, 175, 87),
+        Color32::from_rgb(79, 177, 120),
+        Color32::from_rgb(74, 173, 153),
+        Color32::from_rgb(73, 166, 179),
+        Color32::from_rgb(76, 152, 194),
+        Color32::from_rgb(88, 137, 204),
+        Color32::from_rgb(109, 124, 209),
+        Color32::from_rgb(128, 112, 207),
+        Color32::from_rgb(149, 104, 197),
+        Color32::from_rgb(173, 98, 185),
+        Color32::from_rgb(191, 95, 166),
+        Color32::from_rgb(201, 96, 143),
+        Color32::from_rgb(210, 106, 124),
+        Color32::from_rgb(171, 126, 98),
+        Color32::from_rgb(144, 140, 101),
+        Color32::from_rgb(111, 146, 114),
+        Color32::from_rgb(95, 147, 133),
+        Color32::from_rgb(101, 142, 152),
+        Color32::from_rgb(112, 132, 165),
+        Color32::from_rgb(130, 121, 167),
+    ];
+
+    let index = (stable_hash(&key) % PALETTE.len() as u64) as usize;
+    PALETTE[index]
+}
+
+pub fn stable_hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn build_alias_map(root: &Node) -> HashMap<PathBuf, AliasEntry> {
+    let mut alias_map = HashMap::new();
+    let mut file_counter = 0_usize;
+    let mut folder_counter = 0_usize;
+    assign_alias(
+        root,
+        true,
+        &mut alias_map,
+        &mut file_counter,
+        &mut folder_counter,
+    );
+    alias_map
+}
+
+fn assign_alias(
+    node: &Node,
+    is_root: bool,
+    alias_map: &mut HashMap<PathBuf, AliasEntry>,
+    file_counter: &mut usize,
+    folder_counter: &mut usize,
+) {
+    let is_dir = is_root || !node.children.is_empty();
+    let (kind, code) = if is_dir {
+        let index = *folder_counter;
+        *folder_counter = folder_counter.saturating_add(1);
+        (AliasKind::Folder, alphabet_code(index))
+    } else {
+        let index = *file_counter;
+        *file_counter = file_counter.saturating_add(1);
+        (AliasKind::File, alphabet_code(index))
+    };
+
+    alias_map.insert(node.path.clone(), AliasEntry { code, kind });
+
+    for child in &node.children {
+        assign_alias(child, false, alias_map, file_counter, folder_counter);
+    }
+}
+
+fn alphabet_code(mut index: usize) -> String {
+    // 0 -> A, 25 -> Z, 26 -> AA
+    let mut chars = Vec::new();
+    loop {
+        let rem = (index % 26) as u8;
+        chars.push((b'A' + rem) as char);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    chars.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_file_sizes_on_a_log_scale() {
+        let histogram = SizeHistogram::of([0, 4095, 4096, 70_000, 5 << 30]);
+        assert_eq!(histogram.files, vec![2, 1, 1, 0, 0, 0, 1]);
+        assert_eq!(histogram.bytes[0], 4095);
+        assert_eq!(histogram.bytes[6], 5 << 30);
+        assert_eq!(SizeHistogram::bounds(0), (0, Some(4096)));
+        assert_eq!(SizeHistogram::bounds(6), (1 << 30, None));
+    }
+
+    #[test]
+    fn histograms_leave_out_empty_folders() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("a/b.txt"), 10);
+        root.insert_relative(Path::new("empty"), 0).unwrap().is_dir = true;
+        root.compute_total_size();
+
+        let analysis = ScanAnalysis::of(&root);
+        assert_eq!(analysis.size_histogram.files, vec![1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(analysis.age_histogram.files.iter().sum::<u64>(), 1);
+    }
+
+    #[test]
+    fn lists_zero_byte_files_per_folder() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        for (path, size) in [
+            ("logs/a.log", 0),
+            ("logs/b.log", 0),
+            ("logs/c.log", 9),
+            ("out/part", 0),
+            ("empty", 0),
+        ] {
+            if let Some(node) = root.insert_relative(Path::new(path), size) {
+                node.is_dir = path == "empty";
+            }
+        }
+        let mut files = Vec::new();
+        collect_files(&root, &mut files);
+
+        let report = ZeroByteFiles::of(&files);
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(
+            report.folders,
+            vec![(PathBuf::from("/r/logs"), 2), (PathBuf::from("/r/out"), 1)]
+        );
+    }
+
+    #[test]
+    fn buckets_files_by_age() {
+        let now = 1000 * DAY_SECS;
+        let histogram = AgeHistogram::of(
+            [
+                (now - DAY_SECS, 1),
+                (now - 100 * DAY_SECS, 2),
+                (now - 800 * DAY_SECS, 4),
+                (now - 900 * DAY_SECS, 8),
+                (0, 16),
+            ],
+            now,
+        );
+        assert_eq!(histogram.files, vec![1, 1, 0, 0, 2, 1]);
+        assert_eq!(histogram.bytes, vec![1, 2, 0, 0, 12, 16]);
+        assert_eq!(histogram.bucket(now + 60), 0);
+    }
+}
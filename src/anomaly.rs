@@ -0,0 +1,191 @@
+//! Path and file name anomalies that routinely break backups and sync tools:
+//! paths near OS length limits, control or Windows-invalid characters,
+//! trailing spaces or dots, reserved device names, and siblings whose names
+//! differ only in case.
+
+use crate::model::Node;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Path length (in characters) reported as close to Windows' 260 limit.
+const LONG_PATH_CHARS: usize = 240;
+/// Name length (in bytes) reported as close to the usual 255 byte limit.
+const LONG_NAME_BYTES: usize = 230;
+/// Anomalies listed per scan; all of them are counted.
+const MAX_LISTED: usize = 5_000;
+
+const WINDOWS_INVALID: &[char] = &['<', '>', ':', '"', '|', '?', '*', '\\'];
+const WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnomalyKind {
+    LongPath,
+    LongName,
+    ControlCharacter,
+    WindowsInvalidCharacter,
+    TrailingSpaceOrDot,
+    ReservedName,
+    CaseCollision,
+}
+
+impl AnomalyKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::LongPath => "path near length limit",
+            Self::LongName => "name near length limit",
+            Self::ControlCharacter => "control character",
+            Self::WindowsInvalidCharacter => "character invalid on Windows",
+            Self::TrailingSpaceOrDot => "trailing space or dot",
+            Self::ReservedName => "reserved Windows name",
+            Self::CaseCollision => "differs from a sibling only in case",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anomaly {
+    pub path: PathBuf,
+    pub kind: AnomalyKind,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyReport {
+    /// The first [`MAX_LISTED`] anomalies, in tree order.
+    pub items: Vec<Anomaly>,
+    /// Every anomaly by kind, including those not listed.
+    pub counts: Vec<(AnomalyKind, u64)>,
+}
+
+impl AnomalyReport {
+    pub fn of(root: &Node) -> Self {
+        let mut items = Vec::new();
+        let mut counts = HashMap::new();
+        visit(root, &mut items, &mut counts);
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort();
+        Self { items, counts }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+}
+
+fn visit(node: &Node, items: &mut Vec<Anomaly>, counts: &mut HashMap<AnomalyKind, u64>) {
+    let mut report = |path: &PathBuf, kind| {
+        *counts.entry(kind).or_default() += 1;
+        if items.len() < MAX_LISTED {
+            items.push(Anomaly {
+                path: path.clone(),
+                kind,
+            });
+        }
+    };
+
+    let mut lowercase_names: HashMap<String, usize> = HashMap::new();
+    for child in &node.children {
+        for kind in name_anomalies(&child.name) {
+            report(&child.path, kind);
+        }
+        if child.path.as_os_str().len() >= LONG_PATH_CHARS
+            && child.path.to_string_lossy().chars().count() >= LONG_PATH_CHARS
+        {
+            report(&child.path, AnomalyKind::LongPath);
+        }
+        *lowercase_names
+            .entry(child.name.to_lowercase())
+            .or_default() += 1;
+    }
+    for child in &node.children {
+        if lowercase_names[&child.name.to_lowercase()] > 1 {
+            report(&child.path, AnomalyKind::CaseCollision);
+        }
+    }
+
+    for child in &node.children {
+        visit(child, items, counts);
+    }
+}
+
+fn name_anomalies(name: &str) -> Vec<AnomalyKind> {
+    let mut kinds = Vec::new();
+    if name.len() >= LONG_NAME_BYTES {
+        kinds.push(AnomalyKind::LongName);
+    }
+    if name.chars().any(char::is_control) {
+        kinds.push(AnomalyKind::ControlCharacter);
+    }
+    if name.contains(WINDOWS_INVALID) {
+        kinds.push(AnomalyKind::WindowsInvalidCharacter);
+    }
+    if name.ends_with([' ', '.']) && name != "." && name != ".." {
+        kinds.push(AnomalyKind::TrailingSpaceOrDot);
+    }
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if WINDOWS_RESERVED
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        kinds.push(AnomalyKind::ReservedName);
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn reports_name_anomalies_and_case_collisions() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        for path in [
+            "docs/Readme.md",
+            "docs/README.md",
+            "docs/notes.txt",
+            "bad/what?.txt",
+            "bad/tab\there",
+            "bad/trailing. ",
+            "bad/con.txt",
+            "bad/console.txt",
+        ] {
+            root.insert_relative(Path::new(path), 1);
+        }
+        let long = format!("deep/{}/{}", "a".repeat(120), "b".repeat(120));
+        root.insert_relative(Path::new(&long), 1);
+        root.compute_total_size();
+
+        let report = AnomalyReport::of(&root);
+        let found = |kind| -> Vec<String> {
+            report
+                .items
+                .iter()
+                .filter(|anomaly| anomaly.kind == kind)
+                .map(|anomaly| anomaly.path.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            found(AnomalyKind::CaseCollision),
+            vec!["/r/docs/Readme.md", "/r/docs/README.md"]
+        );
+        assert_eq!(
+            found(AnomalyKind::WindowsInvalidCharacter),
+            vec!["/r/bad/what?.txt"]
+        );
+        assert_eq!(
+            found(AnomalyKind::ControlCharacter),
+            vec!["/r/bad/tab\there"]
+        );
+        assert_eq!(
+            found(AnomalyKind::TrailingSpaceOrDot),
+            vec!["/r/bad/trailing. "]
+        );
+        assert_eq!(found(AnomalyKind::ReservedName), vec!["/r/bad/con.txt"]);
+        assert_eq!(found(AnomalyKind::LongPath).len(), 1);
+        assert_eq!(report.total(), 7);
+    }
+}
@@ -0,0 +1,550 @@
+//! Optional read-only HTTP API (`--serve <ADDR>`) for dashboards and scripts.
+//!
+//! Endpoints (all `GET`, JSON responses):
+//! - `/stats`: totals and scan statistics
+//! - `/tree?path=<abs path>&depth=<n>`: a node and `depth` levels below it
+//! - `/top?n=<n>`: the largest files
+//! - `/types`: per-extension byte and file counts
+//! - `/metrics`: the same figures plus agent action counts in Prometheus text
+//!   format (also served alone with `--metrics <ADDR>`)
+//!
+//! The server thread forwards each query to the UI thread, which answers it
+//! from the current scan, so responses always match what is on screen.
+//! Names and paths in responses are masked in demo mode like every export,
+//! and `/tree?path=` then only accepts the masked paths.
+
+use crate::analysis::TypeStat;
+use crate::export::NodeLabel;
+use crate::model::Node;
+use crate::scanner::ScanResult;
+use crate::wake::{self, Waker, WakingSender};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Response, Server};
+
+/// How long a request waits for the UI thread before failing with 503.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_TREE_DEPTH: usize = 1;
+const MAX_TREE_DEPTH: usize = 8;
+const DEFAULT_TOP_FILES: usize = 20;
+const MAX_TOP_FILES: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiQuery {
+    Stats,
+    Tree { path: Option<PathBuf>, depth: usize },
+    Top { count: usize },
+    Types,
+    Metrics,
+}
+
+/// Which endpoints a server answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiScope {
+    All,
+    MetricsOnly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiBody {
+    Json(Value),
+    /// Prometheus text exposition format.
+    Metrics(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: ApiBody,
+}
+
+impl ApiResponse {
+    fn ok(body: Value) -> Self {
+        Self {
+            status: 200,
+            body: ApiBody::Json(body),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: ApiBody::Json(json!({ "error": message.into() })),
+        }
+    }
+}
+
+/// A parsed query waiting for the UI thread's answer.
+pub struct ApiRequest {
+    pub query: ApiQuery,
+    reply: Sender<ApiResponse>,
+}
+
+impl ApiRequest {
+    pub fn respond(self, response: ApiResponse) {
+        let _ = self.reply.send(response);
+    }
+}
+
+/// What the UI thread currently shows.
+pub struct ApiState<'a> {
+    pub scan: Option<&'a ScanResult>,
+    pub type_stats: &'a [TypeStat],
+    pub scanning: bool,
+    /// Agent actions received since launch, by kind.
+    pub actions_by_kind: &'a BTreeMap<&'static str, u64>,
+    /// Displayed name and path of a node, aliased in demo mode.
+    pub labels: &'a dyn Fn(&Node) -> NodeLabel,
+}
+
+pub struct ApiServer {
+    server: Arc<Server>,
+    address: String,
+    receiver: Receiver<ApiRequest>,
+}
+
+impl ApiServer {
+    pub fn spawn(address: &str, scope: ApiScope, waker: Waker) -> Result<Self, String> {
+        let server = Server::http(address.trim())
+            .map_err(|error| format!("Could not serve on {address}: {error}"))?;
+        let address = server
+            .server_addr()
+            .to_ip()
+            .map_or_else(|| address.trim().to_string(), |address| address.to_string());
+        let server = Arc::new(server);
+        let (tx, rx) = wake::channel::<ApiRequest>(waker);
+
+        let thread_server = Arc::clone(&server);
+        thread::spawn(move || run_server(&thread_server, scope, &tx));
+
+        Ok(Self {
+            server,
+            address,
+            receiver: rx,
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn receiver(&self) -> &Receiver<ApiRequest> {
+        &self.receiver
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+fn run_server(server: &Server, scope: ApiScope, tx: &WakingSender<ApiRequest>) {
+    for request in server.incoming_requests() {
+        let response = if *request.method() != tiny_http::Method::Get {
+            ApiResponse::error(405, "Only GET is supported")
+        } else {
+            match parse_query(request.url()) {
+                Ok(query) if scope == ApiScope::MetricsOnly && query != ApiQuery::Metrics => {
+                    ApiResponse::error(404, "Only /metrics is served here")
+                }
+                Ok(query) => forward(query, tx),
+                Err(response) => response,
+            }
+        };
+
+        let (body, content_type) = match response.body {
+            ApiBody::Json(value) => (
+                serde_json::to_vec_pretty(&value).unwrap_or_default(),
+                "application/json",
+            ),
+            ApiBody::Metrics(text) => (text.into_bytes(), "text/plain; version=0.0.4"),
+        };
+        let mut http_response = Response::from_data(body).with_status_code(response.status);
+        if let Ok(header) = Header::from_bytes("Content-Type", content_type) {
+            http_response.add_header(header);
+        }
+        let _ = request.respond(http_response);
+    }
+}
+
+fn forward(query: ApiQuery, tx: &WakingSender<ApiRequest>) -> ApiResponse {
+    let (reply, answer) = mpsc::channel();
+    if tx.send(ApiRequest { query, reply }).is_err() {
+        return ApiResponse::error(503, "Application is shutting down");
+    }
+    answer
+        .recv_timeout(ANSWER_TIMEOUT)
+        .unwrap_or_else(|_| ApiResponse::error(503, "Application did not answer in time"))
+}
+
+pub fn parse_query(url: &str) -> Result<ApiQuery, ApiResponse> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let parameter = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| percent_decode(value))
+    };
+    let number = |name: &str, default: usize, max: usize| match parameter(name) {
+        Some(text) => text
+            .parse::<usize>()
+            .map(|value| value.min(max))
+            .map_err(|_| ApiResponse::error(400, format!("Invalid {name}: {text}"))),
+        None => Ok(default),
+    };
+
+    match path.trim_end_matches('/') {
+        "/stats" => Ok(ApiQuery::Stats),
+        "/tree" => Ok(ApiQuery::Tree {
+            path: parameter("path").map(PathBuf::from),
+            depth: number("depth", DEFAULT_TREE_DEPTH, MAX_TREE_DEPTH)?,
+        }),
+        "/top" => Ok(ApiQuery::Top {
+            count: number("n", DEFAULT_TOP_FILES, MAX_TOP_FILES)?,
+        }),
+        "/types" => Ok(ApiQuery::Types),
+        "/metrics" => Ok(ApiQuery::Metrics),
+        _ => Err(ApiResponse::error(
+            404,
+            "Unknown endpoint; try /stats, /tree, /top, /types or /metrics",
+        )),
+    }
+}
+
+pub fn answer(query: &ApiQuery, state: &ApiState<'_>) -> ApiResponse {
+    let scan = match (query, state.scan) {
+        (ApiQuery::Metrics, _) => {
+            return ApiResponse {
+                status: 200,
+                body: ApiBody::Metrics(prometheus_metrics(state)),
+            }
+        }
+        (_, None) => return ApiResponse::error(503, "No scan result yet"),
+        (_, Some(scan)) => scan,
+    };
+
+    match query {
+        ApiQuery::Stats => ApiResponse::ok(json!({
+            "root": (state.labels)(&scan.root).path,
+            "total_bytes": scan.root.size,
+            "entries": scan.stats.entries_scanned,
+            "files": scan.stats.files_scanned,
+            "directories": scan.stats.directories_scanned,
+            "warnings": scan.stats.warnings,
+            "truncated": scan.stats.truncated,
+            "elapsed_ms": scan.stats.elapsed.as_millis() as u64,
+            "scanning": state.scanning,
+        })),
+        ApiQuery::Tree { path, depth } => {
+            let node = match path {
+                Some(path) => find_displayed(&scan.root, path, state.labels),
+                None => Some(&scan.root),
+            };
+            match node {
+                Some(node) => ApiResponse::ok(node_json(node, *depth, state.labels)),
+                None => ApiResponse::error(404, "Path is not part of the scanned tree"),
+            }
+        }
+        ApiQuery::Top { count } => ApiResponse::ok(Value::Array(
+            scan.root
+                .largest(*count, false)
+                .into_iter()
+                .map(|node| json!({ "path": (state.labels)(node).path, "size": node.size }))
+                .collect(),
+        )),
+        ApiQuery::Types => ApiResponse::ok(Value::Array(
+            state
+                .type_stats
+                .iter()
+                .map(|stat| json!({ "type": stat.key, "bytes": stat.bytes, "files": stat.files }))
+                .collect(),
+        )),
+        ApiQuery::Metrics => ApiResponse::error(500, "Metrics are answered above"),
+    }
+}
+
+/// Scan and action figures in Prometheus text format. Scan gauges are
+/// omitted until a complete scan is available.
+fn prometheus_metrics(state: &ApiState<'_>) -> String {
+    let mut text = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, f64)]| {
+        let _ = writeln!(text, "# HELP treemapbase_{name} {help}");
+        let _ = writeln!(text, "# TYPE treemapbase_{name} {kind}");
+        for (labels, value) in samples {
+            let _ = writeln!(text, "treemapbase_{name}{labels} {value}");
+        }
+    };
+
+    metric(
+        "scanning",
+        "gauge",
+        "1 while a scan is running.",
+        &[(String::new(), f64::from(u8::from(state.scanning)))],
+    );
+    metric(
+        "agent_actions_total",
+        "counter",
+        "Agent actions received since launch.",
+        &state
+            .actions_by_kind
+            .iter()
+            .map(|(kind, count)| (format!("{{kind=\"{kind}\"}}"), *count as f64))
+            .collect::<Vec<_>>(),
+    );
+
+    let Some(scan) = state.scan else {
+        return text;
+    };
+    let stats = &scan.stats;
+    let gauges = [
+        (
+            "scan_total_bytes",
+            "Total bytes in the scanned tree.",
+            scan.root.size as f64,
+        ),
+        (
+            "scan_entries",
+            "Entries visited by the last scan.",
+            stats.entries_scanned as f64,
+        ),
+        (
+            "scan_files",
+            "Files in the last scan.",
+            stats.files_scanned as f64,
+        ),
+        (
+            "scan_directories",
+            "Directories in the last scan.",
+            stats.directories_scanned as f64,
+        ),
+        (
+            "scan_warnings",
+            "Warnings raised by the last scan.",
+            stats.warnings as f64,
+        ),
+        (
+            "scan_duration_seconds",
+            "Duration of the last scan.",
+            stats.elapsed.as_secs_f64(),
+        ),
+        (
+            "scan_truncated",
+            "1 if the file limit cut the last scan short.",
+            f64::from(u8::from(stats.truncated)),
+        ),
+    ];
+    for (name, help, value) in gauges {
+        metric(name, "gauge", help, &[(String::new(), value)]);
+    }
+    metric(
+        "scan_warnings_by_category",
+        "gauge",
+        "Warnings raised by the last scan, by category.",
+        &scan
+            .warning_counts
+            .iter()
+            .map(|(category, count)| {
+                (
+                    format!("{{category=\"{}\"}}", category.label()),
+                    *count as f64,
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+    metric(
+        "directory_bytes",
+        "gauge",
+        "Bytes below each top-level entry of the scanned root.",
+        &scan
+            .root
+            .children
+            .iter()
+            .map(|child| {
+                let path = (state.labels)(child).path;
+                (
+                    format!("{{path=\"{}\"}}", escape_label(&path)),
+                    child.size as f64,
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+    text
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// The node whose displayed path is `path`. Real paths are looked up
+/// directly; masked ones need a walk, since aliases do not mirror the tree.
+fn find_displayed<'a>(
+    root: &'a Node,
+    path: &Path,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+) -> Option<&'a Node> {
+    let shown = path.display().to_string();
+    if labels(root).path == root.path.display().to_string() {
+        return root.find(path);
+    }
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if labels(node).path == shown {
+            return Some(node);
+        }
+        stack.extend(&node.children);
+    }
+    None
+}
+
+fn node_json(node: &Node, depth: usize, labels: &dyn Fn(&Node) -> NodeLabel) -> Value {
+    let label = labels(node);
+    let mut value = json!({
+        "name": label.name,
+        "path": label.path,
+        "size": node.size,
+        "own_size": node.own_size,
+        "is_dir": !node.children.is_empty(),
+    });
+    if depth > 0 && !node.children.is_empty() {
+        value["children"] = node
+            .children
+            .iter()
+            .map(|child| node_json(child, depth - 1, labels))
+            .collect();
+    }
+    value
+}
+
+fn percent_decode(text: &str) -> String {
+    let hex = |byte: u8| char::from(byte).to_digit(16);
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' if index + 2 < bytes.len() => {
+                if let (Some(high), Some(low)) = (hex(bytes[index + 1]), hex(bytes[index + 2])) {
+                    decoded.push((high * 16 + low) as u8);
+                    index += 3;
+                    continue;
+                }
+                decoded.push(b'%');
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::ScanStats;
+    use std::path::Path;
+
+    #[test]
+    fn parses_queries_and_answers_from_the_tree() {
+        assert_eq!(
+            parse_query("/tree?path=%2Fr%2Fa+b&depth=99"),
+            Ok(ApiQuery::Tree {
+                path: Some(PathBuf::from("/r/a b")),
+                depth: MAX_TREE_DEPTH,
+            })
+        );
+        assert_eq!(parse_query("/top/").unwrap(), ApiQuery::Top { count: 20 });
+        assert_eq!(parse_query("/top?n=x").unwrap_err().status, 400);
+        assert_eq!(parse_query("/nope").unwrap_err().status, 404);
+
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("a b/big.bin"), 500);
+        root.insert_relative(Path::new("a b/small.txt"), 5);
+        root.insert_relative(Path::new("mid.log"), 50);
+        root.compute_total_size();
+        let scan = ScanResult {
+            root,
+            stats: ScanStats::default(),
+            warnings: Vec::new(),
+            warning_counts: Vec::new(),
+            permission_findings: Vec::new(),
+            symlinks: Vec::new(),
+            owners: Vec::new(),
+        };
+        let actions = BTreeMap::from([("read", 3)]);
+        let labels = |node: &Node| NodeLabel {
+            name: node.name.clone(),
+            path: node.path.display().to_string(),
+        };
+        let state = ApiState {
+            scan: Some(&scan),
+            type_stats: &[],
+            scanning: false,
+            actions_by_kind: &actions,
+            labels: &labels,
+        };
+        let json = |query: &ApiQuery| match answer(query, &state).body {
+            ApiBody::Json(value) => value,
+            ApiBody::Metrics(_) => Value::Null,
+        };
+
+        let top = json(&ApiQuery::Top { count: 2 });
+        assert_eq!(top[0]["size"], 500);
+        assert_eq!(top[1]["size"], 50);
+        assert_eq!(top.as_array().unwrap().len(), 2);
+
+        let tree = json(&parse_query("/tree?path=/r/a%20b").unwrap());
+        assert_eq!(tree["size"], 505);
+        assert_eq!(tree["children"].as_array().unwrap().len(), 2);
+        assert_eq!(json(&ApiQuery::Stats)["total_bytes"], 555);
+
+        let ApiBody::Metrics(metrics) = answer(&parse_query("/metrics").unwrap(), &state).body
+        else {
+            panic!("metrics should be text");
+        };
+        assert!(metrics.contains("treemapbase_scan_total_bytes 555\n"));
+        assert!(metrics.contains("treemapbase_agent_actions_total{kind=\"read\"} 3\n"));
+        assert!(metrics.contains("treemapbase_directory_bytes{path=\"/r/a b\"} 505\n"));
+
+        // Demo mode answers with aliases only.
+        let masked = |node: &Node| NodeLabel {
+            name: format!("entry-{}", node.size),
+            path: format!("/demo/entry-{}", node.size),
+        };
+        let demo = ApiState {
+            labels: &masked,
+            ..state
+        };
+        let demo_json = |query: &ApiQuery| match answer(query, &demo).body {
+            ApiBody::Json(value) => value.to_string(),
+            ApiBody::Metrics(text) => text,
+        };
+        for query in [
+            ApiQuery::Stats,
+            ApiQuery::Top { count: 5 },
+            parse_query("/tree?depth=3").unwrap(),
+            ApiQuery::Metrics,
+        ] {
+            let text = demo_json(&query);
+            assert!(!text.contains("/r") && !text.contains("a b"), "{text}");
+        }
+        let aliased = parse_query("/tree?path=/demo/entry-505").unwrap();
+        assert!(demo_json(&aliased).contains("\"size\":505"));
+        let real = parse_query("/tree?path=/r/a%20b").unwrap();
+        assert_eq!(answer(&real, &demo).status, 404);
+
+        let empty = ApiState { scan: None, ..demo };
+        assert_eq!(answer(&ApiQuery::Types, &empty).status, 503);
+    }
+}
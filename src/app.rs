@@ -1,87 +1,188 @@
-use crate::format::human_size;
+use crate::action::{ActionEvent, ActionKind, RateHistogram};
+use crate::alerts::{SensitiveAlert, SensitiveRules, DEFAULT_SENSITIVE_RULES};
+use crate::analysis::{
+    color_for_type_key, file_type_key, stable_hash, AgeHistogram, AliasEntry, AliasKind,
+    ScanAnalysis, SizeHistogram, TypeStat, ZeroByteFiles, AGE_BUCKET_BOUNDS,
+};
+use crate::anomaly::AnomalyReport;
+use crate::api::{answer, ApiScope, ApiServer, ApiState};
+use crate::audit::{PermissionFinding, MAX_PERMISSION_FINDINGS};
+use crate::category::{categories_path, load_categories, CategoryMap};
+use crate::cli::CliOptions;
+use crate::compress::{spawn_compress_estimate, CompressEstimate};
+use crate::correlate::{SizeCorrelator, ATTRIBUTION_WINDOW};
+use crate::exclude::{load_exclusions, save_exclusions, ExcludeRules, RulePreview};
+use crate::export::{markdown_summary, ExportOptions, NodeLabel, TreeExport};
+use crate::format::{
+    byte_rate, count_rate, exact_bytes, format_duration_compact, format_type_key, human_count,
+    human_size_in, percent, relative_time, Language, SizeUnits,
+};
+use crate::gateway::{self, AccessKind, AuditEvent};
+use crate::git::{spawn_git_scan, GitScan};
+use crate::growth::GrowthHistory;
+use crate::import::{import_report, IMPORT_EXTENSIONS};
+use crate::ingest::{
+    spawn_stdin_reader, ActionListener, IngestMessage, ListenerStatus, DEFAULT_LISTEN_ADDR,
+};
+use crate::ipc::describe_endpoint;
+use crate::launch::{
+    open_folder, reveal_in_file_manager, CommandOutput, ExternalCommand, DEFAULT_EXTERNAL_COMMANDS,
+};
+use crate::links::{LinkStatus, MAX_SYMLINKS};
+use crate::loc::{spawn_loc_count, LocReport};
+use crate::lru::LruCache;
+use crate::memory::{total_size, HeapSize};
 use crate::model::Node;
-use crate::scanner::{spawn_scan, ScanConfig, ScanMessage, ScanPhase, ScanProgress, ScanResult};
-use crate::treemap::{squarified_treemap, LayoutRect};
+use crate::openclaw::detect_install_candidates;
+use crate::owners::{owner_names, summarize_owners, OwnerStat};
+use crate::plugin::{
+    load_plugins, plugins_dir, spawn_plugin_analysis, Plugin, PluginPanel, PluginReport,
+};
+use crate::portable::{self, home_dir};
+use crate::reclaim::ReclaimReport;
+use crate::sandbox::{Access, SandboxPolicy};
+use crate::scanner::{
+    spawn_probe, spawn_scan, ProbeHint, ProbeResult, ScanConfig, ScanHandle, ScanMessage,
+    ScanPhase, ScanProgress, ScanResult, ScanStats, PROBE_DEPTH,
+};
+use crate::script::{Entry, ScriptView, DEFAULT_SCRIPT_COLORS};
+use crate::shape::{fan_out_label, TreeShape};
+use crate::simulate::{SimulationTarget, Simulator, TargetBias};
+use crate::snapshot::{
+    anonymized, last_snapshot_path, load_snapshot, save_snapshot, JSON_SNAPSHOT_EXTENSION,
+    SNAPSHOT_EXTENSION,
+};
+use crate::sniff::{spawn_sniff, SniffResult};
+use crate::spatial::SpatialGrid;
+use crate::threshold::{
+    check_thresholds, load_thresholds, notify_desktop, parse_thresholds, save_thresholds,
+    thresholds_path, ThresholdBreach, ThresholdRule,
+};
+use crate::tokens::{spawn_token_count, TokenApproximation, TokenReport, DEFAULT_CONTEXT_BUDGET};
+use crate::treemap::{
+    squarified_treemap, AllocatedBytes, Bytes, FileCount, LayoutRect, SizeMetric, Weights,
+};
+use crate::update::{
+    load_settings, save_settings, spawn_update_check, UpdateInfo, UpdateSettings, CURRENT_VERSION,
+};
+use crate::wake::Waker;
+use crate::watch::{FileWatcher, FsWatcher, WatchMessage, FS_WATCH_AGENT_ID};
 use eframe::egui::{self, Color32};
-use std::collections::hash_map::DefaultHasher;
-use std::collections::{HashMap, VecDeque};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs;
-use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, TryRecvError};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
-const ACTION_LOG_CAPACITY: usize = 500;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_ACTION_LOG_CAPACITY: usize = 500;
+const JUMP_HIGHLIGHT_SECONDS: f32 = 2.0;
+const PULSE_SECONDS: f32 = 1.0;
+const PAUSE_BUFFER_CAPACITY: usize = 50_000;
+const SENSITIVE_ALERT_CAPACITY: usize = 500;
+/// Zero-byte files listed individually; folders show the full counts.
+const ZERO_BYTE_LIST_LIMIT: usize = 500;
+const COMMAND_LOG_CAPACITY: usize = 100;
+const SELECTION_COLOR: Color32 = Color32::from_rgb(80, 200, 255);
+const ALERT_BANNER_SECONDS: f32 = 6.0;
+const SENSITIVE_LINE_COLOR: Color32 = Color32::from_rgb(255, 0, 200);
+const SANDBOX_DENIED_COLOR: Color32 = Color32::from_rgb(220, 40, 40);
+const RATE_BUCKET_SECONDS: u64 = 10;
+const RATE_BUCKET_COUNT: usize = 30;
 const MAX_VISIBLE_LINES: usize = 30;
 const LINE_LIFETIME_SECONDS: f32 = 5.0;
 const MIN_ZOOM_FACTOR: f32 = 0.2;
 const MAX_ZOOM_FACTOR: f32 = 10.0;
+const HEAT_EPSILON: f32 = 0.01;
+/// Layout settings must be stable this long before the treemap is rebuilt.
+const LAYOUT_DEBOUNCE: Duration = Duration::from_millis(150);
+/// Outline and text color of permission audit findings.
+const PERMISSION_FINDING_COLOR: Color32 = Color32::from_rgb(235, 70, 70);
+/// How often the category mapping file is checked for edits when it cannot
+/// be watched.
+const CATEGORY_FILE_POLL: Duration = Duration::from_secs(2);
+
+/// Key of [`PersistedSettings`] in eframe's storage.
+const SETTINGS_STORAGE_KEY: &str = "settings";
+
+/// Settings remembered between sessions in eframe's storage, next to the
+/// window size and position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct PersistedSettings {
+    language: Language,
+    treemap_depth: usize,
+    max_render_nodes: usize,
+    min_cell_pixels: f32,
+    legend_top_n: usize,
+    demo_mode: bool,
+    last_root: Option<PathBuf>,
+    openclaw_path: Option<PathBuf>,
+}
+
+impl Default for PersistedSettings {
+    fn default() -> Self {
+        Self {
+            language: Language::English,
+            treemap_depth: 8,
+            max_render_nodes: 20_000,
+            min_cell_pixels: 1.0,
+            legend_top_n: 12,
+            demo_mode: false,
+            last_root: None,
+            openclaw_path: None,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AppMode {
     AwaitingDirectory,
+    Previewing,
     Scanning,
     Ready,
     Error,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Language {
-    English,
-    Chinese,
-}
-
-impl Language {
-    fn toggle(&mut self) {
-        *self = match self {
-            Self::English => Self::Chinese,
-            Self::Chinese => Self::English,
-        };
-    }
-}
-
 #[derive(Debug, Clone)]
 struct HoveredEntry {
     name: String,
     path: PathBuf,
     size: u64,
+    own_size: u64,
     is_dir: bool,
+    /// Read once when the pointer enters the cell.
+    modified: Option<SystemTime>,
 }
 
 #[derive(Debug, Clone)]
 struct CachedCell {
     rect: egui::Rect,
+    /// Rect and size from the last full layout; live size patches shrink
+    /// `rect` within `slot` instead of relaying out the whole treemap.
+    slot: egui::Rect,
+    laid_out_size: u64,
     name: String,
     path: PathBuf,
     size: u64,
+    own_size: u64,
     is_dir: bool,
+    /// File modification time in Unix seconds (0 when unknown).
+    modified: u64,
     fill: Color32,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum AliasKind {
-    File,
-    Folder,
-}
-
-#[derive(Debug, Clone)]
-struct AliasEntry {
-    code: String,
-    kind: AliasKind,
-}
-
-#[derive(Debug, Clone)]
-struct TypeStat {
-    key: String,
-    bytes: u64,
-    files: u64,
-    color: Color32,
+    /// Label tiers from least to most detailed ("name", "name (size)",
+    /// "name (size, share)"), formatted once per language/demo-mode change.
+    labels: [String; 3],
+    label_chars: [usize; 3],
 }
 
 #[derive(Debug, Clone)]
 struct TreemapCache {
     scan_generation: u64,
+    /// Node the layout was computed for (the scan root until drill-down).
+    root_path: PathBuf,
     depth: usize,
     max_nodes: usize,
     min_cell_pixels: f32,
@@ -89,59 +190,243 @@ struct TreemapCache {
     width_px: u32,
     height_px: u32,
     cells: Vec<CachedCell>,
+    /// Language and demo mode the cell labels were formatted for.
+    label_key: Option<(Language, bool, SizeUnits)>,
+    spatial_index: SpatialGrid,
     cell_centers: HashMap<PathBuf, egui::Pos2>,
     cell_centers_by_key: HashMap<String, egui::Pos2>,
+    /// Tessellated cell fills, borders and labels for the current view.
+    static_layer: Option<StaticLayer>,
+    /// Sizes changed since layout; such caches are never reused from the LRU.
+    patched: bool,
+    /// Patched sizes drifted past [`MAX_PATCH_DISTORTION`].
+    relayout_due: bool,
+}
+
+/// Relative size change a patched cell may show before a full relayout.
+const MAX_PATCH_DISTORTION: f32 = 0.25;
+
+/// Total cells kept across recently used layouts.
+const LAYOUT_CACHE_MAX_CELLS: usize = 600_000;
+
+/// What cell areas are proportional to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayoutMetric {
+    Bytes,
+    AllocatedBytes,
+    FileCount,
+    /// Code lines counted by the lines-of-code analyzer.
+    LinesOfCode,
+    /// Estimated LLM tokens of text files.
+    Tokens,
+    /// A loaded plugin with `tmb_plugin_weight`, by index into `plugins`.
+    Plugin(usize),
+}
+
+/// Inputs that fully determine a treemap layout.
+#[derive(Debug, Clone, PartialEq)]
+struct LayoutKey {
+    scan_generation: u64,
+    root_path: PathBuf,
+    depth: usize,
+    max_nodes: usize,
+    min_cell_pixels: f32,
+    canvas_min: egui::Pos2,
+    width_px: u32,
+    height_px: u32,
+}
+
+/// Everything the static treemap mesh depends on besides the cells themselves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct StaticLayerKey {
+    canvas_rect: egui::Rect,
+    zoom_factor: f32,
+    offset: egui::Vec2,
+    show_labels: bool,
+    detail_level: usize,
+    label_key: Option<(Language, bool, SizeUnits)>,
+    pixels_per_point: f32,
+    font_image_size: [usize; 2],
 }
 
 #[derive(Debug, Clone)]
-struct ActionLogEntry {
-    timestamp: SystemTime,
-    target_path: PathBuf,
-    action_type: String,
+struct StaticLayer {
+    key: StaticLayerKey,
+    mesh: egui::epaint::Mesh,
+}
+
+impl HeapSize for CachedCell {
+    fn heap_size(&self) -> usize {
+        self.name.heap_size()
+            + self.path.heap_size()
+            + self.labels.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl HeapSize for TreemapCache {
+    fn heap_size(&self) -> usize {
+        let static_layer = self.static_layer.as_ref().map_or(0, |layer| {
+            layer.mesh.vertices.capacity() * std::mem::size_of::<egui::epaint::Vertex>()
+                + layer.mesh.indices.capacity() * std::mem::size_of::<u32>()
+        });
+        self.root_path.heap_size()
+            + self.cells.heap_size()
+            + self.spatial_index.heap_size()
+            + self.cell_centers.heap_size()
+            + self.cell_centers_by_key.heap_size()
+            + static_layer
+    }
+}
+
+impl TreemapCache {
+    fn layout_key(&self) -> LayoutKey {
+        LayoutKey {
+            scan_generation: self.scan_generation,
+            root_path: self.root_path.clone(),
+            depth: self.depth,
+            max_nodes: self.max_nodes,
+            min_cell_pixels: self.min_cell_pixels,
+            canvas_min: self.canvas_min,
+            width_px: self.width_px,
+            height_px: self.height_px,
+        }
+    }
+
+    /// Indices of cells intersecting `world_rect`, in paint order.
+    fn visible_cells(&self, world_rect: egui::Rect) -> Vec<usize> {
+        self.spatial_index
+            .query_rect(world_rect)
+            .into_iter()
+            .filter(|&index| self.cells[index].rect.intersects(world_rect))
+            .collect()
+    }
+
+    /// Recomputes cell rects from their layout slots after sizes were
+    /// patched: each cell shrinks along its longer side by its size ratio and
+    /// follows its parent's shrink. Rects stay inside their slots, so the
+    /// spatial index remains valid.
+    fn reflow_patched_cells(&mut self) {
+        let mut ancestors: Vec<(usize, egui::Rect)> = Vec::new();
+        for index in 0..self.cells.len() {
+            while let Some(&(ancestor, _)) = ancestors.last() {
+                if self.cells[index]
+                    .path
+                    .starts_with(&self.cells[ancestor].path)
+                {
+                    break;
+                }
+                ancestors.pop();
+            }
+
+            let cell = &self.cells[index];
+            let ratio = (cell.size as f32 / cell.laid_out_size.max(1) as f32).min(1.0);
+            let slot = cell.slot;
+            let mut rect = if slot.width() >= slot.height() {
+                egui::Rect::from_min_size(slot.min, egui::vec2(slot.width() * ratio, slot.height()))
+            } else {
+                egui::Rect::from_min_size(slot.min, egui::vec2(slot.width(), slot.height() * ratio))
+            };
+            if let Some(&(ancestor, ancestor_rect)) = ancestors.last() {
+                let from = self.cells[ancestor].slot;
+                let map = |pos: egui::Pos2| {
+                    ancestor_rect.min
+                        + (pos - from.min)
+                            * (ancestor_rect.size()
+                                / from.size().max(egui::Vec2::splat(f32::EPSILON)))
+                };
+                rect = egui::Rect::from_min_max(map(rect.min), map(rect.max));
+            }
+
+            if rect != self.cells[index].rect {
+                let path = &self.cells[index].path;
+                self.cell_centers.insert(path.clone(), rect.center());
+                self.cell_centers_by_key
+                    .insert(normalize_path_key(path), rect.center());
+                self.cells[index].rect = rect;
+            }
+            if self.cells[index].is_dir {
+                ancestors.push((index, rect));
+            }
+        }
+    }
+
+    /// Topmost (deepest) cell containing `world_pos`.
+    fn cell_at(&self, world_pos: egui::Pos2) -> Option<&CachedCell> {
+        self.spatial_index
+            .query_point(world_pos)
+            .iter()
+            .rev()
+            .map(|&index| &self.cells[index as usize])
+            .find(|cell| cell.rect.contains(world_pos))
+    }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 struct ActionLog {
-    entries: Arc<Mutex<VecDeque<ActionLogEntry>>>,
+    entries: Arc<Mutex<VecDeque<ActionEvent>>>,
+    capacity: Arc<AtomicUsize>,
 }
 
 impl ActionLog {
-    fn new() -> Self {
+    fn new(capacity: usize) -> Self {
         Self {
-            entries: Arc::new(Mutex::new(VecDeque::with_capacity(ACTION_LOG_CAPACITY))),
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: Arc::new(AtomicUsize::new(capacity.max(1))),
         }
     }
 
-    fn push(&self, target_path: PathBuf, action_type: impl Into<String>) {
+    fn push(&self, event: ActionEvent) {
         let Ok(mut entries) = self.entries.try_lock() else {
             return;
         };
 
-        if entries.len() >= ACTION_LOG_CAPACITY {
+        let capacity = self.capacity();
+        while entries.len() >= capacity {
             entries.pop_front();
         }
 
-        entries.push_back(ActionLogEntry {
-            timestamp: SystemTime::now(),
-            target_path,
-            action_type: action_type.into(),
-        });
+        entries.push_back(event);
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed).max(1)
+    }
+
+    fn set_capacity(&self, capacity: usize) {
+        let capacity = capacity.max(1);
+        self.capacity.store(capacity, Ordering::Relaxed);
+
+        let Ok(mut entries) = self.entries.try_lock() else {
+            return;
+        };
+
+        while entries.len() > capacity {
+            entries.pop_front();
+        }
+    }
+
+    fn with_entries<R>(&self, read: impl FnOnce(&VecDeque<ActionEvent>) -> R) -> Option<R> {
+        let Ok(entries) = self.entries.try_lock() else {
+            return None;
+        };
+
+        Some(read(&entries))
     }
 
-    fn latest(&self) -> Option<ActionLogEntry> {
+    fn latest_matching(&self, predicate: impl Fn(&ActionEvent) -> bool) -> Option<ActionEvent> {
         let Ok(entries) = self.entries.try_lock() else {
             return None;
         };
 
-        entries.back().cloned()
+        entries.iter().rev().find(|event| predicate(event)).cloned()
     }
 
-    fn len(&self) -> usize {
+    fn count_matching(&self, predicate: impl Fn(&ActionEvent) -> bool) -> usize {
         let Ok(entries) = self.entries.try_lock() else {
             return 0;
         };
 
-        entries.len()
+        entries.iter().filter(|event| predicate(event)).count()
     }
 
     fn clear(&self) {
@@ -157,21 +442,329 @@ impl ActionLog {
 struct VisualActionLine {
     timestamp: SystemTime,
     target_path: PathBuf,
+    kind: ActionKind,
+    sensitive: bool,
+    from_watcher: bool,
+    /// Stable per-line offset in `-1.0..=1.0` used to fan out overlapping curves.
+    jitter: f32,
     opacity: f32,
     age: f32,
 }
 
+/// Per-path action counts that decay exponentially over time.
+#[derive(Debug, Clone, Default)]
+struct ActivityHeat {
+    values: HashMap<PathBuf, f32>,
+    max_value: f32,
+}
+
+impl ActivityHeat {
+    fn add(&mut self, path: PathBuf, amount: f32) {
+        let value = self.values.entry(path).or_insert(0.0);
+        *value += amount;
+        self.max_value = self.max_value.max(*value);
+    }
+
+    fn decay(&mut self, delta_seconds: f32, half_life_seconds: f32) {
+        if self.values.is_empty() || delta_seconds <= 0.0 {
+            return;
+        }
+
+        let factor = 0.5_f32.powf(delta_seconds / half_life_seconds.max(0.1));
+        let mut max_value = 0.0_f32;
+        self.values.retain(|_, value| {
+            *value *= factor;
+            max_value = max_value.max(*value);
+            *value > HEAT_EPSILON
+        });
+        self.max_value = max_value;
+    }
+
+    /// Heat relative to the hottest path, in `0.0..=1.0`.
+    fn intensity(&self, path: &Path) -> Option<f32> {
+        let value = *self.values.get(path)?;
+        if self.max_value <= 0.0 {
+            return None;
+        }
+
+        Some((value / self.max_value).clamp(0.0, 1.0))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.max_value = 0.0;
+    }
+}
+
+/// Timings collected for the performance overlay.
+#[derive(Debug, Clone, Default)]
+struct PerfStats {
+    /// Recent frame intervals in seconds, oldest first.
+    frame_intervals: VecDeque<f32>,
+    /// CPU time spent inside the last `update` call.
+    last_update: Duration,
+    cells_drawn: usize,
+    cells_total: usize,
+    last_layout_rebuild: Option<Duration>,
+    /// Layout time included in `last_update`, if the last frame rebuilt it.
+    layout_in_last_update: Duration,
+    last_hit_test: Option<Duration>,
+    scan_started_at: Option<Instant>,
+}
+
+impl PerfStats {
+    const FRAME_SAMPLES: usize = 120;
+
+    fn record_frame(&mut self, interval_seconds: f32) {
+        if self.frame_intervals.len() >= Self::FRAME_SAMPLES {
+            self.frame_intervals.pop_front();
+        }
+        self.frame_intervals.push_back(interval_seconds);
+    }
+
+    /// Mean and worst frame interval over the sample window, in milliseconds.
+    fn frame_summary_ms(&self) -> (f32, f32) {
+        if self.frame_intervals.is_empty() {
+            return (0.0, 0.0);
+        }
+
+        let sum: f32 = self.frame_intervals.iter().sum();
+        let worst = self.frame_intervals.iter().copied().fold(0.0_f32, f32::max);
+        (
+            sum / self.frame_intervals.len() as f32 * 1000.0,
+            worst * 1000.0,
+        )
+    }
+}
+
+/// Approximate heap use per subsystem, for the memory panel.
+#[derive(Debug, Clone, Copy, Default)]
+struct MemoryReport {
+    tree: usize,
+    treemap_cache: usize,
+    layout_cache: usize,
+    alias_map: usize,
+    action_log: usize,
+    action_log_entries: usize,
+}
+
+impl MemoryReport {
+    /// How often the panel re-measures; walking the caches is not free.
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+    fn total(&self) -> usize {
+        self.tree + self.treemap_cache + self.layout_cache + self.alias_map + self.action_log
+    }
+}
+
+/// Update CPU time above which rendered detail is reduced automatically.
+const FRAME_BUDGET: Duration = Duration::from_millis(12);
+
+/// Lowers treemap detail while frames run over [`FRAME_BUDGET`] and restores
+/// it once they are comfortably below again.
+#[derive(Debug, Clone)]
+struct DetailGovernor {
+    enabled: bool,
+    level: usize,
+    smoothed_ms: f32,
+    changed_at: Instant,
+}
+
+impl Default for DetailGovernor {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            level: 0,
+            smoothed_ms: 0.0,
+            changed_at: Instant::now(),
+        }
+    }
+}
+
+impl DetailGovernor {
+    const MAX_LEVEL: usize = 3;
+    /// Multiplier on the configured minimum cell size, per level.
+    const MIN_CELL_SCALE: [f32; 4] = [1.0, 2.0, 4.0, 8.0];
+    /// Cells narrower or shorter than this many screen pixels get no border.
+    const STROKE_MIN_PIXELS: [f32; 4] = [0.0, 6.0, 12.0, 24.0];
+    const RAISE_AFTER: Duration = Duration::from_millis(500);
+    const RESTORE_AFTER: Duration = Duration::from_secs(3);
+
+    fn observe(&mut self, frame_work: Duration) {
+        if !self.enabled {
+            self.level = 0;
+            return;
+        }
+
+        let budget_ms = FRAME_BUDGET.as_secs_f32() * 1000.0;
+        self.smoothed_ms = self.smoothed_ms * 0.9 + frame_work.as_secs_f32() * 1000.0 * 0.1;
+        let since_change = self.changed_at.elapsed();
+
+        let next_level = if self.smoothed_ms > budget_ms
+            && self.level < Self::MAX_LEVEL
+            && since_change >= Self::RAISE_AFTER
+        {
+            self.level + 1
+        } else if self.smoothed_ms < budget_ms * 0.4
+            && self.level > 0
+            && since_change >= Self::RESTORE_AFTER
+        {
+            self.level - 1
+        } else {
+            return;
+        };
+
+        self.level = next_level;
+        self.changed_at = Instant::now();
+        // Start from a neutral estimate so the rebuild spike caused by the
+        // change itself does not trigger another step straight away.
+        self.smoothed_ms = budget_ms * 0.5;
+    }
+
+    fn min_cell_scale(&self) -> f32 {
+        Self::MIN_CELL_SCALE[self.level]
+    }
+
+    fn stroke_min_pixels(&self) -> f32 {
+        Self::STROKE_MIN_PIXELS[self.level]
+    }
+
+    fn labels_allowed(&self) -> bool {
+        self.level < Self::MAX_LEVEL
+    }
+}
+
+/// Action counts rolled up from each target to every ancestor inside the root.
+#[derive(Debug, Clone, Default)]
+struct ActionRollup {
+    counts: HashMap<PathBuf, u64>,
+    /// Highest count among paths strictly below the root.
+    max_count: u64,
+}
+
+impl ActionRollup {
+    fn add(&mut self, root_path: &Path, target_path: &Path) {
+        if !target_path.starts_with(root_path) {
+            return;
+        }
+
+        for ancestor in target_path.ancestors() {
+            let count = self.counts.entry(ancestor.to_path_buf()).or_insert(0);
+            *count += 1;
+            if ancestor == root_path {
+                break;
+            }
+            self.max_count = self.max_count.max(*count);
+        }
+    }
+
+    fn count(&self, path: &Path) -> u64 {
+        self.counts.get(path).copied().unwrap_or(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.counts.clear();
+        self.max_count = 0;
+    }
+}
+
 pub struct TreeMapApp {
     mode: AppMode,
     language: Language,
+    size_units: SizeUnits,
     agent_path: Option<PathBuf>,
+    agent_candidates: Vec<PathBuf>,
     root_path: Option<PathBuf>,
     scan_config: ScanConfig,
-    scan_receiver: Option<Receiver<ScanMessage>>,
+    scan_handle: Option<ScanHandle>,
     scan_progress: ScanProgress,
     scan_result: Option<ScanResult>,
     error_message: Option<String>,
+    export_status: Option<String>,
+    export_options: ExportOptions,
+    /// Where the treemap was last drawn, for cropping image copies.
+    canvas_rect: Option<egui::Rect>,
+    /// A screenshot was requested for "Copy image".
+    image_copy_pending: bool,
+    preview_before_scan: bool,
+    /// Type files by their magic bytes after each scan.
+    content_typing: bool,
+    /// Content-detected type keys that disagree with the extension.
+    content_types: HashMap<PathBuf, String>,
+    sniff_receiver: Option<Receiver<SniffResult>>,
+    sniff_status: Option<String>,
+    /// Folder totals of this session's scans of the current root.
+    growth: GrowthHistory,
+    /// Minutes between automatic rescans; 0 turns them off.
+    rescan_every_minutes: u32,
+    next_rescan: Option<Instant>,
+    /// Estimate how well each folder would compress after each scan.
+    compress_enabled: bool,
+    compress_receiver: Option<Receiver<CompressEstimate>>,
+    compress_estimate: Option<CompressEstimate>,
+    compress_status: Option<String>,
+    loc_enabled: bool,
+    loc_receiver: Option<Receiver<LocReport>>,
+    loc_report: Option<LocReport>,
+    loc_status: Option<String>,
+    /// Shades files by their code lines instead of their type.
+    loc_coloring: bool,
+    tokens_enabled: bool,
+    token_approximation: TokenApproximation,
+    token_receiver: Option<Receiver<TokenReport>>,
+    token_report: Option<TokenReport>,
+    token_status: Option<String>,
+    /// Context window, in tokens, that folders are compared against.
+    context_budget: u64,
+    /// Problematic paths and names in the current tree.
+    anomalies: AnomalyReport,
+    /// Depth and fan-out statistics of the current tree.
+    shape: TreeShape,
+    /// Index of each path in the current result's permission findings.
+    permission_index: HashMap<PathBuf, usize>,
+    /// Build output and caches found in the current tree.
+    reclaim: ReclaimReport,
+    show_reclaim_badges: bool,
+    git_receiver: Option<Receiver<GitScan>>,
+    plugins: Vec<Arc<Plugin>>,
+    plugin_errors: Vec<String>,
+    plugin_receiver: Option<Receiver<PluginReport>>,
+    /// Panels returned by analyzing plugins for the current scan.
+    plugin_panels: Vec<(String, Result<PluginPanel, String>)>,
+    /// Repositories and git-ignored entries found in the current tree.
+    git_scan: Option<GitScan>,
+    hide_git_ignored: bool,
+    /// The tree without git-ignored entries (while they are hidden) and
+    /// without files the custom view filters out.
+    view_root: Option<Node>,
+    /// Folder the treemap is re-rooted on by double-clicking; `None` shows
+    /// the whole tree.
+    drill_path: Option<PathBuf>,
+    /// Extension → category mapping, reloaded whenever its file changes.
+    categories: CategoryMap,
+    categories_path: Option<PathBuf>,
+    categories_modified: Option<SystemTime>,
+    categories_checked: Option<Instant>,
+    /// Wakes the UI when the mapping file changes; polled instead when the
+    /// watcher could not start.
+    category_watcher: Option<FileWatcher>,
+    /// Problems found in the mapping file; the last good mapping stays active.
+    category_status: Option<String>,
+    color_by_category: bool,
+    pending_root: Option<PathBuf>,
+    probe_receiver: Option<Receiver<Result<ProbeResult, String>>>,
+    probe_result: Option<Result<ProbeResult, String>>,
+    scan_history: HashMap<PathBuf, ProbeHint>,
     treemap_depth: usize,
+    layout_metric: LayoutMetric,
     max_render_nodes: usize,
     min_cell_pixels: f32,
     show_cell_labels: bool,
@@ -179,33 +772,232 @@ pub struct TreeMapApp {
     zoom_factor: f32,
     offset: egui::Vec2,
     startup_prompted: bool,
+    /// Root of the previous session, scanned at startup instead of asking.
+    last_root: Option<PathBuf>,
     scan_generation: u64,
     treemap_cache: Option<TreemapCache>,
+    /// Layouts replaced by a settings or canvas change, for quick reuse.
+    layout_cache: LruCache<LayoutKey, TreemapCache>,
     hovered_entry: Option<HoveredEntry>,
+    /// `scan_result` holds a partial tree streamed by the running scan.
+    scan_result_provisional: bool,
     type_stats: Vec<TypeStat>,
     total_file_bytes: u64,
+    size_histogram: SizeHistogram,
+    age_histogram: AgeHistogram,
+    zero_byte_files: ZeroByteFiles,
+    /// Age bucket whose files are highlighted in the treemap.
+    age_filter: Option<usize>,
+    /// Approximate memory held by the scanned tree, measured on the scan thread.
+    tree_bytes: usize,
     legend_top_n: usize,
+    /// Type key or category name hovered in the legend or its chart; the
+    /// treemap outlines matching files.
+    legend_hover: Option<String>,
     alias_map: HashMap<PathBuf, AliasEntry>,
     action_log: ActionLog,
     visual_lines: VecDeque<VisualActionLine>,
+    hidden_action_kinds: HashSet<ActionKind>,
+    sensitive_rules_text: String,
+    sensitive_rules: SensitiveRules,
+    sensitive_rule_errors: Vec<String>,
+    sensitive_alerts: VecDeque<SensitiveAlert>,
+    alert_banner: Option<(String, Instant)>,
+    show_sensitive_panel: bool,
+    thresholds_text: String,
+    thresholds: Vec<ThresholdRule>,
+    threshold_errors: Vec<String>,
+    /// Folders over their limit in the current scan.
+    threshold_breaches: Vec<ThresholdBreach>,
+    threshold_notify: bool,
+    show_threshold_panel: bool,
+    /// Exclusion rules being edited for `exclusions_root`.
+    exclusions_text: String,
+    exclusions_root: Option<PathBuf>,
+    exclusion_errors: Vec<String>,
+    /// Per-rule and total preview, with the text and scan they were computed for.
+    exclusion_preview: Option<(String, u64, Vec<RulePreview>, RulePreview)>,
+    show_exclusion_panel: bool,
+    script_colors_text: String,
+    script_filter_text: String,
+    /// Custom view expressions; applied to the layout when non-empty.
+    script: ScriptView,
+    script_errors: Vec<String>,
+    show_script_panel: bool,
+    /// The agent's allowed-directory configuration, if one is loaded.
+    sandbox: Option<SandboxPolicy>,
+    sandbox_errors: Vec<String>,
+    /// Tints forbidden zones on the treemap.
+    sandbox_shading: bool,
+    /// Ingested actions that landed outside the sandbox.
+    sandbox_alerts: VecDeque<SensitiveAlert>,
+    show_sandbox_panel: bool,
+    show_access_audit: bool,
+    update_settings: UpdateSettings,
+    update_receiver: Option<Receiver<Result<Option<UpdateInfo>, String>>>,
+    /// A release newer than this build, from the last check.
+    update_available: Option<UpdateInfo>,
+    update_status: Option<String>,
+    show_update_panel: bool,
+    external_commands_text: String,
+    external_commands: Vec<ExternalCommand>,
+    external_command_errors: Vec<String>,
+    show_external_commands: bool,
+    /// Entries waiting for confirmation before they go to the trash.
+    pending_trash: Option<Vec<(PathBuf, bool)>>,
+    /// Entry (path, is_dir) the treemap context menu was opened on.
+    context_target: Option<(PathBuf, bool)>,
+    /// Entries picked with Ctrl+click; context menu commands run on all of
+    /// them when one of them is right-clicked.
+    selected_paths: Vec<(PathBuf, bool)>,
+    command_receivers: Vec<Receiver<CommandOutput>>,
+    command_log: VecDeque<CommandOutput>,
+    show_command_log: bool,
+    command_status: Option<String>,
+    size_correlator: SizeCorrelator,
+    show_size_attribution: bool,
+    show_memory_panel: bool,
+    memory_report: Option<(Instant, MemoryReport)>,
+    show_action_history: bool,
+    history_search: String,
+    pending_jump: Option<PathBuf>,
+    jump_highlight: Option<(egui::Pos2, Instant)>,
+    focus_trail: VecDeque<(PathBuf, ActionKind)>,
+    show_focus_trail: bool,
+    /// Arrows from symbolic links to their targets inside the root.
+    show_symlink_arrows: bool,
+    /// Colors files by owning user instead of type.
+    color_by_owner: bool,
+    /// Owner expanded in the ownership panel; other owners are dimmed
+    /// while coloring by owner.
+    selected_owner: Option<u32>,
+    focus_trail_length: usize,
+    activity_heat: ActivityHeat,
+    show_heatmap: bool,
+    action_rollup: ActionRollup,
+    show_action_rollup: bool,
+    simulator: Simulator,
+    layout_settings_changed_at: Option<Instant>,
+    perf_stats: PerfStats,
+    detail_governor: DetailGovernor,
+    show_perf_overlay: bool,
+    layout_setting_dragging: bool,
+    heat_half_life_seconds: f32,
+    listen_address: String,
+    action_listener: Option<ActionListener>,
+    listener_status: ListenerStatus,
+    ipc_name: String,
+    ipc_listener: Option<ActionListener>,
+    ipc_status: ListenerStatus,
+    stream_url: String,
+    stream_listener: Option<ActionListener>,
+    stream_status: ListenerStatus,
+    rejected_events: u64,
+    last_rejected_event: Option<String>,
+    stdin_receiver: Option<Receiver<IngestMessage>>,
+    stdin_closed: bool,
+    ingest_paused: bool,
+    paused_buffer: VecDeque<ActionEvent>,
+    paused_dropped: u64,
+    watch_enabled: bool,
+    fs_watcher: Option<FsWatcher>,
+    /// Read-only JSON API started with `--serve`.
+    api_server: Result<Option<ApiServer>, String>,
+    /// Prometheus-only endpoint started with `--metrics`.
+    metrics_server: Result<Option<ApiServer>, String>,
+    /// Agent actions received since launch, by kind; never reset.
+    actions_by_kind: BTreeMap<&'static str, u64>,
+    watch_error: Option<String>,
+    /// Handed to background workers so new messages trigger a repaint.
+    waker: Waker,
 }
 
 impl TreeMapApp {
-    pub fn new(creation_context: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(creation_context: &eframe::CreationContext<'_>, cli_options: &CliOptions) -> Self {
         configure_fonts_for_cjk(&creation_context.egui_ctx);
-        let scan_config = ScanConfig::default();
-
-        Self {
+        let scan_config = ScanConfig {
+            snapshot_path: last_snapshot_path(),
+            jsonl_export: cli_options.export_jsonl.clone(),
+            ..ScanConfig::default()
+        };
+        let (sensitive_rules, sensitive_rule_errors) =
+            SensitiveRules::parse(DEFAULT_SENSITIVE_RULES);
+        let (external_commands, external_command_errors) =
+            ExternalCommand::parse_list(DEFAULT_EXTERNAL_COMMANDS);
+        let thresholds_text = load_thresholds();
+        let (plugins, plugin_errors) = plugins_dir()
+            .map(|dir| load_plugins(&dir))
+            .unwrap_or_default();
+        let (thresholds, threshold_errors) = parse_thresholds(&thresholds_text);
+        let repaint_context = creation_context.egui_ctx.clone();
+        let waker = Waker::new(move || repaint_context.request_repaint());
+
+        let mut app = Self {
             mode: AppMode::AwaitingDirectory,
             language: Language::English,
+            size_units: SizeUnits::default(),
             agent_path: None,
+            agent_candidates: detect_install_candidates(),
             root_path: None,
             scan_config,
-            scan_receiver: None,
+            scan_handle: None,
             scan_progress: ScanProgress::default(),
             scan_result: None,
             error_message: None,
+            export_status: None,
+            export_options: ExportOptions::default(),
+            canvas_rect: None,
+            image_copy_pending: false,
+            preview_before_scan: true,
+            content_typing: false,
+            content_types: HashMap::new(),
+            sniff_receiver: None,
+            sniff_status: None,
+            growth: GrowthHistory::default(),
+            rescan_every_minutes: 0,
+            next_rescan: None,
+            compress_enabled: false,
+            compress_receiver: None,
+            compress_estimate: None,
+            compress_status: None,
+            loc_enabled: false,
+            loc_receiver: None,
+            loc_report: None,
+            loc_status: None,
+            loc_coloring: false,
+            tokens_enabled: false,
+            token_approximation: TokenApproximation::default(),
+            token_receiver: None,
+            token_report: None,
+            token_status: None,
+            context_budget: DEFAULT_CONTEXT_BUDGET,
+            anomalies: AnomalyReport::default(),
+            shape: TreeShape::default(),
+            permission_index: HashMap::new(),
+            reclaim: ReclaimReport::default(),
+            show_reclaim_badges: true,
+            git_receiver: None,
+            plugins,
+            plugin_errors,
+            plugin_receiver: None,
+            plugin_panels: Vec::new(),
+            git_scan: None,
+            hide_git_ignored: false,
+            view_root: None,
+            drill_path: None,
+            categories: CategoryMap::default(),
+            categories_path: categories_path(),
+            categories_modified: None,
+            categories_checked: None,
+            category_watcher: None,
+            category_status: None,
+            color_by_category: false,
+            pending_root: None,
+            probe_receiver: None,
+            probe_result: None,
+            scan_history: HashMap::new(),
             treemap_depth: 8,
+            layout_metric: LayoutMetric::Bytes,
             max_render_nodes: 20_000,
             min_cell_pixels: 1.0,
             show_cell_labels: true,
@@ -213,16 +1005,189 @@ impl TreeMapApp {
             zoom_factor: 1.0,
             offset: egui::Vec2::ZERO,
             startup_prompted: false,
+            last_root: None,
             scan_generation: 0,
             treemap_cache: None,
+            layout_cache: LruCache::new(LAYOUT_CACHE_MAX_CELLS),
             hovered_entry: None,
+            scan_result_provisional: false,
             type_stats: Vec::new(),
+            size_histogram: SizeHistogram::default(),
+            zero_byte_files: ZeroByteFiles::default(),
+            age_histogram: AgeHistogram::default(),
+            age_filter: None,
             total_file_bytes: 0,
+            tree_bytes: 0,
             legend_top_n: 12,
+            legend_hover: None,
             alias_map: HashMap::new(),
-            action_log: ActionLog::new(),
+            action_log: ActionLog::new(DEFAULT_ACTION_LOG_CAPACITY),
             visual_lines: VecDeque::with_capacity(MAX_VISIBLE_LINES),
+            hidden_action_kinds: HashSet::new(),
+            sensitive_rules_text: DEFAULT_SENSITIVE_RULES.to_string(),
+            sensitive_rules,
+            sensitive_rule_errors,
+            sensitive_alerts: VecDeque::new(),
+            alert_banner: None,
+            show_sensitive_panel: false,
+            thresholds_text,
+            thresholds,
+            threshold_errors,
+            threshold_breaches: Vec::new(),
+            threshold_notify: false,
+            show_threshold_panel: false,
+            exclusions_text: String::new(),
+            exclusions_root: None,
+            exclusion_errors: Vec::new(),
+            exclusion_preview: None,
+            show_exclusion_panel: false,
+            script_colors_text: DEFAULT_SCRIPT_COLORS.to_string(),
+            script_filter_text: String::new(),
+            script: ScriptView::default(),
+            script_errors: Vec::new(),
+            show_script_panel: false,
+            sandbox: None,
+            sandbox_errors: Vec::new(),
+            sandbox_shading: true,
+            sandbox_alerts: VecDeque::new(),
+            show_sandbox_panel: false,
+            show_access_audit: false,
+            update_settings: load_settings(),
+            update_receiver: None,
+            update_available: None,
+            update_status: None,
+            show_update_panel: false,
+            external_commands_text: DEFAULT_EXTERNAL_COMMANDS.to_string(),
+            external_commands,
+            external_command_errors,
+            show_external_commands: false,
+            pending_trash: None,
+            context_target: None,
+            selected_paths: Vec::new(),
+            command_receivers: Vec::new(),
+            command_log: VecDeque::new(),
+            show_command_log: false,
+            command_status: None,
+            size_correlator: SizeCorrelator::default(),
+            show_size_attribution: false,
+            show_memory_panel: false,
+            memory_report: None,
+            show_action_history: false,
+            history_search: String::new(),
+            pending_jump: None,
+            jump_highlight: None,
+            focus_trail: VecDeque::new(),
+            show_focus_trail: false,
+            show_symlink_arrows: false,
+            color_by_owner: false,
+            selected_owner: None,
+            focus_trail_length: 20,
+            activity_heat: ActivityHeat::default(),
+            show_heatmap: false,
+            action_rollup: ActionRollup::default(),
+            show_action_rollup: true,
+            simulator: Simulator::default(),
+            layout_settings_changed_at: None,
+            perf_stats: PerfStats::default(),
+            detail_governor: DetailGovernor::default(),
+            show_perf_overlay: false,
+            layout_setting_dragging: false,
+            heat_half_life_seconds: 60.0,
+            listen_address: DEFAULT_LISTEN_ADDR.to_string(),
+            action_listener: None,
+            listener_status: ListenerStatus::Stopped,
+            ipc_name: cli_options.ipc_name.clone(),
+            ipc_listener: None,
+            ipc_status: ListenerStatus::Stopped,
+            stream_url: String::new(),
+            stream_listener: None,
+            stream_status: ListenerStatus::Stopped,
+            rejected_events: 0,
+            last_rejected_event: None,
+            stdin_receiver: cli_options
+                .actions_stdin
+                .then(|| spawn_stdin_reader(waker.clone())),
+            stdin_closed: false,
+            ingest_paused: false,
+            paused_buffer: VecDeque::new(),
+            paused_dropped: 0,
+            watch_enabled: false,
+            fs_watcher: None,
+            api_server: cli_options
+                .serve
+                .as_deref()
+                .map(|address| ApiServer::spawn(address, ApiScope::All, waker.clone()))
+                .transpose(),
+            metrics_server: cli_options
+                .metrics
+                .as_deref()
+                .map(|address| ApiServer::spawn(address, ApiScope::MetricsOnly, waker.clone()))
+                .transpose(),
+            actions_by_kind: BTreeMap::new(),
+            watch_error: None,
+            waker,
+        };
+        if let Some(settings) = creation_context
+            .storage
+            .and_then(|storage| eframe::get_value(storage, SETTINGS_STORAGE_KEY))
+        {
+            app.apply_persisted_settings(settings);
+        }
+        if let Some(root) = cli_options.root.clone() {
+            // Launched for a specific folder (e.g. from the file manager's
+            // context menu): scan it right away without any dialogs.
+            app.startup_prompted = true;
+            app.agent_path = app.agent_candidates.first().cloned();
+            app.request_scan(root);
+        } else {
+            app.resume_last_snapshot();
+        }
+        if app.update_settings.enabled {
+            app.start_update_check();
+        }
+        app
+    }
+
+    fn apply_persisted_settings(&mut self, settings: PersistedSettings) {
+        self.language = settings.language;
+        self.treemap_depth = settings.treemap_depth.clamp(1, 256);
+        self.max_render_nodes = settings.max_render_nodes.clamp(1_000, 200_000);
+        self.min_cell_pixels = settings.min_cell_pixels.clamp(0.5, 8.0);
+        self.legend_top_n = settings.legend_top_n.clamp(3, 30);
+        self.demo_mode = settings.demo_mode;
+        self.last_root = settings.last_root;
+        self.agent_path = settings.openclaw_path;
+    }
+
+    fn persisted_settings(&self) -> PersistedSettings {
+        PersistedSettings {
+            language: self.language,
+            treemap_depth: self.treemap_depth,
+            max_render_nodes: self.max_render_nodes,
+            min_cell_pixels: self.min_cell_pixels,
+            legend_top_n: self.legend_top_n,
+            demo_mode: self.demo_mode,
+            last_root: self.root_path.clone().or_else(|| self.last_root.clone()),
+            openclaw_path: self.agent_path.clone(),
+        }
+    }
+
+    /// Shows the previous session's tree right away and rescans its root in
+    /// the background; the snapshot stays on screen until the rescan lands.
+    fn resume_last_snapshot(&mut self) {
+        let Some(path) = self.scan_config.snapshot_path.clone() else {
+            return;
+        };
+        let Ok(result) = load_snapshot(&path) else {
+            return;
+        };
+        let root_path = result.root.path.clone();
+        if !root_path.is_dir() {
+            return;
         }
+
+        self.open_scan_result(result);
+        self.start_scan(root_path);
     }
 
     fn t<'a>(&self, english: &'a str, chinese: &'a str) -> &'a str {
@@ -232,7 +1197,33 @@ impl TreeMapApp {
         }
     }
 
-    fn demo_name(&self, real_name: &str, path: &PathBuf, is_dir: bool) -> String {
+    fn size(&self, bytes: u64) -> String {
+        human_size_in(bytes, self.size_units, self.language)
+    }
+
+    /// A size change with its sign, e.g. "+1.2 GB".
+    fn signed_size(&self, delta: i64) -> String {
+        let sign = if delta < 0 { "-" } else { "+" };
+        format!("{sign}{}", self.size(delta.unsigned_abs()))
+    }
+
+    /// "45,210 entries/s | 118 MB/s" for a scan that covered `entries`
+    /// entries and `bytes` of file sizes in `elapsed`.
+    fn throughput_text(&self, entries: u64, bytes: u64, elapsed: Duration) -> String {
+        format!(
+            "{} {} | {}",
+            count_rate(entries, elapsed, self.language.count_grouping()),
+            self.t("entries/s", "条目/秒"),
+            byte_rate(bytes, elapsed, self.size_units, self.language)
+        )
+    }
+
+    /// Entry/file/warning count with the current language's digit grouping.
+    fn count(&self, value: u64) -> String {
+        human_count(value, self.language.count_grouping())
+    }
+
+    fn demo_name(&self, real_name: &str, path: &Path, is_dir: bool) -> String {
         if !self.demo_mode {
             return real_name.to_string();
         }
@@ -248,7 +1239,7 @@ impl TreeMapApp {
         }
     }
 
-    fn demo_path(&self, path: &PathBuf) -> String {
+    fn demo_path(&self, path: &Path) -> String {
         if !self.demo_mode {
             return path.display().to_string();
         }
@@ -256,6 +1247,89 @@ impl TreeMapApp {
         self.alias_path(path)
     }
 
+    /// Masks the scan root and home directory inside free-form text (errors,
+    /// rule patterns) while demo mode is on.
+    fn demo_text(&self, text: &str) -> String {
+        if !self.demo_mode {
+            return text.to_string();
+        }
+
+        let mut masked = text.to_string();
+        if let Some(root_path) = &self.root_path {
+            let root_text = root_path.display().to_string();
+            if !root_text.is_empty() {
+                masked = masked.replace(&root_text, &self.alias_path(root_path));
+            }
+        }
+        if let Some(home) = home_dir() {
+            let home_text = home.display().to_string();
+            if !home_text.is_empty() {
+                masked = masked.replace(&home_text, "~");
+            }
+        }
+        masked
+    }
+
+    /// Copy of `event` safe to write into exported artifacts.
+    ///
+    /// In demo mode the target becomes its alias path and the free-form
+    /// payload is dropped, so no raw path can leave the app.
+    fn export_event(&self, event: &ActionEvent) -> ActionEvent {
+        let mut exported = event.clone();
+        if self.demo_mode {
+            exported.target_path = PathBuf::from(self.alias_path(&event.target_path));
+            exported.payload = None;
+        }
+        exported
+    }
+
+    fn export_json_lines(&mut self, default_name: &str, lines: Result<Vec<String>, String>) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(default_name)
+            .add_filter("JSON Lines", &["jsonl"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = lines.and_then(|lines| {
+            let mut contents = lines.join("\n");
+            contents.push('\n');
+            fs::write(&path, contents).map_err(|error| error.to_string())
+        });
+        self.export_status = Some(match result {
+            Ok(()) => format!("{} {}", self.t("Exported", "已导出"), path.display()),
+            Err(error) => format!("{} {error}", self.t("Export failed:", "导出失败：")),
+        });
+    }
+
+    fn export_action_history(&mut self) {
+        let lines = self.action_log.with_entries(|entries| {
+            entries
+                .iter()
+                .map(|event| serde_json::to_string(&self.export_event(event)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| error.to_string())
+        });
+        let lines = lines.unwrap_or_else(|| Err("Action log is busy".to_string()));
+        self.export_json_lines("action-history.jsonl", lines);
+    }
+
+    fn export_sensitive_alerts(&mut self) {
+        let lines = self
+            .sensitive_alerts
+            .iter()
+            .map(|alert| {
+                serde_json::to_string(&serde_json::json!({
+                    "rule": self.demo_text(&alert.rule),
+                    "event": self.export_event(&alert.event),
+                }))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string());
+        self.export_json_lines("sensitive-alerts.jsonl", lines);
+    }
+
     fn alias_display(&self, alias: &AliasEntry) -> String {
         match alias.kind {
             AliasKind::File => format!("{}{}", self.t("File ", "文件 "), alias.code),
@@ -263,7 +1337,7 @@ impl TreeMapApp {
         }
     }
 
-    fn alias_path(&self, path: &PathBuf) -> String {
+    fn alias_path(&self, path: &Path) -> String {
         let Some(root_path) = &self.root_path else {
             return self.t("(hidden)", "（已隐藏）").to_string();
         };
@@ -363,500 +1437,5552 @@ impl TreeMapApp {
         ctx.request_repaint_after(Duration::from_millis(16));
     }
 
-    fn log_action(&mut self, target_path: PathBuf, action_type: impl Into<String>) {
-        let action_type = action_type.into();
-        self.action_log.push(target_path.clone(), action_type);
+    fn log_action(&mut self, event: ActionEvent) {
+        let matched_rule = self
+            .sensitive_rules
+            .matching_rule(&event.target_path)
+            .map(str::to_string);
+        if let Some(rule) = &matched_rule {
+            self.record_sensitive_alert(&event, rule.clone());
+        }
+        if event.agent_id != FS_WATCH_AGENT_ID {
+            self.check_sandbox(&event);
+        }
+
+        if event.agent_id != FS_WATCH_AGENT_ID {
+            self.size_correlator.record_action(&event);
+        }
+        *self.actions_by_kind.entry(event.kind.as_str()).or_default() += 1;
+        self.activity_heat.add(event.target_path.clone(), 1.0);
+        if let Some(root_path) = &self.root_path {
+            self.action_rollup.add(root_path, &event.target_path);
+        }
+        self.focus_trail
+            .push_back((event.target_path.clone(), event.kind));
+        while self.focus_trail.len() > self.focus_trail_length.max(2) {
+            self.focus_trail.pop_front();
+        }
         self.visual_lines.push_back(VisualActionLine {
             timestamp: SystemTime::now(),
-            target_path,
+            target_path: event.target_path.clone(),
+            kind: event.kind,
+            sensitive: matched_rule.is_some(),
+            from_watcher: event.agent_id == FS_WATCH_AGENT_ID,
+            jitter: (stable_hash(&event.id) % 2001) as f32 / 1000.0 - 1.0,
             opacity: 1.0,
             age: 0.0,
         });
+        self.action_log.push(event);
         while self.visual_lines.len() > MAX_VISIBLE_LINES {
             self.visual_lines.pop_front();
         }
     }
 
-    fn simulate_agent_activity(&mut self) {
-        let Some(cache) = self.treemap_cache.as_ref() else {
-            return;
-        };
+    fn record_sensitive_alert(&mut self, event: &ActionEvent, rule: String) {
+        let message = format!(
+            "{} {} {} ({} {})",
+            self.t("Sensitive path touched:", "敏感路径被访问："),
+            event.kind.as_str(),
+            self.demo_path(&event.target_path),
+            self.t("rule", "规则"),
+            self.demo_text(&rule)
+        );
+        self.alert_banner = Some((message, Instant::now()));
 
-        if cache.cells.is_empty() {
-            return;
+        if self.sensitive_alerts.len() >= SENSITIVE_ALERT_CAPACITY {
+            self.sensitive_alerts.pop_front();
         }
+        self.sensitive_alerts.push_back(SensitiveAlert {
+            event: event.clone(),
+            rule,
+        });
+    }
 
-        const ACTION_TYPES: [&str; 6] = [
-            "inspect",
-            "classify",
-            "correlate",
-            "trace",
-            "verify",
-            "highlight",
-        ];
+    /// Raises an alert when an agent action lands outside its sandbox.
+    fn check_sandbox(&mut self, event: &ActionEvent) {
+        let Some(policy) = &self.sandbox else {
+            return;
+        };
+        let rule = match policy.access(&event.target_path) {
+            Access::Allowed => return,
+            Access::Denied(rule) => rule.to_string(),
+            Access::OutsideAllowlist => self.t("outside allowlist", "不在允许列表内").to_string(),
+        };
+        let message = format!(
+            "{} {} {} ({})",
+            self.t("Sandbox violation:", "越出沙箱："),
+            event.kind.as_str(),
+            self.demo_path(&event.target_path),
+            self.demo_text(&rule)
+        );
+        self.alert_banner = Some((message, Instant::now()));
 
-        let total = cache.cells.len();
-        let event_count = total.min(6);
-        let mut seed = time_seed();
-        let mut selected = Vec::with_capacity(event_count);
+        if self.sandbox_alerts.len() >= SENSITIVE_ALERT_CAPACITY {
+            self.sandbox_alerts.pop_front();
+        }
+        self.sandbox_alerts.push_back(SensitiveAlert {
+            event: event.clone(),
+            rule,
+        });
+    }
 
-        for offset in 0..event_count {
-            seed = next_seed(seed ^ ((offset as u64 + 1) * 0x9E37_79B9));
-            let index = (seed as usize) % total;
-            let action_type = ACTION_TYPES[(seed as usize) % ACTION_TYPES.len()];
-            if let Some(cell) = cache.cells.get(index) {
-                selected.push((cell.path.clone(), action_type));
+    fn load_sandbox_policy(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        match SandboxPolicy::load(&path) {
+            Ok((policy, errors)) => {
+                self.sandbox = Some(policy);
+                self.sandbox_errors = errors;
             }
+            Err(error) => self.sandbox_errors = vec![error],
         }
+        self.layout_cache.clear();
+        self.treemap_cache = None;
+    }
 
-        for (path, action_type) in selected {
-            self.log_action(path, action_type);
+    fn render_external_commands_panel(&mut self, ui: &mut egui::Ui) {
+        ui.label(self.t(
+            "One command per line as Name = program arguments. {path} is the right-clicked entry, {dir} its folder, {name} its file name, {paths} every Ctrl+clicked entry. Commands run without a shell; start a line with > to show its output in the command log.",
+            "每行一条命令，格式为 名称 = 程序 参数。{path} 为右键点击的条目，{dir} 为其所在文件夹，{name} 为文件名，{paths} 为所有 Ctrl+点击选中的条目。命令不经过 shell 运行；以 > 开头的命令会在命令日志中显示输出。",
+        ));
+        ui.add(
+            egui::TextEdit::multiline(&mut self.external_commands_text)
+                .code_editor()
+                .desired_rows(6)
+                .desired_width(f32::INFINITY),
+        );
+        ui.horizontal(|ui| {
+            if ui.button(self.t("Apply commands", "应用命令")).clicked() {
+                let (commands, errors) = ExternalCommand::parse_list(&self.external_commands_text);
+                self.external_commands = commands;
+                self.external_command_errors = errors;
+            }
+            ui.small(format!(
+                "{} {}",
+                self.t("Commands:", "命令数："),
+                self.external_commands.len()
+            ));
+        });
+        for error in &self.external_command_errors {
+            ui.colored_label(Color32::from_rgb(210, 70, 70), error);
         }
     }
 
-    fn update_visual_lines(&mut self, delta_seconds: f32) {
-        let dt = delta_seconds.max(0.0);
-        let now = SystemTime::now();
-
-        for line in &mut self.visual_lines {
-            let age_from_timestamp = now
-                .duration_since(line.timestamp)
-                .unwrap_or(Duration::ZERO)
-                .as_secs_f32();
-            line.age = (line.age + dt).max(age_from_timestamp);
+    /// Right-click menu on a treemap cell with the external commands.
+    fn render_cell_context_menu(&mut self, ui: &mut egui::Ui) {
+        let Some((path, is_dir)) = self.context_target.clone() else {
+            ui.close_menu();
+            return;
+        };
+        let targets = if self
+            .selected_paths
+            .iter()
+            .any(|(selected, _)| *selected == path)
+        {
+            self.selected_paths.clone()
+        } else {
+            vec![(path.clone(), is_dir)]
+        };
+        if targets.len() > 1 {
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} {}",
+                    self.count(targets.len() as u64),
+                    self.t("selected entries", "个已选条目")
+                ))
+                .strong(),
+            );
+        } else {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string());
+            ui.label(egui::RichText::new(self.demo_name(&name, &path, is_dir)).strong());
         }
-
-        while self
-            .visual_lines
-            .front()
-            .map(|line| line.age > LINE_LIFETIME_SECONDS)
-            .unwrap_or(false)
+        ui.separator();
+        if ui
+            .button(self.t("Open in file manager", "在文件管理器中打开"))
+            .clicked()
         {
-            self.visual_lines.pop_front();
+            ui.close_menu();
+            if let Err(error) = reveal_in_file_manager(&path, is_dir) {
+                self.command_status = Some(error);
+            }
+            return;
         }
-
-        let total = self.visual_lines.len();
-        for (idx, line) in self.visual_lines.iter_mut().enumerate() {
-            let rank_from_newest = total.saturating_sub(idx + 1);
-            let base_opacity = if rank_from_newest < 10 {
-                1.0
-            } else if rank_from_newest < 20 {
-                0.5
-            } else {
-                0.2
-            };
-            let fade = (1.0 - line.age / LINE_LIFETIME_SECONDS).clamp(0.0, 1.0);
-            line.opacity = base_opacity * fade;
+        if ui
+            .button(self.t("Open containing folder", "打开所在文件夹"))
+            .clicked()
+        {
+            ui.close_menu();
+            if let Err(error) = open_folder(path.parent().unwrap_or(&path)) {
+                self.command_status = Some(error);
+            }
+            return;
         }
-    }
-
-    fn resolve_openclaw_world_pos(&self, cache: &TreemapCache) -> Option<egui::Pos2> {
-        let agent_path = self.agent_path.as_ref()?;
-        let agent_key = normalize_path_key(agent_path);
-
-        if let Some(pos) = cache.cell_centers.get(agent_path) {
-            return Some(*pos);
+        if ui
+            .button(self.t("Copy full path", "复制完整路径"))
+            .clicked()
+        {
+            // Masked in demo mode like every export.
+            let paths: Vec<String> = targets
+                .iter()
+                .map(|(target, _)| self.demo_path(target))
+                .collect();
+            ui.ctx().copy_text(paths.join("\n"));
+            ui.close_menu();
+            return;
         }
-
-        if let Some(pos) = cache.cell_centers_by_key.get(&agent_key) {
-            return Some(*pos);
+        if ui.button(self.t("Copy size", "复制大小")).clicked() {
+            let size = self.targets_size(&targets);
+            ui.ctx().copy_text(format!(
+                "{} ({})",
+                self.size(size),
+                exact_bytes(size, self.language)
+            ));
+            ui.close_menu();
+            return;
         }
-
-        let mut candidate = agent_path.clone();
-        while candidate.pop() {
-            if let Some(root) = &self.root_path {
-                if !path_within_root(&candidate, root) {
-                    break;
-                }
+        ui.separator();
+        if self.external_commands.is_empty() {
+            ui.label(self.t("No external commands defined", "未定义外部命令"));
+        }
+        let mut started = None;
+        for command in &self.external_commands {
+            if ui.button(&command.name).clicked() {
+                started = Some(command.clone());
             }
-
-            if let Some(pos) = cache.cell_centers.get(&candidate) {
-                return Some(*pos);
+        }
+        if ui
+            .button(self.t("Edit commands...", "编辑命令..."))
+            .clicked()
+        {
+            self.show_external_commands = true;
+            ui.close_menu();
+        }
+        if gateway::cleanup_mode() {
+            ui.separator();
+            let trash_text = egui::RichText::new(self.t("Move to trash...", "移到回收站..."))
+                .color(SANDBOX_DENIED_COLOR);
+            if ui.button(trash_text).clicked() {
+                ui.close_menu();
+                self.pending_trash = Some(targets);
+                return;
             }
-
-            let candidate_key = normalize_path_key(&candidate);
-            if let Some(pos) = cache.cell_centers_by_key.get(&candidate_key) {
-                return Some(*pos);
+        }
+        if !self.selected_paths.is_empty()
+            && ui.button(self.t("Clear selection", "清除选择")).clicked()
+        {
+            self.selected_paths.clear();
+            ui.close_menu();
+        }
+        if let Some(command) = started {
+            ui.close_menu();
+            if command.logged {
+                self.command_receivers
+                    .push(command.spawn_logged(&targets, self.waker.clone()));
+                self.show_command_log = true;
+                self.command_status = Some(format!(
+                    "{} {}",
+                    self.t("Running", "正在运行"),
+                    command.name
+                ));
+                return;
             }
+            self.command_status = Some(match command.run(&targets) {
+                Ok(()) => format!("{} {}", self.t("Started", "已启动"), command.name),
+                Err(error) => error,
+            });
         }
+    }
 
-        None
+    /// Total size of the targets; entries inside another target are already
+    /// in its size.
+    fn targets_size(&self, targets: &[(PathBuf, bool)]) -> u64 {
+        targets
+            .iter()
+            .filter(|(target, _)| {
+                !targets
+                    .iter()
+                    .any(|(other, _)| other != target && target.starts_with(other))
+            })
+            .filter_map(|(target, _)| self.scan_result.as_ref()?.root.find(target))
+            .map(|node| node.size)
+            .sum()
     }
 
-    fn render_openclaw_overlay(
-        &self,
-        painter: &egui::Painter,
-        cache: &TreemapCache,
-        canvas_rect: egui::Rect,
-    ) -> bool {
-        let Some(openclaw_world_pos) = self.resolve_openclaw_world_pos(cache) else {
-            return false;
+    /// Confirmation for "Move to trash...", listing what goes and how much.
+    fn render_trash_confirmation(&mut self, ui: &mut egui::Ui) {
+        let Some(targets) = self.pending_trash.clone() else {
+            return;
         };
-        let openclaw_pos = self.world_to_screen(openclaw_world_pos);
-        painter.circle_filled(openclaw_pos, 6.0, Color32::from_rgb(208, 58, 58));
-        painter.text(
-            openclaw_pos + egui::vec2(8.0, -8.0),
-            egui::Align2::LEFT_BOTTOM,
-            "OpenCLAW",
-            egui::FontId::proportional(12.0),
-            Color32::from_rgb(255, 210, 210),
-        );
-
-        let mut has_visible_line = false;
-        for line in &self.visual_lines {
-            if line.opacity <= 0.0 {
-                continue;
+        let size = self.targets_size(&targets);
+        ui.label(format!(
+            "{} {} ({}, {})",
+            self.t("Move", "将"),
+            self.count(targets.len() as u64),
+            self.size(size),
+            exact_bytes(size, self.language),
+        ));
+        ui.label(self.t(
+            "to the system trash? They can be restored from there.",
+            "个条目移到系统回收站？之后可以从回收站恢复。",
+        ));
+        egui::ScrollArea::vertical()
+            .max_height(160.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for (path, _) in &targets {
+                    ui.small(self.demo_path(path));
+                }
+            });
+        ui.separator();
+        ui.horizontal(|ui| {
+            let confirm_text = egui::RichText::new(self.t("Move to trash", "移到回收站"))
+                .color(SANDBOX_DENIED_COLOR);
+            if ui.button(confirm_text).clicked() {
+                self.pending_trash = None;
+                self.trash_entries(&targets);
             }
-
-            let Some(target_world_pos) = cache.cell_centers.get(&line.target_path) else {
-                continue;
-            };
-            let target_pos = self.world_to_screen(*target_world_pos);
-
-            if !canvas_rect.expand(32.0).contains(target_pos) {
-                continue;
+            if ui.button(self.t("Cancel", "取消")).clicked() {
+                self.pending_trash = None;
             }
-
-            let alpha = (line.opacity * 255.0).round().clamp(0.0, 255.0) as u8;
-            let line_color = Color32::from_rgba_unmultiplied(255, 0, 0, alpha);
-            painter.line_segment(
-                [target_pos, openclaw_pos],
-                egui::Stroke::new(1.0, line_color),
-            );
-            has_visible_line = true;
-        }
-
-        has_visible_line
-    }
-
-    fn pick_agent_path(&mut self) -> Option<PathBuf> {
-        rfd::FileDialog::new()
-            .set_title(self.t("Select OpenCLAW location", "选择 OpenCLAW 位置"))
-            .pick_folder()
+        });
     }
 
-    fn pick_and_scan(&mut self) {
-        if let Some(directory) = rfd::FileDialog::new()
-            .set_title(self.t("Select root directory", "选择根目录"))
-            .pick_folder()
-        {
-            self.start_scan(directory);
+    /// Moves entries to the trash through the gateway and drops them from
+    /// the tree; only offered in cleanup mode.
+    fn trash_entries(&mut self, targets: &[(PathBuf, bool)]) {
+        let mut removed = Vec::new();
+        let mut errors = Vec::new();
+        for (path, is_dir) in targets {
+            match gateway::remove(path) {
+                Ok(()) => removed.push((path.clone(), *is_dir)),
+                Err(error) => errors.push(error),
+            }
         }
+        self.selected_paths.clear();
+        self.remove_from_scan(&removed);
+        self.command_status = Some(match errors.first() {
+            Some(error) => error.clone(),
+            None => format!(
+                "{} {} {}",
+                self.t("Moved", "已将"),
+                self.count(removed.len() as u64),
+                self.t(
+                    "entries to the trash; restore them from the system trash",
+                    "个条目移到回收站；可从系统回收站恢复"
+                )
+            ),
+        });
     }
 
-    fn pick_startup_paths_and_scan(&mut self) {
-        let Some(agent_path) = self.pick_agent_path() else {
-            self.mode = AppMode::AwaitingDirectory;
+    /// Drops removed entries from the scan, shrinking folder sizes and the
+    /// scan counts, without a rescan.
+    fn remove_from_scan(&mut self, removed: &[(PathBuf, bool)]) {
+        if removed.is_empty() {
+            return;
+        }
+        let Some(mut result) = self.scan_result.take() else {
             return;
         };
+        for (path, is_dir) in removed {
+            let Some(node) = result.root.remove(path) else {
+                continue;
+            };
+            let (files, directories) = entry_counts(&node, *is_dir);
+            let stats = &mut result.stats;
+            stats.files_scanned = stats.files_scanned.saturating_sub(files);
+            stats.directories_scanned = stats.directories_scanned.saturating_sub(directories);
+            stats.entries_scanned = stats.entries_scanned.saturating_sub(files + directories);
+        }
+        let gone = |entry: &Path| removed.iter().any(|(path, _)| entry.starts_with(path));
+        result.symlinks.retain(|link| !gone(&link.path));
+        result
+            .permission_findings
+            .retain(|finding| !gone(&finding.path));
+        if !result.owners.is_empty() {
+            result.owners = summarize_owners(&result.root, &owner_names());
+        }
+        let analysis = ScanAnalysis::of(&result.root);
+        self.apply_scan_result(result, analysis);
+    }
 
-        let Some(root_path) = rfd::FileDialog::new()
-            .set_title(self.t("Select root directory", "选择根目录"))
-            .pick_folder()
-        else {
-            self.mode = AppMode::AwaitingDirectory;
+    /// Ctrl+click (Cmd+click on macOS) adds or removes the hovered entry.
+    fn toggle_selection(&mut self) {
+        let Some(hovered) = &self.hovered_entry else {
             return;
         };
+        match self
+            .selected_paths
+            .iter()
+            .position(|(path, _)| *path == hovered.path)
+        {
+            Some(index) => {
+                self.selected_paths.remove(index);
+            }
+            None => self
+                .selected_paths
+                .push((hovered.path.clone(), hovered.is_dir)),
+        }
+    }
 
-        self.agent_path = Some(agent_path);
-        self.start_scan(root_path);
+    fn poll_command_output(&mut self) {
+        let mut finished = Vec::new();
+        self.command_receivers.retain(|receiver| loop {
+            match receiver.try_recv() {
+                Ok(output) => finished.push(output),
+                Err(TryRecvError::Empty) => break true,
+                Err(TryRecvError::Disconnected) => break false,
+            }
+        });
+        for output in finished {
+            self.command_status = Some(format!(
+                "{} {}",
+                command_exit_text(&output.status),
+                output.name
+            ));
+            if self.command_log.len() >= COMMAND_LOG_CAPACITY {
+                self.command_log.pop_front();
+            }
+            self.command_log.push_back(output);
+        }
     }
 
-    fn start_scan(&mut self, root_path: PathBuf) {
-        self.scan_generation = self.scan_generation.wrapping_add(1);
-        self.root_path = Some(root_path.clone());
-        self.mode = AppMode::Scanning;
-        self.error_message = None;
-        self.scan_result = None;
-        self.scan_progress = ScanProgress::default();
-        self.hovered_entry = None;
-        self.treemap_cache = None;
-        self.type_stats.clear();
-        self.total_file_bytes = 0;
-        self.alias_map.clear();
-        self.action_log.clear();
-        self.visual_lines.clear();
-        self.scan_receiver = Some(spawn_scan(root_path, self.scan_config.clone()));
+    fn render_command_log(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if !self.command_receivers.is_empty() {
+                ui.spinner();
+                ui.small(self.t("Running...", "正在运行..."));
+            }
+            if ui.small_button(self.t("Clear", "清空")).clicked() {
+                self.command_log.clear();
+            }
+        });
+        if self.command_log.is_empty() {
+            ui.small(self.t(
+                "Commands marked with > in External commands log their output here.",
+                "外部命令中以 > 开头的命令会在此记录输出。",
+            ));
+        }
+        egui::ScrollArea::vertical()
+            .max_height(360.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for (index, output) in self.command_log.iter().enumerate().rev() {
+                    let failed = !matches!(output.status, Ok(Some(0)));
+                    let title = format!("{} - {}", output.name, command_exit_text(&output.status));
+                    let title = if failed {
+                        egui::RichText::new(title).color(Color32::from_rgb(210, 70, 70))
+                    } else {
+                        egui::RichText::new(title)
+                    };
+                    egui::CollapsingHeader::new(title)
+                        .id_salt(("command_log", index))
+                        .default_open(index + 1 == self.command_log.len())
+                        .show(ui, |ui| {
+                            ui.small(self.demo_text(&output.command_line.join(" ")));
+                            for text in [&output.stdout, &output.stderr] {
+                                if !text.is_empty() {
+                                    ui.label(
+                                        egui::RichText::new(self.demo_text(text.trim_end()))
+                                            .monospace(),
+                                    );
+                                }
+                            }
+                        });
+                }
+            });
     }
 
-    fn poll_scan_messages(&mut self, ctx: &egui::Context) {
-        if self.mode != AppMode::Scanning {
+    fn apply_sensitive_rules(&mut self) {
+        let (rules, errors) = SensitiveRules::parse(&self.sensitive_rules_text);
+        self.sensitive_rules = rules;
+        self.sensitive_rule_errors = errors;
+    }
+
+    /// Compares the current tree with the size limits, raising the alert
+    /// banner (and a desktop notification when enabled) for any breach.
+    fn check_thresholds(&mut self) {
+        self.threshold_breaches = self
+            .scan_result
+            .as_ref()
+            .map(|result| check_thresholds(&self.thresholds, &result.root))
+            .unwrap_or_default();
+        if self.threshold_breaches.is_empty() {
             return;
         }
 
-        let mut final_result = None;
-
-        if let Some(receiver) = &self.scan_receiver {
-            loop {
-                match receiver.try_recv() {
-                    Ok(ScanMessage::Progress(progress)) => {
-                        self.scan_progress = progress;
-                    }
-                    Ok(ScanMessage::Finished(result)) => {
-                        final_result = Some(result);
-                        break;
-                    }
-                    Err(TryRecvError::Empty) => break,
-                    Err(TryRecvError::Disconnected) => {
-                        final_result =
-                            Some(Err("Scan worker disconnected unexpectedly".to_string()));
-                        break;
-                    }
-                }
+        let details = self
+            .threshold_breaches
+            .iter()
+            .map(|breach| {
+                format!(
+                    "{} {} > {}",
+                    self.demo_path(&breach.path),
+                    self.size(breach.size),
+                    self.size(breach.limit)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let message = format!(
+            "{} {details}",
+            self.t("Size limit exceeded:", "超出大小限制：")
+        );
+        if self.threshold_notify {
+            if let Err(error) = notify_desktop("Tree Map Base", &message) {
+                self.threshold_errors = vec![error];
             }
         }
+        self.alert_banner = Some((message, Instant::now()));
+    }
 
-        if let Some(result) = final_result {
-            self.scan_receiver = None;
-
-            match result {
-                Ok(result) => {
-                    self.treemap_depth = self.treemap_depth.min(self.scan_config.max_depth.max(1));
-                    let (type_stats, total_file_bytes) = compute_type_stats(&result.root);
-                    self.alias_map = build_alias_map(&result.root);
-                    self.scan_result = Some(result);
-                    self.type_stats = type_stats;
-                    self.total_file_bytes = total_file_bytes;
-                    self.mode = AppMode::Ready;
-                    self.treemap_cache = None;
-                }
-                Err(error) => {
-                    self.error_message = Some(error);
-                    self.mode = AppMode::Error;
-                }
-            }
-        } else {
-            ctx.request_repaint_after(Duration::from_millis(80));
+    fn apply_thresholds(&mut self) {
+        let (thresholds, mut errors) = parse_thresholds(&self.thresholds_text);
+        self.thresholds = thresholds;
+        if let Err(error) = save_thresholds(&self.thresholds_text) {
+            errors.push(error);
         }
+        self.threshold_errors = errors;
+        self.check_thresholds();
     }
 
-    fn render_top_bar(&mut self, ui: &mut egui::Ui) {
-        let scanning = self.mode == AppMode::Scanning;
+    fn render_threshold_panel(&mut self, ui: &mut egui::Ui) {
+        ui.label(self.t(
+            "One rule per line, like \"D:\\backups > 500 GB\". Checked after every scan and rescan. Units are 1024-based like the size display (1 GB = 1024 MB).",
+            "每行一条规则，例如 \"D:\\backups > 500 GB\"。每次扫描和重新扫描后检查。单位与大小显示一致，按 1024 进位（1 GB = 1024 MB）。",
+        ));
+        ui.add(
+            egui::TextEdit::multiline(&mut self.thresholds_text)
+                .code_editor()
+                .desired_rows(4)
+                .desired_width(f32::INFINITY),
+        );
+        ui.horizontal(|ui| {
+            if ui.button(self.t("Apply rules", "应用规则")).clicked() {
+                self.apply_thresholds();
+            }
+            ui.small(format!(
+                "{} {}",
+                self.t("Active rules:", "生效规则："),
+                self.thresholds.len()
+            ));
+            let notify_text = self.t("Desktop notification", "桌面通知");
+            ui.checkbox(&mut self.threshold_notify, notify_text);
+        });
+        if let Some(path) = thresholds_path() {
+            ui.small(format!(
+                "{} {}",
+                self.t("Saved to", "保存到"),
+                path.display()
+            ));
+        }
+        for error in &self.threshold_errors {
+            ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+        }
 
-        ui.horizontal_wrapped(|ui| {
-            if ui
-                .button(self.t("Select OpenCLAW location...", "选择 OpenCLAW 位置..."))
-                .clicked()
-            {
-                if let Some(path) = self.pick_agent_path() {
-                    self.agent_path = Some(path);
-                    self.visual_lines.clear();
+        ui.separator();
+        if self.threshold_breaches.is_empty() {
+            ui.small(self.t("No folder is over its limit.", "没有文件夹超出限制。"));
+            return;
+        }
+        let mut jump = None;
+        egui::Grid::new("threshold_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for breach in &self.threshold_breaches {
+                    if ui
+                        .link(self.demo_path(&breach.path))
+                        .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                        .clicked()
+                    {
+                        jump = Some(breach.path.clone());
+                    }
+                    ui.colored_label(
+                        Color32::from_rgb(210, 70, 70),
+                        format!("{} > {}", self.size(breach.size), self.size(breach.limit)),
+                    );
+                    ui.end_row();
                 }
-            }
+            });
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
 
-            if let Some(agent) = &self.agent_path {
-                let agent_text = self.demo_path(agent);
-                ui.label(format!(
-                    "{} {}",
-                    self.t("OpenCLAW:", "OpenCLAW："),
-                    agent_text
-                ));
-            } else {
-                ui.label(self.t("OpenCLAW: (not selected)", "OpenCLAW：（未选择）"));
+    fn render_exclusion_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(root) = self.exclusions_root.clone() else {
+            ui.label(self.t(
+                "Choose a folder first; rules are saved per scanned folder.",
+                "请先选择文件夹；规则按扫描的文件夹分别保存。",
+            ));
+            return;
+        };
+        ui.label(format!(
+            "{} {}",
+            self.t("Rules for", "规则适用于"),
+            self.demo_path(&root)
+        ));
+        ui.small(self.t(
+            "One pattern per line: node_modules, *.iso, ~/Downloads/**, /var/cache. Paths without wildcards exclude everything beneath them.",
+            "每行一个模式：node_modules、*.iso、~/Downloads/**、/var/cache。不含通配符的路径会排除其下所有内容。",
+        ));
+        ui.add(
+            egui::TextEdit::multiline(&mut self.exclusions_text)
+                .code_editor()
+                .desired_rows(5)
+                .desired_width(f32::INFINITY),
+        );
+        let mut rescan = false;
+        ui.horizontal(|ui| {
+            if ui.button(self.t("Save", "保存")).clicked() {
+                self.save_root_exclusions();
             }
-
-            ui.separator();
             if ui
-                .button(self.t("Select root directory...", "选择根目录..."))
+                .button(self.t("Save and rescan", "保存并重新扫描"))
                 .clicked()
             {
-                self.pick_and_scan();
+                self.save_root_exclusions();
+                rescan = true;
             }
+        });
+        for error in &self.exclusion_errors {
+            ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+        }
 
-            if let Some(root) = &self.root_path {
-                let root_text = self.demo_path(root);
-                ui.label(format!("{} {}", self.t("Root:", "根目录："), root_text));
-            } else {
-                ui.label(self.t("Root: (not selected)", "根目录：（未选择）"));
+        let Some(result) = self
+            .scan_result
+            .as_ref()
+            .filter(|result| result.root.path == root)
+        else {
+            if rescan {
+                self.start_scan(root);
             }
+            return;
+        };
+        let stale = self
+            .exclusion_preview
+            .as_ref()
+            .is_none_or(|(text, generation, ..)| {
+                *text != self.exclusions_text || *generation != self.scan_generation
+            });
+        if stale {
+            let (rules, _) = ExcludeRules::parse(&self.exclusions_text);
+            let (rules, total) = rules.preview(&result.root);
+            self.exclusion_preview = Some((
+                self.exclusions_text.clone(),
+                self.scan_generation,
+                rules,
+                total,
+            ));
+        }
+        let Some((_, _, rules, total)) = &self.exclusion_preview else {
+            return;
+        };
 
-            if let (Some(agent), Some(root)) = (&self.agent_path, &self.root_path) {
-                if !path_within_root(agent, root) {
-                    ui.colored_label(
-                        Color32::from_rgb(210, 70, 70),
-                        self.t(
-                            "OpenCLAW path is outside root; marker will not be shown.",
-                            "OpenCLAW 路径不在根目录内，无法显示位置。",
-                        ),
-                    );
+        ui.separator();
+        ui.small(self.t(
+            "Preview against the current scan; entries already excluded by it are not counted.",
+            "基于当前扫描结果预览；当前扫描已排除的条目不计入。",
+        ));
+        let mut jump = None;
+        egui::Grid::new("exclusion_preview")
+            .striped(true)
+            .show(ui, |ui| {
+                for preview in rules.iter().chain([total]) {
+                    if preview.pattern.is_empty() {
+                        ui.strong(self.t("Total", "合计"));
+                    } else {
+                        ui.monospace(&preview.pattern);
+                    }
+                    ui.label(format!(
+                        "{} {}, {}",
+                        self.count(preview.files),
+                        self.t("files", "个文件"),
+                        self.size(preview.bytes)
+                    ));
+                    ui.vertical(|ui| {
+                        for example in &preview.examples {
+                            if ui
+                                .link(self.demo_path(example))
+                                .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                                .clicked()
+                            {
+                                jump = Some(example.clone());
+                            }
+                        }
+                    });
+                    ui.end_row();
                 }
+            });
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+        if rescan {
+            self.start_scan(root);
+        }
+    }
+
+    fn render_script_panel(&mut self, ui: &mut egui::Ui) {
+        ui.label(self.t(
+            "Color rules, one per line as `expression => #rrggbb`; the first match wins. Properties: path, name, ext, size, age (days), depth, is_dir.",
+            "颜色规则，每行一条，格式为 `表达式 => #rrggbb`；第一条匹配的规则生效。属性：path、name、ext、size、age（天）、depth、is_dir。",
+        ));
+        ui.add(
+            egui::TextEdit::multiline(&mut self.script_colors_text)
+                .code_editor()
+                .desired_rows(4)
+                .desired_width(f32::INFINITY),
+        );
+        ui.label(self.t(
+            "Filter: only files for which this is true are laid out (empty shows everything).",
+            "过滤：只布局使表达式为真的文件（留空显示全部）。",
+        ));
+        ui.add(
+            egui::TextEdit::singleline(&mut self.script_filter_text)
+                .code_editor()
+                .hint_text("size > 10 MB && age > 365")
+                .desired_width(f32::INFINITY),
+        );
+        ui.horizontal(|ui| {
+            if ui.button(self.t("Apply", "应用")).clicked() {
+                self.apply_script();
+            }
+            if ui.button(self.t("Clear", "清除")).clicked() {
+                self.script_filter_text.clear();
+                self.script = ScriptView::default();
+                self.script_errors.clear();
+                self.update_view_root();
+            }
+            if !self.script.is_empty() {
+                ui.small(format!(
+                    "{} {}",
+                    self.t("Active color rules:", "生效颜色规则："),
+                    self.script.colors.len()
+                ));
             }
+        });
+        for error in &self.script_errors {
+            ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+        }
+    }
 
-            ui.separator();
-            ui.label(self.t("Max recursion depth:", "最大递归深度："));
-            ui.add(egui::DragValue::new(&mut self.scan_config.max_depth).range(1..=256));
+    fn render_access_audit_panel(&mut self, ui: &mut egui::Ui) {
+        ui.label(self.t(
+            "Every access to scanned files goes through a read-only gateway. Moving entries to the trash is refused unless cleanup mode is on.",
+            "对扫描文件的每次访问都经过只读网关。除非开启清理模式，否则拒绝移到回收站。",
+        ));
+        let stats = gateway::stats();
+        egui::Grid::new("access_counts")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for kind in AccessKind::ALL {
+                    ui.label(access_kind_label(kind, self.language));
+                    ui.label(self.count(stats.count(kind)));
+                    ui.end_row();
+                }
+                ui.label(self.t("refused removals", "被拒绝的删除"));
+                ui.label(self.count(stats.denied));
+                ui.end_row();
+            });
 
-            let mut file_limit_enabled = self.scan_config.max_files.is_some();
+        let mut cleanup = gateway::cleanup_mode();
+        let cleanup_text = self.t(
+            "Cleanup mode (allow moving to the trash from the context menu)",
+            "清理模式（允许通过右键菜单移到回收站）",
+        );
+        if ui.checkbox(&mut cleanup, cleanup_text).changed() {
+            gateway::set_cleanup_mode(cleanup);
+        }
+
+        ui.separator();
+        let log = gateway::audit_log();
+        ui.horizontal(|ui| {
+            ui.strong(format!(
+                "{} ({})",
+                self.t("Audit log", "审计日志"),
+                log.len()
+            ));
             if ui
-                .checkbox(
-                    &mut file_limit_enabled,
-                    self.t("File count limit", "文件数量上限"),
+                .add_enabled(
+                    !log.is_empty(),
+                    egui::Button::new(self.t("Export...", "导出...")).small(),
                 )
-                .changed()
+                .clicked()
             {
-                if file_limit_enabled {
-                    if self.scan_config.max_files.is_none() {
-                        self.scan_config.max_files = Some(250_000);
-                    }
-                } else {
-                    self.scan_config.max_files = None;
+                self.export_access_audit();
+            }
+        });
+        egui::ScrollArea::vertical()
+            .max_height(260.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for record in log.iter().rev() {
+                    let event = match &record.event {
+                        AuditEvent::Scan => self.t("scan", "扫描").to_string(),
+                        AuditEvent::CleanupEnabled => {
+                            self.t("cleanup mode on", "清理模式开启").to_string()
+                        }
+                        AuditEvent::CleanupDisabled => {
+                            self.t("cleanup mode off", "清理模式关闭").to_string()
+                        }
+                        AuditEvent::Removed => self.t("moved to trash", "已移到回收站").to_string(),
+                        AuditEvent::RemovalDenied => {
+                            self.t("removal refused", "删除被拒绝").to_string()
+                        }
+                        AuditEvent::RemovalFailed(error) => {
+                            format!("{} ({error})", self.t("removal failed", "删除失败"))
+                        }
+                    };
+                    let path = record
+                        .path
+                        .as_ref()
+                        .map(|path| self.demo_path(path))
+                        .unwrap_or_default();
+                    ui.small(format!(
+                        "{} {event} {path}",
+                        format_clock_time(UNIX_EPOCH + Duration::from_secs(record.timestamp))
+                    ));
+                }
+            });
+    }
+
+    fn export_access_audit(&mut self) {
+        let lines = gateway::audit_log()
+            .into_iter()
+            .map(|mut record| {
+                if self.demo_mode {
+                    record.path = record
+                        .path
+                        .map(|path| PathBuf::from(self.alias_path(&path)));
                 }
+                serde_json::to_string(&record)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string());
+        self.export_json_lines("access-audit.jsonl", lines);
+    }
+
+    fn render_sandbox_panel(&mut self, ui: &mut egui::Ui) {
+        ui.label(self.t(
+            "The agent's allowed-directory configuration: a JSON file with `allow` and `deny` path patterns. Forbidden zones are tinted red, paths outside the allowlist are dimmed.",
+            "代理的允许目录配置：包含 `allow` 与 `deny` 路径模式的 JSON 文件。禁止区域以红色标出，允许列表外的路径变暗。",
+        ));
+        ui.horizontal(|ui| {
+            if ui.button(self.t("Load policy...", "加载策略...")).clicked() {
+                self.load_sandbox_policy();
+            }
+            if self.sandbox.is_some() && ui.button(self.t("Unload", "卸载")).clicked() {
+                self.sandbox = None;
+                self.sandbox_errors.clear();
+                self.layout_cache.clear();
+                self.treemap_cache = None;
             }
+            let shade_text = self.t("Shade treemap", "着色树状图");
+            if ui.checkbox(&mut self.sandbox_shading, shade_text).changed() {
+                self.layout_cache.clear();
+                self.treemap_cache = None;
+            }
+        });
+        if let Some(policy) = &self.sandbox {
+            let (allow, deny) = policy.rule_counts();
+            ui.small(format!(
+                "{} · {} {allow} · {} {deny}",
+                self.demo_path(&policy.source),
+                self.t("allowed:", "允许："),
+                self.t("denied:", "禁止："),
+            ));
+        }
+        for error in &self.sandbox_errors {
+            ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+        }
 
-            if let Some(limit) = &mut self.scan_config.max_files {
-                ui.add(
-                    egui::DragValue::new(limit)
-                        .range(1..=5_000_000)
-                        .speed(250.0),
-                );
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong(format!(
+                "{} ({})",
+                self.t("Violations", "越界动作"),
+                self.sandbox_alerts.len()
+            ));
+            if ui.small_button(self.t("Clear", "清空")).clicked() {
+                self.sandbox_alerts.clear();
             }
+        });
 
-            let can_rescan = !scanning && self.root_path.is_some();
-            if ui
-                .add_enabled(can_rescan, egui::Button::new(self.t("Rescan", "重新扫描")))
-                .clicked()
-            {
-                if let Some(root) = self.root_path.clone() {
-                    self.start_scan(root);
+        let mut jump_target = None;
+        egui::ScrollArea::vertical()
+            .max_height(260.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for alert in self.sandbox_alerts.iter().rev() {
+                    let text = format!(
+                        "{} {} {} [{}]",
+                        format_clock_time(alert.event.timestamp),
+                        alert.event.kind.as_str(),
+                        self.demo_path(&alert.event.target_path),
+                        self.demo_text(&alert.rule)
+                    );
+                    if ui
+                        .add(
+                            egui::Label::new(
+                                egui::RichText::new(text)
+                                    .small()
+                                    .color(SANDBOX_DENIED_COLOR),
+                            )
+                            .sense(egui::Sense::click()),
+                        )
+                        .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                        .clicked()
+                    {
+                        jump_target = Some(alert.event.target_path.clone());
+                    }
                 }
-            }
+            });
+        if jump_target.is_some() {
+            self.pending_jump = jump_target;
+        }
+    }
 
-            ui.separator();
-            let show_labels_text = self.t("Show labels in cells", "在方块中显示名称");
-            ui.checkbox(&mut self.show_cell_labels, show_labels_text);
-            let demo_mode_text = self.t("Demo anonymous mode", "演示匿名模式");
-            ui.checkbox(&mut self.demo_mode, demo_mode_text);
-            let simulate_text = self.t("Simulate OpenCLAW", "模拟 OpenCLAW");
-            if ui.button(simulate_text).clicked() {
-                self.simulate_agent_activity();
+    fn render_sensitive_panel(&mut self, ui: &mut egui::Ui) {
+        ui.label(self.t(
+            "One rule per line. Plain paths match everything beneath them; globs like **/secrets/** are supported; ~ is your home directory.",
+            "每行一条规则。普通路径匹配其下所有内容；支持 **/secrets/** 等通配；~ 表示主目录。",
+        ));
+        ui.add(
+            egui::TextEdit::multiline(&mut self.sensitive_rules_text)
+                .code_editor()
+                .desired_rows(6)
+                .desired_width(f32::INFINITY),
+        );
+        ui.horizontal(|ui| {
+            if ui.button(self.t("Apply rules", "应用规则")).clicked() {
+                self.apply_sensitive_rules();
             }
-
-            let action_count = self.action_log.len();
             ui.small(format!(
                 "{} {}",
-                self.t("OpenCLAW actions:", "OpenCLAW 动作："),
-                action_count
+                self.t("Active rules:", "生效规则："),
+                self.sensitive_rules.len()
             ));
+        });
+        for error in &self.sensitive_rule_errors {
+            ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+        }
 
-            if let Some(last_action) = self.action_log.latest() {
-                let age_seconds = SystemTime::now()
-                    .duration_since(last_action.timestamp)
-                    .unwrap_or(Duration::ZERO)
-                    .as_secs_f32();
-                let target_text = self.demo_path(&last_action.target_path);
-                ui.small(format!(
-                    "{} {} ({:.1}s) | {}",
-                    self.t("Last:", "最近："),
-                    last_action.action_type,
-                    age_seconds,
-                    target_text
-                ));
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong(format!(
+                "{} ({})",
+                self.t("Alerts", "警报"),
+                self.sensitive_alerts.len()
+            ));
+            if ui.small_button(self.t("Clear", "清空")).clicked() {
+                self.sensitive_alerts.clear();
             }
-
-            let language_button = match self.language {
-                Language::English => "中文",
-                Language::Chinese => "English",
-            };
-            if ui.button(language_button).clicked() {
-                self.language.toggle();
+            if ui
+                .add_enabled(
+                    !self.sensitive_alerts.is_empty(),
+                    egui::Button::new(self.t("Export...", "导出...")).small(),
+                )
+                .clicked()
+            {
+                self.export_sensitive_alerts();
             }
+        });
 
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui
-                    .small_button(self.t("Reset View", "重置视图"))
-                    .on_hover_text(self.t("Reset pan and zoom", "重置平移与缩放"))
-                    .clicked()
-                {
-                    self.zoom_factor = 1.0;
-                    self.offset = egui::Vec2::ZERO;
+        let mut jump_target = None;
+        egui::ScrollArea::vertical()
+            .max_height(260.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for alert in self.sensitive_alerts.iter().rev() {
+                    let text = format!(
+                        "{} {} {} [{}]",
+                        format_clock_time(alert.event.timestamp),
+                        alert.event.kind.as_str(),
+                        self.demo_path(&alert.event.target_path),
+                        self.demo_text(&alert.rule)
+                    );
+                    if ui
+                        .add(
+                            egui::Label::new(
+                                egui::RichText::new(text)
+                                    .small()
+                                    .color(SENSITIVE_LINE_COLOR),
+                            )
+                            .sense(egui::Sense::click()),
+                        )
+                        .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                        .clicked()
+                    {
+                        jump_target = Some(alert.event.target_path.clone());
+                    }
                 }
             });
-        });
+        if jump_target.is_some() {
+            self.pending_jump = jump_target;
+        }
     }
 
-    fn render_status_bar(&self, ui: &mut egui::Ui) {
-        ui.horizontal_wrapped(|ui| {
-            ui.small(self.t(
-                "Mode: read-only visualization (no file operations)",
-                "模式：只读可视化（不进行文件操作）",
-            ));
+    fn simulation_targets(&self) -> Vec<SimulationTarget> {
+        let Some(cache) = self.treemap_cache.as_ref() else {
+            return Vec::new();
+        };
 
-            if let Some(hovered) = &self.hovered_entry {
-                let name_text = self.demo_name(&hovered.name, &hovered.path, hovered.is_dir);
-                let path_text = self.demo_path(&hovered.path);
-                ui.separator();
-                ui.small(format!(
-                    "{} | {} | {}",
-                    name_text,
-                    human_size(hovered.size),
-                    path_text
-                ));
-            } else if let Some(root) = &self.root_path {
-                let root_text = self.demo_path(root);
-                ui.separator();
-                ui.small(format!(
-                    "{} {}",
-                    self.t(
-                        "Hover a rectangle to inspect full path. Root:",
-                        "把鼠标停在方块上可查看完整路径。根目录："
-                    ),
-                    root_text
-                ));
-            }
-        });
+        cache
+            .cells
+            .iter()
+            .map(|cell| SimulationTarget {
+                path: cell.path.clone(),
+                size: cell.size,
+                is_dir: cell.is_dir,
+            })
+            .collect()
     }
 
-    fn render_warning_banner(&self, ui: &mut egui::Ui, message: &str) {
-        egui::Frame::none()
-            .fill(Color32::from_rgb(255, 232, 147))
-            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(177, 116, 0)))
-            .inner_margin(egui::Margin::same(6.0))
-            .show(ui, |ui| {
-                ui.colored_label(Color32::from_rgb(74, 54, 0), message);
+    fn simulate_agent_activity(&mut self) {
+        let targets = self.simulation_targets();
+        for event in self.simulator.burst(&targets) {
+            self.log_action(event);
+        }
+    }
+
+    fn run_continuous_simulation(&mut self, ctx: &egui::Context, delta_seconds: f32) {
+        if !self.simulator.config.continuous {
+            return;
+        }
+
+        let targets = self.simulation_targets();
+        for event in self.simulator.tick(delta_seconds, &targets) {
+            self.log_action(event);
+        }
+        ctx.request_repaint_after(Duration::from_millis(33));
+    }
+
+    fn render_simulation_settings(&mut self, ui: &mut egui::Ui) {
+        let menu_text = self.t("Simulation...", "模拟设置...");
+        ui.menu_button(menu_text, |ui| {
+            let config = &mut self.simulator.config;
+            let mut reset = false;
+
+            egui::Grid::new("simulation_settings")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(match self.language {
+                        Language::English => "Events per second:",
+                        Language::Chinese => "每秒事件数：",
+                    });
+                    ui.add(
+                        egui::DragValue::new(&mut config.events_per_second)
+                            .range(0.1..=200.0)
+                            .speed(0.2),
+                    );
+                    ui.end_row();
+
+                    ui.label(match self.language {
+                        Language::English => "Target bias:",
+                        Language::Chinese => "目标偏好：",
+                    });
+                    egui::ComboBox::from_id_salt("simulation_target_bias")
+                        .selected_text(target_bias_label(config.target_bias, self.language))
+                        .show_ui(ui, |ui| {
+                            for bias in TargetBias::ALL {
+                                ui.selectable_value(
+                                    &mut config.target_bias,
+                                    bias,
+                                    target_bias_label(bias, self.language),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    let mut fixed_seed = config.seed.is_some();
+                    let fixed_seed_text = match self.language {
+                        Language::English => "Fixed seed:",
+                        Language::Chinese => "固定种子：",
+                    };
+                    if ui.checkbox(&mut fixed_seed, fixed_seed_text).changed() {
+                        config.seed = fixed_seed.then_some(1);
+                        reset = true;
+                    }
+                    if let Some(seed) = &mut config.seed {
+                        reset |= ui.add(egui::DragValue::new(seed)).changed();
+                    } else {
+                        ui.label("-");
+                    }
+                    ui.end_row();
+                });
+
+            ui.separator();
+            ui.label(match self.language {
+                Language::English => "Action mix (relative weights):",
+                Language::Chinese => "动作比例（相对权重）：",
+            });
+            egui::Grid::new("simulation_kind_weights")
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for (index, (kind, weight)) in config.kind_weights.iter_mut().enumerate() {
+                        ui.colored_label(color_for_action_kind(*kind), kind.as_str());
+                        ui.add(egui::DragValue::new(weight).range(0..=100));
+                        if index % 2 == 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+
+            ui.separator();
+            let continuous_text = match self.language {
+                Language::English => "Run continuously",
+                Language::Chinese => "持续运行",
+            };
+            if ui
+                .checkbox(&mut config.continuous, continuous_text)
+                .changed()
+                && config.continuous
+            {
+                reset = true;
+            }
+            let restart_text = match self.language {
+                Language::English => "Restart sequence",
+                Language::Chinese => "重新开始序列",
+            };
+            reset |= ui.button(restart_text).clicked();
+
+            if reset {
+                self.simulator.reset();
+            }
+        });
+    }
+
+    fn toggle_action_listener(&mut self) {
+        if self.action_listener.take().is_some() {
+            self.listener_status = ListenerStatus::Stopped;
+            return;
+        }
+
+        self.action_listener = Some(ActionListener::spawn(
+            &self.listen_address,
+            self.waker.clone(),
+        ));
+    }
+
+    fn toggle_stream_listener(&mut self) {
+        if self.stream_listener.take().is_some() {
+            self.stream_status = ListenerStatus::Stopped;
+            return;
+        }
+
+        if self.stream_url.trim().is_empty() {
+            return;
+        }
+        self.stream_listener = Some(ActionListener::spawn_stream(
+            &self.stream_url,
+            self.waker.clone(),
+        ));
+    }
+
+    fn toggle_ipc_listener(&mut self) {
+        if self.ipc_listener.take().is_some() {
+            self.ipc_status = ListenerStatus::Stopped;
+            return;
+        }
+
+        self.ipc_listener = Some(ActionListener::spawn_local(
+            &self.ipc_name,
+            self.waker.clone(),
+        ));
+    }
+
+    /// Entry point for externally ingested events; honors the pause buffer.
+    fn ingest_action(&mut self, mut event: ActionEvent) {
+        if let Some(root) = &self.root_path {
+            if event.target_path.is_relative() {
+                event.target_path = root.join(&event.target_path);
+            }
+        }
+
+        if self.ingest_paused {
+            if self.paused_buffer.len() >= PAUSE_BUFFER_CAPACITY {
+                self.paused_buffer.pop_front();
+                self.paused_dropped = self.paused_dropped.saturating_add(1);
+            }
+            self.paused_buffer.push_back(event);
+            return;
+        }
+
+        self.log_action(event);
+    }
+
+    fn resume_ingest(&mut self, replay: bool) {
+        self.ingest_paused = false;
+        self.paused_dropped = 0;
+        let buffered = std::mem::take(&mut self.paused_buffer);
+        if replay {
+            for event in buffered {
+                self.log_action(event);
+            }
+        }
+    }
+
+    fn render_listener_status(&self, ui: &mut egui::Ui, status: &ListenerStatus, off_text: &str) {
+        match status {
+            ListenerStatus::Listening { address, clients } => {
+                let color = if *clients > 0 {
+                    Color32::from_rgb(80, 170, 90)
+                } else {
+                    Color32::from_rgb(200, 160, 60)
+                };
+                ui.colored_label(
+                    color,
+                    format!(
+                        "{} {} ({} {})",
+                        self.t("Listening on", "监听中"),
+                        address,
+                        clients,
+                        self.t("connected", "个连接")
+                    ),
+                );
+            }
+            ListenerStatus::Connected { address } => {
+                ui.colored_label(
+                    Color32::from_rgb(80, 170, 90),
+                    format!("{} {}", self.t("Connected to", "已连接"), address),
+                );
+            }
+            ListenerStatus::Reconnecting {
+                address,
+                retry_in,
+                error,
+            } => {
+                ui.colored_label(
+                    Color32::from_rgb(200, 160, 60),
+                    format!(
+                        "{} {} ({} {}s)",
+                        self.t("Reconnecting to", "正在重连"),
+                        address,
+                        self.t("retry in", "重试于"),
+                        retry_in.as_secs()
+                    ),
+                )
+                .on_hover_text(error);
+            }
+            ListenerStatus::Failed(error) => {
+                ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+            }
+            ListenerStatus::Stopped => {
+                ui.small(off_text);
+            }
+        }
+    }
+
+    fn render_ingest_pause_controls(&mut self, ui: &mut egui::Ui) {
+        if !self.ingest_paused {
+            if ui
+                .button(self.t("Pause feed", "暂停动作"))
+                .on_hover_text(self.t(
+                    "Buffer incoming actions without drawing them",
+                    "缓存传入动作但不绘制",
+                ))
+                .clicked()
+            {
+                self.ingest_paused = true;
+            }
+            return;
+        }
+
+        let mut badge = format!(
+            "{} {}",
+            self.t("Paused, buffered:", "已暂停，缓存："),
+            self.count(self.paused_buffer.len() as u64)
+        );
+        if self.paused_dropped > 0 {
+            badge.push_str(&format!(
+                " (+{} {})",
+                self.count(self.paused_dropped),
+                self.t("dropped", "已丢弃")
+            ));
+        }
+        ui.colored_label(Color32::from_rgb(200, 160, 60), badge);
+
+        if ui.button(self.t("Resume + replay", "恢复并回放")).clicked() {
+            self.resume_ingest(true);
+        }
+        if ui
+            .button(self.t("Resume + discard", "恢复并丢弃"))
+            .clicked()
+        {
+            self.resume_ingest(false);
+        }
+    }
+
+    fn restart_fs_watcher(&mut self) {
+        self.fs_watcher = None;
+        self.watch_error = None;
+        if !self.watch_enabled {
+            return;
+        }
+
+        let Some(root) = &self.root_path else {
+            return;
+        };
+
+        match FsWatcher::start(root, self.waker.clone()) {
+            Ok(watcher) => self.fs_watcher = Some(watcher),
+            Err(error) => self.watch_error = Some(error),
+        }
+    }
+
+    fn poll_watch_messages(&mut self) {
+        let Some(watcher) = &self.fs_watcher else {
+            return;
+        };
+
+        let mut changes = Vec::new();
+        while let Ok(message) = watcher.receiver().try_recv() {
+            match message {
+                WatchMessage::Change(event) => changes.push(event),
+                WatchMessage::Error(error) => self.watch_error = Some(error),
+            }
+        }
+
+        let now = SystemTime::now();
+        for event in &changes {
+            self.observe_size_change(event, now);
+        }
+        self.size_correlator.prune(now);
+
+        for event in changes {
+            self.ingest_action(event);
+        }
+    }
+
+    /// Feeds a watcher change into the size correlator and the live treemap.
+    fn observe_size_change(&mut self, event: &ActionEvent, now: SystemTime) {
+        let path = &event.target_path;
+        let new_size = match gateway::metadata(path) {
+            Ok(metadata) if metadata.is_file() => Some(metadata.len()),
+            Ok(_) => return,
+            Err(_) => None,
+        };
+        let baseline = self
+            .scan_result
+            .as_ref()
+            .and_then(|scan_result| scan_result.root.find(path))
+            .filter(|node| node.children.is_empty())
+            .map(|node| node.size);
+
+        self.size_correlator
+            .observe_size(path, new_size, baseline, now);
+        if baseline.is_some() {
+            self.apply_live_size_change(path, new_size.unwrap_or(0));
+        }
+    }
+
+    /// Updates the tree for one changed file and patches the cached treemap
+    /// in place; a full relayout waits until the distortion grows too large.
+    fn apply_live_size_change(&mut self, path: &Path, new_size: u64) {
+        let Some(scan_result) = self.scan_result.as_mut() else {
+            return;
+        };
+        let Some(old_size) = scan_result.root.update_file_size(path, new_size) else {
+            return;
+        };
+        if old_size == new_size {
+            return;
+        }
+        let root_size = scan_result.root.size.max(1);
+
+        self.total_file_bytes = self
+            .total_file_bytes
+            .saturating_sub(old_size)
+            .saturating_add(new_size);
+        let type_key = self.type_key_of(path);
+        if let Some(stat) = self.type_stats.iter_mut().find(|stat| stat.key == type_key) {
+            stat.bytes = stat.bytes.saturating_sub(old_size).saturating_add(new_size);
+        }
+        // Other remembered layouts still show the old sizes.
+        self.layout_cache.clear();
+
+        let Some(mut cache) = self.treemap_cache.take() else {
+            return;
+        };
+        for cell in &mut cache.cells {
+            if !path.starts_with(&cell.path) {
+                continue;
+            }
+            cell.size = cell.size.saturating_sub(old_size).saturating_add(new_size);
+            if path.parent() == Some(cell.path.as_path()) {
+                cell.own_size = cell
+                    .own_size
+                    .saturating_sub(old_size)
+                    .saturating_add(new_size);
+            }
+            self.format_cell_labels(cell, root_size);
+
+            let drift = cell.size as f32 / cell.laid_out_size.max(1) as f32 - 1.0;
+            // Patches scale cells by bytes; other metrics need a real layout.
+            if drift.abs() > MAX_PATCH_DISTORTION || self.layout_metric != LayoutMetric::Bytes {
+                cache.relayout_due = true;
+            }
+        }
+        cache.reflow_patched_cells();
+        cache.patched = true;
+        cache.static_layer = None;
+        self.treemap_cache = Some(cache);
+    }
+
+    fn poll_api_requests(&mut self) {
+        let labels = |node: &Node| self.export_label(node);
+        let state = ApiState {
+            scan: self
+                .scan_result
+                .as_ref()
+                .filter(|_| !self.scan_result_provisional),
+            type_stats: &self.type_stats,
+            scanning: self.mode == AppMode::Scanning,
+            actions_by_kind: &self.actions_by_kind,
+            labels: &labels,
+        };
+        for server in [&self.api_server, &self.metrics_server] {
+            let Ok(Some(server)) = server else {
+                continue;
+            };
+            while let Ok(request) = server.receiver().try_recv() {
+                let response = answer(&request.query, &state);
+                request.respond(response);
+            }
+        }
+    }
+
+    fn measure_memory(&self) -> MemoryReport {
+        let (action_log, action_log_entries) = self
+            .action_log
+            .with_entries(|entries| (total_size(entries), entries.len()))
+            .unwrap_or_default();
+
+        MemoryReport {
+            tree: self.tree_bytes,
+            treemap_cache: self.treemap_cache.as_ref().map_or(0, total_size),
+            layout_cache: self.layout_cache.values().map(total_size).sum(),
+            alias_map: total_size(&self.alias_map),
+            action_log,
+            action_log_entries,
+        }
+    }
+
+    fn render_memory_panel(&mut self, ui: &mut egui::Ui) {
+        let stale = self
+            .memory_report
+            .is_none_or(|(measured_at, _)| measured_at.elapsed() >= MemoryReport::REFRESH_INTERVAL);
+        if stale {
+            self.memory_report = Some((Instant::now(), self.measure_memory()));
+        }
+        let Some((_, report)) = self.memory_report else {
+            return;
+        };
+
+        ui.label(self.t(
+            "Approximate heap use; allocator overhead is not included.",
+            "近似堆内存占用，不含分配器开销。",
+        ));
+        egui::Grid::new("memory_usage_grid")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                let rows = [
+                    (
+                        self.t("Node tree", "节点树"),
+                        report.tree,
+                        self.t("Lower the file count limit", "降低文件数量上限"),
+                    ),
+                    (
+                        self.t("Treemap cache", "Treemap 缓存"),
+                        report.treemap_cache,
+                        self.t("Lower max rendered nodes", "降低最大渲染节点"),
+                    ),
+                    (
+                        self.t("Recent layouts", "最近布局"),
+                        report.layout_cache,
+                        "",
+                    ),
+                    (
+                        self.t("Alias map", "别名表"),
+                        report.alias_map,
+                        self.t("Grows with the node tree", "随节点树增长"),
+                    ),
+                    (
+                        self.t("Action log", "动作日志"),
+                        report.action_log,
+                        self.t("Lower the history capacity", "降低历史容量"),
+                    ),
+                ];
+                for (label, bytes, hint) in rows {
+                    ui.label(label);
+                    ui.monospace(self.size(bytes as u64));
+                    ui.small(hint);
+                    ui.end_row();
+                }
+                ui.strong(self.t("Total", "合计"));
+                ui.monospace(self.size(report.total() as u64));
+                ui.small(format!(
+                    "{} {}",
+                    self.count(report.action_log_entries as u64),
+                    self.t("logged actions", "条动作记录")
+                ));
+                ui.end_row();
+            });
+    }
+
+    fn render_size_attribution(&self, ui: &mut egui::Ui) {
+        let Some(root_path) = &self.root_path else {
+            return;
+        };
+
+        ui.label(self.t(
+            "Size changes seen by the filesystem watcher, attributed to the agent that acted on the same path within 5 minutes.",
+            "文件系统监视器观察到的大小变化，归因于 5 分钟内操作过同一路径的代理。",
+        ));
+        if self.fs_watcher.is_none() {
+            ui.colored_label(
+                Color32::from_rgb(200, 160, 60),
+                self.t(
+                    "Enable \"Watch filesystem\" to observe size changes.",
+                    "启用“监视文件系统”以观察大小变化。",
+                ),
+            );
+        }
+        ui.separator();
+
+        let summaries =
+            self.size_correlator
+                .summaries(root_path, SystemTime::now(), ATTRIBUTION_WINDOW);
+        if summaries.is_empty() {
+            ui.label(self.t("No attributed size changes yet.", "尚无可归因的大小变化。"));
+            return;
+        }
+
+        for summary in summaries {
+            let directory = if summary.directory == *root_path {
+                self.demo_path(&summary.directory)
+            } else {
+                format!("{}/", self.demo_path(&summary.directory))
+            };
+            let net = summary.bytes_added as i128 - summary.bytes_removed as i128;
+            let (verb, sign, color) = if net >= 0 {
+                (
+                    self.t("wrote", "写入"),
+                    "+",
+                    Color32::from_rgb(220, 120, 80),
+                )
+            } else {
+                (self.t("freed", "释放"), "-", Color32::from_rgb(80, 170, 90))
+            };
+            ui.colored_label(
+                color,
+                format!(
+                    "{} {} {}{} {} {} {}",
+                    summary.agent_id,
+                    verb,
+                    sign,
+                    self.size(net.unsigned_abs() as u64),
+                    self.t("to", "到"),
+                    directory,
+                    self.t("in the last 5 min", "（最近 5 分钟）"),
+                ),
+            )
+            .on_hover_text(format!(
+                "+{} / -{} ({} {})",
+                self.size(summary.bytes_added),
+                self.size(summary.bytes_removed),
+                summary.changes,
+                self.t("changes", "次变化")
+            ));
+        }
+    }
+
+    fn poll_ingest_messages(&mut self) {
+        let mut actions = Vec::new();
+
+        if let Some(listener) = &self.action_listener {
+            let disconnected = drain_ingest_messages(
+                listener.receiver(),
+                &mut actions,
+                &mut self.rejected_events,
+                &mut self.last_rejected_event,
+                &mut self.listener_status,
+            );
+            if disconnected {
+                self.action_listener = None;
+            }
+        }
+
+        if let Some(listener) = &self.ipc_listener {
+            let disconnected = drain_ingest_messages(
+                listener.receiver(),
+                &mut actions,
+                &mut self.rejected_events,
+                &mut self.last_rejected_event,
+                &mut self.ipc_status,
+            );
+            if disconnected {
+                self.ipc_listener = None;
+            }
+        }
+
+        if let Some(listener) = &self.stream_listener {
+            let disconnected = drain_ingest_messages(
+                listener.receiver(),
+                &mut actions,
+                &mut self.rejected_events,
+                &mut self.last_rejected_event,
+                &mut self.stream_status,
+            );
+            if disconnected {
+                self.stream_listener = None;
+            }
+        }
+
+        if let Some(receiver) = &self.stdin_receiver {
+            let mut status = ListenerStatus::Stopped;
+            let disconnected = drain_ingest_messages(
+                receiver,
+                &mut actions,
+                &mut self.rejected_events,
+                &mut self.last_rejected_event,
+                &mut status,
+            );
+            if disconnected {
+                self.stdin_receiver = None;
+                self.stdin_closed = true;
+            }
+        }
+
+        for action in actions {
+            self.ingest_action(action);
+        }
+    }
+
+    fn update_visual_lines(&mut self, delta_seconds: f32) {
+        let dt = delta_seconds.max(0.0);
+        self.activity_heat.decay(dt, self.heat_half_life_seconds);
+        let now = SystemTime::now();
+
+        for line in &mut self.visual_lines {
+            let age_from_timestamp = now
+                .duration_since(line.timestamp)
+                .unwrap_or(Duration::ZERO)
+                .as_secs_f32();
+            line.age = (line.age + dt).max(age_from_timestamp);
+        }
+
+        while self
+            .visual_lines
+            .front()
+            .map(|line| line.age > LINE_LIFETIME_SECONDS)
+            .unwrap_or(false)
+        {
+            self.visual_lines.pop_front();
+        }
+
+        let total = self.visual_lines.len();
+        for (idx, line) in self.visual_lines.iter_mut().enumerate() {
+            let rank_from_newest = total.saturating_sub(idx + 1);
+            let base_opacity = if rank_from_newest < 10 {
+                1.0
+            } else if rank_from_newest < 20 {
+                0.5
+            } else {
+                0.2
+            };
+            let fade = (1.0 - line.age / LINE_LIFETIME_SECONDS).clamp(0.0, 1.0);
+            line.opacity = base_opacity * fade;
+        }
+    }
+
+    fn resolve_openclaw_world_pos(&self, cache: &TreemapCache) -> Option<egui::Pos2> {
+        let agent_path = self.agent_path.as_ref()?;
+        self.resolve_world_pos(cache, agent_path)
+    }
+
+    /// Center of the cell for `path`, or of its nearest laid-out ancestor.
+    fn resolve_world_pos(&self, cache: &TreemapCache, path: &Path) -> Option<egui::Pos2> {
+        if let Some(pos) = cache.cell_centers.get(path) {
+            return Some(*pos);
+        }
+
+        if let Some(pos) = cache.cell_centers_by_key.get(&normalize_path_key(path)) {
+            return Some(*pos);
+        }
+
+        let mut candidate = path.to_path_buf();
+        while candidate.pop() {
+            if let Some(root) = &self.root_path {
+                if !path_within_root(&candidate, root) {
+                    break;
+                }
+            }
+
+            if let Some(pos) = cache.cell_centers.get(&candidate) {
+                return Some(*pos);
+            }
+
+            let candidate_key = normalize_path_key(&candidate);
+            if let Some(pos) = cache.cell_centers_by_key.get(&candidate_key) {
+                return Some(*pos);
+            }
+        }
+
+        None
+    }
+
+    /// Flashes the cells hit by recent actions; returns whether any pulse is animating.
+    fn render_action_pulses(
+        &self,
+        painter: &egui::Painter,
+        cache: &TreemapCache,
+        canvas_rect: egui::Rect,
+    ) -> bool {
+        let mut animating = false;
+        for line in &self.visual_lines {
+            if line.age >= PULSE_SECONDS || self.hidden_action_kinds.contains(&line.kind) {
+                continue;
+            }
+
+            let Some(world_pos) = self.resolve_world_pos(cache, &line.target_path) else {
+                continue;
+            };
+            let Some(cell) = cache
+                .cells
+                .iter()
+                .rev()
+                .find(|cell| cell.rect.contains(world_pos))
+            else {
+                continue;
+            };
+
+            let rect = self.transform_rect_for_view(cell.rect);
+            if !rect.intersects(canvas_rect) {
+                continue;
+            }
+
+            let progress = (line.age / PULSE_SECONDS).clamp(0.0, 1.0);
+            let envelope = 1.0 - progress;
+            let grow = 4.0 * (progress * std::f32::consts::PI).sin();
+            let [r, g, b, _] = color_for_action_kind(line.kind).to_array();
+            let fill_alpha = (150.0 * envelope).round() as u8;
+            let stroke_alpha = (230.0 * envelope).round() as u8;
+
+            painter.rect_filled(
+                rect,
+                0.0,
+                Color32::from_rgba_unmultiplied(r, g, b, fill_alpha),
+            );
+            painter.rect_stroke(
+                rect.expand(grow),
+                0.0,
+                egui::Stroke::new(
+                    2.0,
+                    Color32::from_rgba_unmultiplied(255, 255, 255, stroke_alpha),
+                ),
+            );
+            animating = true;
+        }
+
+        animating
+    }
+
+    /// Connects the most recent action targets in order, fading older hops.
+    fn render_focus_trail(&self, painter: &egui::Painter, cache: &TreemapCache) {
+        let points: Vec<egui::Pos2> = self
+            .focus_trail
+            .iter()
+            .filter(|(_, kind)| !self.hidden_action_kinds.contains(kind))
+            .filter_map(|(path, _)| self.resolve_world_pos(cache, path))
+            .map(|world_pos| self.world_to_screen(world_pos))
+            .collect();
+        if points.is_empty() {
+            return;
+        }
+
+        let count = points.len();
+        for (index, segment) in points.windows(2).enumerate() {
+            let recency = (index + 2) as f32 / count as f32;
+            let alpha = (40.0 + 200.0 * recency).round() as u8;
+            painter.line_segment(
+                [segment[0], segment[1]],
+                egui::Stroke::new(
+                    1.0 + 2.0 * recency,
+                    Color32::from_rgba_unmultiplied(120, 230, 255, alpha),
+                ),
+            );
+        }
+
+        for (index, point) in points.iter().enumerate() {
+            let recency = (index + 1) as f32 / count as f32;
+            let alpha = (60.0 + 195.0 * recency).round() as u8;
+            painter.circle_filled(
+                *point,
+                2.0 + 2.0 * recency,
+                Color32::from_rgba_unmultiplied(200, 245, 255, alpha),
+            );
+        }
+    }
+
+    fn render_symlink_arrows(&self, painter: &egui::Painter, cache: &TreemapCache) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        for link in result
+            .symlinks
+            .iter()
+            .filter(|link| link.status == LinkStatus::Inside)
+        {
+            let (Some(from), Some(to)) = (
+                self.resolve_world_pos(cache, &link.path),
+                self.resolve_world_pos(cache, &link.resolved),
+            ) else {
+                continue;
+            };
+            let (from, to) = (self.world_to_screen(from), self.world_to_screen(to));
+            if from.distance(to) < 4.0 {
+                continue;
+            }
+            painter.arrow(
+                from,
+                to - from,
+                egui::Stroke::new(1.5, symlink_status_color(LinkStatus::Inside)),
+            );
+        }
+    }
+
+    /// Tints folders by the actions beneath them and badges them with the count.
+    ///
+    /// Cells are visited parent-first, so a badge overlapping an earlier one
+    /// is skipped and zoomed-out views show the outermost folders.
+    fn render_action_rollup(
+        &self,
+        painter: &egui::Painter,
+        cache: &TreemapCache,
+        visible_cells: &[usize],
+        font: egui::FontId,
+    ) {
+        let max_count = self.action_rollup.max_count.max(1) as f32;
+        let mut placed_badges: Vec<egui::Rect> = Vec::new();
+
+        for cell in visible_cells
+            .iter()
+            .map(|&index| &cache.cells[index])
+            .filter(|cell| cell.is_dir)
+        {
+            let count = self.action_rollup.count(&cell.path);
+            if count == 0 {
+                continue;
+            }
+            let transformed_rect = self.transform_rect_for_view(cell.rect);
+
+            let share = (count as f32 / max_count).clamp(0.0, 1.0);
+            let alpha = (60.0 + share * 195.0).round() as u8;
+            painter.rect_stroke(
+                transformed_rect.shrink(1.0),
+                0.0,
+                egui::Stroke::new(
+                    1.0 + share * 2.0,
+                    Color32::from_rgba_unmultiplied(255, 150, 40, alpha),
+                ),
+            );
+
+            if transformed_rect.width() < 40.0 || transformed_rect.height() < 24.0 {
+                continue;
+            }
+
+            let galley = painter.layout_no_wrap(count.to_string(), font.clone(), Color32::BLACK);
+            let badge_size = galley.size() + egui::vec2(8.0, 2.0);
+            let badge_rect = egui::Rect::from_min_size(
+                transformed_rect.right_top() + egui::vec2(-badge_size.x - 3.0, 3.0),
+                badge_size,
+            );
+            if placed_badges
+                .iter()
+                .any(|placed| placed.intersects(badge_rect))
+            {
+                continue;
+            }
+
+            painter.rect_filled(badge_rect, 4.0, Color32::from_rgb(255, 170, 60));
+            painter.galley(
+                badge_rect.min + egui::vec2(4.0, 1.0),
+                galley,
+                Color32::BLACK,
+            );
+            placed_badges.push(badge_rect);
+        }
+    }
+
+    /// Outlines reclaimable folders and badges them with what they are.
+    fn render_reclaim_badges(
+        &self,
+        painter: &egui::Painter,
+        cache: &TreemapCache,
+        visible_cells: &[usize],
+        font: egui::FontId,
+    ) {
+        let color = Color32::from_rgb(120, 210, 120);
+        for cell in visible_cells
+            .iter()
+            .map(|&index| &cache.cells[index])
+            .filter(|cell| cell.is_dir)
+        {
+            let Some(item) = self.reclaim.get(&cell.path) else {
+                continue;
+            };
+            let transformed_rect = self.transform_rect_for_view(cell.rect);
+            painter.rect_stroke(
+                transformed_rect.shrink(1.0),
+                0.0,
+                egui::Stroke::new(2.0, color),
+            );
+
+            if transformed_rect.width() < 80.0 || transformed_rect.height() < 24.0 {
+                continue;
+            }
+            let galley =
+                painter.layout_no_wrap(item.label.to_string(), font.clone(), Color32::BLACK);
+            let badge_rect = egui::Rect::from_min_size(
+                transformed_rect.left_bottom() + egui::vec2(3.0, -galley.size().y - 5.0),
+                galley.size() + egui::vec2(8.0, 2.0),
+            );
+            if badge_rect.width() > transformed_rect.width() - 6.0 {
+                continue;
+            }
+            painter.rect_filled(badge_rect, 4.0, color);
+            painter.galley(
+                badge_rect.min + egui::vec2(4.0, 1.0),
+                galley,
+                Color32::BLACK,
+            );
+        }
+    }
+
+    /// Marks filesystem changes with rings instead of lines to the agent marker.
+    fn render_watch_markers(
+        &self,
+        painter: &egui::Painter,
+        cache: &TreemapCache,
+        canvas_rect: egui::Rect,
+    ) -> bool {
+        let mut has_marker = false;
+        for line in &self.visual_lines {
+            if !line.from_watcher
+                || line.opacity <= 0.0
+                || self.hidden_action_kinds.contains(&line.kind)
+            {
+                continue;
+            }
+
+            let Some(world_pos) = self.resolve_world_pos(cache, &line.target_path) else {
+                continue;
+            };
+            let pos = self.world_to_screen(world_pos);
+            if !canvas_rect.expand(16.0).contains(pos) {
+                continue;
+            }
+
+            let alpha = (line.opacity * 255.0).round().clamp(0.0, 255.0) as u8;
+            let [r, g, b, _] = color_for_action_kind(line.kind).to_array();
+            let freshness = (1.0 - line.age / LINE_LIFETIME_SECONDS).clamp(0.0, 1.0);
+            let radius = 4.0 + 8.0 * (1.0 - freshness);
+            painter.circle_stroke(
+                pos,
+                radius,
+                egui::Stroke::new(2.0, Color32::from_rgba_unmultiplied(r, g, b, alpha)),
+            );
+            painter.circle_filled(pos, 2.5, Color32::from_rgba_unmultiplied(r, g, b, alpha));
+            has_marker = true;
+        }
+
+        has_marker
+    }
+
+    fn render_openclaw_overlay(
+        &self,
+        painter: &egui::Painter,
+        cache: &TreemapCache,
+        canvas_rect: egui::Rect,
+    ) -> bool {
+        let Some(openclaw_world_pos) = self.resolve_openclaw_world_pos(cache) else {
+            return false;
+        };
+        let openclaw_pos = self.world_to_screen(openclaw_world_pos);
+        painter.circle_filled(openclaw_pos, 6.0, Color32::from_rgb(208, 58, 58));
+        painter.text(
+            openclaw_pos + egui::vec2(8.0, -8.0),
+            egui::Align2::LEFT_BOTTOM,
+            "OpenCLAW",
+            egui::FontId::proportional(12.0),
+            Color32::from_rgb(255, 210, 210),
+        );
+
+        let mut has_visible_line = false;
+        for line in &self.visual_lines {
+            if line.opacity <= 0.0
+                || line.from_watcher
+                || self.hidden_action_kinds.contains(&line.kind)
+            {
+                continue;
+            }
+
+            let Some(target_world_pos) = cache.cell_centers.get(&line.target_path) else {
+                continue;
+            };
+            let target_pos = self.world_to_screen(*target_world_pos);
+
+            if !canvas_rect.expand(32.0).contains(target_pos) {
+                continue;
+            }
+
+            let alpha = (line.opacity * 255.0).round().clamp(0.0, 255.0) as u8;
+            let base_color = if line.sensitive {
+                SENSITIVE_LINE_COLOR
+            } else {
+                color_for_action_kind(line.kind)
+            };
+            let [r, g, b, _] = base_color.to_array();
+            let line_color = Color32::from_rgba_unmultiplied(r, g, b, alpha);
+            let freshness = (1.0 - line.age / LINE_LIFETIME_SECONDS).clamp(0.0, 1.0);
+            let mut width = 0.8 + 2.2 * freshness;
+            if line.sensitive {
+                width += 1.5;
+            }
+            paint_action_curve(
+                painter,
+                openclaw_pos,
+                target_pos,
+                line.jitter,
+                egui::Stroke::new(width, line_color),
+            );
+            has_visible_line = true;
+        }
+
+        has_visible_line
+    }
+
+    fn pick_agent_path(&mut self) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .set_title(self.t("Select OpenCLAW location", "选择 OpenCLAW 位置"))
+            .pick_folder()
+    }
+
+    fn pick_and_scan(&mut self) {
+        if let Some(directory) = rfd::FileDialog::new()
+            .set_title(self.t("Select root directory", "选择根目录"))
+            .pick_folder()
+        {
+            self.request_scan(directory);
+        }
+    }
+
+    fn pick_startup_paths_and_scan(&mut self) {
+        // The previous session's paths are reused while they still exist.
+        let remembered = self.agent_path.clone().filter(|path| path.exists());
+        let detected = remembered.or_else(|| self.agent_candidates.first().cloned());
+        let Some(agent_path) = detected.or_else(|| self.pick_agent_path()) else {
+            self.mode = AppMode::AwaitingDirectory;
+            return;
+        };
+
+        let remembered_root = self.last_root.take().filter(|root| root.is_dir());
+        let Some(root_path) = remembered_root.or_else(|| {
+            rfd::FileDialog::new()
+                .set_title(self.t("Select root directory", "选择根目录"))
+                .pick_folder()
+        }) else {
+            self.mode = AppMode::AwaitingDirectory;
+            return;
+        };
+
+        self.agent_path = Some(agent_path);
+        // A resumed snapshot is already being rescanned.
+        if self.root_path.as_ref() != Some(&root_path) {
+            self.request_scan(root_path);
+        }
+    }
+
+    fn request_scan(&mut self, root_path: PathBuf) {
+        if self.preview_before_scan {
+            self.start_probe(root_path);
+        } else {
+            self.start_scan(root_path);
+        }
+    }
+
+    /// Feeds the rules saved for `root` into the scan config, and into the
+    /// editor when it was showing another root's rules.
+    fn load_root_exclusions(&mut self, root: &Path) {
+        let text = load_exclusions(root);
+        let (rules, errors) = ExcludeRules::parse(&text);
+        self.scan_config.exclude = rules;
+        if self.exclusions_root.as_deref() != Some(root) {
+            self.exclusions_root = Some(root.to_path_buf());
+            self.exclusions_text = text;
+            self.exclusion_errors = errors;
+        }
+    }
+
+    fn save_root_exclusions(&mut self) {
+        let Some(root) = self.exclusions_root.clone() else {
+            return;
+        };
+        let (rules, mut errors) = ExcludeRules::parse(&self.exclusions_text);
+        if let Err(error) = save_exclusions(&root, &self.exclusions_text) {
+            errors.push(error);
+        }
+        self.scan_config.exclude = rules;
+        self.exclusion_errors = errors;
+    }
+
+    fn start_probe(&mut self, root_path: PathBuf) {
+        self.load_root_exclusions(&root_path);
+        let hint = self.scan_history.get(&root_path).copied();
+        self.mode = AppMode::Previewing;
+        self.probe_result = None;
+        self.pending_root = Some(root_path.clone());
+        self.probe_receiver = Some(spawn_probe(
+            root_path,
+            self.scan_config.clone(),
+            hint,
+            self.waker.clone(),
+        ));
+    }
+
+    fn cancel_preview(&mut self) {
+        self.pending_root = None;
+        self.probe_receiver = None;
+        self.probe_result = None;
+        self.mode = if self.scan_result.is_some() {
+            AppMode::Ready
+        } else {
+            AppMode::AwaitingDirectory
+        };
+    }
+
+    fn poll_probe_messages(&mut self) {
+        let Some(receiver) = &self.probe_receiver else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.probe_result = Some(result);
+                self.probe_receiver = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => {
+                self.probe_result = Some(Err("Probe worker disconnected unexpectedly".to_string()));
+                self.probe_receiver = None;
+            }
+        }
+    }
+
+    fn start_scan(&mut self, root_path: PathBuf) {
+        self.load_root_exclusions(&root_path);
+        let is_rescan = !self.scan_result_provisional
+            && self
+                .scan_result
+                .as_ref()
+                .is_some_and(|result| result.root.path == root_path);
+        if is_rescan {
+            // Keep showing the current tree until the new one replaces it.
+            self.cancel_scan();
+            self.pending_root = None;
+            self.probe_receiver = None;
+            self.probe_result = None;
+            self.error_message = None;
+            self.scan_progress = ScanProgress::default();
+        } else {
+            self.reset_scan_state(root_path.clone());
+        }
+        self.mode = AppMode::Scanning;
+        self.perf_stats.scan_started_at = Some(Instant::now());
+        self.scan_handle = Some(spawn_scan(
+            root_path,
+            self.scan_config.clone(),
+            self.waker.clone(),
+        ));
+    }
+
+    /// Invalidates every layout computed for the previous tree.
+    fn bump_scan_generation(&mut self) {
+        self.scan_generation = self.scan_generation.wrapping_add(1);
+        self.layout_cache.clear();
+    }
+
+    /// Drops everything derived from the previous scan before a new tree arrives.
+    fn reset_scan_state(&mut self, root_path: PathBuf) {
+        self.pending_root = None;
+        self.probe_receiver = None;
+        self.probe_result = None;
+        // Stop a scan that is still walking the old root.
+        self.cancel_scan();
+        self.scan_handle = None;
+        self.bump_scan_generation();
+        // A rescan keeps the drill-down while the folder is still there.
+        self.drill_path = self
+            .drill_path
+            .take()
+            .filter(|path| path.starts_with(&root_path));
+        self.root_path = Some(root_path);
+        self.error_message = None;
+        self.scan_result = None;
+        self.scan_result_provisional = false;
+        self.scan_progress = ScanProgress::default();
+        self.hovered_entry = None;
+        self.treemap_cache = None;
+        self.type_stats.clear();
+        self.size_histogram = SizeHistogram::default();
+        self.age_histogram = AgeHistogram::default();
+        self.zero_byte_files = ZeroByteFiles::default();
+        self.content_types.clear();
+        self.git_scan = None;
+        self.view_root = None;
+        self.plugin_receiver = None;
+        self.plugin_panels.clear();
+        self.reclaim = ReclaimReport::default();
+        self.permission_index.clear();
+        self.anomalies = AnomalyReport::default();
+        self.shape = TreeShape::default();
+        self.compress_receiver = None;
+        self.compress_estimate = None;
+        self.compress_status = None;
+        self.loc_receiver = None;
+        self.loc_report = None;
+        self.loc_status = None;
+        self.token_receiver = None;
+        self.token_report = None;
+        self.token_status = None;
+        self.sniff_receiver = None;
+        self.sniff_status = None;
+        self.total_file_bytes = 0;
+        self.tree_bytes = 0;
+        self.alias_map.clear();
+        self.action_log.clear();
+        self.visual_lines.clear();
+        self.activity_heat.clear();
+        self.action_rollup.clear();
+        self.size_correlator.clear();
+        self.focus_trail.clear();
+        self.restart_fs_watcher();
+    }
+
+    fn poll_scan_messages(&mut self) {
+        if self.mode != AppMode::Scanning {
+            return;
+        }
+
+        let mut final_result = None;
+        let mut partial_tree = None;
+        let mut cancelled = false;
+
+        if let Some(handle) = &self.scan_handle {
+            if let Some(progress) = handle.take_progress() {
+                self.scan_progress = progress;
+            }
+
+            loop {
+                match handle.receiver().try_recv() {
+                    Ok(ScanMessage::Partial { root, analysis }) => {
+                        partial_tree = Some((root, analysis));
+                    }
+                    Ok(ScanMessage::Finished(result)) => {
+                        final_result = Some(result);
+                        break;
+                    }
+                    Ok(ScanMessage::Cancelled) => {
+                        cancelled = true;
+                        break;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        final_result =
+                            Some(Err("Scan worker disconnected unexpectedly".to_string()));
+                        break;
+                    }
+                }
+            }
+        }
+
+        if cancelled {
+            self.finish_cancelled_scan();
+            return;
+        }
+
+        // A rescan keeps showing the previous complete tree instead.
+        let showing_complete_tree = self.scan_result.is_some() && !self.scan_result_provisional;
+        if let (Some((root, analysis)), None, false) =
+            (partial_tree, &final_result, showing_complete_tree)
+        {
+            self.apply_partial_tree(root, analysis);
+        }
+
+        if let Some(result) = final_result {
+            self.scan_handle = None;
+            if self.scan_result_provisional {
+                self.scan_result = None;
+                self.scan_result_provisional = false;
+            }
+
+            match result {
+                Ok((result, analysis)) => {
+                    if let Some(root) = &self.root_path {
+                        self.scan_history.insert(
+                            root.clone(),
+                            ProbeHint {
+                                entries: result.stats.entries_scanned,
+                                elapsed: result.stats.elapsed,
+                            },
+                        );
+                    }
+                    self.treemap_depth = self.treemap_depth.min(self.scan_config.max_depth.max(1));
+                    self.growth.record(&result.root, SystemTime::now());
+                    self.schedule_rescan();
+                    self.apply_scan_result(result, analysis);
+                }
+                Err(error) => {
+                    self.error_message = Some(error);
+                    self.mode = AppMode::Error;
+                }
+            }
+        }
+    }
+
+    fn cancel_scan(&mut self) {
+        if let Some(handle) = &self.scan_handle {
+            handle.cancel();
+        }
+    }
+
+    /// A cancelled rescan goes back to the previous tree; otherwise the
+    /// partial tree is dropped and a new folder can be picked.
+    fn finish_cancelled_scan(&mut self) {
+        self.scan_handle = None;
+        self.scan_progress = ScanProgress::default();
+        self.perf_stats.scan_started_at = None;
+        if self.scan_result_provisional {
+            self.scan_result = None;
+            self.scan_result_provisional = false;
+            self.hovered_entry = None;
+            self.treemap_cache = None;
+            self.bump_scan_generation();
+        }
+        self.mode = if self.scan_result.is_some() {
+            AppMode::Ready
+        } else {
+            AppMode::AwaitingDirectory
+        };
+    }
+
+    fn apply_scan_result(&mut self, result: ScanResult, analysis: ScanAnalysis) {
+        self.bump_scan_generation();
+        self.hovered_entry = None;
+        self.scan_result_provisional = false;
+        self.scan_result = Some(result);
+        self.apply_analysis(analysis);
+        self.mode = AppMode::Ready;
+        self.treemap_cache = None;
+        self.content_types.clear();
+        if self.content_typing {
+            self.start_content_sniff();
+        }
+        self.compress_estimate = None;
+        if self.compress_enabled {
+            self.start_compress_estimate();
+        }
+        self.loc_report = None;
+        if self.loc_enabled {
+            self.start_loc_count();
+        }
+        self.token_report = None;
+        if self.tokens_enabled {
+            self.start_token_count();
+        }
+        self.git_scan = None;
+        self.update_view_root();
+        self.reclaim = self
+            .scan_result
+            .as_ref()
+            .map(|result| ReclaimReport::of(&result.root))
+            .unwrap_or_default();
+        self.anomalies = self
+            .scan_result
+            .as_ref()
+            .map(|result| AnomalyReport::of(&result.root))
+            .unwrap_or_default();
+        self.check_thresholds();
+        self.shape = self
+            .scan_result
+            .as_ref()
+            .map(|result| TreeShape::of(&result.root))
+            .unwrap_or_default();
+        self.permission_index = self
+            .scan_result
+            .iter()
+            .flat_map(|result| result.permission_findings.iter().enumerate())
+            .map(|(index, finding)| (finding.path.clone(), index))
+            .collect();
+        self.git_receiver = self
+            .scan_result
+            .as_ref()
+            .map(|result| spawn_git_scan(&result.root, self.scan_generation, self.waker.clone()));
+        self.plugin_panels.clear();
+        self.plugin_receiver = self.scan_result.as_ref().and_then(|result| {
+            spawn_plugin_analysis(
+                &self.plugins,
+                &result.root,
+                self.scan_generation,
+                self.waker.clone(),
+            )
+        });
+    }
+
+    fn poll_plugin_reports(&mut self) {
+        let Some(receiver) = &self.plugin_receiver else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                Ok(report) if report.scan_generation == self.scan_generation => {
+                    self.plugin_panels.push((report.plugin, report.panel));
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.plugin_receiver = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Plugin load errors and the panels analyzing plugins returned.
+    fn render_plugin_panels(&mut self, ui: &mut egui::Ui) {
+        if !self.plugins.is_empty() {
+            let names: Vec<&str> = self
+                .plugins
+                .iter()
+                .map(|plugin| plugin.name.as_str())
+                .collect();
+            let paths: Vec<String> = self
+                .plugins
+                .iter()
+                .map(|plugin| plugin.path.display().to_string())
+                .collect();
+            ui.small(format!(
+                "{} {}",
+                self.t("Plugins:", "插件："),
+                names.join(", ")
+            ))
+            .on_hover_text(paths.join("\n"));
+        }
+        for error in &self.plugin_errors {
+            ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+        }
+
+        let mut jump = None;
+        for (index, (plugin, panel)) in self.plugin_panels.iter().enumerate() {
+            let panel = match panel {
+                Ok(panel) => panel,
+                Err(error) => {
+                    ui.colored_label(Color32::from_rgb(210, 70, 70), format!("{plugin}: {error}"));
+                    continue;
+                }
+            };
+            egui::CollapsingHeader::new(format!("{} ({plugin})", panel.title))
+                .id_salt(("plugin_panel", index))
+                .default_open(false)
+                .show(ui, |ui| {
+                    egui::Grid::new(("plugin_grid", index))
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for row in &panel.rows {
+                                match &row.path {
+                                    Some(path) => {
+                                        if ui
+                                            .link(&row.label)
+                                            .on_hover_text(
+                                                self.t("Click to jump to target", "点击跳转到目标"),
+                                            )
+                                            .clicked()
+                                        {
+                                            jump = Some(path.clone());
+                                        }
+                                    }
+                                    None => {
+                                        ui.label(&row.label);
+                                    }
+                                }
+                                ui.label(&row.value);
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    fn poll_git_messages(&mut self) {
+        let Some(receiver) = &self.git_receiver else {
+            return;
+        };
+        let scan = match receiver.try_recv() {
+            Ok(scan) => scan,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.git_receiver = None;
+                return;
+            }
+        };
+        self.git_receiver = None;
+        if scan.scan_generation != self.scan_generation {
+            return;
+        }
+        self.git_scan = Some(scan);
+        self.update_view_root();
+    }
+
+    /// Rebuilds the filtered tree for "Hide git-ignored files" and the custom
+    /// view filter, and forces a new layout (ignored cells are dimmed even
+    /// when shown, and color rules apply while laying out).
+    fn update_view_root(&mut self) {
+        let ignored = self
+            .git_scan
+            .as_ref()
+            .filter(|git| self.hide_git_ignored && !git.ignored.is_empty())
+            .map(|git| &git.ignored);
+        let filter = self.script.filter.is_some().then_some(&self.script);
+        self.view_root = match &self.scan_result {
+            Some(result) if ignored.is_some() || filter.is_some() => {
+                let root = &result.root;
+                let now = unix_now();
+                Some(root.without(&|node| {
+                    ignored.is_some_and(|ignored| ignored.contains(&node.path))
+                        || filter.is_some_and(|script| {
+                            script.hides(&Entry {
+                                node,
+                                depth: node
+                                    .path
+                                    .strip_prefix(&root.path)
+                                    .map_or(0, |relative| relative.components().count()),
+                                now,
+                            })
+                        })
+                }))
+            }
+            _ => None,
+        };
+        self.layout_cache.clear();
+        self.treemap_cache = None;
+    }
+
+    fn apply_script(&mut self) {
+        let (script, errors) =
+            ScriptView::parse(&self.script_colors_text, &self.script_filter_text);
+        self.script = script;
+        self.script_errors = errors;
+        self.update_view_root();
+    }
+
+    fn start_content_sniff(&mut self) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        self.sniff_receiver = Some(spawn_sniff(
+            &result.root,
+            self.scan_generation,
+            self.waker.clone(),
+        ));
+        self.sniff_status = Some(
+            self.t("Reading file headers...", "正在读取文件头...")
+                .to_string(),
+        );
+    }
+
+    fn schedule_rescan(&mut self) {
+        self.next_rescan = (self.rescan_every_minutes > 0).then(|| {
+            Instant::now() + Duration::from_secs(u64::from(self.rescan_every_minutes) * 60)
+        });
+    }
+
+    /// Starts the scheduled rescan once it is due and nothing else is running.
+    fn run_scheduled_rescan(&mut self, ctx: &egui::Context) {
+        let Some(due) = self.next_rescan else {
+            return;
+        };
+        if self.scan_handle.is_some() || self.probe_receiver.is_some() {
+            return;
+        }
+        let now = Instant::now();
+        if now < due {
+            ctx.request_repaint_after(due - now);
+            return;
+        }
+        self.next_rescan = None;
+        if let Some(root) = self.root_path.clone() {
+            self.start_scan(root);
+        }
+    }
+
+    fn start_compress_estimate(&mut self) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        self.compress_receiver = Some(spawn_compress_estimate(
+            &result.root,
+            self.scan_generation,
+            self.waker.clone(),
+        ));
+        self.compress_status = Some(
+            self.t("Sampling file contents...", "正在采样文件内容...")
+                .to_string(),
+        );
+    }
+
+    fn poll_compress_messages(&mut self) {
+        let Some(receiver) = &self.compress_receiver else {
+            return;
+        };
+        let estimate = match receiver.try_recv() {
+            Ok(estimate) => estimate,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.compress_receiver = None;
+                self.compress_status = None;
+                return;
+            }
+        };
+        self.compress_receiver = None;
+        if estimate.scan_generation != self.scan_generation || !self.compress_enabled {
+            return;
+        }
+        self.compress_status = Some(format!(
+            "{} {} ({})",
+            self.t("Sampled", "已采样"),
+            self.count(estimate.files_sampled),
+            self.size(estimate.bytes_read)
+        ));
+        self.compress_estimate = Some(estimate);
+    }
+
+    fn start_update_check(&mut self) {
+        self.update_receiver = Some(spawn_update_check(
+            self.update_settings.url.clone(),
+            self.waker.clone(),
+        ));
+        self.update_status = Some(self.t("Checking...", "正在检查...").to_string());
+    }
+
+    fn poll_update_messages(&mut self) {
+        let Some(receiver) = &self.update_receiver else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.update_receiver = None;
+                self.update_status = None;
+                return;
+            }
+        };
+        self.update_receiver = None;
+        self.update_status = Some(match &result {
+            Ok(Some(update)) => format!("{} {}", self.t("Available:", "可用："), update.version),
+            Ok(None) => format!(
+                "{} ({CURRENT_VERSION})",
+                self.t("Up to date", "已是最新版本")
+            ),
+            Err(error) => format!("{} {error}", self.t("Check failed:", "检查失败：")),
+        });
+        self.update_available = result.ok().flatten();
+    }
+
+    fn save_update_settings(&mut self) {
+        if let Err(error) = save_settings(&self.update_settings) {
+            self.update_status = Some(error);
+        }
+    }
+
+    fn render_update_panel(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!(
+            "{} {CURRENT_VERSION}",
+            self.t("This version:", "当前版本：")
+        ));
+        let enabled_text = self.t("Check for updates at startup", "启动时检查更新");
+        if ui
+            .checkbox(&mut self.update_settings.enabled, enabled_text)
+            .on_hover_text(self.t(
+                "Off by default; only the releases URL below is contacted",
+                "默认关闭；只会访问下面的发布地址",
+            ))
+            .changed()
+        {
+            self.save_update_settings();
+        }
+        ui.horizontal(|ui| {
+            ui.label(self.t("Releases URL:", "发布地址："));
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.update_settings.url)
+                    .desired_width(f32::INFINITY),
+            );
+            if response.lost_focus() {
+                self.save_update_settings();
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    self.update_receiver.is_none(),
+                    egui::Button::new(self.t("Check now", "立即检查")),
+                )
+                .clicked()
+            {
+                self.start_update_check();
+            }
+            if ui
+                .small_button(self.t("Reset URL", "恢复默认地址"))
+                .clicked()
+            {
+                self.update_settings.url = UpdateSettings::default().url;
+                self.save_update_settings();
+            }
+            if let Some(status) = &self.update_status {
+                ui.small(status);
+            }
+        });
+
+        let Some(update) = self.update_available.clone() else {
+            return;
+        };
+        ui.separator();
+        ui.strong(format!(
+            "{} {}",
+            self.t("New version available:", "有新版本可用："),
+            update.version
+        ));
+        if let Some(url) = &update.url {
+            ui.hyperlink_to(self.t("Open release page", "打开发布页面"), url);
+        }
+        egui::ScrollArea::vertical()
+            .max_height(240.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                ui.label(if update.notes.trim().is_empty() {
+                    self.t("(no release notes)", "（无发布说明）")
+                } else {
+                    update.notes.as_str()
+                });
+            });
+        if ui.button(self.t("Dismiss", "忽略")).clicked() {
+            self.update_settings.dismissed = Some(update.version);
+            self.save_update_settings();
+            self.show_update_panel = false;
+        }
+    }
+
+    fn start_loc_count(&mut self) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        self.loc_receiver = Some(spawn_loc_count(
+            &result.root,
+            self.scan_generation,
+            self.waker.clone(),
+        ));
+        self.loc_status = Some(self.t("Counting lines...", "正在统计行数...").to_string());
+    }
+
+    fn poll_loc_messages(&mut self) {
+        let Some(receiver) = &self.loc_receiver else {
+            return;
+        };
+        let report = match receiver.try_recv() {
+            Ok(report) => report,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.loc_receiver = None;
+                self.loc_status = None;
+                return;
+            }
+        };
+        self.loc_receiver = None;
+        if report.scan_generation != self.scan_generation || !self.loc_enabled {
+            return;
+        }
+        self.loc_status = Some(format!(
+            "{} {} ({} {})",
+            self.count(report.files.len() as u64),
+            self.t("source files", "个源文件"),
+            self.count(
+                report
+                    .languages
+                    .iter()
+                    .map(|(_, _, counts)| counts.code)
+                    .sum()
+            ),
+            self.t("code lines", "行代码")
+        ));
+        self.loc_report = Some(report);
+        if self.layout_metric == LayoutMetric::LinesOfCode || self.loc_coloring {
+            self.layout_cache.clear();
+            self.treemap_cache = None;
+        }
+    }
+
+    fn start_token_count(&mut self) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        self.token_receiver = Some(spawn_token_count(
+            &result.root,
+            self.token_approximation,
+            self.scan_generation,
+            self.waker.clone(),
+        ));
+        self.token_status = Some(
+            self.t("Estimating tokens...", "正在估算 token...")
+                .to_string(),
+        );
+    }
+
+    fn poll_token_messages(&mut self) {
+        let Some(receiver) = &self.token_receiver else {
+            return;
+        };
+        let report = match receiver.try_recv() {
+            Ok(report) => report,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.token_receiver = None;
+                self.token_status = None;
+                return;
+            }
+        };
+        self.token_receiver = None;
+        if report.scan_generation != self.scan_generation
+            || report.approximation != self.token_approximation
+            || !self.tokens_enabled
+        {
+            return;
+        }
+        self.token_status = Some(format!(
+            "{} {} ({} {})",
+            self.count(report.files.len() as u64),
+            self.t("text files", "个文本文件"),
+            self.count(report.binary_files),
+            self.t("binary skipped", "个二进制已跳过")
+        ));
+        self.token_report = Some(report);
+        if self.layout_metric == LayoutMetric::Tokens {
+            self.layout_cache.clear();
+            self.treemap_cache = None;
+        }
+    }
+
+    fn poll_sniff_messages(&mut self) {
+        let Some(receiver) = &self.sniff_receiver else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.sniff_receiver = None;
+                self.sniff_status = None;
+                return;
+            }
+        };
+        self.sniff_receiver = None;
+        if result.scan_generation != self.scan_generation || !self.content_typing {
+            return;
+        }
+        self.sniff_status = Some(format!(
+            "{} {} / {}",
+            self.t("Content types differ for", "内容类型与扩展名不符："),
+            self.count(result.content_types.len() as u64),
+            self.count(result.files_read)
+        ));
+        self.content_types = result.content_types;
+        self.type_stats = result.type_stats;
+        self.layout_cache.clear();
+        self.treemap_cache = None;
+    }
+
+    fn set_content_typing(&mut self, enabled: bool) {
+        if enabled {
+            self.start_content_sniff();
+            return;
+        }
+        self.sniff_receiver = None;
+        self.sniff_status = None;
+        if !self.content_types.is_empty() {
+            self.content_types.clear();
+            if let Some(result) = &self.scan_result {
+                self.type_stats = ScanAnalysis::of(&result.root).type_stats;
+            }
+            self.layout_cache.clear();
+            self.treemap_cache = None;
+        }
+    }
+
+    /// The type key shown for `path`: its detected content type when that
+    /// disagrees with the extension, otherwise the extension.
+    fn type_key_of(&self, path: &Path) -> String {
+        self.content_types
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| file_type_key(path))
+    }
+
+    /// Reloads the category mapping when its file was edited, keeping the
+    /// previous mapping if the new one does not parse.
+    fn poll_category_file(&mut self) {
+        let due = match &self.category_watcher {
+            Some(watcher) => watcher.changed(),
+            None => self
+                .categories_checked
+                .is_none_or(|checked| checked.elapsed() >= CATEGORY_FILE_POLL),
+        };
+        if !due {
+            return;
+        }
+        self.categories_checked = Some(Instant::now());
+        let Some(path) = self.categories_path.clone() else {
+            return;
+        };
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified.is_some() && modified == self.categories_modified {
+            return;
+        }
+        match load_categories(&path) {
+            Ok((categories, warnings)) => {
+                self.categories = categories;
+                self.category_status = (!warnings.is_empty()).then(|| warnings.join("; "));
+                if self.color_by_category {
+                    self.layout_cache.clear();
+                    self.treemap_cache = None;
+                }
+            }
+            Err(error) => self.category_status = Some(error),
+        }
+        self.categories_modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        // Started after the first load, which creates the file's folder.
+        if self.category_watcher.is_none() {
+            self.category_watcher = FileWatcher::start(&path, self.waker.clone()).ok();
+        }
+    }
+
+    fn apply_analysis(&mut self, analysis: ScanAnalysis) {
+        self.type_stats = analysis.type_stats;
+        self.total_file_bytes = analysis.total_file_bytes;
+        self.size_histogram = analysis.size_histogram;
+        self.age_histogram = analysis.age_histogram;
+        self.zero_byte_files = analysis.zero_byte_files;
+        self.alias_map = analysis.alias_map;
+        self.tree_bytes = analysis.tree_bytes;
+    }
+
+    /// Shows a streamed partial tree while the scan is still running.
+    fn apply_partial_tree(&mut self, root: Node, analysis: ScanAnalysis) {
+        self.apply_analysis(analysis);
+        self.scan_result = Some(ScanResult {
+            root,
+            stats: ScanStats {
+                entries_scanned: self.scan_progress.entries_scanned,
+                files_scanned: self.scan_progress.files_scanned,
+                directories_scanned: self.scan_progress.directories_scanned,
+                warnings: self.scan_progress.warnings,
+                truncated: self.scan_progress.truncated,
+                estimated_total_entries: self.scan_progress.total_estimated_entries,
+                elapsed: self
+                    .perf_stats
+                    .scan_started_at
+                    .map(|started| started.elapsed())
+                    .unwrap_or_default(),
+            },
+            warnings: Vec::new(),
+            warning_counts: Vec::new(),
+            permission_findings: Vec::new(),
+            symlinks: Vec::new(),
+            owners: Vec::new(),
+        });
+        self.scan_result_provisional = true;
+        self.bump_scan_generation();
+        self.treemap_cache = None;
+    }
+
+    /// Saves the current scan as a compact snapshot, or as JSON with `json`
+    /// set; with `anonymize` set, or while demo mode is on, every name and
+    /// path is replaced by its demo alias first.
+    fn save_snapshot(&mut self, anonymize: bool, json: bool) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        let anonymize = anonymize || self.demo_mode;
+        let default_name = if anonymize { "scan-anonymized" } else { "scan" };
+        let (filter, extension) = if json {
+            ("JSON", JSON_SNAPSHOT_EXTENSION)
+        } else {
+            ("Tree map snapshot", SNAPSHOT_EXTENSION)
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(format!("{default_name}.{extension}"))
+            .add_filter(filter, &[extension])
+            .save_file()
+        else {
+            return;
+        };
+
+        let saved = if anonymize {
+            save_snapshot(&path, &anonymized(result, &self.alias_map))
+        } else {
+            save_snapshot(&path, result)
+        };
+        self.export_status = Some(match saved {
+            Ok(()) => format!(
+                "{} {}",
+                self.t("Saved snapshot", "已保存快照"),
+                path.display()
+            ),
+            Err(error) => format!("{} {error}", self.t("Snapshot failed:", "快照保存失败：")),
+        });
+    }
+
+    fn load_snapshot(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(
+                "Tree map snapshot",
+                &[SNAPSHOT_EXTENSION, JSON_SNAPSHOT_EXTENSION],
+            )
+            .pick_file()
+        else {
+            return;
+        };
+
+        match load_snapshot(&path) {
+            Ok(result) => {
+                self.open_scan_result(result);
+                self.export_status = Some(format!(
+                    "{} {}",
+                    self.t("Loaded snapshot", "已加载快照"),
+                    path.display()
+                ));
+            }
+            Err(error) => {
+                self.export_status = Some(format!(
+                    "{} {error}",
+                    self.t("Snapshot load failed:", "快照加载失败：")
+                ));
+            }
+        }
+    }
+
+    /// Writes the current tree in another tool's format; names and paths are
+    /// masked in demo mode like every other export.
+    fn export_tree(&mut self, export: TreeExport) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(export.default_file_name())
+            .add_filter(export.label(), &[export.extension()])
+            .save_file()
+        else {
+            return;
+        };
+
+        let labels = |node: &Node| self.export_label(node);
+        let written = export.write_file(
+            &path,
+            result,
+            &self.export_options,
+            &labels,
+            !self.demo_mode,
+        );
+        self.export_status = Some(match written {
+            Ok(()) => format!("{} {}", self.t("Exported", "已导出"), path.display()),
+            Err(error) => format!("{} {error}", self.t("Export failed:", "导出失败：")),
+        });
+    }
+
+    fn export_label(&self, node: &Node) -> NodeLabel {
+        NodeLabel {
+            name: self.demo_name(&node.name, &node.path, !node.children.is_empty()),
+            path: self.demo_path(&node.path),
+        }
+    }
+
+    /// Copies the Markdown summary to the clipboard, or saves it when `save`
+    /// is set.
+    fn export_markdown_summary(&mut self, ctx: &egui::Context, save: bool) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        let categories = self.categories.summarize(&self.type_stats);
+        let summary = markdown_summary(result, &self.type_stats, &categories, &|node| {
+            self.export_label(node)
+        });
+        if !save {
+            ctx.copy_text(summary);
+            self.export_status = Some(
+                self.t("Copied Markdown summary", "已复制 Markdown 摘要")
+                    .to_string(),
+            );
+            return;
+        }
+
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("scan-summary.md")
+            .add_filter("Markdown", &["md"])
+            .save_file()
+        else {
+            return;
+        };
+        self.export_status = Some(match fs::write(&path, summary) {
+            Ok(()) => format!("{} {}", self.t("Exported", "已导出"), path.display()),
+            Err(error) => format!("{} {error}", self.t("Export failed:", "导出失败：")),
+        });
+    }
+
+    /// Crops the requested screenshot to the treemap and puts it on the
+    /// system clipboard.
+    fn poll_image_copy(&mut self, ctx: &egui::Context) {
+        if !self.image_copy_pending {
+            return;
+        }
+        let Some(screenshot) = ctx.input(|input| {
+            input.raw.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        }) else {
+            return;
+        };
+        self.image_copy_pending = false;
+
+        let image = match self.canvas_rect {
+            Some(rect) => screenshot.region(&rect, Some(ctx.pixels_per_point())),
+            None => (*screenshot).clone(),
+        };
+        let copied = arboard::Clipboard::new().and_then(|mut clipboard| {
+            clipboard.set_image(arboard::ImageData {
+                width: image.width(),
+                height: image.height(),
+                bytes: image.as_raw().into(),
+            })
+        });
+        self.export_status = Some(match copied {
+            Ok(()) => format!(
+                "{} {}x{}",
+                self.t("Copied image", "已复制图像"),
+                image.width(),
+                image.height()
+            ),
+            Err(error) => format!("{} {error}", self.t("Copy failed:", "复制失败：")),
+        });
+    }
+
+    /// Pruning controls for the formats where `TreeExport::is_pruned` holds.
+    fn draw_export_options(&mut self, ui: &mut egui::Ui) {
+        let pruned: Vec<&str> = TreeExport::ALL
+            .into_iter()
+            .filter(|export| export.is_pruned())
+            .map(TreeExport::label)
+            .collect();
+        ui.label(format!(
+            "{} {}",
+            self.t("Pruning for", "裁剪范围："),
+            pruned.join(", ")
+        ));
+
+        let mut limit_depth = self.export_options.max_depth.is_some();
+        let mut depth = self.export_options.max_depth.unwrap_or(3);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut limit_depth, self.t("Max depth", "最大深度"));
+            ui.add_enabled(limit_depth, egui::DragValue::new(&mut depth).range(1..=64));
+        });
+        self.export_options.max_depth = limit_depth.then_some(depth);
+
+        let mut min_mib = self.export_options.min_size as f64 / (1024.0 * 1024.0);
+        ui.horizontal(|ui| {
+            ui.label(self.t("Fold entries below (MiB)", "合并小于此大小的条目 (MiB)"));
+            ui.add(
+                egui::DragValue::new(&mut min_mib)
+                    .range(0.0..=1_048_576.0)
+                    .speed(1.0),
+            );
+        });
+        self.export_options.min_size = (min_mib * 1024.0 * 1024.0) as u64;
+    }
+
+    /// Opens a disk-usage report written by another tool (see `import`).
+    fn import_report(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Disk usage report", IMPORT_EXTENSIONS)
+            .pick_file()
+        else {
+            return;
+        };
+
+        match import_report(&path) {
+            Ok(result) => {
+                self.open_scan_result(result);
+                self.export_status = Some(format!(
+                    "{} {}",
+                    self.t("Imported", "已导入"),
+                    path.display()
+                ));
+            }
+            Err(error) => {
+                self.export_status = Some(format!(
+                    "{} {error}",
+                    self.t("Import failed:", "导入失败：")
+                ));
+            }
+        }
+    }
+
+    /// Replaces the current tree with a loaded or imported one.
+    fn open_scan_result(&mut self, result: ScanResult) {
+        let analysis = ScanAnalysis::of(&result.root);
+        self.reset_scan_state(result.root.path.clone());
+        self.apply_scan_result(result, analysis);
+    }
+
+    fn render_top_bar(&mut self, ui: &mut egui::Ui) {
+        let scanning = self.mode == AppMode::Scanning;
+
+        ui.horizontal_wrapped(|ui| {
+            if !self.agent_candidates.is_empty() {
+                let selected_text = match &self.agent_path {
+                    Some(agent) => self.demo_path(agent),
+                    None => self.t("(detected)", "（已检测）").to_string(),
+                };
+                let mut selected = None;
+                egui::ComboBox::from_id_salt("openclaw_candidates")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        for candidate in &self.agent_candidates {
+                            let is_current = self.agent_path.as_ref() == Some(candidate);
+                            let label = if self.demo_mode {
+                                self.demo_path(candidate)
+                            } else {
+                                candidate.display().to_string()
+                            };
+                            if ui.selectable_label(is_current, label).clicked() && !is_current {
+                                selected = Some(candidate.clone());
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(self.t(
+                        "Detected OpenCLAW installations",
+                        "检测到的 OpenCLAW 安装位置",
+                    ));
+                if let Some(path) = selected {
+                    self.agent_path = Some(path);
+                    self.visual_lines.clear();
+                }
+            }
+
+            let browse_text = if self.agent_candidates.is_empty() {
+                self.t("Select OpenCLAW location...", "选择 OpenCLAW 位置...")
+            } else {
+                self.t("Browse...", "浏览...")
+            };
+            if ui.button(browse_text).clicked() {
+                if let Some(path) = self.pick_agent_path() {
+                    self.agent_path = Some(path);
+                    self.visual_lines.clear();
+                }
+            }
+            if ui
+                .small_button(self.t("Detect", "检测"))
+                .on_hover_text(self.t(
+                    "Search environment variables and common install locations",
+                    "搜索环境变量与常见安装位置",
+                ))
+                .clicked()
+            {
+                self.agent_candidates = detect_install_candidates();
+            }
+
+            if let Some(agent) = &self.agent_path {
+                let agent_text = self.demo_path(agent);
+                ui.label(format!(
+                    "{} {}",
+                    self.t("OpenCLAW:", "OpenCLAW："),
+                    agent_text
+                ));
+            } else {
+                ui.label(self.t("OpenCLAW: (not selected)", "OpenCLAW：（未选择）"));
+            }
+
+            ui.separator();
+            if ui
+                .button(self.t("Select root directory...", "选择根目录..."))
+                .clicked()
+            {
+                self.pick_and_scan();
+            }
+            if ui
+                .add_enabled(
+                    !scanning,
+                    egui::Button::new(self.t("Load snapshot...", "加载快照...")),
+                )
+                .on_hover_text(self.t(
+                    "Open a saved scan (compact snapshot or JSON) without rescanning",
+                    "打开已保存的扫描结果（紧凑快照或 JSON），无需重新扫描",
+                ))
+                .clicked()
+            {
+                self.load_snapshot();
+            }
+            if ui
+                .add_enabled(
+                    !scanning,
+                    egui::Button::new(self.t("Import report...", "导入报告...")),
+                )
+                .on_hover_text(self.t(
+                    "Open a disk usage report: Baobab/gdmap XML, WizTree CSV or a `du -a` listing",
+                    "打开磁盘占用报告：Baobab/gdmap XML、WizTree CSV 或 `du -a` 列表",
+                ))
+                .clicked()
+            {
+                self.import_report();
+            }
+            if ui
+                .add_enabled(
+                    !scanning && self.scan_result.is_some(),
+                    egui::Button::new(self.t("Save snapshot...", "保存快照...")),
+                )
+                .clicked()
+            {
+                self.save_snapshot(false, false);
+            }
+            ui.add_enabled_ui(!scanning && self.scan_result.is_some(), |ui| {
+                ui.menu_button(self.t("Export", "导出"), |ui| {
+                    for export in TreeExport::ALL {
+                        if ui.button(format!("{}...", export.label())).clicked() {
+                            ui.close_menu();
+                            self.export_tree(export);
+                        }
+                    }
+                    if ui
+                        .button(self.t("Anonymized snapshot...", "匿名快照..."))
+                        .on_hover_text(self.t(
+                            "Snapshot with every name replaced by its demo alias; sizes and structure are kept",
+                            "所有名称替换为演示别名的快照；保留大小与结构",
+                        ))
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.save_snapshot(true, false);
+                    }
+                    if ui
+                        .button(self.t("Scan as JSON...", "扫描结果 JSON..."))
+                        .on_hover_text(self.t(
+                            "The whole scan as JSON, loadable with Load snapshot or by other tools",
+                            "整个扫描结果导出为 JSON，可通过“加载快照”或其他工具读取",
+                        ))
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.save_snapshot(false, true);
+                    }
+                    ui.separator();
+                    if ui.button(self.t("Copy Markdown summary", "复制 Markdown 摘要")).clicked() {
+                        ui.close_menu();
+                        self.export_markdown_summary(ui.ctx(), false);
+                    }
+                    if ui
+                        .button(self.t("Save Markdown summary...", "保存 Markdown 摘要..."))
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.export_markdown_summary(ui.ctx(), true);
+                    }
+                    ui.separator();
+                    self.draw_export_options(ui);
+                });
+                if ui.button(self.t("Copy image", "复制图像")).clicked() {
+                    // The frame is captured as drawn, so demo mode masking
+                    // carries over to the copy.
+                    self.image_copy_pending = true;
+                    ui.ctx().send_viewport_cmd(egui::ViewportCommand::Screenshot);
+                }
+            });
+
+            if let Some(root) = &self.root_path {
+                let root_text = self.demo_path(root);
+                ui.label(format!("{} {}", self.t("Root:", "根目录："), root_text));
+            } else {
+                ui.label(self.t("Root: (not selected)", "根目录：（未选择）"));
+            }
+
+            if let (Some(agent), Some(root)) = (&self.agent_path, &self.root_path) {
+                if !path_within_root(agent, root) {
+                    ui.colored_label(
+                        Color32::from_rgb(210, 70, 70),
+                        self.t(
+                            "OpenCLAW path is outside root; marker will not be shown.",
+                            "OpenCLAW 路径不在根目录内，无法显示位置。",
+                        ),
+                    );
+                }
+            }
+
+            ui.separator();
+            ui.label(self.t("Max recursion depth:", "最大递归深度："));
+            ui.add(egui::DragValue::new(&mut self.scan_config.max_depth).range(1..=256));
+
+            ui.label(self.t("Scan threads:", "扫描线程："));
+            ui.add(egui::DragValue::new(&mut self.scan_config.threads).range(1..=64))
+                .on_hover_text(self.t(
+                    "List folders in parallel on fast SSDs; 1 scans serially, which suits spinning disks and network shares",
+                    "在高速 SSD 上并行列出文件夹；1 表示串行扫描，更适合机械硬盘和网络共享",
+                ));
+
+            let mut file_limit_enabled = self.scan_config.max_files.is_some();
+            if ui
+                .checkbox(
+                    &mut file_limit_enabled,
+                    self.t("File count limit", "文件数量上限"),
+                )
+                .changed()
+            {
+                if file_limit_enabled {
+                    if self.scan_config.max_files.is_none() {
+                        self.scan_config.max_files = Some(250_000);
+                    }
+                } else {
+                    self.scan_config.max_files = None;
+                }
+            }
+
+            if let Some(limit) = &mut self.scan_config.max_files {
+                ui.add(
+                    egui::DragValue::new(limit)
+                        .range(1..=5_000_000)
+                        .speed(250.0),
+                );
+            }
+
+            let log_text = match &self.scan_config.warning_log {
+                Some(_) => self.t("Warning log: on", "警告日志：开"),
+                None => self.t("Warning log...", "警告日志..."),
+            };
+            let log_hover = match &self.scan_config.warning_log {
+                Some(path) => format!(
+                    "{} {}\n{}",
+                    self.t("Writing every scan warning to", "所有扫描警告写入"),
+                    self.demo_path(path),
+                    self.t("Click to turn off", "点击关闭")
+                ),
+                None => self
+                    .t(
+                        "Only the first warnings are kept in memory; choose a file to log them all",
+                        "内存中只保留前若干条警告；选择文件以记录全部警告",
+                    )
+                    .to_string(),
+            };
+            if ui.button(log_text).on_hover_text(log_hover).clicked() {
+                self.scan_config.warning_log = match self.scan_config.warning_log {
+                    Some(_) => None,
+                    None => rfd::FileDialog::new()
+                        .set_file_name("scan-warnings.log")
+                        .save_file(),
+                };
+            }
+
+            let preview_text = self.t("Preview before scan", "扫描前预估");
+            ui.checkbox(&mut self.preview_before_scan, preview_text)
+                .on_hover_text(self.t(
+                    "Probe the first two levels and show an estimate before the full scan",
+                    "先探测前两层并在完整扫描前显示预估",
+                ));
+
+            let content_text = self.t("Detect types from content", "按内容识别类型");
+            if ui
+                .checkbox(&mut self.content_typing, content_text)
+                .on_hover_text(self.t(
+                    "Read the first bytes of every file and use the detected type where it disagrees with the extension",
+                    "读取每个文件的开头字节，在与扩展名不符时使用识别出的类型",
+                ))
+                .changed()
+            {
+                self.set_content_typing(self.content_typing);
+            }
+            if let Some(status) = &self.sniff_status {
+                ui.small(status);
+            }
+
+            let audit_text = self.t("Audit permissions", "审计权限");
+            ui.checkbox(&mut self.scan_config.audit_permissions, audit_text)
+                .on_hover_text(self.t(
+                    "On the next scan, record world-writable entries and setuid/setgid files (Unix permission bits only)",
+                    "下次扫描时记录所有人可写的条目以及 setuid/setgid 文件（仅限 Unix 权限位）",
+                ));
+
+            let compress_text = self.t("Estimate compressibility", "估算可压缩性");
+            if ui
+                .checkbox(&mut self.compress_enabled, compress_text)
+                .on_hover_text(self.t(
+                    "Compress a sample of each large file to estimate how small every folder would get",
+                    "压缩大文件的样本，估算每个文件夹压缩后的大小",
+                ))
+                .changed()
+            {
+                if self.compress_enabled {
+                    self.start_compress_estimate();
+                } else {
+                    self.compress_receiver = None;
+                    self.compress_estimate = None;
+                    self.compress_status = None;
+                }
+            }
+            if let Some(status) = &self.compress_status {
+                ui.small(status);
+            }
+
+            let loc_text = self.t("Count lines of code", "统计代码行数");
+            if ui
+                .checkbox(&mut self.loc_enabled, loc_text)
+                .on_hover_text(self.t(
+                    "Read every source file and count code, comment and blank lines, to size or color the treemap by code",
+                    "读取每个源文件并统计代码、注释和空行，以按代码量调整面积或着色",
+                ))
+                .changed()
+            {
+                if self.loc_enabled {
+                    self.start_loc_count();
+                } else {
+                    self.loc_receiver = None;
+                    self.loc_report = None;
+                    self.loc_status = None;
+                    if self.layout_metric == LayoutMetric::LinesOfCode {
+                        self.layout_metric = LayoutMetric::Bytes;
+                    }
+                    self.layout_cache.clear();
+                    self.treemap_cache = None;
+                }
+            }
+            if let Some(status) = &self.loc_status {
+                ui.small(status);
+            }
+            if self.loc_report.is_some() {
+                let coloring_text = self.t("Color by lines of code", "按代码行数着色");
+                if ui.checkbox(&mut self.loc_coloring, coloring_text).changed() {
+                    self.layout_cache.clear();
+                    self.treemap_cache = None;
+                }
+            }
+
+            let tokens_text = self.t("Estimate LLM tokens", "估算 LLM token");
+            let mut restart_tokens = ui
+                .checkbox(&mut self.tokens_enabled, tokens_text)
+                .on_hover_text(self.t(
+                    "Estimate how many tokens each text file would take in an agent's context",
+                    "估算每个文本文件在智能体上下文中占用的 token 数",
+                ))
+                .changed();
+            if self.tokens_enabled {
+                let mut approximation = self.token_approximation;
+                let characters_text = self.t("Characters per token", "每 token 字符数");
+                let words_text = self.t("Words and punctuation", "单词与标点");
+                egui::ComboBox::from_id_salt("token_approximation")
+                    .selected_text(match approximation {
+                        TokenApproximation::Characters(_) => characters_text,
+                        TokenApproximation::Words => words_text,
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut approximation,
+                            TokenApproximation::Characters(4.0),
+                            characters_text,
+                        );
+                        ui.selectable_value(&mut approximation, TokenApproximation::Words, words_text);
+                    });
+                // Re-estimating reads every file, so wait for drags to end.
+                let mut dragging = false;
+                if let TokenApproximation::Characters(per_token) = &mut approximation {
+                    let response = ui.add(
+                        egui::DragValue::new(per_token)
+                            .range(1.0..=10.0)
+                            .speed(0.1),
+                    );
+                    dragging = response.dragged();
+                    restart_tokens |= response.drag_stopped();
+                }
+                if approximation != self.token_approximation {
+                    self.token_approximation = approximation;
+                    restart_tokens |= !dragging;
+                }
+            }
+            if restart_tokens {
+                if self.tokens_enabled {
+                    self.start_token_count();
+                } else {
+                    self.token_receiver = None;
+                    self.token_report = None;
+                    self.token_status = None;
+                    if self.layout_metric == LayoutMetric::Tokens {
+                        self.layout_metric = LayoutMetric::Bytes;
+                    }
+                    self.layout_cache.clear();
+                    self.treemap_cache = None;
+                }
+            }
+            if let Some(status) = &self.token_status {
+                ui.small(status);
+            }
+
+            let watch_text = self.t("Watch filesystem", "监视文件系统");
+            if ui
+                .checkbox(&mut self.watch_enabled, watch_text)
+                .on_hover_text(self.t(
+                    "Show created/modified/deleted files as overlay markers",
+                    "以叠加标记显示新建/修改/删除的文件",
+                ))
+                .changed()
+            {
+                self.restart_fs_watcher();
+            }
+            if let Some(error) = &self.watch_error {
+                ui.colored_label(Color32::from_rgb(210, 70, 70), self.demo_text(error));
+            }
+
+            let can_rescan = !scanning && self.root_path.is_some();
+            if ui
+                .add_enabled(can_rescan, egui::Button::new(self.t("Rescan", "重新扫描")))
+                .clicked()
+            {
+                if let Some(root) = self.root_path.clone() {
+                    self.start_scan(root);
+                }
+            }
+            let every_text = self.t("every", "每");
+            ui.label(every_text);
+            let minutes_suffix = self.t(" min", " 分钟");
+            if ui
+                .add(
+                    egui::DragValue::new(&mut self.rescan_every_minutes)
+                        .range(0..=1440)
+                        .suffix(minutes_suffix),
+                )
+                .on_hover_text(self.t(
+                    "Rescan automatically at this interval to track growth; 0 turns it off",
+                    "按此间隔自动重新扫描以跟踪增长；0 表示关闭",
+                ))
+                .changed()
+            {
+                self.schedule_rescan();
+            }
+
+            ui.separator();
+            let show_labels_text = self.t("Show labels in cells", "在方块中显示名称");
+            ui.checkbox(&mut self.show_cell_labels, show_labels_text);
+            let perf_text = self.t("Performance overlay", "性能面板");
+            ui.checkbox(&mut self.show_perf_overlay, perf_text);
+            let memory_text = self.t("Memory", "内存");
+            ui.toggle_value(&mut self.show_memory_panel, memory_text)
+                .on_hover_text(self.t(
+                    "Show approximate memory used by each subsystem",
+                    "显示各子系统的近似内存占用",
+                ));
+            let updates_text = self.t("Updates", "更新");
+            ui.toggle_value(&mut self.show_update_panel, updates_text);
+            let demo_mode_text = self.t("Demo anonymous mode", "演示匿名模式");
+            ui.checkbox(&mut self.demo_mode, demo_mode_text)
+                .on_hover_text(self.t(
+                    "Replace real names and paths with aliases everywhere, including history, alerts, and exports",
+                    "在所有位置（包括历史、警报和导出）用别名替换真实名称和路径",
+                ));
+            let simulate_text = self.t("Simulate OpenCLAW", "模拟 OpenCLAW");
+            if ui.button(simulate_text).clicked() {
+                self.simulate_agent_activity();
+            }
+            self.render_simulation_settings(ui);
+
+            ui.separator();
+            let listening = self.action_listener.is_some();
+            ui.add_enabled(
+                !listening,
+                egui::TextEdit::singleline(&mut self.listen_address).desired_width(130.0),
+            )
+            .on_hover_text(self.t(
+                "TCP address for live OpenCLAW action events",
+                "接收 OpenCLAW 实时动作事件的 TCP 地址",
+            ));
+            let listen_button = if listening {
+                self.t("Stop listening", "停止监听")
+            } else {
+                self.t("Listen", "监听")
+            };
+            if ui.button(listen_button).clicked() {
+                self.toggle_action_listener();
+            }
+            let tcp_off_text = self.t("Live feed: off", "实时动作：关闭");
+            self.render_listener_status(ui, &self.listener_status, tcp_off_text);
+
+            let ipc_listening = self.ipc_listener.is_some();
+            let ipc_button = if ipc_listening {
+                self.t("Stop IPC", "停止 IPC")
+            } else {
+                self.t("Local IPC", "本地 IPC")
+            };
+            let ipc_hover = format!(
+                "{} {}",
+                self.t(
+                    "Accept action events on the local endpoint",
+                    "在本地端点接收动作事件"
+                ),
+                describe_endpoint(&self.ipc_name)
+            );
+            if ui.button(ipc_button).on_hover_text(ipc_hover).clicked() {
+                self.toggle_ipc_listener();
+            }
+            if ipc_listening || matches!(self.ipc_status, ListenerStatus::Failed(_)) {
+                self.render_listener_status(ui, &self.ipc_status, "");
+            }
+            for (server, label) in [
+                (&self.api_server, self.t("API:", "API：")),
+                (&self.metrics_server, self.t("Metrics:", "指标：")),
+            ] {
+                match server {
+                    Ok(Some(server)) => {
+                        ui.small(format!("{label} http://{}", server.address()));
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+                    }
+                }
+            }
+
+            let streaming = self.stream_listener.is_some();
+            let stream_hint = self.t("http://host:port/events", "http://主机:端口/events");
+            ui.add_enabled(
+                !streaming,
+                egui::TextEdit::singleline(&mut self.stream_url)
+                    .hint_text(stream_hint)
+                    .desired_width(180.0),
+            )
+            .on_hover_text(self.t(
+                "Remote SSE or newline-delimited HTTP stream of action events",
+                "远程 SSE 或按行分隔的 HTTP 动作事件流",
+            ));
+            let stream_button = if streaming {
+                self.t("Disconnect", "断开")
+            } else {
+                self.t("Connect", "连接")
+            };
+            if ui.button(stream_button).clicked() {
+                self.toggle_stream_listener();
+            }
+            if streaming {
+                self.render_listener_status(ui, &self.stream_status, "");
+            }
+            if self.stdin_receiver.is_some() {
+                ui.colored_label(
+                    Color32::from_rgb(80, 170, 90),
+                    self.t("stdin feed", "标准输入"),
+                );
+            } else if self.stdin_closed {
+                ui.small(self.t("stdin closed", "标准输入已关闭"));
+            }
+            self.render_ingest_pause_controls(ui);
+            if self.rejected_events > 0 {
+                let response = ui.small(format!(
+                    "{} {}",
+                    self.t("Rejected events:", "无效事件："),
+                    self.count(self.rejected_events)
+                ));
+                if let Some(error) = &self.last_rejected_event {
+                    response.on_hover_text(self.demo_text(error));
+                }
+            }
+
+            self.render_action_kind_filter(ui);
+            let history_text = self.t("History", "历史");
+            ui.toggle_value(&mut self.show_action_history, history_text);
+            let alerts_text = if self.sensitive_alerts.is_empty() {
+                self.t("Sensitive paths", "敏感路径").to_string()
+            } else {
+                format!(
+                    "{} ({})",
+                    self.t("Sensitive paths", "敏感路径"),
+                    self.sensitive_alerts.len()
+                )
+            };
+            ui.toggle_value(&mut self.show_sensitive_panel, alerts_text);
+            let limits_text = if self.threshold_breaches.is_empty() {
+                self.t("Size limits", "大小限制").to_string()
+            } else {
+                format!(
+                    "{} ({})",
+                    self.t("Size limits", "大小限制"),
+                    self.threshold_breaches.len()
+                )
+            };
+            ui.toggle_value(&mut self.show_threshold_panel, limits_text);
+            let attribution_text = self.t("Size attribution", "大小归因");
+            ui.toggle_value(&mut self.show_size_attribution, attribution_text);
+            let commands_text = self.t("External commands", "外部命令");
+            ui.toggle_value(&mut self.show_external_commands, commands_text);
+            let log_text = self.t("Command log", "命令日志");
+            ui.toggle_value(&mut self.show_command_log, log_text);
+            let exclusions_text = if self.scan_config.exclude.is_empty() {
+                self.t("Exclusions", "排除规则").to_string()
+            } else {
+                format!(
+                    "{} ({})",
+                    self.t("Exclusions", "排除规则"),
+                    self.scan_config.exclude.len()
+                )
+            };
+            ui.toggle_value(&mut self.show_exclusion_panel, exclusions_text);
+            let script_text = self.t("Custom view", "自定义视图");
+            ui.toggle_value(&mut self.show_script_panel, script_text);
+            let sandbox_text = if self.sandbox_alerts.is_empty() {
+                self.t("Sandbox", "沙箱").to_string()
+            } else {
+                format!(
+                    "{} ({})",
+                    self.t("Sandbox", "沙箱"),
+                    self.sandbox_alerts.len()
+                )
+            };
+            ui.toggle_value(&mut self.show_sandbox_panel, sandbox_text);
+            let audit_text = self.t("Access audit", "访问审计");
+            ui.toggle_value(&mut self.show_access_audit, audit_text);
+
+            let hidden_kinds = &self.hidden_action_kinds;
+            let is_visible = |event: &ActionEvent| !hidden_kinds.contains(&event.kind);
+            let action_count = self.action_log.count_matching(is_visible);
+            ui.small(format!(
+                "{} {}",
+                self.t("OpenCLAW actions:", "OpenCLAW 动作："),
+                action_count
+            ));
+
+            self.render_action_rate(ui);
+
+            if let Some(last_action) = self.action_log.latest_matching(is_visible) {
+                let target_text = self.demo_path(&last_action.target_path);
+                ui.small(format!(
+                    "{} {} ({}) | {}",
+                    self.t("Last:", "最近："),
+                    last_action.kind.as_str(),
+                    relative_time(last_action.timestamp, self.language),
+                    target_text
+                ));
+            }
+
+            if let Some(update) = self.update_available.as_ref().filter(|update| {
+                self.update_settings.dismissed.as_ref() != Some(&update.version)
+            }) {
+                let notice = egui::RichText::new(format!(
+                    "{} {}",
+                    self.t("New version available:", "有新版本可用："),
+                    update.version
+                ))
+                .small()
+                .color(Color32::from_rgb(90, 170, 250));
+                if ui
+                    .add(egui::Button::new(notice).frame(false))
+                    .on_hover_text(self.t("Show release notes", "查看发布说明"))
+                    .clicked()
+                {
+                    self.show_update_panel = true;
+                }
+            }
+
+            let language_button = match self.language {
+                Language::English => "中文",
+                Language::Chinese => "English",
+            };
+            if ui.button(language_button).clicked() {
+                self.language.toggle();
+            }
+            egui::ComboBox::from_id_salt("size_units")
+                .selected_text(self.size_units.label())
+                .show_ui(ui, |ui| {
+                    for units in SizeUnits::ALL {
+                        ui.selectable_value(&mut self.size_units, units, units.label());
+                    }
+                })
+                .response
+                .on_hover_text(self.t("Size units", "大小单位"));
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .small_button(self.t("Reset View", "重置视图"))
+                    .on_hover_text(self.t("Reset pan and zoom", "重置平移与缩放"))
+                    .clicked()
+                {
+                    self.zoom_factor = 1.0;
+                    self.offset = egui::Vec2::ZERO;
+                }
+            });
+        });
+    }
+
+    fn render_action_kind_filter(&mut self, ui: &mut egui::Ui) {
+        let title = if self.hidden_action_kinds.is_empty() {
+            self.t("Action kinds", "动作类型").to_string()
+        } else {
+            format!(
+                "{} ({}/{})",
+                self.t("Action kinds", "动作类型"),
+                ActionKind::ALL.len() - self.hidden_action_kinds.len(),
+                ActionKind::ALL.len()
+            )
+        };
+
+        ui.menu_button(title, |ui| {
+            ui.horizontal(|ui| {
+                if ui.small_button(self.t("All", "全部")).clicked() {
+                    self.hidden_action_kinds.clear();
+                }
+                if ui.small_button(self.t("None", "无")).clicked() {
+                    self.hidden_action_kinds.extend(ActionKind::ALL);
+                }
+            });
+            ui.separator();
+
+            for kind in ActionKind::ALL {
+                ui.horizontal(|ui| {
+                    let (swatch_rect, _) =
+                        ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                    ui.painter()
+                        .rect_filled(swatch_rect, 2.0, color_for_action_kind(kind));
+
+                    let mut visible = !self.hidden_action_kinds.contains(&kind);
+                    if ui.checkbox(&mut visible, kind.as_str()).changed() {
+                        if visible {
+                            self.hidden_action_kinds.remove(&kind);
+                        } else {
+                            self.hidden_action_kinds.insert(kind);
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    fn render_action_history(&mut self, ui: &mut egui::Ui) {
+        ui.heading(self.t("Action history", "动作历史"));
+
+        ui.horizontal(|ui| {
+            ui.label(self.t("Capacity:", "容量："));
+            let mut capacity = self.action_log.capacity();
+            if ui
+                .add(
+                    egui::DragValue::new(&mut capacity)
+                        .range(50..=100_000)
+                        .speed(50.0),
+                )
+                .changed()
+            {
+                self.action_log.set_capacity(capacity);
+            }
+            if ui.small_button(self.t("Clear", "清空")).clicked() {
+                self.action_log.clear();
+            }
+            if ui.small_button(self.t("Export...", "导出...")).clicked() {
+                self.export_action_history();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(self.t("Search:", "搜索："));
+            let hint = self.t("path, kind or agent", "路径、类型或代理");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.history_search)
+                    .hint_text(hint)
+                    .desired_width(f32::INFINITY),
+            );
+        });
+        ui.separator();
+
+        let query = self.history_search.trim().to_lowercase();
+        let rows: Vec<(String, String, PathBuf, Color32)> = self
+            .action_log
+            .with_entries(|entries| {
+                entries
+                    .iter()
+                    .rev()
+                    .filter(|event| !self.hidden_action_kinds.contains(&event.kind))
+                    .filter_map(|event| {
+                        let path_text = self.demo_path(&event.target_path);
+                        let matches = query.is_empty()
+                            || path_text.to_lowercase().contains(&query)
+                            || event.kind.as_str().contains(&query)
+                            || event.agent_id.to_lowercase().contains(&query);
+                        if !matches {
+                            return None;
+                        }
+
+                        let header = format!(
+                            "{} ({}) {} [{}]",
+                            format_clock_time(event.timestamp),
+                            relative_time(event.timestamp, self.language),
+                            event.kind.as_str(),
+                            event.agent_id
+                        );
+                        Some((
+                            header,
+                            path_text,
+                            event.target_path.clone(),
+                            color_for_action_kind(event.kind),
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        ui.small(format!(
+            "{} {} / {}",
+            self.t("Showing", "显示"),
+            rows.len(),
+            self.action_log.count_matching(|_| true)
+        ));
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Small) * 2.0 + 6.0;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, rows.len(), |ui, range| {
+                for (header, path_text, target_path, color) in &rows[range] {
+                    let response = ui
+                        .vertical(|ui| {
+                            ui.small(egui::RichText::new(header).color(*color));
+                            ui.small(path_text);
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"));
+                    if response.clicked() {
+                        self.pending_jump = Some(target_path.clone());
+                    }
+                    ui.separator();
+                }
+            });
+    }
+
+    fn render_action_rate(&self, ui: &mut egui::Ui) {
+        let Some(histogram) = self.action_log.with_entries(|entries| {
+            let visible = entries
+                .iter()
+                .filter(|event| !self.hidden_action_kinds.contains(&event.kind));
+            RateHistogram::from_events(
+                visible,
+                SystemTime::now(),
+                Duration::from_secs(RATE_BUCKET_SECONDS),
+                RATE_BUCKET_COUNT,
+            )
+        }) else {
+            return;
+        };
+
+        let per_minute = RateHistogram::per_minute(&histogram.total, histogram.bucket);
+        let response = paint_sparkline(
+            ui,
+            &histogram.total,
+            Color32::from_rgb(255, 120, 120),
+            egui::vec2(90.0, 16.0),
+        );
+        ui.small(format!("{per_minute:.0}{}", self.t("/min", "/分钟")));
+
+        response.on_hover_ui(|ui| {
+            ui.label(format!(
+                "{} {} {}",
+                self.t("Actions per", "每"),
+                RATE_BUCKET_SECONDS,
+                self.t("s bucket, last 5 min", "秒一格，最近 5 分钟")
+            ));
+            let mut kinds: Vec<_> = histogram.per_kind.iter().collect();
+            kinds.sort_by_key(|(kind, _)| kind.as_str());
+            for (kind, buckets) in kinds {
+                ui.horizontal(|ui| {
+                    paint_sparkline(
+                        ui,
+                        buckets,
+                        color_for_action_kind(*kind),
+                        egui::vec2(90.0, 14.0),
+                    );
+                    ui.small(format!(
+                        "{} {:.1}{}",
+                        kind.as_str(),
+                        RateHistogram::per_minute(buckets, histogram.bucket),
+                        self.t("/min", "/分钟")
+                    ));
+                });
+            }
+        });
+
+        // Keep the sparkline scrolling while recent actions are in the window.
+        if histogram.total.iter().any(|count| *count > 0) {
+            ui.ctx().request_repaint_after(Duration::from_secs(1));
+        }
+    }
+
+    fn render_status_bar(&self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            if gateway::cleanup_mode() {
+                ui.colored_label(
+                    SANDBOX_DENIED_COLOR,
+                    egui::RichText::new(self.t(
+                        "Mode: cleanup (moving to the trash is allowed)",
+                        "模式：清理（允许移到回收站）",
+                    ))
+                    .small(),
+                );
+            } else {
+                ui.small(format!(
+                    "{} · {} {}",
+                    self.t(
+                        "Mode: read-only visualization (no file operations)",
+                        "模式：只读可视化（不进行文件操作）",
+                    ),
+                    self.t("file reads:", "文件读取："),
+                    self.count(gateway::stats().content_reads)
+                ));
+            }
+            if let Some(data_dir) = portable::data_dir() {
+                ui.separator();
+                ui.small(format!(
+                    "{} {}",
+                    self.t("Portable, data in", "便携模式，数据位于"),
+                    self.demo_path(data_dir)
+                ));
+            }
+
+            if let Some(hovered) = &self.hovered_entry {
+                let name_text = self.demo_name(&hovered.name, &hovered.path, hovered.is_dir);
+                let path_text = self.demo_path(&hovered.path);
+                let size_text = if hovered.is_dir {
+                    format!(
+                        "{} ({} {})",
+                        self.size(hovered.size),
+                        self.t("own", "自身"),
+                        self.size(hovered.own_size)
+                    )
+                } else {
+                    self.size(hovered.size)
+                };
+                ui.separator();
+                ui.small(format!("{} | {} | {}", name_text, size_text, path_text));
+            } else if let Some(root) = &self.root_path {
+                let root_text = self.demo_path(root);
+                ui.separator();
+                ui.small(format!(
+                    "{} {}",
+                    self.t(
+                        "Hover a rectangle to inspect full path. Root:",
+                        "把鼠标停在方块上可查看完整路径。根目录："
+                    ),
+                    root_text
+                ));
+            }
+
+            if let Some(status) = &self.export_status {
+                ui.separator();
+                ui.small(self.demo_text(status));
+            }
+            if let Some(status) = &self.command_status {
+                ui.separator();
+                ui.small(self.demo_text(status));
+            }
+        });
+    }
+
+    fn render_perf_overlay(&self, ctx: &egui::Context) {
+        let stats = &self.perf_stats;
+        let (mean_ms, worst_ms) = stats.frame_summary_ms();
+        let format_ms = |duration: Option<Duration>| match duration {
+            Some(duration) => format!("{:.2} ms", duration.as_secs_f64() * 1000.0),
+            None => "-".to_string(),
+        };
+
+        let (entries, bytes, elapsed) = match (self.mode, &self.scan_result) {
+            (AppMode::Scanning, _) => (
+                self.scan_progress.entries_scanned,
+                self.scan_progress.bytes_scanned,
+                stats
+                    .scan_started_at
+                    .map(|started| started.elapsed())
+                    .unwrap_or_default(),
+            ),
+            (_, Some(scan_result)) => (
+                scan_result.stats.entries_scanned,
+                scan_result.root.size,
+                scan_result.stats.elapsed,
+            ),
+            _ => (0, 0, Duration::ZERO),
+        };
+        let throughput = self.throughput_text(entries, bytes, elapsed);
+
+        egui::Area::new(egui::Id::new("perf_overlay"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -36.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .fill(Color32::from_black_alpha(200))
+                    .show(ui, |ui| {
+                        egui::Grid::new("perf_overlay_grid")
+                            .num_columns(2)
+                            .show(ui, |ui| {
+                                let rows = [
+                                    (
+                                        self.t("Frame (mean / worst)", "帧时间（平均/最差）"),
+                                        format!("{mean_ms:.1} / {worst_ms:.1} ms"),
+                                    ),
+                                    (
+                                        self.t("Update CPU", "更新 CPU"),
+                                        format_ms(Some(stats.last_update)),
+                                    ),
+                                    (
+                                        self.t("Cells drawn", "绘制方块"),
+                                        format!("{} / {}", stats.cells_drawn, stats.cells_total),
+                                    ),
+                                    (
+                                        self.t("Layout rebuild", "布局重建"),
+                                        format_ms(stats.last_layout_rebuild),
+                                    ),
+                                    (
+                                        self.t("Hover hit-test", "悬停命中检测"),
+                                        format_ms(stats.last_hit_test),
+                                    ),
+                                    (self.t("Scanner throughput", "扫描吞吐"), throughput),
+                                    (
+                                        self.t("Detail reduction", "细节降级"),
+                                        format!(
+                                            "{} / {}",
+                                            self.detail_governor.level,
+                                            DetailGovernor::MAX_LEVEL
+                                        ),
+                                    ),
+                                ];
+                                for (label, value) in rows {
+                                    ui.small(label);
+                                    ui.monospace(value);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+    }
+
+    fn render_warning_banner(&self, ui: &mut egui::Ui, message: &str) {
+        egui::Frame::none()
+            .fill(Color32::from_rgb(255, 232, 147))
+            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(177, 116, 0)))
+            .inner_margin(egui::Margin::same(6.0))
+            .show(ui, |ui| {
+                ui.colored_label(Color32::from_rgb(74, 54, 0), message);
+            });
+    }
+
+    fn render_type_legend(&mut self, ui: &mut egui::Ui) {
+        self.legend_hover = None;
+        if self.type_stats.is_empty() || self.total_file_bytes == 0 {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(self.t("Color by:", "着色依据："));
+            let mut by_category = self.color_by_category;
+            ui.selectable_value(&mut by_category, false, self.t("Type", "类型"));
+            ui.selectable_value(&mut by_category, true, self.t("Category", "类别"));
+            if by_category != self.color_by_category {
+                self.color_by_category = by_category;
+                self.layout_cache.clear();
+                self.treemap_cache = None;
+            }
+        });
+
+        if self.color_by_category {
+            self.render_category_legend(ui);
+        } else {
+            self.render_extension_legend(ui);
+        }
+        self.render_legend_chart(ui);
+    }
+
+    fn render_extension_legend(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(self.t("Top N types:", "前 N 个类型："));
+            ui.add(
+                egui::DragValue::new(&mut self.legend_top_n)
+                    .range(3..=30)
+                    .speed(0.2),
+            );
+        });
+
+        let mut hovered = None;
+        egui::CollapsingHeader::new(self.t("Type Legend", "类型图例"))
+            .default_open(true)
+            .show(ui, |ui| {
+                let count = self.legend_top_n.min(self.type_stats.len());
+                for stat in self.type_stats.iter().take(count) {
+                    let ratio = stat.bytes as f32 / self.total_file_bytes as f32;
+
+                    let row = ui.horizontal(|ui| {
+                        let (swatch_rect, _) =
+                            ui.allocate_exact_size(egui::vec2(14.0, 14.0), egui::Sense::hover());
+                        ui.painter().rect_filled(swatch_rect, 2.0, stat.color);
+
+                        ui.label(format_type_key(&stat.key, self.language));
+                        ui.add(
+                            egui::ProgressBar::new(ratio.clamp(0.0, 1.0))
+                                .desired_width(160.0)
+                                .text(percent(f64::from(ratio))),
+                        );
+                        ui.label(self.size(stat.bytes));
+                        ui.small(format!(
+                            "{} {}",
+                            self.count(stat.files),
+                            self.t("files", "个文件")
+                        ));
+                    });
+                    if row.response.contains_pointer() {
+                        hovered = Some(stat.key.clone());
+                    }
+                }
+            });
+        self.legend_hover = hovered;
+    }
+
+    /// The legend's entries as chart slices, plus one for the types beyond
+    /// the legend's top N.
+    fn legend_slices(&self) -> Vec<LegendSlice> {
+        let mut slices: Vec<LegendSlice> = if self.color_by_category {
+            self.categories
+                .summarize(&self.type_stats)
+                .into_iter()
+                .map(|stat| LegendSlice {
+                    key: stat.name.clone(),
+                    label: stat.name,
+                    bytes: stat.bytes,
+                    color: stat.color,
+                })
+                .collect()
+        } else {
+            self.type_stats
+                .iter()
+                .take(self.legend_top_n)
+                .map(|stat| LegendSlice {
+                    key: stat.key.clone(),
+                    label: format_type_key(&stat.key, self.language),
+                    bytes: stat.bytes,
+                    color: stat.color,
+                })
+                .collect()
+        };
+        let shown: u64 = slices.iter().map(|slice| slice.bytes).sum();
+        let other = self.total_file_bytes.saturating_sub(shown);
+        if other > 0 {
+            slices.push(LegendSlice {
+                key: String::new(),
+                label: self.t("Other", "其他").to_string(),
+                bytes: other,
+                color: Color32::from_gray(110),
+            });
+        }
+        slices
+    }
+
+    /// The legend entry a file belongs to: its category name when coloring by
+    /// category, otherwise its type key.
+    fn legend_key_of(&self, path: &Path) -> String {
+        let key = self.type_key_of(path);
+        if self.color_by_category {
+            self.categories.category_of(&key).name.clone()
+        } else {
+            key
+        }
+    }
+
+    /// Donut chart of the legend's bytes. Hovering a slice outlines its files
+    /// in the treemap; hovering a legend row or a treemap file lifts its slice.
+    fn render_legend_chart(&mut self, ui: &mut egui::Ui) {
+        let slices = self.legend_slices();
+        let total: u64 = slices.iter().map(|slice| slice.bytes).sum();
+        if total == 0 {
+            return;
+        }
+
+        let mut hovered_key = None;
+        egui::CollapsingHeader::new(self.t("Chart", "图表"))
+            .id_salt("legend_chart")
+            .default_open(false)
+            .show(ui, |ui| {
+                let (rect, response) =
+                    ui.allocate_exact_size(egui::vec2(200.0, 200.0), egui::Sense::hover());
+                let center = rect.center();
+                let outer = rect.width() / 2.0 - 6.0;
+                let inner = outer * 0.55;
+
+                // Slices run clockwise from the top.
+                let mut ends = Vec::with_capacity(slices.len());
+                let mut running = 0_u64;
+                for slice in &slices {
+                    running += slice.bytes;
+                    ends.push(running as f32 / total as f32);
+                }
+                let pointed = response.hover_pos().and_then(|pos| {
+                    let offset = pos - center;
+                    if offset.length() < inner || offset.length() > outer {
+                        return None;
+                    }
+                    let angle = (offset.y.atan2(offset.x) + std::f32::consts::FRAC_PI_2)
+                        .rem_euclid(std::f32::consts::TAU);
+                    let fraction = angle / std::f32::consts::TAU;
+                    ends.iter().position(|&end| fraction < end)
+                });
+                let linked = pointed.or_else(|| {
+                    let key = self.legend_hover.clone().or_else(|| {
+                        let hovered = self.hovered_entry.as_ref()?;
+                        (!hovered.is_dir).then(|| self.legend_key_of(&hovered.path))
+                    })?;
+                    slices
+                        .iter()
+                        .position(|slice| slice.key == key)
+                        .or_else(|| (!key.is_empty()).then(|| slices.len() - 1))
+                        .filter(|&index| slices[index].key.is_empty() || slices[index].key == key)
+                });
+
+                let mut mesh = egui::Mesh::default();
+                let mut start = 0.0;
+                for (index, (slice, &end)) in slices.iter().zip(&ends).enumerate() {
+                    let lift = if linked == Some(index) { 5.0 } else { 0.0 };
+                    let color = if linked.is_some_and(|linked| linked != index) {
+                        slice.color.gamma_multiply(0.6)
+                    } else {
+                        slice.color
+                    };
+                    add_ring_segment(&mut mesh, center, inner, outer + lift, start, end, color);
+                    start = end;
+                }
+                ui.painter().add(egui::Shape::mesh(mesh));
+
+                let center_text = match linked {
+                    Some(index) => format!(
+                        "{}\n{}\n{}",
+                        slices[index].label,
+                        self.size(slices[index].bytes),
+                        percent(slices[index].bytes as f64 / total as f64)
+                    ),
+                    None => self.size(total),
+                };
+                ui.painter().text(
+                    center,
+                    egui::Align2::CENTER_CENTER,
+                    center_text,
+                    egui::TextStyle::Small.resolve(ui.style()),
+                    ui.visuals().text_color(),
+                );
+                hovered_key = pointed
+                    .map(|index| slices[index].key.clone())
+                    .filter(|key| !key.is_empty());
+            });
+        if hovered_key.is_some() {
+            self.legend_hover = hovered_key;
+        }
+    }
+
+    fn render_git_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(git) = &self.git_scan else {
+            return;
+        };
+        if git.repos.is_empty() {
+            return;
+        }
+
+        let title = format!(
+            "{} ({})",
+            self.t("Git repositories", "Git 仓库"),
+            self.count(git.repos.len() as u64)
+        );
+        let mut jump = None;
+        let mut hide_ignored = self.hide_git_ignored;
+        egui::CollapsingHeader::new(title)
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.checkbox(
+                    &mut hide_ignored,
+                    self.t("Hide git-ignored files", "隐藏被 git 忽略的文件"),
+                )
+                .on_hover_text(self.t(
+                    "Lay out only what the repositories track or could track",
+                    "只显示仓库跟踪或可跟踪的内容",
+                ));
+                egui::Grid::new("git_repos").striped(true).show(ui, |ui| {
+                    ui.strong(self.t("Repository", "仓库"));
+                    ui.strong(".git");
+                    ui.strong(self.t("Working tree", "工作区"));
+                    ui.strong(self.t("Ignored", "已忽略"));
+                    ui.end_row();
+                    for repo in &git.repos {
+                        if ui
+                            .link(self.demo_path(&repo.root))
+                            .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                            .clicked()
+                        {
+                            jump = Some(repo.root.clone());
+                        }
+                        ui.label(self.size(repo.git_dir_bytes));
+                        ui.label(self.size(repo.working_bytes));
+                        ui.label(self.size(repo.ignored_bytes));
+                        ui.end_row();
+                    }
+                });
+            });
+
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+        if hide_ignored != self.hide_git_ignored {
+            self.hide_git_ignored = hide_ignored;
+            self.update_view_root();
+        }
+    }
+
+    /// Total size over this session's scans and the folders growing fastest.
+    fn render_growth_panel(&mut self, ui: &mut egui::Ui) {
+        let samples = self.growth.samples();
+        let (Some(first), Some(last)) = (samples.front(), samples.back()) else {
+            return;
+        };
+        if samples.len() < 2 {
+            return;
+        }
+
+        let delta = last.total as i64 - first.total as i64;
+        let span = last.at.duration_since(first.at).unwrap_or_default();
+        let title = format!(
+            "{} {} {} {} {}",
+            self.t("Growth:", "增长："),
+            self.signed_size(delta),
+            self.t("over", "，共"),
+            self.count(samples.len() as u64),
+            self.t("scans", "次扫描")
+        );
+        let totals: Vec<u64> = samples.iter().map(|sample| sample.total).collect();
+        let mut jump = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("growth_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    paint_growth_chart(ui, &totals, egui::vec2(260.0, 48.0));
+                    ui.vertical(|ui| {
+                        ui.small(format!(
+                            "{} → {}",
+                            self.size(first.total),
+                            self.size(last.total)
+                        ));
+                        ui.small(format!(
+                            "{} {}",
+                            self.t("in", "用时"),
+                            format_duration_compact(span, self.language)
+                        ));
+                    });
+                });
+                ui.label(self.t("Fastest growing folders", "增长最快的文件夹"));
+                egui::Grid::new("growth_folders")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for growth in self.growth.fastest_growing(15) {
+                            if ui
+                                .link(self.demo_path(&growth.path))
+                                .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                                .clicked()
+                            {
+                                jump = Some(growth.path.clone());
+                            }
+                            ui.label(self.signed_size(growth.delta()));
+                            ui.label(format!(
+                                "{} → {}",
+                                self.size(growth.before),
+                                self.size(growth.after)
+                            ));
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    /// Code, comment and blank lines per language and the files with the
+    /// most code.
+    fn render_loc_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(report) = &self.loc_report else {
+            return;
+        };
+        let total: u64 = report
+            .languages
+            .iter()
+            .map(|(_, _, counts)| counts.code)
+            .sum();
+        let title = format!(
+            "{} {}",
+            self.t("Lines of code:", "代码行数："),
+            self.count(total)
+        );
+        let mut largest: Vec<(&PathBuf, u64)> = report
+            .files
+            .iter()
+            .map(|(path, counts)| (path, counts.code))
+            .collect();
+        largest.sort_by_key(|(path, code)| (std::cmp::Reverse(*code), *path));
+        let mut jump = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("loc_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                egui::Grid::new("loc_languages")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong(self.t("Language", "语言"));
+                        ui.strong(self.t("Files", "文件"));
+                        ui.strong(self.t("Code", "代码"));
+                        ui.strong(self.t("Comments", "注释"));
+                        ui.strong(self.t("Blank", "空行"));
+                        ui.end_row();
+                        for (name, files, counts) in &report.languages {
+                            ui.label(*name);
+                            ui.label(self.count(*files));
+                            ui.label(self.count(counts.code));
+                            ui.label(self.count(counts.comments));
+                            ui.label(self.count(counts.blanks));
+                            ui.end_row();
+                        }
+                    });
+                ui.separator();
+                egui::Grid::new("loc_largest_files")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (path, code) in largest.iter().take(10) {
+                            if ui
+                                .link(self.demo_path(path))
+                                .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                                .clicked()
+                            {
+                                jump = Some((*path).clone());
+                            }
+                            ui.label(self.count(*code));
+                            ui.end_row();
+                        }
+                    });
+            });
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    /// The folders and files with the most estimated tokens, against the
+    /// context budget.
+    fn render_token_panel(&mut self, ui: &mut egui::Ui) {
+        let (Some(report), Some(result)) = (&self.token_report, &self.scan_result) else {
+            return;
+        };
+        let total = report.tokens(&result.root.path).unwrap_or(0);
+        let title = format!(
+            "{} {}",
+            self.t("Estimated tokens:", "估算 token："),
+            self.count(total)
+        );
+        let mut folders: Vec<(&PathBuf, u64)> = report
+            .folders
+            .iter()
+            .filter(|(path, _)| **path != result.root.path)
+            .map(|(path, tokens)| (path, *tokens))
+            .collect();
+        folders.sort_by_key(|(path, tokens)| (std::cmp::Reverse(*tokens), *path));
+        let mut files: Vec<(&PathBuf, u64)> = report
+            .files
+            .iter()
+            .map(|(path, tokens)| (path, *tokens))
+            .collect();
+        files.sort_by_key(|(path, tokens)| (std::cmp::Reverse(*tokens), *path));
+
+        let mut budget = self.context_budget;
+        let mut jump = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("token_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(self.t("Context budget:", "上下文预算："));
+                    ui.add(
+                        egui::DragValue::new(&mut budget)
+                            .range(1_000..=10_000_000)
+                            .speed(1_000.0),
+                    );
+                });
+                for (grid, entries) in [("token_folders", &folders), ("token_files", &files)] {
+                    ui.separator();
+                    egui::Grid::new(grid).striped(true).show(ui, |ui| {
+                        for (path, tokens) in entries.iter().take(10) {
+                            if ui
+                                .link(self.demo_path(path))
+                                .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                                .clicked()
+                            {
+                                jump = Some((*path).clone());
+                            }
+                            let text = format!(
+                                "{} ({})",
+                                self.count(*tokens),
+                                percent(*tokens as f64 / budget.max(1) as f64)
+                            );
+                            if *tokens > budget {
+                                ui.colored_label(Color32::from_rgb(210, 70, 70), text);
+                            } else {
+                                ui.label(text);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                }
+            });
+        self.context_budget = budget;
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    /// Lists the outermost folders estimated to compress to half their size
+    /// or less, by bytes saved.
+    fn render_compress_panel(&mut self, ui: &mut egui::Ui) {
+        let (Some(estimate), Some(result)) = (&self.compress_estimate, &self.scan_result) else {
+            return;
+        };
+
+        let mut folders = Vec::new();
+        let mut stack = vec![&result.root];
+        while let Some(node) = stack.pop() {
+            match estimate.ratio(node) {
+                Some(ratio) if ratio <= 0.5 && node.size >= 1024 * 1024 => folders.push(node),
+                _ => stack.extend(
+                    node.children
+                        .iter()
+                        .filter(|child| !child.children.is_empty()),
+                ),
+            }
+        }
+        folders.sort_by_key(|node| {
+            std::cmp::Reverse(node.size - estimate.folders.get(&node.path).copied().unwrap_or(0))
+        });
+
+        let whole = estimate
+            .folders
+            .get(&result.root.path)
+            .copied()
+            .unwrap_or(result.root.size);
+        let title = format!(
+            "{} {} → ~{}",
+            self.t("Compressibility:", "可压缩性："),
+            self.size(result.root.size),
+            self.size(whole)
+        );
+        let mut jump = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("compress_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                if folders.is_empty() {
+                    ui.small(self.t(
+                        "No folder would shrink to half its size.",
+                        "没有文件夹能压缩到一半以下。",
+                    ));
+                }
+                egui::Grid::new("compress_folders")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for node in folders.iter().take(20) {
+                            let compressed = estimate.folders.get(&node.path).copied().unwrap_or(0);
+                            if ui
+                                .link(self.demo_path(&node.path))
+                                .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                                .clicked()
+                            {
+                                jump = Some(node.path.clone());
+                            }
+                            ui.label(format!(
+                                "{} → ~{}",
+                                self.size(node.size),
+                                self.size(compressed)
+                            ));
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    fn render_permission_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        let findings = &result.permission_findings;
+        if findings.is_empty() {
+            return;
+        }
+
+        let title = format!(
+            "{} {}",
+            self.t("Permission findings:", "权限问题："),
+            self.count(findings.len() as u64)
+        );
+        let mut jump = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("permission_panel")
+            .default_open(true)
+            .show(ui, |ui| {
+                if findings.len() >= MAX_PERMISSION_FINDINGS {
+                    ui.small(self.t("Only the first findings were kept.", "只保留了前面的结果。"));
+                }
+                egui::ScrollArea::vertical()
+                    .id_salt("permission_findings")
+                    .max_height(180.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("permission_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for finding in findings {
+                                    if ui
+                                        .link(self.demo_path(&finding.path))
+                                        .on_hover_text(
+                                            self.t("Click to jump to target", "点击跳转到目标"),
+                                        )
+                                        .clicked()
+                                    {
+                                        jump = Some(finding.path.clone());
+                                    }
+                                    ui.colored_label(
+                                        PERMISSION_FINDING_COLOR,
+                                        permission_issue_text(finding),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    /// An owner's name, or a rank-based stand-in in demo mode.
+    fn owner_label(&self, rank: usize, owner: &OwnerStat) -> String {
+        if self.demo_mode {
+            format!("{} {}", self.t("Owner", "所有者"), rank + 1)
+        } else {
+            owner.display_name()
+        }
+    }
+
+    fn render_owner_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        let owners = &result.owners;
+        if owners.len() < 2 {
+            return;
+        }
+
+        let total_bytes = owners.iter().map(|owner| owner.bytes).sum::<u64>().max(1);
+        let mut jump = None;
+        let mut color_by_owner = self.color_by_owner;
+        let mut selected_owner = self.selected_owner;
+        egui::CollapsingHeader::new(format!(
+            "{} {}",
+            self.t("Owners:", "所有者："),
+            self.count(owners.len() as u64)
+        ))
+        .id_salt("owner_panel")
+        .default_open(false)
+        .show(ui, |ui| {
+            let color_text = self.t("Color by owner", "按所有者着色");
+            ui.checkbox(&mut color_by_owner, color_text);
+            egui::ScrollArea::vertical()
+                .id_salt("owner_items")
+                .max_height(260.0)
+                .show(ui, |ui| {
+                    for (rank, owner) in owners.iter().enumerate() {
+                        let share = owner.bytes as f64 / total_bytes as f64;
+                        let is_selected = selected_owner == Some(owner.uid);
+                        ui.horizontal(|ui| {
+                            let (swatch, _) = ui
+                                .allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                            ui.painter()
+                                .rect_filled(swatch, 2.0, owner_color(owner.uid));
+                            if ui
+                                .selectable_label(is_selected, self.owner_label(rank, owner))
+                                .on_hover_text(
+                                    self.t(
+                                        "Show largest files and folders",
+                                        "显示最大的文件和文件夹",
+                                    ),
+                                )
+                                .clicked()
+                            {
+                                selected_owner = (!is_selected).then_some(owner.uid);
+                            }
+                            ui.add(
+                                egui::ProgressBar::new(share as f32)
+                                    .desired_width(90.0)
+                                    .text(percent(share)),
+                            );
+                            ui.small(format!(
+                                "{} · {} {}",
+                                self.size(owner.bytes),
+                                self.count(owner.files),
+                                self.t("files", "个文件")
+                            ));
+                        });
+                        if !is_selected {
+                            continue;
+                        }
+                        ui.indent(("owner_details", owner.uid), |ui| {
+                            for (heading, entries) in [
+                                (self.t("Top folders", "最大文件夹"), &owner.top_folders),
+                                (self.t("Largest files", "最大文件"), &owner.largest_files),
+                            ] {
+                                ui.small(heading);
+                                for (path, bytes) in entries {
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .link(self.demo_path(path))
+                                            .on_hover_text(
+                                                self.t("Click to jump to target", "点击跳转到目标"),
+                                            )
+                                            .clicked()
+                                        {
+                                            jump = Some(path.clone());
+                                        }
+                                        ui.small(self.size(*bytes));
+                                    });
+                                }
+                            }
+                        });
+                    }
+                });
+        });
+
+        if color_by_owner != self.color_by_owner
+            || (color_by_owner && selected_owner != self.selected_owner)
+        {
+            self.layout_cache.clear();
+            self.treemap_cache = None;
+        }
+        self.color_by_owner = color_by_owner;
+        self.selected_owner = selected_owner;
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    fn render_symlink_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(result) = &self.scan_result else {
+            return;
+        };
+        let links = &result.symlinks;
+        if links.is_empty() {
+            return;
+        }
+
+        let broken = links
+            .iter()
+            .filter(|link| link.status == LinkStatus::Broken)
+            .count();
+        let title = format!(
+            "{} {} ({} {})",
+            self.t("Symbolic links:", "符号链接："),
+            self.count(links.len() as u64),
+            self.count(broken as u64),
+            self.t("broken", "已失效")
+        );
+        let mut jump = None;
+        let mut show_arrows = self.show_symlink_arrows;
+        egui::CollapsingHeader::new(title)
+            .id_salt("symlink_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.small(self.t(
+                    "Links and junctions are never followed; their targets are counted where they live.",
+                    "从不跟随链接与联接点；其目标的大小计入目标所在位置。",
+                ));
+                if links.len() >= MAX_SYMLINKS {
+                    ui.small(self.t("Only the first links were kept.", "只保留了前面的链接。"));
+                }
+                let arrows_text = self.t("Draw arrows to targets inside the root", "绘制指向根目录内目标的箭头");
+                ui.checkbox(&mut show_arrows, arrows_text);
+                egui::ScrollArea::vertical()
+                    .id_salt("symlink_items")
+                    .max_height(180.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("symlink_grid").striped(true).show(ui, |ui| {
+                            for link in links {
+                                if ui
+                                    .link(self.demo_path(&link.path))
+                                    .on_hover_text(
+                                        self.t("Click to jump to target", "点击跳转到目标"),
+                                    )
+                                    .clicked()
+                                {
+                                    jump = Some(link.path.clone());
+                                }
+                                let target_text = format!("→ {}", self.demo_path(&link.target));
+                                if link.status == LinkStatus::Inside {
+                                    if ui
+                                        .link(target_text)
+                                        .on_hover_text(
+                                            self.t("Click to jump to target", "点击跳转到目标"),
+                                        )
+                                        .clicked()
+                                    {
+                                        jump = Some(link.resolved.clone());
+                                    }
+                                } else {
+                                    ui.label(target_text);
+                                }
+                                ui.colored_label(
+                                    symlink_status_color(link.status),
+                                    symlink_status_text(link.status, self.language),
+                                );
+                                ui.end_row();
+                            }
+                        });
+                    });
+            });
+
+        self.show_symlink_arrows = show_arrows;
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    fn render_anomaly_panel(&mut self, ui: &mut egui::Ui) {
+        let total = self.anomalies.total();
+        if total == 0 {
+            return;
+        }
+
+        let title = format!(
+            "{} {}",
+            self.t("Path anomalies:", "路径异常："),
+            self.count(total)
+        );
+        let mut jump = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("anomaly_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.small(self.t(
+                    "Names and paths that often break backups and sync tools.",
+                    "经常导致备份和同步工具出错的名称和路径。",
+                ));
+                for (kind, count) in &self.anomalies.counts {
+                    ui.small(format!("{}: {}", kind.label(), self.count(*count)));
+                }
+                if (self.anomalies.items.len() as u64) < total {
+                    ui.small(format!(
+                        "{} {}",
+                        self.t("Listing the first", "仅列出前"),
+                        self.count(self.anomalies.items.len() as u64)
+                    ));
+                }
+                egui::ScrollArea::vertical()
+                    .id_salt("anomaly_items")
+                    .max_height(180.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("anomaly_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for anomaly in &self.anomalies.items {
+                                    let path = escape_control_chars(&self.demo_path(&anomaly.path));
+                                    if ui
+                                        .link(path)
+                                        .on_hover_text(
+                                            self.t("Click to jump to target", "点击跳转到目标"),
+                                        )
+                                        .clicked()
+                                    {
+                                        jump = Some(anomaly.path.clone());
+                                    }
+                                    ui.label(anomaly.kind.label());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    fn render_size_histogram_panel(&mut self, ui: &mut egui::Ui) {
+        let histogram = &self.size_histogram;
+        let max_files = histogram.files.iter().copied().max().unwrap_or(0);
+        if max_files == 0 {
+            return;
+        }
+
+        egui::CollapsingHeader::new(self.t("File sizes", "文件大小分布"))
+            .id_salt("size_histogram_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                let total_bytes = histogram.bytes.iter().sum::<u64>().max(1);
+                egui::Grid::new("size_histogram_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.small(self.t("Size", "大小"));
+                        ui.small(self.t("Files", "文件数"));
+                        ui.small("");
+                        ui.small(self.t("Bytes", "字节"));
+                        ui.small("");
+                        ui.end_row();
+                        for (bucket, (&files, &bytes)) in
+                            histogram.files.iter().zip(&histogram.bytes).enumerate()
+                        {
+                            let label = match SizeHistogram::bounds(bucket) {
+                                (0, Some(high)) => format!("< {}", self.size(high)),
+                                (low, Some(high)) => {
+                                    format!("{} - {}", self.size(low), self.size(high))
+                                }
+                                (low, None) => format!("> {}", self.size(low)),
+                            };
+                            ui.small(label);
+                            ui.add(
+                                egui::ProgressBar::new(files as f32 / max_files as f32)
+                                    .desired_width(100.0),
+                            );
+                            ui.small(self.count(files));
+                            let share = bytes as f64 / total_bytes as f64;
+                            ui.add(
+                                egui::ProgressBar::new(share as f32)
+                                    .desired_width(100.0)
+                                    .text(percent(share)),
+                            );
+                            ui.small(self.size(bytes));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn render_zero_byte_panel(&mut self, ui: &mut egui::Ui) {
+        if self.zero_byte_files.files.is_empty() {
+            return;
+        }
+
+        let title = format!(
+            "{} {}",
+            self.t("Zero-byte files:", "零字节文件："),
+            self.count(self.zero_byte_files.files.len() as u64)
+        );
+        let mut jump = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("zero_byte_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.small(self.t(
+                    "Empty files take no area in the treemap; many in one place often mean failed transfers or a broken generator.",
+                    "空文件在 treemap 中不占面积；同一位置出现大量空文件通常意味着传输失败或生成器出错。",
+                ));
+                ui.label(self.t("By folder", "按文件夹"));
+                egui::ScrollArea::vertical()
+                    .id_salt("zero_byte_folders")
+                    .max_height(140.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("zero_byte_folder_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (folder, count) in &self.zero_byte_files.folders {
+                                    if ui
+                                        .link(self.demo_path(folder))
+                                        .on_hover_text(
+                                            self.t("Click to jump to target", "点击跳转到目标"),
+                                        )
+                                        .clicked()
+                                    {
+                                        jump = Some(folder.clone());
+                                    }
+                                    ui.label(self.count(*count));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                ui.label(self.t("Files", "文件"));
+                egui::ScrollArea::vertical()
+                    .id_salt("zero_byte_file_list")
+                    .max_height(160.0)
+                    .show(ui, |ui| {
+                        for file in self.zero_byte_files.files.iter().take(ZERO_BYTE_LIST_LIMIT) {
+                            if ui
+                                .link(self.demo_path(file))
+                                .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                                .clicked()
+                            {
+                                jump = Some(file.clone());
+                            }
+                        }
+                        let hidden = self
+                            .zero_byte_files
+                            .files
+                            .len()
+                            .saturating_sub(ZERO_BYTE_LIST_LIMIT);
+                        if hidden > 0 {
+                            ui.small(format!(
+                                "{} {}",
+                                self.count(hidden as u64),
+                                self.t("more not listed", "个未列出")
+                            ));
+                        }
+                    });
+            });
+
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    fn render_age_histogram_panel(&mut self, ui: &mut egui::Ui) {
+        let histogram = &self.age_histogram;
+        let max_bytes = histogram.bytes.iter().copied().max().unwrap_or(0);
+        if max_bytes == 0 {
+            return;
+        }
+
+        let mut filter = self.age_filter;
+        egui::CollapsingHeader::new(self.t("File ages", "文件年龄分布"))
+            .id_salt("age_histogram_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.small(self.t(
+                    "Bytes by last modification. Click a row to highlight its files.",
+                    "按最后修改时间统计字节数。点击一行以高亮对应文件。",
+                ));
+                egui::Grid::new("age_histogram_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (bucket, (&files, &bytes)) in
+                            histogram.files.iter().zip(&histogram.bytes).enumerate()
+                        {
+                            let selected = filter == Some(bucket);
+                            if ui
+                                .selectable_label(selected, self.age_bucket_label(bucket))
+                                .clicked()
+                            {
+                                filter = (!selected).then_some(bucket);
+                            }
+                            ui.add(
+                                egui::ProgressBar::new(bytes as f32 / max_bytes as f32)
+                                    .desired_width(120.0),
+                            );
+                            ui.small(self.size(bytes));
+                            ui.small(format!(
+                                "{} {}",
+                                self.count(files),
+                                self.t("files", "个文件")
+                            ));
+                            ui.end_row();
+                        }
+                    });
+            });
+        self.age_filter = filter;
+    }
+
+    fn age_bucket_label(&self, bucket: usize) -> &'static str {
+        const LABELS: [(&str, &str); AGE_BUCKET_BOUNDS.len() + 2] = [
+            ("< 1 month", "< 1 个月"),
+            ("1-6 months", "1-6 个月"),
+            ("6-12 months", "6-12 个月"),
+            ("1-2 years", "1-2 年"),
+            ("> 2 years", "> 2 年"),
+            ("Unknown", "未知"),
+        ];
+        let (english, chinese) = LABELS[bucket];
+        self.t(english, chinese)
+    }
+
+    fn render_shape_panel(&mut self, ui: &mut egui::Ui) {
+        if self.shape.depth_histogram.is_empty() {
+            return;
+        }
+
+        let mut jump = None;
+        egui::CollapsingHeader::new(self.t("Tree statistics", "树结构统计"))
+            .id_salt("shape_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                let shape = &self.shape;
+                ui.small(format!(
+                    "{} {} | {} {} | {} {}",
+                    self.count(shape.files),
+                    self.t("files", "个文件"),
+                    self.count(shape.folders),
+                    self.t("folders", "个文件夹"),
+                    self.t("average file", "平均文件"),
+                    shape
+                        .average_file_size()
+                        .map_or_else(|| "-".to_string(), |average| self.size(average)),
+                ));
+
+                let mut link = |ui: &mut egui::Ui, label: &str, path: &Path, value: usize| {
+                    ui.horizontal(|ui| {
+                        ui.small(format!("{label} ({value}):"));
+                        if ui
+                            .link(self.demo_path(path))
+                            .on_hover_text(self.t("Click to jump to target", "点击跳转到目标"))
+                            .clicked()
+                        {
+                            jump = Some(path.to_path_buf());
+                        }
+                    });
+                };
+                if let Some((path, depth)) = &shape.deepest {
+                    link(ui, self.t("Deepest", "最深"), path, *depth);
+                }
+                if let Some((path, fan_out)) = &shape.widest {
+                    link(ui, self.t("Widest", "最宽"), path, *fan_out);
+                }
+
+                let histogram = |ui: &mut egui::Ui, id: &str, rows: Vec<(String, u64)>| {
+                    let max = rows
+                        .iter()
+                        .map(|(_, count)| *count)
+                        .max()
+                        .unwrap_or(0)
+                        .max(1);
+                    egui::Grid::new(id).striped(true).show(ui, |ui| {
+                        for (label, count) in rows {
+                            ui.small(label);
+                            ui.add(
+                                egui::ProgressBar::new(count as f32 / max as f32)
+                                    .desired_width(140.0),
+                            );
+                            ui.small(self.count(count));
+                            ui.end_row();
+                        }
+                    });
+                };
+                ui.label(self.t("Entries per depth", "各层级条目数"));
+                histogram(
+                    ui,
+                    "shape_depth_grid",
+                    shape
+                        .depth_histogram
+                        .iter()
+                        .enumerate()
+                        .map(|(depth, count)| ((depth + 1).to_string(), *count))
+                        .collect(),
+                );
+                ui.label(self.t("Folders by number of children", "按子项数量统计文件夹"));
+                histogram(
+                    ui,
+                    "shape_fan_out_grid",
+                    shape
+                        .fan_out_histogram
+                        .iter()
+                        .enumerate()
+                        .map(|(bucket, count)| (fan_out_label(bucket), *count))
+                        .collect(),
+                );
             });
+
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
     }
 
-    fn render_type_legend(&mut self, ui: &mut egui::Ui) {
-        if self.type_stats.is_empty() || self.total_file_bytes == 0 {
+    fn render_reclaim_panel(&mut self, ui: &mut egui::Ui) {
+        if self.reclaim.items.is_empty() {
             return;
         }
 
-        ui.horizontal(|ui| {
-            ui.label(self.t("Top N types:", "前 N 个类型："));
-            ui.add(
-                egui::DragValue::new(&mut self.legend_top_n)
-                    .range(3..=30)
-                    .speed(0.2),
-            );
-        });
+        let title = format!(
+            "{} {}",
+            self.t("Probably safe to delete:", "大概可以安全删除："),
+            self.size(self.reclaim.total_bytes)
+        );
+        let mut jump = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("reclaim_panel")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.small(self.t(
+                    "Build output, dependency folders and caches their tools can recreate. Nothing is deleted here.",
+                    "可由相应工具重新生成的构建输出、依赖目录和缓存。此处不会删除任何内容。",
+                ));
+                let badge_text = self.t("Badge in treemap", "在 treemap 中标记");
+                ui.checkbox(&mut self.show_reclaim_badges, badge_text);
+                egui::ScrollArea::vertical()
+                    .max_height(180.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("reclaim_items").striped(true).show(ui, |ui| {
+                            for item in &self.reclaim.items {
+                                if ui
+                                    .link(self.demo_path(&item.path))
+                                    .on_hover_text(
+                                        self.t("Click to jump to target", "点击跳转到目标"),
+                                    )
+                                    .clicked()
+                                {
+                                    jump = Some(item.path.clone());
+                                }
+                                ui.label(item.label);
+                                ui.label(self.size(item.size));
+                                ui.end_row();
+                            }
+                        });
+                    });
+            });
 
-        egui::CollapsingHeader::new(self.t("Type Legend", "类型图例"))
+        if jump.is_some() {
+            self.pending_jump = jump;
+        }
+    }
+
+    fn render_category_legend(&mut self, ui: &mut egui::Ui) {
+        let stats = self.categories.summarize(&self.type_stats);
+        let mut hovered = None;
+        egui::CollapsingHeader::new(self.t("Category Legend", "类别图例"))
             .default_open(true)
             .show(ui, |ui| {
-                let count = self.legend_top_n.min(self.type_stats.len());
-                for stat in self.type_stats.iter().take(count) {
+                for stat in &stats {
                     let ratio = stat.bytes as f32 / self.total_file_bytes as f32;
-                    let percent = ratio * 100.0;
 
-                    ui.horizontal(|ui| {
+                    let row = ui.horizontal(|ui| {
                         let (swatch_rect, _) =
                             ui.allocate_exact_size(egui::vec2(14.0, 14.0), egui::Sense::hover());
                         ui.painter().rect_filled(swatch_rect, 2.0, stat.color);
 
-                        ui.label(format_type_key(&stat.key, self.language));
+                        ui.label(&stat.name);
                         ui.add(
                             egui::ProgressBar::new(ratio.clamp(0.0, 1.0))
                                 .desired_width(160.0)
-                                .text(format!("{percent:.1}%")),
+                                .text(percent(f64::from(ratio))),
                         );
-                        ui.label(human_size(stat.bytes));
-                        ui.small(format!("{} {}", stat.files, self.t("files", "个文件")));
+                        ui.label(self.size(stat.bytes));
+                        ui.small(format!(
+                            "{} {}",
+                            self.count(stat.files),
+                            self.t("files", "个文件")
+                        ));
                     });
+                    if row.response.contains_pointer() {
+                        hovered = Some(stat.name.clone());
+                    }
+                }
+                if let Some(path) = &self.categories_path {
+                    ui.small(format!(
+                        "{} {}",
+                        self.t("Edit categories in", "在此编辑类别："),
+                        path.display()
+                    ))
+                    .on_hover_text(self.t("Changes are picked up automatically", "修改会自动生效"));
                 }
+                if let Some(status) = &self.category_status {
+                    ui.colored_label(Color32::from_rgb(220, 90, 90), status);
+                }
+            });
+        self.legend_hover = hovered;
+    }
+
+    /// Progress strip above a dimmed tree while scanning: either the previous
+    /// result for the same root or the provisional tree streamed by the scan.
+    fn render_rescanning_state(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            let heading = if self.scan_result_provisional {
+                self.t("Scanning...", "正在扫描...")
+            } else {
+                self.t("Rescanning...", "正在重新扫描...")
+            };
+            ui.label(egui::RichText::new(heading).strong());
+            if let Some(percent) = self.scan_progress.progress_percent {
+                ui.add(
+                    egui::ProgressBar::new((percent / 100.0).clamp(0.0, 1.0))
+                        .desired_width(220.0)
+                        .text(format!("{percent:.1}%")),
+                );
+            }
+            ui.small(format!(
+                "{} {}",
+                self.t("Entries:", "条目："),
+                self.count(self.scan_progress.entries_scanned)
+            ));
+            ui.small(if self.scan_result_provisional {
+                self.t(
+                    "Provisional tree; sizes refine as the scan progresses.",
+                    "临时结果：大小会随扫描进度逐步完善。",
+                )
+            } else {
+                self.t(
+                    "Showing the previous result until the scan finishes.",
+                    "扫描完成前显示上一次的结果。",
+                )
             });
+            self.render_cancel_scan_button(ui);
+        });
+        ui.separator();
+
+        ui.scope(|ui| {
+            ui.multiply_opacity(0.55);
+            self.render_ready_state(ui);
+        });
+    }
+
+    fn render_cancel_scan_button(&mut self, ui: &mut egui::Ui) {
+        let cancelling = self
+            .scan_handle
+            .as_ref()
+            .is_some_and(|handle| handle.is_cancelled());
+        let text = if cancelling {
+            self.t("Cancelling...", "正在取消...")
+        } else {
+            self.t("Cancel scan", "取消扫描")
+        };
+        if ui
+            .add_enabled(!cancelling, egui::Button::new(text))
+            .clicked()
+        {
+            self.cancel_scan();
+        }
     }
 
-    fn render_scanning_state(&self, ui: &mut egui::Ui) {
+    fn render_scanning_state(&mut self, ui: &mut egui::Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(40.0);
             ui.spinner();
@@ -899,12 +7025,27 @@ impl TreeMapApp {
                 }
             }
 
+            if let Some(started) = self.perf_stats.scan_started_at {
+                let elapsed = started.elapsed();
+                ui.small(format!(
+                    "{} {} | {} {}",
+                    self.t("Elapsed:", "耗时："),
+                    format_duration_compact(elapsed, self.language),
+                    self.t("Throughput:", "吞吐："),
+                    self.throughput_text(
+                        self.scan_progress.entries_scanned,
+                        self.scan_progress.bytes_scanned,
+                        elapsed
+                    )
+                ));
+            }
+
             if let Some(eta) = self.scan_progress.eta {
                 if self.scan_progress.phase == ScanPhase::Scanning && eta > Duration::ZERO {
                     ui.small(format!(
                         "{} {}",
                         self.t("Estimated remaining time:", "预计剩余时间："),
-                        format_duration_compact(eta)
+                        format_duration_compact(eta, self.language)
                     ));
                 }
             }
@@ -912,13 +7053,13 @@ impl TreeMapApp {
             ui.label(format!(
                 "{} {} | {} {} | {} {} | {} {}",
                 self.t("Entries:", "条目："),
-                self.scan_progress.entries_scanned,
+                self.count(self.scan_progress.entries_scanned),
                 self.t("Files:", "文件："),
-                self.scan_progress.files_scanned,
+                self.count(self.scan_progress.files_scanned),
                 self.t("Directories:", "目录："),
-                self.scan_progress.directories_scanned,
+                self.count(self.scan_progress.directories_scanned),
                 self.t("Warnings:", "警告："),
-                self.scan_progress.warnings
+                self.count(self.scan_progress.warnings)
             ));
 
             if let Some(path) = &self.scan_progress.current_path {
@@ -944,7 +7085,125 @@ impl TreeMapApp {
                     ),
                 );
             }
+
+            ui.add_space(12.0);
+            self.render_cancel_scan_button(ui);
+        });
+    }
+
+    fn render_preview_state(&mut self, ui: &mut egui::Ui) {
+        let mut start_requested = false;
+        let mut reprobe_requested = false;
+        let mut cancel_requested = false;
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(40.0);
+            ui.heading(self.t("Scan preview", "扫描预估"));
+
+            if let Some(root) = &self.pending_root {
+                let root_text = if self.demo_mode {
+                    self.t("(hidden)", "（已隐藏）").to_string()
+                } else {
+                    root.display().to_string()
+                };
+                ui.label(format!("{} {}", self.t("Root:", "根目录："), root_text));
+            }
+            ui.add_space(12.0);
+
+            match &self.probe_result {
+                None => {
+                    ui.spinner();
+                    ui.label(format!(
+                        "{} {}",
+                        self.t("Probing levels:", "正在探测层数："),
+                        PROBE_DEPTH
+                    ));
+                }
+                Some(Err(error)) => {
+                    ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+                }
+                Some(Ok(probe)) => {
+                    let source_text = if probe.from_previous_scan {
+                        self.t("(from previous scan)", "（来自上次扫描）")
+                    } else {
+                        self.t("(extrapolated)", "（推算）")
+                    };
+                    ui.label(format!(
+                        "{} ~{} {}",
+                        self.t("Estimated entries:", "预计条目："),
+                        self.count(probe.estimated_total_entries),
+                        source_text
+                    ));
+                    if let Some(duration) = probe.estimated_duration {
+                        ui.label(format!(
+                            "{} ~{}",
+                            self.t("Estimated scan time:", "预计扫描时间："),
+                            format_duration_compact(duration, self.language)
+                        ));
+                    }
+                    ui.small(format!(
+                        "{} {} | {} {} | {} {} | {} {}",
+                        self.t("Probed entries:", "已探测条目："),
+                        self.count(probe.entries_probed),
+                        self.t("Files:", "文件："),
+                        self.count(probe.files_probed),
+                        self.t("Directories:", "目录："),
+                        self.count(probe.directories_probed),
+                        self.t("Unexplored directories:", "未展开目录："),
+                        self.count(probe.unexplored_directories)
+                    ));
+                    if probe.warnings > 0 {
+                        ui.small(format!(
+                            "{} {}",
+                            self.t("Warnings:", "警告："),
+                            self.count(probe.warnings)
+                        ));
+                    }
+                }
+            }
+
+            ui.add_space(8.0);
+            ui.small(self.t(
+                "Adjust recursion depth and file limit in the top bar, then start the scan.",
+                "可在顶部栏调整递归深度与文件上限，然后开始扫描。",
+            ));
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                let probing = self.probe_receiver.is_some();
+                if ui
+                    .add_enabled(
+                        self.pending_root.is_some(),
+                        egui::Button::new(self.t("Start scan", "开始扫描")),
+                    )
+                    .clicked()
+                {
+                    start_requested = true;
+                }
+                if ui
+                    .add_enabled(
+                        !probing,
+                        egui::Button::new(self.t("Probe again", "重新探测")),
+                    )
+                    .clicked()
+                {
+                    reprobe_requested = true;
+                }
+                if ui.button(self.t("Cancel", "取消")).clicked() {
+                    cancel_requested = true;
+                }
+            });
         });
+
+        if cancel_requested {
+            self.cancel_preview();
+        } else if let Some(root) = self.pending_root.clone() {
+            if start_requested {
+                self.start_scan(root);
+            } else if reprobe_requested {
+                self.start_probe(root);
+            }
+        }
     }
 
     fn render_error_state(&mut self, ui: &mut egui::Ui) {
@@ -953,7 +7212,7 @@ impl TreeMapApp {
             ui.heading(self.t("Scan failed", "扫描失败"));
 
             if let Some(error) = &self.error_message {
-                ui.colored_label(Color32::from_rgb(210, 70, 70), error);
+                ui.colored_label(Color32::from_rgb(210, 70, 70), self.demo_text(error));
             }
 
             if ui
@@ -965,29 +7224,246 @@ impl TreeMapApp {
         });
     }
 
+    /// Reformats cached cell labels when the language, demo mode or size
+    /// units changed.
+    fn refresh_cell_labels(&mut self) {
+        let key = (self.language, self.demo_mode, self.size_units);
+        let Some(mut cache) = self.treemap_cache.take() else {
+            return;
+        };
+
+        if cache.label_key != Some(key) {
+            let root_size = self
+                .scan_result
+                .as_ref()
+                .map(|result| result.root.size)
+                .unwrap_or(0)
+                .max(1);
+            for cell in &mut cache.cells {
+                self.format_cell_labels(cell, root_size);
+            }
+            cache.label_key = Some(key);
+        }
+
+        self.treemap_cache = Some(cache);
+    }
+
+    fn format_cell_labels(&self, cell: &mut CachedCell, root_size: u64) {
+        let label_name = self.demo_name(&cell.name, &cell.path, cell.is_dir);
+        let size_text = self.size(cell.size);
+        let share = percent(cell.size as f64 / root_size as f64);
+        cell.labels = [
+            label_name.clone(),
+            format!("{label_name} ({size_text})"),
+            format!("{label_name} ({size_text}, {share})"),
+        ];
+        cell.label_chars = cell.labels.each_ref().map(|label| label.chars().count());
+    }
+
+    /// Minimum cell size after automatic detail reduction.
+    fn effective_min_cell_pixels(&self) -> f32 {
+        self.min_cell_pixels * self.detail_governor.min_cell_scale()
+    }
+
+    fn layout_metric_label(&self, metric: LayoutMetric) -> String {
+        match metric {
+            LayoutMetric::Bytes => self.t("Bytes", "字节").to_string(),
+            LayoutMetric::AllocatedBytes => self.t("Allocated bytes", "占用空间").to_string(),
+            LayoutMetric::FileCount => self.t("File count", "文件数").to_string(),
+            LayoutMetric::LinesOfCode => self.t("Lines of code", "代码行数").to_string(),
+            LayoutMetric::Tokens => self
+                .t("LLM tokens (estimated)", "LLM token（估算）")
+                .to_string(),
+            LayoutMetric::Plugin(index) => self
+                .plugins
+                .get(index)
+                .map_or_else(String::new, |plugin| plugin.name.clone()),
+        }
+    }
+
+    fn size_metric(&self) -> &dyn SizeMetric {
+        match self.layout_metric {
+            LayoutMetric::Bytes => &Bytes,
+            LayoutMetric::AllocatedBytes => &AllocatedBytes,
+            LayoutMetric::FileCount => &FileCount,
+            LayoutMetric::LinesOfCode => match &self.loc_report {
+                Some(report) => report,
+                None => &Bytes,
+            },
+            LayoutMetric::Tokens => match &self.token_report {
+                Some(report) => report,
+                None => &Bytes,
+            },
+            LayoutMetric::Plugin(index) => match self.plugins.get(index) {
+                Some(plugin) => plugin.as_ref(),
+                None => &Bytes,
+            },
+        }
+    }
+
+    /// The node the treemap is laid out from: the drilled-into folder if it
+    /// still exists in the shown tree, otherwise the shown tree itself.
+    fn treemap_root(&self) -> Option<&Node> {
+        let result = self.scan_result.as_ref()?;
+        let shown = self.view_root.as_ref().unwrap_or(&result.root);
+        Some(
+            self.drill_path
+                .as_deref()
+                .and_then(|path| shown.find(path))
+                .filter(|node| !node.children.is_empty())
+                .unwrap_or(shown),
+        )
+    }
+
+    /// Re-roots the treemap on `path`, or on the whole tree for `None`.
+    fn drill_into(&mut self, path: Option<PathBuf>) {
+        let root = self.scan_result.as_ref().map(|result| &result.root.path);
+        let path = path.filter(|path| Some(path) != root);
+        if path != self.drill_path {
+            self.drill_path = path;
+            self.zoom_factor = 1.0;
+            self.offset = egui::Vec2::ZERO;
+            self.treemap_cache = None;
+        }
+    }
+
+    fn render_breadcrumbs(&mut self, ui: &mut egui::Ui) {
+        let Some(root) = self.treemap_root() else {
+            return;
+        };
+        let Some(result) = self.scan_result.as_ref() else {
+            return;
+        };
+        if root.path == result.root.path {
+            return;
+        }
+        let mut crumbs = vec![result.root.path.clone()];
+        if let Ok(relative) = root.path.strip_prefix(&result.root.path) {
+            for component in relative.components() {
+                let next = crumbs[crumbs.len() - 1].join(component);
+                crumbs.push(next);
+            }
+        }
+
+        let mut target = None;
+        ui.horizontal_wrapped(|ui| {
+            if ui
+                .small_button("⬆")
+                .on_hover_text(self.t("Up one level", "返回上一级"))
+                .clicked()
+            {
+                target = Some(crumbs[crumbs.len() - 2].clone());
+            }
+            for (index, path) in crumbs.iter().enumerate() {
+                if index > 0 {
+                    ui.label("›");
+                }
+                let name = if index == 0 {
+                    self.demo_path(path)
+                } else {
+                    let real_name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    self.demo_name(&real_name, path, true)
+                };
+                let is_current = index == crumbs.len() - 1;
+                if ui.selectable_label(is_current, name).clicked() && !is_current {
+                    target = Some(path.clone());
+                }
+            }
+            ui.separator();
+            ui.label(format!(
+                "{} {}",
+                self.t("Showing:", "当前显示："),
+                self.size(root.size)
+            ));
+        });
+        if let Some(target) = target {
+            self.drill_into(Some(target));
+        }
+    }
+
     fn cache_needs_rebuild(&self, canvas_min: egui::Pos2, width_px: u32, height_px: u32) -> bool {
+        let root_path = self.treemap_root().map(|root| &root.path);
         match &self.treemap_cache {
             Some(cache) => {
-                cache.scan_generation != self.scan_generation
+                Some(&cache.root_path) != root_path
+                    || !Self::cache_matches_canvas(
+                        cache,
+                        self.scan_generation,
+                        canvas_min,
+                        width_px,
+                        height_px,
+                    )
+                    || cache.relayout_due
                     || cache.depth != self.treemap_depth
                     || cache.max_nodes != self.max_render_nodes
-                    || cache.canvas_min.distance(canvas_min) > f32::EPSILON
-                    || cache.width_px != width_px
-                    || cache.height_px != height_px
-                    || (cache.min_cell_pixels - self.min_cell_pixels).abs() > f32::EPSILON
+                    || (cache.min_cell_pixels - self.effective_min_cell_pixels()).abs()
+                        > f32::EPSILON
             }
             None => true,
         }
     }
 
+    fn cache_matches_canvas(
+        cache: &TreemapCache,
+        scan_generation: u64,
+        canvas_min: egui::Pos2,
+        width_px: u32,
+        height_px: u32,
+    ) -> bool {
+        cache.scan_generation == scan_generation
+            && cache.canvas_min.distance(canvas_min) <= f32::EPSILON
+            && cache.width_px == width_px
+            && cache.height_px == height_px
+    }
+
+    /// Keeps the stale cache while layout settings are still being dragged or
+    /// changed within [`LAYOUT_DEBOUNCE`]; scan or canvas changes never wait.
+    fn defer_layout_rebuild(
+        &self,
+        ctx: &egui::Context,
+        canvas_min: egui::Pos2,
+        width_px: u32,
+        height_px: u32,
+    ) -> bool {
+        let Some(cache) = &self.treemap_cache else {
+            return false;
+        };
+        if !Self::cache_matches_canvas(cache, self.scan_generation, canvas_min, width_px, height_px)
+        {
+            return false;
+        }
+
+        if self.layout_setting_dragging {
+            ctx.request_repaint_after(LAYOUT_DEBOUNCE);
+            return true;
+        }
+
+        match self.layout_settings_changed_at {
+            Some(changed_at) if changed_at.elapsed() < LAYOUT_DEBOUNCE => {
+                ctx.request_repaint_after(LAYOUT_DEBOUNCE - changed_at.elapsed());
+                true
+            }
+            _ => false,
+        }
+    }
+
     fn build_treemap_cache(
-        scan_result: &ScanResult,
+        root: &Node,
+        metric: &dyn SizeMetric,
         canvas_rect: egui::Rect,
-        scan_generation: u64,
-        depth: usize,
-        max_nodes: usize,
-        min_cell_pixels: f32,
+        key: &LayoutKey,
+        coloring: CellColoring,
     ) -> TreemapCache {
+        let LayoutKey {
+            scan_generation,
+            depth,
+            max_nodes,
+            min_cell_pixels,
+            ..
+        } = *key;
         let bounds = LayoutRect::new(
             canvas_rect.min.x,
             canvas_rect.min.y,
@@ -995,7 +7471,8 @@ impl TreeMapApp {
             canvas_rect.height(),
         );
 
-        let raw_cells = squarified_treemap(&scan_result.root, bounds, depth, max_nodes);
+        let weights = Weights::of(root, metric);
+        let raw_cells = squarified_treemap(root, &weights, bounds, depth, max_nodes);
 
         let mut cells = Vec::with_capacity(raw_cells.len());
         let mut cell_centers = HashMap::with_capacity(raw_cells.len());
@@ -1021,16 +7498,26 @@ impl TreeMapApp {
 
             cells.push(CachedCell {
                 rect,
+                slot: rect,
+                laid_out_size: cell.node.size,
                 name: cell.node.name.clone(),
                 path,
                 size: cell.node.size,
+                own_size: cell.node.own_size,
                 is_dir: !cell.node.children.is_empty(),
-                fill: color_for_node(cell.node, cell.depth),
+                modified: cell.node.modified,
+                fill: coloring.color(cell.node, cell.depth),
+                labels: Default::default(),
+                label_chars: [0; 3],
             });
         }
 
+        let cell_rects: Vec<egui::Rect> = cells.iter().map(|cell| cell.rect).collect();
+        let spatial_index = SpatialGrid::build(canvas_rect, &cell_rects);
+
         TreemapCache {
             scan_generation,
+            root_path: root.path.clone(),
             depth,
             max_nodes,
             min_cell_pixels,
@@ -1038,8 +7525,106 @@ impl TreeMapApp {
             width_px: canvas_rect.width().round().max(1.0) as u32,
             height_px: canvas_rect.height().round().max(1.0) as u32,
             cells,
+            label_key: None,
+            spatial_index,
             cell_centers,
             cell_centers_by_key,
+            static_layer: None,
+            patched: false,
+            relayout_due: false,
+        }
+    }
+
+    /// Re-tessellates the static cell layer only when the view changed, so
+    /// frames that merely animate overlays reuse the previous mesh.
+    fn refresh_static_layer(
+        &mut self,
+        ctx: &egui::Context,
+        canvas_rect: egui::Rect,
+        visible_cells: &[usize],
+        label_font: &egui::FontId,
+    ) {
+        let Some(cache) = self.treemap_cache.as_ref() else {
+            return;
+        };
+        let key = StaticLayerKey {
+            canvas_rect,
+            zoom_factor: self.zoom_factor,
+            offset: self.offset,
+            show_labels: self.show_cell_labels,
+            detail_level: self.detail_governor.level,
+            label_key: cache.label_key,
+            pixels_per_point: ctx.pixels_per_point(),
+            font_image_size: ctx.fonts(|fonts| fonts.font_image_size()),
+        };
+        if cache
+            .static_layer
+            .as_ref()
+            .is_some_and(|layer| layer.key == key)
+        {
+            return;
+        }
+
+        let border = egui::Stroke::new(1.0, Color32::from_black_alpha(45));
+        let stroke_min_pixels = self.detail_governor.stroke_min_pixels();
+        let show_labels = self.show_cell_labels && self.detail_governor.labels_allowed();
+        let shapes = ctx.fonts(|fonts| {
+            let mut shapes = Vec::with_capacity(visible_cells.len() * 2);
+            for &index in visible_cells {
+                let cell = &cache.cells[index];
+                let transformed_rect = self.transform_rect_for_view(cell.rect);
+                shapes.push(egui::Shape::rect_filled(transformed_rect, 0.0, cell.fill));
+                if transformed_rect.width().min(transformed_rect.height()) >= stroke_min_pixels {
+                    shapes.push(egui::Shape::rect_stroke(transformed_rect, 0.0, border));
+                }
+
+                let label = if show_labels {
+                    cell_label_text(cell, cell.rect.size() * self.zoom_factor)
+                } else {
+                    None
+                };
+                if let Some(text) = label {
+                    shapes.push(egui::Shape::text(
+                        fonts,
+                        transformed_rect.left_top() + egui::vec2(4.0, 4.0),
+                        egui::Align2::LEFT_TOP,
+                        text,
+                        label_font.clone(),
+                        Color32::WHITE,
+                    ));
+                }
+            }
+            shapes
+        });
+
+        // Laying out labels may have grown the font atlas; glyph UVs are
+        // normalized against its final size.
+        let (font_image_size, prepared_discs) = ctx.fonts(|fonts| {
+            (
+                fonts.font_image_size(),
+                fonts.texture_atlas().lock().prepared_discs(),
+            )
+        });
+        let mut tessellator = egui::epaint::Tessellator::new(
+            key.pixels_per_point,
+            ctx.tessellation_options(|options| *options),
+            font_image_size,
+            prepared_discs,
+        );
+        tessellator.set_clip_rect(canvas_rect);
+        let mut mesh = egui::epaint::Mesh::default();
+        for shape in shapes {
+            tessellator.tessellate_shape(shape, &mut mesh);
+        }
+
+        if let Some(cache) = self.treemap_cache.as_mut() {
+            cache.static_layer = Some(StaticLayer {
+                key: StaticLayerKey {
+                    font_image_size,
+                    ..key
+                },
+                mesh,
+            });
         }
     }
 
@@ -1054,40 +7639,50 @@ impl TreeMapApp {
                 ui.label(format!(
                     "{} {}",
                     self.t("Total size:", "总大小："),
-                    human_size(scan_result.root.size)
-                ));
+                    self.size(scan_result.root.size)
+                ))
+                .on_hover_text(exact_bytes(scan_result.root.size, self.language));
                 ui.separator();
                 ui.label(format!(
                     "{} {}",
                     self.t("Entries:", "条目："),
-                    scan_result.stats.entries_scanned
+                    self.count(scan_result.stats.entries_scanned)
                 ));
                 if let Some(estimated_total_entries) = scan_result.stats.estimated_total_entries {
                     ui.label(format!(
                         "{} {}",
                         self.t("Estimated total entries:", "预计总条目："),
-                        estimated_total_entries
+                        self.count(estimated_total_entries)
                     ));
                 }
                 ui.label(format!(
                     "{} {}",
                     self.t("Files:", "文件："),
-                    scan_result.stats.files_scanned
+                    self.count(scan_result.stats.files_scanned)
                 ));
                 ui.label(format!(
                     "{} {}",
                     self.t("Directories:", "目录："),
-                    scan_result.stats.directories_scanned
+                    self.count(scan_result.stats.directories_scanned)
                 ));
                 ui.label(format!(
-                    "{} {:.2?}",
+                    "{} {}",
                     self.t("Elapsed:", "耗时："),
-                    scan_result.stats.elapsed
+                    format_duration_compact(scan_result.stats.elapsed, self.language)
+                ));
+                ui.label(format!(
+                    "{} {}",
+                    self.t("Throughput:", "吞吐："),
+                    self.throughput_text(
+                        scan_result.stats.entries_scanned,
+                        scan_result.root.size,
+                        scan_result.stats.elapsed
+                    )
                 ));
                 ui.label(format!(
                     "{} {}",
                     self.t("Warnings:", "警告："),
-                    scan_result.stats.warnings
+                    self.count(scan_result.stats.warnings)
                 ));
             });
 
@@ -1101,26 +7696,46 @@ impl TreeMapApp {
                 );
             }
 
-            if !scan_result.warnings.is_empty() {
+            if scan_result.stats.warnings > 0 {
                 egui::CollapsingHeader::new(format!(
                     "{} ({})",
                     self.t("Warnings", "警告"),
-                    scan_result.warnings.len()
+                    self.count(scan_result.stats.warnings)
                 ))
                 .default_open(false)
                 .show(ui, |ui| {
+                    let breakdown = scan_result
+                        .warning_counts
+                        .iter()
+                        .map(|(category, count)| {
+                            format!("{}: {}", category.label(), self.count(*count))
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    if !breakdown.is_empty() {
+                        ui.small(breakdown);
+                    }
+
                     for warning in scan_result.warnings.iter().take(20) {
                         ui.small(warning);
                     }
 
-                    if scan_result.warnings.len() > 20 {
+                    let shown = scan_result.warnings.len().min(20) as u64;
+                    if scan_result.stats.warnings > shown {
                         ui.small(format!(
                             "{} {} {}",
                             self.t("... and", "... 还有"),
-                            scan_result.warnings.len() - 20,
+                            self.count(scan_result.stats.warnings - shown),
                             self.t("additional warnings", "条警告")
                         ));
                     }
+                    if let Some(log) = &self.scan_config.warning_log {
+                        ui.small(format!(
+                            "{} {}",
+                            self.t("Full list:", "完整列表："),
+                            self.demo_path(log)
+                        ));
+                    }
                 });
             }
 
@@ -1130,28 +7745,140 @@ impl TreeMapApp {
         ui.separator();
 
         ui.horizontal(|ui| {
+            ui.label(self.t("Size by:", "面积依据："));
+            let mut metric = self.layout_metric;
+            egui::ComboBox::from_id_salt("layout_metric")
+                .selected_text(self.layout_metric_label(metric))
+                .show_ui(ui, |ui| {
+                    let plugins = self
+                        .plugins
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, plugin)| plugin.has_weights())
+                        .map(|(index, _)| LayoutMetric::Plugin(index));
+                    let loc = self
+                        .loc_report
+                        .is_some()
+                        .then_some(LayoutMetric::LinesOfCode);
+                    let tokens = self.token_report.is_some().then_some(LayoutMetric::Tokens);
+                    for option in [
+                        LayoutMetric::Bytes,
+                        LayoutMetric::AllocatedBytes,
+                        LayoutMetric::FileCount,
+                    ]
+                    .into_iter()
+                    .chain(loc)
+                    .chain(tokens)
+                    .chain(plugins)
+                    {
+                        ui.selectable_value(&mut metric, option, self.layout_metric_label(option));
+                    }
+                });
+            if metric != self.layout_metric {
+                self.layout_metric = metric;
+                self.layout_cache.clear();
+                self.treemap_cache = None;
+            }
+
             ui.label(self.t("Treemap depth:", "Treemap 深度："));
-            ui.add(
+            let depth_response = ui.add(
                 egui::DragValue::new(&mut self.treemap_depth)
                     .range(1..=self.scan_config.max_depth.max(1)),
             );
 
             ui.label(self.t("Max rendered nodes:", "最大渲染节点："));
-            ui.add(
+            let max_nodes_response = ui.add(
                 egui::DragValue::new(&mut self.max_render_nodes)
                     .range(1_000..=200_000)
                     .speed(500.0),
             );
 
             ui.label(self.t("Min cell px:", "最小方块像素："));
-            ui.add(
+            let min_cell_response = ui.add(
                 egui::DragValue::new(&mut self.min_cell_pixels)
                     .range(0.5..=8.0)
                     .speed(0.1),
             );
+
+            let auto_detail_text = self.t("Auto detail", "自动细节");
+            ui.checkbox(&mut self.detail_governor.enabled, auto_detail_text)
+                .on_hover_text(self.t(
+                    "Hide borders, labels and small cells while frames take longer than 12 ms",
+                    "当帧耗时超过 12 毫秒时隐藏边框、标签和小方块",
+                ));
+            if self.detail_governor.level > 0 {
+                ui.colored_label(
+                    Color32::from_rgb(220, 170, 60),
+                    format!(
+                        "{} {}",
+                        self.t("Detail reduced:", "细节已降级："),
+                        self.detail_governor.level
+                    ),
+                );
+            }
+
+            let layout_responses = [depth_response, max_nodes_response, min_cell_response];
+            if layout_responses.iter().any(egui::Response::changed) {
+                self.layout_settings_changed_at = Some(Instant::now());
+            }
+            self.layout_setting_dragging = layout_responses.iter().any(egui::Response::dragged);
+
+            ui.separator();
+            let heatmap_text = self.t("Activity heatmap", "活动热力图");
+            ui.checkbox(&mut self.show_heatmap, heatmap_text);
+            ui.label(self.t("Half-life (s):", "衰减半衰期（秒）："));
+            ui.add(
+                egui::DragValue::new(&mut self.heat_half_life_seconds)
+                    .range(1.0..=3600.0)
+                    .speed(1.0),
+            )
+            .on_hover_text(self.t(
+                "Time for a cell's accumulated heat to halve",
+                "方块累计热度减半所需的时间",
+            ));
+
+            ui.separator();
+            let rollup_text = self.t("Subtree action counts", "子树动作计数");
+            ui.checkbox(&mut self.show_action_rollup, rollup_text)
+                .on_hover_text(self.t(
+                    "Badge folders with the number of actions anywhere beneath them",
+                    "在文件夹上标出其下所有位置发生的动作数",
+                ));
+
+            ui.separator();
+            let trail_text = self.t("Focus trail", "关注轨迹");
+            ui.checkbox(&mut self.show_focus_trail, trail_text);
+            let hops_suffix = self.t(" hops", " 步");
+            if ui
+                .add(
+                    egui::DragValue::new(&mut self.focus_trail_length)
+                        .range(2..=200)
+                        .suffix(hops_suffix),
+                )
+                .changed()
+            {
+                while self.focus_trail.len() > self.focus_trail_length {
+                    self.focus_trail.pop_front();
+                }
+            }
         });
 
         self.render_type_legend(ui);
+        self.render_git_panel(ui);
+        self.render_reclaim_panel(ui);
+        self.render_owner_panel(ui);
+        self.render_permission_panel(ui);
+        self.render_symlink_panel(ui);
+        self.render_anomaly_panel(ui);
+        self.render_shape_panel(ui);
+        self.render_size_histogram_panel(ui);
+        self.render_zero_byte_panel(ui);
+        self.render_plugin_panels(ui);
+        self.render_age_histogram_panel(ui);
+        self.render_compress_panel(ui);
+        self.render_loc_panel(ui);
+        self.render_token_panel(ui);
+        self.render_growth_panel(ui);
 
         ui.add_space(4.0);
 
@@ -1163,32 +7890,100 @@ impl TreeMapApp {
             return;
         }
 
+        self.render_breadcrumbs(ui);
+
         let available = ui.available_size();
         if available.x < 40.0 || available.y < 40.0 {
             return;
         }
 
+        // Jumping to something outside the drilled-into folder shows the
+        // whole tree again.
+        if let Some((target, drill_path)) = self.pending_jump.as_ref().zip(self.drill_path.as_ref())
+        {
+            if !target.starts_with(drill_path) {
+                self.drill_into(None);
+            }
+        }
+
         let (canvas_rect, canvas_response) =
             ui.allocate_exact_size(available, egui::Sense::click_and_drag());
+        self.canvas_rect = Some(canvas_rect);
         self.handle_pan_and_zoom(ui.ctx(), &canvas_response);
         let width_px = canvas_rect.width().round().max(1.0) as u32;
         let height_px = canvas_rect.height().round().max(1.0) as u32;
 
-        if self.cache_needs_rebuild(canvas_rect.min, width_px, height_px) {
-            let Some(scan_result) = self.scan_result.as_ref() else {
+        if self.cache_needs_rebuild(canvas_rect.min, width_px, height_px)
+            && !self.defer_layout_rebuild(ui.ctx(), canvas_rect.min, width_px, height_px)
+        {
+            let Some(root_path) = self.treemap_root().map(|root| root.path.clone()) else {
                 return;
             };
 
-            let rebuilt = Self::build_treemap_cache(
-                scan_result,
-                canvas_rect,
-                self.scan_generation,
-                self.treemap_depth,
-                self.max_render_nodes,
-                self.min_cell_pixels,
-            );
+            let key = LayoutKey {
+                scan_generation: self.scan_generation,
+                root_path,
+                depth: self.treemap_depth,
+                max_nodes: self.max_render_nodes,
+                min_cell_pixels: self.effective_min_cell_pixels(),
+                canvas_min: canvas_rect.min,
+                width_px,
+                height_px,
+            };
+            let rebuilt = match self.layout_cache.take(&key) {
+                Some(cached) => cached,
+                None => {
+                    let layout_started = Instant::now();
+                    let Some(root) = self.treemap_root() else {
+                        return;
+                    };
+                    let rebuilt = Self::build_treemap_cache(
+                        root,
+                        self.size_metric(),
+                        canvas_rect,
+                        &key,
+                        CellColoring {
+                            content_types: &self.content_types,
+                            categories: self.color_by_category.then_some(&self.categories),
+                            git: self.git_scan.as_ref(),
+                            plugins: &self.plugins,
+                            script: (!self.script.colors.is_empty()).then_some(&self.script),
+                            loc: self.loc_report.as_ref().filter(|_| self.loc_coloring),
+                            sandbox: self.sandbox.as_ref().filter(|_| self.sandbox_shading),
+                            owners: self.color_by_owner.then_some(self.selected_owner),
+                            now: unix_now(),
+                        },
+                    );
+                    let layout_elapsed = layout_started.elapsed();
+                    self.perf_stats.last_layout_rebuild = Some(layout_elapsed);
+                    self.perf_stats.layout_in_last_update = layout_elapsed;
+                    rebuilt
+                }
+            };
 
-            self.treemap_cache = Some(rebuilt);
+            if let Some(previous) = self.treemap_cache.replace(rebuilt) {
+                if previous.scan_generation == self.scan_generation && !previous.patched {
+                    let weight = previous.cells.len();
+                    self.layout_cache
+                        .insert(previous.layout_key(), previous, weight);
+                }
+            }
+        }
+
+        if let Some(target) = self.pending_jump.take() {
+            let world_pos = self
+                .treemap_cache
+                .as_ref()
+                .and_then(|cache| self.resolve_world_pos(cache, &target));
+            if let Some(world_pos) = world_pos {
+                self.offset =
+                    canvas_rect.center().to_vec2() - world_pos.to_vec2() * self.zoom_factor;
+                self.jump_highlight = Some((world_pos, Instant::now()));
+            }
+        }
+
+        if self.show_cell_labels {
+            self.refresh_cell_labels();
         }
 
         let Some(cache) = self.treemap_cache.as_ref() else {
@@ -1198,65 +7993,163 @@ impl TreeMapApp {
         let painter = ui.painter_at(canvas_rect);
         painter.rect_filled(canvas_rect, 0.0, Color32::from_rgb(26, 30, 34));
 
-        for cell in &cache.cells {
-            let transformed_rect = self.transform_rect_for_view(cell.rect);
-            if !transformed_rect.intersects(canvas_rect) {
-                continue;
+        let visible_world_rect = egui::Rect::from_min_max(
+            self.screen_to_world(canvas_rect.min),
+            self.screen_to_world(canvas_rect.max),
+        );
+        let visible_cells = cache.visible_cells(visible_world_rect);
+        self.perf_stats.cells_drawn = visible_cells.len();
+        self.perf_stats.cells_total = cache.cells.len();
+        let label_font = egui::TextStyle::Small.resolve(ui.style());
+
+        self.refresh_static_layer(ui.ctx(), canvas_rect, &visible_cells, &label_font);
+        let Some(cache) = self.treemap_cache.as_ref() else {
+            return;
+        };
+        if let Some(layer) = &cache.static_layer {
+            painter.add(egui::Shape::mesh(layer.mesh.clone()));
+        }
+
+        if self.show_heatmap && !self.activity_heat.is_empty() {
+            for &index in &visible_cells {
+                let cell = &cache.cells[index];
+                let Some(intensity) = self.activity_heat.intensity(&cell.path) else {
+                    continue;
+                };
+                painter.rect_filled(
+                    self.transform_rect_for_view(cell.rect),
+                    0.0,
+                    heat_color(intensity),
+                );
             }
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+        }
 
-            painter.rect_filled(transformed_rect, 0.0, cell.fill);
-            painter.rect_stroke(
-                transformed_rect,
-                0.0,
-                egui::Stroke::new(1.0, Color32::from_black_alpha(45)),
-            );
+        if self.show_action_rollup && !self.action_rollup.is_empty() {
+            self.render_action_rollup(&painter, cache, &visible_cells, label_font.clone());
+        }
 
-            if self.show_cell_labels
-                && transformed_rect.width() > 95.0
-                && transformed_rect.height() > 20.0
-            {
-                let label_name = self.demo_name(&cell.name, &cell.path, cell.is_dir);
-                let label = format!("{} ({})", label_name, human_size(cell.size));
-                let max_chars = (transformed_rect.width() / 7.0).floor().max(6.0) as usize;
-                let text = truncate_label(&label, max_chars);
-
-                painter.text(
-                    transformed_rect.left_top() + egui::vec2(4.0, 4.0),
-                    egui::Align2::LEFT_TOP,
-                    text,
-                    egui::TextStyle::Small.resolve(ui.style()),
-                    Color32::WHITE,
+        if !self.selected_paths.is_empty() {
+            for cell in visible_cells.iter().map(|&index| &cache.cells[index]) {
+                if self
+                    .selected_paths
+                    .iter()
+                    .any(|(path, _)| *path == cell.path)
+                {
+                    painter.rect_stroke(
+                        self.transform_rect_for_view(cell.rect).shrink(1.5),
+                        0.0,
+                        egui::Stroke::new(3.0, SELECTION_COLOR),
+                    );
+                }
+            }
+        }
+
+        if !self.permission_index.is_empty() {
+            for cell in visible_cells.iter().map(|&index| &cache.cells[index]) {
+                if self.permission_index.contains_key(&cell.path) {
+                    painter.rect_stroke(
+                        self.transform_rect_for_view(cell.rect).shrink(1.0),
+                        0.0,
+                        egui::Stroke::new(2.5, PERMISSION_FINDING_COLOR),
+                    );
+                }
+            }
+        }
+
+        if let Some(key) = &self.legend_hover {
+            for cell in visible_cells.iter().map(|&index| &cache.cells[index]) {
+                if !cell.is_dir && self.legend_key_of(&cell.path) == *key {
+                    painter.rect_stroke(
+                        self.transform_rect_for_view(cell.rect).shrink(1.0),
+                        0.0,
+                        egui::Stroke::new(2.0, Color32::WHITE),
+                    );
+                }
+            }
+        }
+
+        if let Some(bucket) = self.age_filter {
+            for cell in visible_cells.iter().map(|&index| &cache.cells[index]) {
+                if !cell.is_dir && self.age_histogram.bucket(cell.modified) != bucket {
+                    painter.rect_filled(
+                        self.transform_rect_for_view(cell.rect),
+                        0.0,
+                        Color32::from_black_alpha(170),
+                    );
+                }
+            }
+        }
+
+        if self.show_reclaim_badges && !self.reclaim.items.is_empty() {
+            self.render_reclaim_badges(&painter, cache, &visible_cells, label_font.clone());
+        }
+
+        if let Some((world_pos, started)) = self.jump_highlight {
+            let age = started.elapsed().as_secs_f32();
+            let highlighted = cache.cell_at(world_pos);
+            if let (true, Some(cell)) = (age < JUMP_HIGHLIGHT_SECONDS, highlighted) {
+                let alpha = ((1.0 - age / JUMP_HIGHLIGHT_SECONDS) * 255.0).round() as u8;
+                painter.rect_stroke(
+                    self.transform_rect_for_view(cell.rect),
+                    0.0,
+                    egui::Stroke::new(3.0, Color32::from_rgba_unmultiplied(255, 230, 80, alpha)),
                 );
+                ui.ctx().request_repaint_after(Duration::from_millis(33));
             }
         }
 
+        if self.show_focus_trail {
+            self.render_focus_trail(&painter, cache);
+        }
+        if self.show_symlink_arrows {
+            self.render_symlink_arrows(&painter, cache);
+        }
+
+        if self.render_action_pulses(&painter, cache, canvas_rect) {
+            ui.ctx().request_repaint_after(Duration::from_millis(16));
+        }
+
+        let has_watch_markers = self.render_watch_markers(&painter, cache, canvas_rect);
         let has_active_lines = self.render_openclaw_overlay(&painter, cache, canvas_rect);
-        if has_active_lines {
+        if has_active_lines || has_watch_markers {
             ui.ctx().request_repaint_after(Duration::from_millis(33));
         }
 
+        let mut hit_test_duration = None;
         let hovered_snapshot = if canvas_response.hovered() {
             let pointer_pos = ui.ctx().input(|input| input.pointer.hover_pos());
 
             pointer_pos.and_then(|pos| {
                 let world_pos = self.screen_to_world(pos);
-                cache
-                    .cells
-                    .iter()
-                    .rev()
-                    .find(|cell| cell.rect.contains(world_pos))
-                    .map(|cell| HoveredEntry {
+                let hit_test_started = Instant::now();
+                let hit = cache.cell_at(world_pos);
+                hit_test_duration = Some(hit_test_started.elapsed());
+                hit.map(|cell| {
+                    let modified = match &self.hovered_entry {
+                        Some(previous) if previous.path == cell.path => previous.modified,
+                        _ => gateway::metadata(&cell.path)
+                            .and_then(|metadata| metadata.modified())
+                            .ok(),
+                    };
+                    HoveredEntry {
                         name: cell.name.clone(),
                         path: cell.path.clone(),
                         size: cell.size,
+                        own_size: cell.own_size,
                         is_dir: cell.is_dir,
-                    })
+                        modified,
+                    }
+                })
             })
         } else {
             None
         };
 
         self.hovered_entry = hovered_snapshot.clone();
+        if hit_test_duration.is_some() {
+            self.perf_stats.last_hit_test = hit_test_duration;
+        }
 
         if let Some(hovered) = hovered_snapshot {
             #[allow(deprecated)]
@@ -1268,6 +8161,13 @@ impl TreeMapApp {
                     ui.set_min_width(420.0);
                     let type_text = if hovered.is_dir {
                         self.t("Folder", "文件夹").to_string()
+                    } else if let Some(content_key) = self.content_types.get(&hovered.path) {
+                        format!(
+                            "{} ({} {})",
+                            format_type_key(content_key, self.language),
+                            self.t("by content; extension says", "按内容识别；扩展名为"),
+                            format_type_key(&file_type_key(&hovered.path), self.language)
+                        )
                     } else {
                         let type_key = file_type_key(&hovered.path);
                         format_type_key(&type_key, self.language)
@@ -1276,21 +8176,163 @@ impl TreeMapApp {
                     let path_text = self.demo_path(&hovered.path);
                     ui.label(format!("{} {}", self.t("Name:", "名称："), name_text));
                     ui.label(format!("{} {}", self.t("Type:", "类型："), type_text));
+                    if let Some(compressed) = self
+                        .compress_estimate
+                        .as_ref()
+                        .and_then(|estimate| estimate.folders.get(&hovered.path))
+                    {
+                        ui.label(format!(
+                            "{} ~{} ({})",
+                            self.t("Would compress to:", "估计压缩后："),
+                            self.size(*compressed),
+                            percent(*compressed as f64 / hovered.size.max(1) as f64)
+                        ));
+                    }
+                    if let Some(tokens) = self
+                        .token_report
+                        .as_ref()
+                        .and_then(|report| report.tokens(&hovered.path))
+                    {
+                        ui.label(format!(
+                            "{} ~{}",
+                            self.t("Estimated tokens:", "估算 token："),
+                            self.count(tokens)
+                        ));
+                    }
+                    if let Some(code) = self
+                        .loc_report
+                        .as_ref()
+                        .and_then(|report| report.code_lines(&hovered.path))
+                    {
+                        ui.label(format!(
+                            "{} {}",
+                            self.t("Lines of code:", "代码行数："),
+                            self.count(code)
+                        ));
+                    }
+                    if let Some(finding) = self
+                        .permission_index
+                        .get(&hovered.path)
+                        .zip(self.scan_result.as_ref())
+                        .map(|(&index, result)| &result.permission_findings[index])
+                    {
+                        ui.colored_label(
+                            PERMISSION_FINDING_COLOR,
+                            format!(
+                                "{} {}",
+                                self.t("Permissions:", "权限："),
+                                permission_issue_text(finding)
+                            ),
+                        );
+                    }
+                    if let Some(item) = self.reclaim.get(&hovered.path) {
+                        ui.label(format!(
+                            "{} {}",
+                            self.t("Probably safe to delete:", "大概可以安全删除："),
+                            item.label
+                        ));
+                    }
+                    if let Some(git) = &self.git_scan {
+                        if let Some(repo) = git.repos.iter().find(|repo| repo.root == hovered.path)
+                        {
+                            ui.label(format!(
+                                "{} .git {} · {} {} · {} {}",
+                                self.t("Git:", "Git："),
+                                self.size(repo.git_dir_bytes),
+                                self.t("working tree", "工作区"),
+                                self.size(repo.working_bytes),
+                                self.t("ignored", "已忽略"),
+                                self.size(repo.ignored_bytes)
+                            ));
+                        } else if git.is_ignored(&hovered.path) {
+                            ui.label(self.t("Ignored by git", "被 git 忽略"));
+                        }
+                    }
+                    if !hovered.is_dir {
+                        let category = self
+                            .categories
+                            .category_of(&self.type_key_of(&hovered.path));
+                        ui.label(format!(
+                            "{} {}",
+                            self.t("Category:", "类别："),
+                            category.name
+                        ));
+                    }
                     ui.label(format!(
-                        "{} {}",
+                        "{} {} ({})",
                         self.t("Size:", "大小："),
-                        human_size(hovered.size)
+                        self.size(hovered.size),
+                        exact_bytes(hovered.size, self.language)
                     ));
+                    if hovered.is_dir {
+                        ui.label(format!(
+                            "{} {} ({})",
+                            self.t("Own files size:", "直属文件大小："),
+                            self.size(hovered.own_size),
+                            exact_bytes(hovered.own_size, self.language)
+                        ));
+                    }
+                    if let Some(modified) = hovered.modified {
+                        ui.label(format!(
+                            "{} {}",
+                            self.t("Modified:", "修改时间："),
+                            relative_time(modified, self.language)
+                        ));
+                    }
                     ui.label(format!("{} {}", self.t("Path:", "路径："), path_text));
+                    let action_count = self.action_rollup.count(&hovered.path);
+                    if action_count > 0 {
+                        let label = if hovered.is_dir {
+                            self.t("Actions beneath:", "其下动作：")
+                        } else {
+                            self.t("Actions:", "动作：")
+                        };
+                        ui.label(format!("{label} {action_count}"));
+                    }
                 },
             );
         }
+
+        if canvas_response.secondary_clicked() {
+            self.context_target = self
+                .hovered_entry
+                .as_ref()
+                .map(|hovered| (hovered.path.clone(), hovered.is_dir));
+        }
+        if canvas_response.clicked() && ui.input(|input| input.modifiers.command) {
+            self.toggle_selection();
+        }
+        if canvas_response.double_clicked() {
+            // A file drills into the folder holding it.
+            let target = self.hovered_entry.as_ref().and_then(|hovered| {
+                if hovered.is_dir {
+                    Some(hovered.path.clone())
+                } else {
+                    hovered.path.parent().map(Path::to_path_buf)
+                }
+            });
+            if let Some(target) = target {
+                self.drill_into(Some(target));
+            }
+        }
+        canvas_response.context_menu(|ui| self.render_cell_context_menu(ui));
     }
 }
 
 impl eframe::App for TreeMapApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let update_started = Instant::now();
+        // One-off layout rebuilds are not a sign of sustained overload.
+        self.detail_governor.observe(
+            self.perf_stats
+                .last_update
+                .saturating_sub(self.perf_stats.layout_in_last_update),
+        );
+        self.perf_stats.layout_in_last_update = Duration::ZERO;
         let delta_seconds = ctx.input(|input| input.stable_dt);
+        self.perf_stats
+            .record_frame(ctx.input(|input| input.unstable_dt));
+        self.run_continuous_simulation(ctx, delta_seconds);
         self.update_visual_lines(delta_seconds);
         if !self.visual_lines.is_empty() {
             ctx.request_repaint_after(Duration::from_millis(33));
@@ -1301,18 +8343,205 @@ impl eframe::App for TreeMapApp {
             self.pick_startup_paths_and_scan();
         }
 
-        self.poll_scan_messages(ctx);
+        self.poll_probe_messages();
+        self.poll_ingest_messages();
+        self.poll_watch_messages();
+        self.poll_scan_messages();
+        self.poll_sniff_messages();
+        self.poll_git_messages();
+        self.poll_plugin_reports();
+        self.poll_compress_messages();
+        self.poll_update_messages();
+        self.poll_loc_messages();
+        self.poll_token_messages();
+        self.poll_command_output();
+        self.run_scheduled_rescan(ctx);
+        self.poll_category_file();
+        self.poll_api_requests();
+        self.poll_image_copy(ctx);
 
         egui::TopBottomPanel::top("top_controls").show(ctx, |ui| {
             self.render_top_bar(ui);
         });
 
+        if let Some((message, started)) = &self.alert_banner {
+            let age = started.elapsed().as_secs_f32();
+            if age < ALERT_BANNER_SECONDS {
+                // Blink for the first couple of seconds to draw attention.
+                let blink_on = age > 2.0 || ((age * 4.0) as u32).is_multiple_of(2);
+                let fill = if blink_on {
+                    Color32::from_rgb(170, 0, 120)
+                } else {
+                    Color32::from_rgb(110, 0, 80)
+                };
+                let message = message.clone();
+                egui::TopBottomPanel::top("sensitive_alert_banner")
+                    .frame(
+                        egui::Frame::none()
+                            .fill(fill)
+                            .inner_margin(egui::Margin::same(6.0)),
+                    )
+                    .show(ctx, |ui| {
+                        ui.colored_label(Color32::WHITE, egui::RichText::new(message).strong());
+                    });
+                ctx.request_repaint_after(Duration::from_millis(125));
+            } else {
+                self.alert_banner = None;
+            }
+        }
+
+        if self.show_sensitive_panel {
+            let mut open = true;
+            egui::Window::new(self.t("Sensitive paths", "敏感路径"))
+                .open(&mut open)
+                .default_width(420.0)
+                .show(ctx, |ui| {
+                    self.render_sensitive_panel(ui);
+                });
+            self.show_sensitive_panel = open;
+        }
+
+        if self.show_threshold_panel {
+            let mut open = true;
+            egui::Window::new(self.t("Size limits", "大小限制"))
+                .open(&mut open)
+                .default_width(420.0)
+                .show(ctx, |ui| {
+                    self.render_threshold_panel(ui);
+                });
+            self.show_threshold_panel = open;
+        }
+
+        if self.show_exclusion_panel {
+            let mut open = true;
+            egui::Window::new(self.t("Exclusions", "排除规则"))
+                .open(&mut open)
+                .default_width(520.0)
+                .show(ctx, |ui| {
+                    self.render_exclusion_panel(ui);
+                });
+            self.show_exclusion_panel = open;
+        }
+
+        if self.show_script_panel {
+            let mut open = true;
+            egui::Window::new(self.t("Custom view", "自定义视图"))
+                .open(&mut open)
+                .default_width(480.0)
+                .show(ctx, |ui| {
+                    self.render_script_panel(ui);
+                });
+            self.show_script_panel = open;
+        }
+
+        if self.show_sandbox_panel {
+            let mut open = true;
+            egui::Window::new(self.t("Sandbox", "沙箱"))
+                .open(&mut open)
+                .default_width(460.0)
+                .show(ctx, |ui| {
+                    self.render_sandbox_panel(ui);
+                });
+            self.show_sandbox_panel = open;
+        }
+
+        if self.show_update_panel {
+            let mut open = true;
+            egui::Window::new(self.t("Updates", "更新"))
+                .open(&mut open)
+                .default_width(440.0)
+                .show(ctx, |ui| {
+                    self.render_update_panel(ui);
+                });
+            self.show_update_panel = open;
+        }
+
+        if self.show_access_audit {
+            let mut open = true;
+            egui::Window::new(self.t("Access audit", "访问审计"))
+                .open(&mut open)
+                .default_width(460.0)
+                .show(ctx, |ui| {
+                    self.render_access_audit_panel(ui);
+                });
+            self.show_access_audit = open;
+        }
+
+        if self.pending_trash.is_some() {
+            let mut open = true;
+            egui::Window::new(self.t("Move to trash", "移到回收站"))
+                .open(&mut open)
+                .collapsible(false)
+                .default_width(420.0)
+                .show(ctx, |ui| {
+                    self.render_trash_confirmation(ui);
+                });
+            if !open {
+                self.pending_trash = None;
+            }
+        }
+
+        if self.show_command_log {
+            let mut open = true;
+            egui::Window::new(self.t("Command log", "命令日志"))
+                .open(&mut open)
+                .default_width(520.0)
+                .show(ctx, |ui| {
+                    self.render_command_log(ui);
+                });
+            self.show_command_log = open;
+        }
+
+        if self.show_external_commands {
+            let mut open = true;
+            egui::Window::new(self.t("External commands", "外部命令"))
+                .open(&mut open)
+                .default_width(460.0)
+                .show(ctx, |ui| {
+                    self.render_external_commands_panel(ui);
+                });
+            self.show_external_commands = open;
+        }
+
+        if self.show_size_attribution {
+            let mut open = true;
+            egui::Window::new(self.t("Size attribution", "大小归因"))
+                .open(&mut open)
+                .default_width(420.0)
+                .show(ctx, |ui| {
+                    self.render_size_attribution(ui);
+                });
+            self.show_size_attribution = open;
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+
+        if self.show_memory_panel {
+            let mut open = true;
+            egui::Window::new(self.t("Memory usage", "内存占用"))
+                .open(&mut open)
+                .default_width(460.0)
+                .show(ctx, |ui| {
+                    self.render_memory_panel(ui);
+                });
+            self.show_memory_panel = open;
+            ctx.request_repaint_after(MemoryReport::REFRESH_INTERVAL);
+        }
+
         egui::TopBottomPanel::bottom("status_bar")
             .resizable(false)
             .show(ctx, |ui| {
                 self.render_status_bar(ui);
             });
 
+        if self.show_action_history {
+            egui::SidePanel::right("action_history")
+                .resizable(true)
+                .default_width(360.0)
+                .show(ctx, |ui| {
+                    self.render_action_history(ui);
+                });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| match self.mode {
             AppMode::AwaitingDirectory => {
                 ui.vertical_centered(|ui| {
@@ -1327,28 +8556,178 @@ impl eframe::App for TreeMapApp {
                     }
                 });
             }
+            AppMode::Previewing => self.render_preview_state(ui),
+            AppMode::Scanning if self.scan_result.is_some() => self.render_rescanning_state(ui),
             AppMode::Scanning => self.render_scanning_state(ui),
             AppMode::Ready => self.render_ready_state(ui),
             AppMode::Error => self.render_error_state(ui),
         });
+
+        if self.show_perf_overlay {
+            self.render_perf_overlay(ctx);
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        self.perf_stats.last_update = update_started.elapsed();
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SETTINGS_STORAGE_KEY, &self.persisted_settings());
+    }
+}
+
+/// Draws a curved arrow from `from` to `to`, bowed sideways by `jitter`.
+fn paint_action_curve(
+    painter: &egui::Painter,
+    from: egui::Pos2,
+    to: egui::Pos2,
+    jitter: f32,
+    stroke: egui::Stroke,
+) {
+    let chord = to - from;
+    let length = chord.length();
+    if length < 1.0 {
+        return;
+    }
+
+    let normal = egui::vec2(-chord.y, chord.x) / length;
+    let bow = length * (0.18 + 0.12 * jitter);
+    let control = from + chord * 0.5 + normal * bow;
+
+    painter.add(egui::epaint::QuadraticBezierShape::from_points_stroke(
+        [from, control, to],
+        false,
+        Color32::TRANSPARENT,
+        stroke,
+    ));
+
+    // Arrowhead along the curve's tangent at the target (derivative at t = 1).
+    let tangent = to - control;
+    let tangent_length = tangent.length();
+    if tangent_length <= f32::EPSILON {
+        return;
+    }
+
+    let direction = tangent / tangent_length;
+    let side = egui::vec2(-direction.y, direction.x);
+    let head_length = 5.0 + stroke.width * 2.0;
+    let head_width = head_length * 0.55;
+    let base = to - direction * head_length;
+    painter.add(egui::Shape::convex_polygon(
+        vec![to, base + side * head_width, base - side * head_width],
+        stroke.color,
+        egui::Stroke::NONE,
+    ));
+}
+
+fn paint_sparkline(
+    ui: &mut egui::Ui,
+    values: &[u32],
+    color: Color32,
+    size: egui::Vec2,
+) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, Color32::from_black_alpha(60));
+
+    let max_value = values.iter().copied().max().unwrap_or(0);
+    if values.len() < 2 || max_value == 0 {
+        return response;
     }
+
+    let step = rect.width() / (values.len() - 1) as f32;
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let ratio = *value as f32 / max_value as f32;
+            egui::pos2(
+                rect.left() + index as f32 * step,
+                rect.bottom() - 1.0 - ratio * (rect.height() - 2.0),
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.2, color)));
+
+    response
 }
 
-fn format_duration_compact(duration: Duration) -> String {
-    let total_seconds = duration.as_secs();
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
-    let seconds = total_seconds % 60;
+/// Line chart scaled between the smallest and largest value, with a dot per
+/// sample.
+fn paint_growth_chart(ui: &mut egui::Ui, values: &[u64], size: egui::Vec2) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, Color32::from_black_alpha(60));
+
+    let min_value = values.iter().copied().min().unwrap_or(0);
+    let max_value = values.iter().copied().max().unwrap_or(0);
+    if values.len() < 2 {
+        return response;
+    }
 
-    if hours > 0 {
-        return format!("{hours}h {minutes:02}m {seconds:02}s");
+    let range = (max_value - min_value).max(1) as f32;
+    let step = (rect.width() - 6.0) / (values.len() - 1) as f32;
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let ratio = (value - min_value) as f32 / range;
+            egui::pos2(
+                rect.left() + 3.0 + index as f32 * step,
+                rect.bottom() - 3.0 - ratio * (rect.height() - 6.0),
+            )
+        })
+        .collect();
+    let color = Color32::from_rgb(110, 190, 255);
+    for point in &points {
+        painter.circle_filled(*point, 2.0, color);
     }
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+
+    response
+}
 
-    if minutes > 0 {
-        return format!("{minutes}m {seconds:02}s");
+/// Files and folders in a removed subtree; folders are the entries with
+/// children, plus the top entry when it was a folder.
+fn entry_counts(node: &Node, is_dir: bool) -> (u64, u64) {
+    let (mut files, mut directories) = if is_dir || !node.children.is_empty() {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    for child in &node.children {
+        let (child_files, child_directories) = entry_counts(child, false);
+        files += child_files;
+        directories += child_directories;
     }
+    (files, directories)
+}
+
+/// Shows control characters in names as escapes (`\t`, `\u{1b}`).
+fn escape_control_chars(text: &str) -> String {
+    text.chars()
+        .map(|ch| {
+            if ch.is_control() {
+                ch.escape_default().to_string()
+            } else {
+                ch.to_string()
+            }
+        })
+        .collect()
+}
+
+fn permission_issue_text(finding: &PermissionFinding) -> String {
+    finding
+        .issues
+        .iter()
+        .map(|issue| issue.label())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    format!("{seconds}s")
+fn format_clock_time(timestamp: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(timestamp)
+        .format("%H:%M:%S%.3f")
+        .to_string()
 }
 
 fn configure_fonts_for_cjk(ctx: &egui::Context) {
@@ -1358,10 +8737,9 @@ fn configure_fonts_for_cjk(ctx: &egui::Context) {
     let mut loaded_font_names = Vec::with_capacity(loaded_fonts.len());
 
     for (font_name, font_data) in loaded_fonts {
-        fonts.font_data.insert(
-            font_name.clone(),
-            egui::FontData::from_owned(font_data).into(),
-        );
+        fonts
+            .font_data
+            .insert(font_name.clone(), egui::FontData::from_owned(font_data));
         loaded_font_names.push(font_name);
     }
 
@@ -1403,111 +8781,177 @@ fn load_system_cjk_fonts() -> Vec<(String, Vec<u8>)> {
     loaded
 }
 
-fn compute_type_stats(root: &Node) -> (Vec<TypeStat>, u64) {
-    let mut map: HashMap<String, (u64, u64)> = HashMap::new();
-    let mut total_file_bytes = 0_u64;
-    collect_type_stats(root, &mut map, &mut total_file_bytes);
-
-    let mut stats: Vec<TypeStat> = map
-        .into_iter()
-        .map(|(key, (bytes, files))| TypeStat {
-            color: color_for_type_key(&key),
-            key,
-            bytes,
-            files,
-        })
-        .collect();
+/// How a logged command ended, for the command log.
+fn command_exit_text(status: &Result<Option<i32>, String>) -> String {
+    match status {
+        Ok(Some(code)) => format!("exit {code}"),
+        Ok(None) => "terminated".to_string(),
+        Err(error) => error.clone(),
+    }
+}
 
-    stats.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.key.cmp(&b.key)));
-    (stats, total_file_bytes)
+struct LegendSlice {
+    /// Type key or category name; empty for the remainder slice.
+    key: String,
+    label: String,
+    bytes: u64,
+    color: Color32,
 }
 
-fn collect_type_stats(
-    node: &Node,
-    map: &mut HashMap<String, (u64, u64)>,
-    total_file_bytes: &mut u64,
+/// Adds the part of a ring between `start` and `end` (fractions of a turn,
+/// clockwise from the top) to `mesh`.
+fn add_ring_segment(
+    mesh: &mut egui::Mesh,
+    center: egui::Pos2,
+    inner: f32,
+    outer: f32,
+    start: f32,
+    end: f32,
+    color: Color32,
 ) {
-    if node.children.is_empty() {
-        let key = file_type_key(&node.path);
-        let entry = map.entry(key).or_insert((0, 0));
-        entry.0 = entry.0.saturating_add(node.size);
-        entry.1 = entry.1.saturating_add(1);
-        *total_file_bytes = total_file_bytes.saturating_add(node.size);
-        return;
+    let steps = ((end - start) * 96.0).ceil().max(1.0) as u32;
+    for step in 0..=steps {
+        let fraction = start + (end - start) * step as f32 / steps as f32;
+        let angle = fraction * std::f32::consts::TAU - std::f32::consts::FRAC_PI_2;
+        let direction = egui::vec2(angle.cos(), angle.sin());
+        let base = mesh.vertices.len() as u32;
+        mesh.colored_vertex(center + direction * inner, color);
+        mesh.colored_vertex(center + direction * outer, color);
+        if step > 0 {
+            mesh.add_triangle(base - 2, base - 1, base);
+            mesh.add_triangle(base - 1, base + 1, base);
+        }
     }
+}
 
-    for child in &node.children {
-        collect_type_stats(child, map, total_file_bytes);
-    }
+/// What file cells are colored by: their type (content-detected where known)
+/// or, when `categories` is set, the type's category.
+struct CellColoring<'a> {
+    content_types: &'a HashMap<PathBuf, String>,
+    categories: Option<&'a CategoryMap>,
+    /// Dims git-ignored entries.
+    git: Option<&'a GitScan>,
+    /// Loaded plugins; the first one with a color for a cell wins.
+    plugins: &'a [Arc<Plugin>],
+    /// Custom view color rules, ahead of plugins.
+    script: Option<&'a ScriptView>,
+    /// Shades files by code lines instead of type.
+    loc: Option<&'a LocReport>,
+    /// Tints forbidden zones red and dims paths outside the allowlist.
+    sandbox: Option<&'a SandboxPolicy>,
+    /// Colors files by owner, dimming all but the selected owner if any.
+    owners: Option<Option<u32>>,
+    /// Unix seconds the custom view measures ages from.
+    now: u64,
 }
 
-fn format_type_key(key: &str, language: Language) -> String {
-    if key == "(no_ext)" {
-        return match language {
-            Language::English => "(no extension)".to_string(),
-            Language::Chinese => "（无扩展名）".to_string(),
+impl CellColoring<'_> {
+    fn color(&self, node: &Node, depth: usize) -> Color32 {
+        let color = self
+            .script
+            .and_then(|script| {
+                script.color(&Entry {
+                    node,
+                    depth,
+                    now: self.now,
+                })
+            })
+            .or_else(|| {
+                self.plugins
+                    .iter()
+                    .filter(|plugin| plugin.has_colors())
+                    .find_map(|plugin| plugin.color(node, depth))
+                    .map(|[r, g, b, a]| Color32::from_rgba_unmultiplied(r, g, b, a))
+            })
+            .unwrap_or_else(|| self.base_color(node, depth));
+        let color = match self.sandbox.map(|sandbox| sandbox.access(&node.path)) {
+            Some(Access::Denied(_)) => color.lerp_to_gamma(SANDBOX_DENIED_COLOR, 0.55),
+            Some(Access::OutsideAllowlist) => color.gamma_multiply(0.35),
+            _ => color,
+        };
+        if self.git.is_some_and(|git| git.is_ignored(&node.path)) {
+            color.gamma_multiply(0.4)
+        } else {
+            color
+        }
+    }
+
+    fn base_color(&self, node: &Node, depth: usize) -> Color32 {
+        if !node.children.is_empty() {
+            return folder_color(depth);
+        }
+        if let Some(selected) = self.owners {
+            let color = shade_color(owner_color(node.owner), depth);
+            return match selected {
+                Some(uid) if uid != node.owner => color.gamma_multiply(0.25),
+                _ => color,
+            };
+        }
+        if let Some(loc) = self.loc {
+            return match loc.heat(&node.path) {
+                Some(heat) => shade_color(code_lines_color(heat), depth),
+                None => shade_color(Color32::from_rgb(90, 90, 90), depth),
+            };
+        }
+
+        let key = match self.content_types.get(&node.path) {
+            Some(key) => key.clone(),
+            None => file_type_key(&node.path),
         };
+        let base = match self.categories {
+            Some(categories) => categories.category_of(&key).color,
+            None => color_for_type_key(&key),
+        };
+        shade_color(base, depth)
     }
+}
 
-    format!(".{key}")
+fn owner_color(uid: u32) -> Color32 {
+    color_for_type_key(&format!("uid:{uid}"))
 }
 
-fn color_for_node(node: &Node, depth: usize) -> Color32 {
-    if !node.children.is_empty() {
-        return folder_color(depth);
+fn symlink_status_color(status: LinkStatus) -> Color32 {
+    match status {
+        LinkStatus::Inside => Color32::from_rgb(120, 200, 255),
+        LinkStatus::Outside => Color32::from_rgb(235, 170, 60),
+        LinkStatus::Broken => PERMISSION_FINDING_COLOR,
     }
+}
 
-    let key = file_type_key(&node.path);
-    let base = color_for_type_key(&key);
-    shade_color(base, depth)
+fn symlink_status_text(status: LinkStatus, language: Language) -> &'static str {
+    match (status, language) {
+        (LinkStatus::Inside, Language::Chinese) => "根目录内",
+        (LinkStatus::Outside, Language::Chinese) => "根目录外",
+        (LinkStatus::Broken, Language::Chinese) => "已失效",
+        (status, _) => status.label(),
+    }
 }
 
-fn folder_color(depth: usize) -> Color32 {
-    shade_color(Color32::from_rgb(72, 78, 86), depth)
+fn access_kind_label(kind: AccessKind, language: Language) -> &'static str {
+    match (kind, language) {
+        (AccessKind::Metadata, Language::Chinese) => "元数据读取",
+        (AccessKind::DirectoryListing, Language::Chinese) => "目录列举",
+        (AccessKind::ContentRead, Language::Chinese) => "文件内容读取",
+        (AccessKind::Removal, Language::Chinese) => "删除",
+        (kind, _) => kind.label(),
+    }
 }
 
-fn file_type_key(path: &std::path::Path) -> String {
-    path.extension()
-        .and_then(|value| value.to_str())
-        .map(|value| value.trim().to_ascii_lowercase())
-        .filter(|value| !value.is_empty())
-        .unwrap_or_else(|| "(no_ext)".to_string())
+/// Blue for few code lines through red for the most.
+fn code_lines_color(heat: f32) -> Color32 {
+    let heat = heat.clamp(0.0, 1.0);
+    let mix = |low: u8, high: u8| (low as f32 + (high as f32 - low as f32) * heat).round() as u8;
+    Color32::from_rgb(mix(60, 220), mix(110, 70), mix(200, 50))
 }
 
-fn color_for_type_key(key: &str) -> Color32 {
-    if key == "(no_ext)" {
-        return Color32::from_rgb(122, 128, 136);
-    }
-
-    const PALETTE: [Color32; 24] = [
-        Color32::from_rgb(210, 96, 96),
-        Color32::from_rgb(214, 127, 78),
-        Color32::from_rgb(196, 151, 72),
-        Color32::from_rgb(153, 171, 72),
-        Color32::from_rgb(106, 175, 87),
-        Color32::from_rgb(79, 177, 120),
-        Color32::from_rgb(74, 173, 153),
-        Color32::from_rgb(73, 166, 179),
-        Color32::from_rgb(76, 152, 194),
-        Color32::from_rgb(88, 137, 204),
-        Color32::from_rgb(109, 124, 209),
-        Color32::from_rgb(128, 112, 207),
-        Color32::from_rgb(149, 104, 197),
-        Color32::from_rgb(173, 98, 185),
-        Color32::from_rgb(191, 95, 166),
-        Color32::from_rgb(201, 96, 143),
-        Color32::from_rgb(210, 106, 124),
-        Color32::from_rgb(171, 126, 98),
-        Color32::from_rgb(144, 140, 101),
-        Color32::from_rgb(111, 146, 114),
-        Color32::from_rgb(95, 147, 133),
-        Color32::from_rgb(101, 142, 152),
-        Color32::from_rgb(112, 132, 165),
-        Color32::from_rgb(130, 121, 167),
-    ];
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
 
-    let index = (stable_hash(&key) % PALETTE.len() as u64) as usize;
-    PALETTE[index]
+fn folder_color(depth: usize) -> Color32 {
+    shade_color(Color32::from_rgb(72, 78, 86), depth)
 }
 
 fn shade_color(base: Color32, depth: usize) -> Color32 {
@@ -1521,21 +8965,68 @@ fn shade_color(base: Color32, depth: usize) -> Color32 {
     Color32::from_rgb(scaled_r, scaled_g, scaled_b)
 }
 
-fn stable_hash<T: Hash>(value: &T) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    value.hash(&mut hasher);
-    hasher.finish()
+/// Drains one ingestion channel; returns `true` once its sender is gone.
+fn drain_ingest_messages(
+    receiver: &Receiver<IngestMessage>,
+    actions: &mut Vec<ActionEvent>,
+    rejected_events: &mut u64,
+    last_rejected_event: &mut Option<String>,
+    status: &mut ListenerStatus,
+) -> bool {
+    loop {
+        match receiver.try_recv() {
+            Ok(IngestMessage::Action(action)) => actions.push(action),
+            Ok(IngestMessage::Rejected(error)) => {
+                *rejected_events = rejected_events.saturating_add(1);
+                *last_rejected_event = Some(error);
+            }
+            Ok(IngestMessage::Status(new_status)) => *status = new_status,
+            Err(TryRecvError::Empty) => return false,
+            Err(TryRecvError::Disconnected) => {
+                if !matches!(status, ListenerStatus::Failed(_)) {
+                    *status = ListenerStatus::Stopped;
+                }
+                return true;
+            }
+        }
+    }
+}
+
+fn target_bias_label(bias: TargetBias, language: Language) -> &'static str {
+    match (bias, language) {
+        (TargetBias::Uniform, Language::English) => "Uniform",
+        (TargetBias::Uniform, Language::Chinese) => "均匀",
+        (TargetBias::LargeFiles, Language::English) => "Prefer large files",
+        (TargetBias::LargeFiles, Language::Chinese) => "偏好大文件",
+        (TargetBias::SmallFiles, Language::English) => "Prefer small files",
+        (TargetBias::SmallFiles, Language::Chinese) => "偏好小文件",
+    }
 }
 
-fn time_seed() -> u64 {
-    match SystemTime::now().duration_since(UNIX_EPOCH) {
-        Ok(duration) => duration.as_nanos() as u64,
-        Err(_) => 0xA5A5_5A5A_1234_5678,
+fn color_for_action_kind(kind: ActionKind) -> Color32 {
+    match kind {
+        ActionKind::Inspect => Color32::from_rgb(90, 170, 255),
+        ActionKind::Read => Color32::from_rgb(120, 210, 250),
+        ActionKind::Write => Color32::from_rgb(255, 150, 40),
+        ActionKind::Create => Color32::from_rgb(80, 200, 110),
+        ActionKind::Delete => Color32::from_rgb(235, 50, 50),
+        ActionKind::Rename => Color32::from_rgb(200, 120, 230),
+        ActionKind::Execute => Color32::from_rgb(255, 220, 60),
+        ActionKind::Classify => Color32::from_rgb(150, 150, 255),
+        ActionKind::Correlate => Color32::from_rgb(60, 200, 200),
+        ActionKind::Trace => Color32::from_rgb(255, 110, 180),
+        ActionKind::Verify => Color32::from_rgb(160, 220, 90),
+        ActionKind::Highlight => Color32::from_rgb(255, 255, 255),
+        ActionKind::Other => Color32::from_rgb(180, 180, 180),
     }
 }
 
-fn next_seed(seed: u64) -> u64 {
-    seed.wrapping_mul(6364136223846793005).wrapping_add(1)
+fn heat_color(intensity: f32) -> Color32 {
+    // Ramp from a faint amber glow to an opaque hot red.
+    let t = intensity.clamp(0.0, 1.0);
+    let green = (200.0 - 170.0 * t).round() as u8;
+    let alpha = (50.0 + 170.0 * t).round() as u8;
+    Color32::from_rgba_unmultiplied(255, green, 20, alpha)
 }
 
 fn normalize_path_key(path: &std::path::Path) -> String {
@@ -1560,57 +9051,24 @@ fn path_within_root(path: &std::path::Path, root: &std::path::Path) -> bool {
     path_key.starts_with(&root_prefix)
 }
 
-fn build_alias_map(root: &Node) -> HashMap<PathBuf, AliasEntry> {
-    let mut alias_map = HashMap::new();
-    let mut file_counter = 0_usize;
-    let mut folder_counter = 0_usize;
-    assign_alias(
-        root,
-        true,
-        &mut alias_map,
-        &mut file_counter,
-        &mut folder_counter,
-    );
-    alias_map
-}
-
-fn assign_alias(
-    node: &Node,
-    is_root: bool,
-    alias_map: &mut HashMap<PathBuf, AliasEntry>,
-    file_counter: &mut usize,
-    folder_counter: &mut usize,
-) {
-    let is_dir = is_root || !node.children.is_empty();
-    let (kind, code) = if is_dir {
-        let index = *folder_counter;
-        *folder_counter = folder_counter.saturating_add(1);
-        (AliasKind::Folder, alphabet_code(index))
-    } else {
-        let index = *file_counter;
-        *file_counter = file_counter.saturating_add(1);
-        (AliasKind::File, alphabet_code(index))
-    };
-
-    alias_map.insert(node.path.clone(), AliasEntry { code, kind });
+/// Cells smaller than this on screen get no label at all.
+const LABEL_MIN_SIZE: egui::Vec2 = egui::vec2(40.0, 16.0);
+/// Approximate advance of one small-font character, in points.
+const LABEL_CHAR_WIDTH: f32 = 7.0;
 
-    for child in &node.children {
-        assign_alias(child, false, alias_map, file_counter, folder_counter);
+/// Picks the most detailed label tier that fits a cell of `screen_size`
+/// (its world size scaled by the zoom), so zooming in reveals more text.
+fn cell_label_text(cell: &CachedCell, screen_size: egui::Vec2) -> Option<String> {
+    if screen_size.x < LABEL_MIN_SIZE.x || screen_size.y < LABEL_MIN_SIZE.y {
+        return None;
     }
-}
 
-fn alphabet_code(mut index: usize) -> String {
-    // 0 -> A, 25 -> Z, 26 -> AA
-    let mut chars = Vec::new();
-    loop {
-        let rem = (index % 26) as u8;
-        chars.push((b'A' + rem) as char);
-        if index < 26 {
-            break;
-        }
-        index = index / 26 - 1;
-    }
-    chars.iter().rev().collect()
+    let max_chars = ((screen_size.x - 8.0) / LABEL_CHAR_WIDTH).floor().max(4.0) as usize;
+    (0..cell.labels.len())
+        .rev()
+        .find(|&tier| cell.label_chars[tier] <= max_chars)
+        .map(|tier| cell.labels[tier].clone())
+        .or_else(|| Some(truncate_label(&cell.labels[0], max_chars)))
 }
 
 fn truncate_label(text: &str, max_chars: usize) -> String {
@@ -1622,7 +9080,7 @@ fn truncate_label(text: &str, max_chars: usize) -> String {
         return "...".to_string();
     }
 
-    let mut truncated = String::new();
+    let mut truncated = String::with_capacity(max_chars * 4);
     for (index, ch) in text.chars().enumerate() {
         if index + 3 >= max_chars {
             break;
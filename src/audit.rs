@@ -0,0 +1,95 @@
+//! Permission audit collected during a scan when
+//! [`ScanConfig::audit_permissions`](crate::scanner::ScanConfig) is set:
+//! world-writable entries and set-user/group-ID files.
+//!
+//! Only Unix permission bits are checked. World-writable folders with the
+//! sticky bit (like `/tmp`) are expected and not reported; Windows ACLs are
+//! not inspected.
+
+use serde::{Deserialize, Serialize};
+use std::fs::Metadata;
+use std::path::PathBuf;
+
+/// Findings kept per scan; a badly misconfigured share could otherwise
+/// produce one per file.
+pub const MAX_PERMISSION_FINDINGS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PermissionIssue {
+    WorldWritable,
+    SetUid,
+    SetGid,
+}
+
+impl PermissionIssue {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::WorldWritable => "world-writable",
+            Self::SetUid => "setuid",
+            Self::SetGid => "setgid",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionFinding {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub issues: Vec<PermissionIssue>,
+}
+
+#[cfg(unix)]
+pub fn permission_issues(metadata: &Metadata) -> Vec<PermissionIssue> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if metadata.file_type().is_symlink() {
+        return Vec::new();
+    }
+    mode_issues(metadata.permissions().mode(), metadata.is_dir())
+}
+
+#[cfg(not(unix))]
+pub fn permission_issues(_metadata: &Metadata) -> Vec<PermissionIssue> {
+    Vec::new()
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+fn mode_issues(mode: u32, is_dir: bool) -> Vec<PermissionIssue> {
+    const OTHERS_WRITE: u32 = 0o002;
+    const STICKY: u32 = 0o1000;
+    const SET_GID: u32 = 0o2000;
+    const SET_UID: u32 = 0o4000;
+
+    let mut issues = Vec::new();
+    if mode & OTHERS_WRITE != 0 && !(is_dir && mode & STICKY != 0) {
+        issues.push(PermissionIssue::WorldWritable);
+    }
+    if mode & SET_UID != 0 && !is_dir {
+        issues.push(PermissionIssue::SetUid);
+    }
+    // On folders setgid only makes new entries inherit the group.
+    if mode & SET_GID != 0 && !is_dir {
+        issues.push(PermissionIssue::SetGid);
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_risky_modes_only() {
+        assert!(mode_issues(0o644, false).is_empty());
+        assert!(mode_issues(0o1777, true).is_empty());
+        assert!(mode_issues(0o2775, true).is_empty());
+        assert_eq!(
+            mode_issues(0o777, true),
+            vec![PermissionIssue::WorldWritable]
+        );
+        assert_eq!(
+            mode_issues(0o6755, false),
+            vec![PermissionIssue::SetUid, PermissionIssue::SetGid]
+        );
+    }
+}
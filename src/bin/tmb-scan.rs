@@ -0,0 +1,192 @@
+//! Headless scanner: scans a directory with the same code as the desktop app
+//! and prints a summary, JSON, or a snapshot the app can open.
+
+use serde_json::json;
+use std::path::PathBuf;
+use tree_map_base::category::{categories_path, load_categories, CategoryMap};
+use tree_map_base::format::{human_size, percent, Language};
+use tree_map_base::portable;
+use tree_map_base::scanner::{spawn_scan, ScanConfig, ScanMessage};
+use tree_map_base::snapshot::save_snapshot;
+use tree_map_base::wake::Waker;
+
+const USAGE: &str = "\
+Usage: tmb-scan [OPTIONS] <DIR>
+
+Options:
+  --top <N>            Largest files to list (default: 10)
+  --max-files <N>      Stop after N files; 0 for no limit (default: 250000)
+  --max-depth <N>      Maximum directory depth (default: 64)
+  --threads <N>        Folders listed in parallel (default: 1)
+  --json               Print the summary as JSON
+  --audit-permissions  List world-writable entries and setuid/setgid files
+  --snapshot <FILE>    Also save the scan as a snapshot for tree-map-base
+                       (JSON when FILE ends in .json)
+  -h, --help           Print this help
+";
+
+struct Options {
+    root: PathBuf,
+    top: usize,
+    json: bool,
+    snapshot: Option<PathBuf>,
+    config: ScanConfig,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut root = None;
+    let mut top = 10;
+    let mut json = false;
+    let mut snapshot = None;
+    let mut config = ScanConfig::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .filter(|value| !value.starts_with("--"))
+                .ok_or_else(|| format!("Missing value for {flag}"))
+        };
+        let number = |text: String, flag: &str| {
+            text.parse::<usize>()
+                .map_err(|_| format!("Invalid number for {flag}: {text}"))
+        };
+        match arg.as_str() {
+            "--top" => top = number(value(&arg)?, &arg)?,
+            "--max-files" => {
+                config.max_files = Some(number(value(&arg)?, &arg)?).filter(|&max| max > 0);
+            }
+            "--max-depth" => config.max_depth = number(value(&arg)?, &arg)?,
+            "--threads" => config.threads = number(value(&arg)?, &arg)?.max(1),
+            "--json" => json = true,
+            "--audit-permissions" => config.audit_permissions = true,
+            "--snapshot" => snapshot = Some(PathBuf::from(value(&arg)?)),
+            "-h" | "--help" => return Ok(None),
+            other if other.starts_with('-') => return Err(format!("Unknown argument: {other}")),
+            other if root.is_none() => root = Some(PathBuf::from(other)),
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    let root = root.ok_or_else(|| "Missing <DIR>".to_string())?;
+    Ok(Some(Options {
+        root,
+        top,
+        json,
+        snapshot,
+        config,
+    }))
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return;
+        }
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    // Honor the portable marker so categories come from the same place.
+    portable::init(false);
+
+    let handle = spawn_scan(options.root.clone(), options.config, Waker::default());
+    let result = loop {
+        match handle.receiver().recv() {
+            Ok(ScanMessage::Finished(result)) => break result,
+            Ok(ScanMessage::Partial { .. }) => continue,
+            Ok(ScanMessage::Cancelled) => break Err("Scan cancelled".to_string()),
+            Err(_) => break Err("Scan thread stopped unexpectedly".to_string()),
+        }
+    };
+    let (result, analysis) = match result {
+        Ok(finished) => finished,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(path) = &options.snapshot {
+        if let Err(error) = save_snapshot(path, &result) {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    }
+
+    let root = &result.root;
+    let stats = &result.stats;
+    let largest = root.largest(options.top, false);
+    if options.json {
+        // Uses the app's mapping file when one exists, without creating it.
+        let categories = categories_path()
+            .filter(|path| path.exists())
+            .and_then(|path| load_categories(&path).ok())
+            .map_or_else(CategoryMap::default, |(categories, _)| categories);
+        let summary = json!({
+            "root": root.path.to_string_lossy(),
+            "size": root.size,
+            "files": stats.files_scanned,
+            "directories": stats.directories_scanned,
+            "warnings": stats.warnings,
+            "truncated": stats.truncated,
+            "elapsed_ms": stats.elapsed.as_millis() as u64,
+            "largest": largest
+                .iter()
+                .map(|node| json!({ "path": node.path.to_string_lossy(), "size": node.size }))
+                .collect::<Vec<_>>(),
+            "types": analysis
+                .type_stats
+                .iter()
+                .map(|stat| json!({ "type": stat.key, "size": stat.bytes, "files": stat.files }))
+                .collect::<Vec<_>>(),
+            "categories": categories
+                .summarize(&analysis.type_stats)
+                .iter()
+                .map(|stat| json!({ "category": stat.name, "size": stat.bytes, "files": stat.files }))
+                .collect::<Vec<_>>(),
+            "permission_findings": result
+                .permission_findings
+                .iter()
+                .map(|finding| json!({
+                    "path": finding.path.to_string_lossy(),
+                    "issues": finding.issues.iter().map(|issue| issue.label()).collect::<Vec<_>>(),
+                }))
+                .collect::<Vec<_>>(),
+        });
+        println!("{summary:#}");
+        return;
+    }
+
+    let size = |bytes: u64| human_size(bytes, Language::English);
+    println!("{}", root.path.display());
+    println!(
+        "  {} in {} files and {} folders ({} warnings{})",
+        size(root.size),
+        stats.files_scanned,
+        stats.directories_scanned,
+        stats.warnings,
+        if stats.truncated { ", truncated" } else { "" }
+    );
+    if !largest.is_empty() {
+        println!("\nLargest files:");
+    }
+    for node in largest {
+        println!(
+            "  {:>10}  {:>6}  {}",
+            size(node.size),
+            percent(node.size as f64 / root.size.max(1) as f64),
+            node.path.display()
+        );
+    }
+    if !result.permission_findings.is_empty() {
+        println!("\nPermission findings:");
+    }
+    for finding in &result.permission_findings {
+        let issues: Vec<_> = finding.issues.iter().map(|issue| issue.label()).collect();
+        println!("  {:<24}  {}", issues.join(", "), finding.path.display());
+    }
+}
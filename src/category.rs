@@ -0,0 +1,243 @@
+//! Extension → category mapping (Video, Images, Code, ...) behind the
+//! category legend, the category coloring mode and the report tables.
+//!
+//! The mapping lives in `categories.json` in the config directory, written
+//! from [`DEFAULT_CATEGORIES`] on first use so users can edit it. Extensions
+//! are matched case-insensitively; anything unlisted falls into "Other".
+
+use crate::analysis::TypeStat;
+use crate::portable::config_dir;
+use ecolor::Color32;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const OTHER_CATEGORY: &str = "Other";
+const OTHER_COLOR: Color32 = Color32::from_rgb(122, 128, 136);
+
+pub const DEFAULT_CATEGORIES: &str = r##"{
+  "categories": [
+    { "name": "Video", "color": "#d0607a", "extensions": ["mp4", "mkv", "mov", "avi", "wmv", "webm", "m4v", "mpg", "mpeg", "flv", "vob"] },
+    { "name": "Images", "color": "#e0a040", "extensions": ["jpg", "jpeg", "png", "gif", "bmp", "tif", "tiff", "webp", "heic", "svg", "ico", "psd", "raw", "cr2", "nef", "dng"] },
+    { "name": "Audio", "color": "#c070d0", "extensions": ["mp3", "flac", "wav", "ogg", "opus", "m4a", "aac", "wma", "aiff", "mid"] },
+    { "name": "Code", "color": "#50b080", "extensions": ["rs", "c", "h", "cpp", "hpp", "cc", "cs", "go", "java", "kt", "py", "rb", "js", "mjs", "ts", "tsx", "jsx", "php", "swift", "sh", "ps1", "lua", "toml", "yaml", "yml", "json", "xml", "html", "css", "scss"] },
+    { "name": "Documents", "color": "#5090d0", "extensions": ["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "txt", "md", "rtf", "csv", "epub"] },
+    { "name": "Archives", "color": "#a08060", "extensions": ["zip", "7z", "rar", "tar", "gz", "tgz", "bz2", "xz", "zst", "iso", "dmg", "cab"] },
+    { "name": "Databases", "color": "#40a0b0", "extensions": ["db", "sqlite", "sqlite3", "mdb", "accdb", "ldf", "mdf", "parquet", "tmbsnap"] },
+    { "name": "Executables", "color": "#d07050", "extensions": ["exe", "dll", "so", "dylib", "msi", "app", "apk", "bin", "sys", "o", "a", "lib", "jar", "wasm"] },
+    { "name": "Logs & temp", "color": "#909070", "extensions": ["log", "tmp", "temp", "bak", "old", "swp", "dmp", "cache"] }
+  ]
+}
+"##;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub name: String,
+    pub color: Color32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryStat {
+    pub name: String,
+    pub color: Color32,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CategoryMap {
+    categories: Vec<Category>,
+    by_extension: HashMap<String, usize>,
+    other: Category,
+}
+
+#[derive(Deserialize)]
+struct CategoryFile {
+    categories: Vec<CategoryEntry>,
+}
+
+#[derive(Deserialize)]
+struct CategoryEntry {
+    name: String,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    extensions: Vec<String>,
+}
+
+impl Default for CategoryMap {
+    fn default() -> Self {
+        Self::parse(DEFAULT_CATEGORIES)
+            .expect("built-in categories parse")
+            .0
+    }
+}
+
+impl CategoryMap {
+    /// Parses a mapping file, returning the map and one warning per skipped
+    /// color or repeated extension; malformed JSON is an error.
+    pub fn parse(text: &str) -> Result<(Self, Vec<String>), String> {
+        let file: CategoryFile =
+            serde_json::from_str(text).map_err(|error| format!("Invalid categories: {error}"))?;
+        let mut warnings = Vec::new();
+        let mut categories = Vec::with_capacity(file.categories.len());
+        let mut by_extension: HashMap<String, usize> = HashMap::new();
+
+        for (index, entry) in file.categories.into_iter().enumerate() {
+            let color = match entry.color.as_deref().map(parse_hex_color) {
+                Some(Some(color)) => color,
+                Some(None) => {
+                    warnings.push(format!("{}: color must look like #rrggbb", entry.name));
+                    palette_color(index)
+                }
+                None => palette_color(index),
+            };
+            categories.push(Category {
+                name: entry.name,
+                color,
+            });
+            for extension in entry.extensions {
+                let extension = extension
+                    .trim()
+                    .trim_start_matches('.')
+                    .to_ascii_lowercase();
+                if let Some(&first) = by_extension.get(&extension) {
+                    warnings.push(format!(
+                        "{}: .{extension} is already in {}",
+                        categories[index].name, categories[first].name
+                    ));
+                    continue;
+                }
+                by_extension.insert(extension, index);
+            }
+        }
+
+        let map = Self {
+            categories,
+            by_extension,
+            other: Category {
+                name: OTHER_CATEGORY.to_string(),
+                color: OTHER_COLOR,
+            },
+        };
+        Ok((map, warnings))
+    }
+
+    /// The category of a type key as produced by
+    /// [`file_type_key`](crate::analysis::file_type_key).
+    pub fn category_of(&self, type_key: &str) -> &Category {
+        self.by_extension
+            .get(type_key)
+            .map_or(&self.other, |&index| &self.categories[index])
+    }
+
+    /// Folds per-type totals into per-category totals, largest first.
+    pub fn summarize(&self, type_stats: &[TypeStat]) -> Vec<CategoryStat> {
+        let mut stats: Vec<CategoryStat> = Vec::new();
+        for stat in type_stats {
+            let category = self.category_of(&stat.key);
+            match stats.iter_mut().find(|entry| entry.name == category.name) {
+                Some(entry) => {
+                    entry.bytes += stat.bytes;
+                    entry.files += stat.files;
+                }
+                None => stats.push(CategoryStat {
+                    name: category.name.clone(),
+                    color: category.color,
+                    bytes: stat.bytes,
+                    files: stat.files,
+                }),
+            }
+        }
+        stats.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        stats
+    }
+}
+
+/// `categories.json` in the user's config directory.
+pub fn categories_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("categories.json"))
+}
+
+/// Reads the mapping at `path`, first writing the defaults there when the
+/// file does not exist yet.
+pub fn load_categories(path: &Path) -> Result<(CategoryMap, Vec<String>), String> {
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|error| format!("Could not create {}: {error}", parent.display()))?;
+        }
+        fs::write(path, DEFAULT_CATEGORIES)
+            .map_err(|error| format!("Could not write {}: {error}", path.display()))?;
+    }
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("Could not read {}: {error}", path.display()))?;
+    CategoryMap::parse(&text)
+}
+
+fn parse_hex_color(text: &str) -> Option<Color32> {
+    let hex = text.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color32::from_rgb(
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ))
+}
+
+fn palette_color(index: usize) -> Color32 {
+    const PALETTE: [Color32; 8] = [
+        Color32::from_rgb(210, 96, 96),
+        Color32::from_rgb(196, 151, 72),
+        Color32::from_rgb(106, 175, 87),
+        Color32::from_rgb(74, 173, 153),
+        Color32::from_rgb(86, 140, 204),
+        Color32::from_rgb(139, 112, 204),
+        Color32::from_rgb(196, 100, 170),
+        Color32::from_rgb(150, 150, 110),
+    ];
+    PALETTE[index % PALETTE.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_extensions_and_folds_stats() {
+        let (map, warnings) = CategoryMap::parse(
+            r##"{"categories": [
+                {"name": "Video", "color": "#ff0000", "extensions": ["MP4", ".mkv"]},
+                {"name": "Clips", "color": "red", "extensions": ["mp4", "gif"]}
+            ]}"##,
+        )
+        .unwrap();
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(map.category_of("mp4").name, "Video");
+        assert_eq!(map.category_of("mkv").color, Color32::from_rgb(255, 0, 0));
+        assert_eq!(map.category_of("gif").name, "Clips");
+        assert_eq!(map.category_of("(no_ext)").name, OTHER_CATEGORY);
+
+        let stat = |key: &str, bytes| TypeStat {
+            key: key.to_string(),
+            bytes,
+            files: 1,
+            color: Color32::BLACK,
+        };
+        let stats = map.summarize(&[stat("mp4", 10), stat("txt", 4), stat("mkv", 5)]);
+        assert_eq!(
+            stats
+                .iter()
+                .map(|stat| (stat.name.as_str(), stat.bytes, stat.files))
+                .collect::<Vec<_>>(),
+            vec![("Video", 15, 2), (OTHER_CATEGORY, 4, 1)]
+        );
+        assert!(CategoryMap::parse("{").is_err());
+        assert!(CategoryMap::parse(DEFAULT_CATEGORIES).unwrap().1.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::action::ActionKind;
+use crate::ipc::DEFAULT_IPC_NAME;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: tree-map-base [OPTIONS]
+
+Options:
+  --root <DIR>                 Scan <DIR> on startup instead of asking for it
+  --export-jsonl <FILE>        Write every scanned file to <FILE> as a JSON line
+                               while the scan runs
+  --actions-stdin              Read newline-delimited action events from stdin
+  --ipc-name <NAME>            Local IPC endpoint name (default: tree-map-base)
+  --send-action <KIND> <PATH>  Send one action event to a running instance over
+                               the local IPC endpoint, then exit
+  --serve <ADDR>               Serve scan results as read-only JSON over HTTP
+                               (e.g. 127.0.0.1:7878; /stats, /tree, /top, /types,
+                               /metrics)
+  --metrics <ADDR>             Serve only Prometheus metrics at <ADDR>/metrics
+  --install-context-menu       Add \"Scan with tree-map-base\" to the folder context
+                               menu of Explorer (Windows) or Nautilus (Linux)
+  --uninstall-context-menu     Remove that context menu entry again
+  --portable                   Keep settings, caches and snapshots next to the
+                               executable (also enabled by a
+                               tree-map-base.portable file there)
+  -h, --help                   Print this help
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliOptions {
+    pub root: Option<PathBuf>,
+    pub export_jsonl: Option<PathBuf>,
+    pub actions_stdin: bool,
+    pub ipc_name: String,
+    pub send_action: Option<(ActionKind, PathBuf)>,
+    pub serve: Option<String>,
+    pub metrics: Option<String>,
+    pub context_menu: Option<ContextMenuAction>,
+    pub portable: bool,
+    pub show_help: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextMenuAction {
+    Install,
+    Uninstall,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        Self {
+            root: None,
+            export_jsonl: None,
+            actions_stdin: false,
+            ipc_name: DEFAULT_IPC_NAME.to_string(),
+            send_action: None,
+            serve: None,
+            metrics: None,
+            context_menu: None,
+            portable: false,
+            show_help: false,
+        }
+    }
+}
+
+impl CliOptions {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--root" => options.root = Some(PathBuf::from(required_value(&mut args, &arg)?)),
+                "--export-jsonl" => {
+                    options.export_jsonl = Some(PathBuf::from(required_value(&mut args, &arg)?));
+                }
+                "--actions-stdin" => options.actions_stdin = true,
+                "--ipc-name" => options.ipc_name = required_value(&mut args, &arg)?,
+                "--send-action" => {
+                    let kind = required_value(&mut args, &arg)?;
+                    let path = required_value(&mut args, &arg)?;
+                    options.send_action = Some((ActionKind::from_name(&kind), PathBuf::from(path)));
+                }
+                "--serve" => options.serve = Some(required_value(&mut args, &arg)?),
+                "--metrics" => options.metrics = Some(required_value(&mut args, &arg)?),
+                "--install-context-menu" => options.context_menu = Some(ContextMenuAction::Install),
+                "--uninstall-context-menu" => {
+                    options.context_menu = Some(ContextMenuAction::Uninstall);
+                }
+                "--portable" => options.portable = true,
+                "-h" | "--help" => options.show_help = true,
+                other => return Err(format!("Unknown argument: {other}")),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn required_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .filter(|value| !value.starts_with("--"))
+        .ok_or_else(|| format!("Missing value for {flag}"))
+}
@@ -0,0 +1,171 @@
+//! Optional compressibility estimate: compresses a sample from the start of
+//! files with zstd and extrapolates an estimated compressed size for every
+//! folder ("this 40 GB log folder would compress to ~4 GB").
+//!
+//! Reading is bounded: the largest files are sampled first until the byte
+//! budget runs out, and the remaining files take the average ratio of their
+//! type (or of everything sampled when the type was never sampled).
+
+use crate::analysis::{collect_files, file_type_key};
+use crate::gateway;
+use crate::model::Node;
+use crate::wake::{self, Waker};
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+/// Bytes compressed per sampled file.
+const SAMPLE_BYTES: usize = 64 * 1024;
+/// Upper bound on file content read for one estimate.
+const READ_BUDGET: u64 = 512 * 1024 * 1024;
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Debug)]
+pub struct CompressEstimate {
+    /// The scan generation the estimated tree belonged to.
+    pub scan_generation: u64,
+    /// Estimated compressed bytes per folder.
+    pub folders: HashMap<PathBuf, u64>,
+    pub files_sampled: u64,
+    pub bytes_read: u64,
+}
+
+impl CompressEstimate {
+    /// Estimated compressed size of a folder as a fraction of its size.
+    pub fn ratio(&self, node: &Node) -> Option<f64> {
+        let compressed = *self.folders.get(&node.path)?;
+        (node.size > 0).then(|| compressed as f64 / node.size as f64)
+    }
+}
+
+/// Estimates every folder of `root` on a background thread.
+pub fn spawn_compress_estimate(
+    root: &Node,
+    scan_generation: u64,
+    waker: Waker,
+) -> Receiver<CompressEstimate> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    let mut files: Vec<(PathBuf, u64)> = files
+        .into_iter()
+        .filter(|node| !std::ptr::eq(*node, root) && node.size > 0)
+        .map(|node| (node.path.clone(), node.size))
+        .collect();
+    let root_path = root.path.clone();
+    let (tx, rx) = wake::channel(waker);
+
+    thread::spawn(move || {
+        files.sort_by_key(|(_, size)| Reverse(*size));
+        let mut budget = READ_BUDGET;
+        let sampled_count = files
+            .iter()
+            .take_while(|(_, size)| {
+                let read = (*size).min(SAMPLE_BYTES as u64);
+                budget = budget.saturating_sub(read);
+                budget > 0
+            })
+            .count();
+        let samples: Vec<Option<(u64, u64)>> = files[..sampled_count]
+            .par_iter()
+            .map(|(path, _)| sample_ratio(path))
+            .collect();
+
+        // Per-type and overall ratios, weighted by bytes read.
+        let mut by_type: HashMap<String, (u64, u64)> = HashMap::new();
+        let mut overall = (0_u64, 0_u64);
+        for ((path, _), sample) in files.iter().zip(&samples) {
+            if let Some((read, compressed)) = sample {
+                let entry = by_type.entry(file_type_key(path)).or_default();
+                entry.0 += read;
+                entry.1 += compressed;
+                overall.0 += read;
+                overall.1 += compressed;
+            }
+        }
+        let ratio_of = |(read, compressed): (u64, u64)| compressed as f64 / read.max(1) as f64;
+        let fallback = if overall.0 > 0 {
+            ratio_of(overall)
+        } else {
+            1.0
+        };
+
+        let mut folders: HashMap<PathBuf, u64> = HashMap::new();
+        for (index, (path, size)) in files.iter().enumerate() {
+            let ratio = match samples.get(index) {
+                Some(Some(sample)) => ratio_of(*sample),
+                _ => by_type
+                    .get(&file_type_key(path))
+                    .map_or(fallback, |sample| ratio_of(*sample)),
+            };
+            let estimate = (*size as f64 * ratio.min(1.0)).round() as u64;
+            for folder in path.ancestors().skip(1) {
+                *folders.entry(folder.to_path_buf()).or_default() += estimate;
+                if folder == root_path {
+                    break;
+                }
+            }
+        }
+
+        let _ = tx.send(CompressEstimate {
+            scan_generation,
+            folders,
+            files_sampled: samples.iter().flatten().count() as u64,
+            bytes_read: overall.0,
+        });
+    });
+
+    rx
+}
+
+/// Bytes read and their zstd-compressed size for the start of `path`.
+fn sample_ratio(path: &Path) -> Option<(u64, u64)> {
+    let mut buffer = Vec::with_capacity(SAMPLE_BYTES);
+    gateway::open(path)
+        .ok()?
+        .take(SAMPLE_BYTES as u64)
+        .read_to_end(&mut buffer)
+        .ok()?;
+    if buffer.is_empty() {
+        return None;
+    }
+    let compressed = zstd::bulk::compress(&buffer, ZSTD_LEVEL).ok()?;
+    Some((
+        buffer.len() as u64,
+        compressed.len().min(buffer.len()) as u64,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repetitive_text_compresses_better_than_noise() {
+        let dir =
+            std::env::temp_dir().join(format!("tree-map-base-compress-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let text = "2024-01-01 INFO request served\n".repeat(2000);
+        let mut noise = Vec::with_capacity(60_000);
+        let mut state = 0x9E37_79B9_u32;
+        for _ in 0..60_000 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            noise.push(state as u8);
+        }
+        std::fs::write(dir.join("app.log"), &text).unwrap();
+        std::fs::write(dir.join("noise.bin"), &noise).unwrap();
+
+        let (text_read, text_compressed) = sample_ratio(&dir.join("app.log")).unwrap();
+        let (noise_read, noise_compressed) = sample_ratio(&dir.join("noise.bin")).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(text_read, text.len() as u64);
+        assert!(text_compressed * 20 < text_read);
+        assert!(noise_compressed * 10 > noise_read * 9);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::action::ActionEvent;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How long after an agent action a size change on the same path is attributed to it.
+pub const ATTRIBUTION_WINDOW: Duration = Duration::from_secs(5 * 60);
+const MAX_ATTRIBUTED_DELTAS: usize = 10_000;
+
+/// A size change observed on disk and the agent action it is attributed to.
+#[derive(Debug, Clone)]
+pub struct AttributedDelta {
+    pub timestamp: SystemTime,
+    pub agent_id: String,
+    pub path: PathBuf,
+    pub bytes: i64,
+}
+
+/// Net size change per agent and directory over a time window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeltaSummary {
+    pub agent_id: String,
+    pub directory: PathBuf,
+    pub bytes_added: u64,
+    pub bytes_removed: u64,
+    pub changes: usize,
+}
+
+/// Attributes observed file size changes to recent agent actions on the same path.
+#[derive(Debug, Default)]
+pub struct SizeCorrelator {
+    /// Last size seen per path, seeded lazily from the scanned tree.
+    known_sizes: HashMap<PathBuf, u64>,
+    recent_actions: HashMap<PathBuf, (String, SystemTime)>,
+    deltas: VecDeque<AttributedDelta>,
+}
+
+impl SizeCorrelator {
+    pub fn record_action(&mut self, event: &ActionEvent) {
+        self.recent_actions.insert(
+            event.target_path.clone(),
+            (event.agent_id.clone(), event.timestamp),
+        );
+    }
+
+    /// Records the new size of `path` (`None` once deleted).
+    ///
+    /// `baseline` is the size from the last scan, used the first time a path
+    /// changes. Returns the delta when it can be attributed to an agent.
+    pub fn observe_size(
+        &mut self,
+        path: &Path,
+        new_size: Option<u64>,
+        baseline: Option<u64>,
+        now: SystemTime,
+    ) -> Option<AttributedDelta> {
+        let previous = self
+            .known_sizes
+            .get(path)
+            .copied()
+            .or(baseline)
+            .unwrap_or(0);
+        let current = new_size.unwrap_or(0);
+        match new_size {
+            Some(size) => self.known_sizes.insert(path.to_path_buf(), size),
+            None => self.known_sizes.remove(path),
+        };
+
+        let bytes = current as i64 - previous as i64;
+        if bytes == 0 {
+            return None;
+        }
+
+        let (agent_id, acted_at) = self.recent_actions.get(path)?;
+        let age = now.duration_since(*acted_at).unwrap_or(Duration::ZERO);
+        if age > ATTRIBUTION_WINDOW {
+            return None;
+        }
+
+        let delta = AttributedDelta {
+            timestamp: now,
+            agent_id: agent_id.clone(),
+            path: path.to_path_buf(),
+            bytes,
+        };
+        if self.deltas.len() >= MAX_ATTRIBUTED_DELTAS {
+            self.deltas.pop_front();
+        }
+        self.deltas.push_back(delta.clone());
+        Some(delta)
+    }
+
+    /// Groups deltas newer than `window` by agent and the top-level directory
+    /// under `root_path`, largest net change first.
+    pub fn summaries(
+        &self,
+        root_path: &Path,
+        now: SystemTime,
+        window: Duration,
+    ) -> Vec<DeltaSummary> {
+        let mut grouped: HashMap<(String, PathBuf), DeltaSummary> = HashMap::new();
+
+        for delta in self.deltas.iter().rev() {
+            let age = now
+                .duration_since(delta.timestamp)
+                .unwrap_or(Duration::ZERO);
+            if age > window {
+                break;
+            }
+
+            let directory = top_level_directory(root_path, &delta.path);
+            let summary = grouped
+                .entry((delta.agent_id.clone(), directory.clone()))
+                .or_insert_with(|| DeltaSummary {
+                    agent_id: delta.agent_id.clone(),
+                    directory,
+                    bytes_added: 0,
+                    bytes_removed: 0,
+                    changes: 0,
+                });
+            if delta.bytes >= 0 {
+                summary.bytes_added = summary.bytes_added.saturating_add(delta.bytes as u64);
+            } else {
+                summary.bytes_removed = summary
+                    .bytes_removed
+                    .saturating_add(delta.bytes.unsigned_abs());
+            }
+            summary.changes += 1;
+        }
+
+        let mut summaries: Vec<DeltaSummary> = grouped.into_values().collect();
+        summaries.sort_by_key(|summary| {
+            std::cmp::Reverse(summary.bytes_added.abs_diff(summary.bytes_removed))
+        });
+        summaries
+    }
+
+    /// Drops actions too old to attribute anything to.
+    pub fn prune(&mut self, now: SystemTime) {
+        self.recent_actions.retain(|_, (_, acted_at)| {
+            now.duration_since(*acted_at).unwrap_or(Duration::ZERO) <= ATTRIBUTION_WINDOW
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.known_sizes.clear();
+        self.recent_actions.clear();
+        self.deltas.clear();
+    }
+}
+
+/// First path component of `path` below `root_path`, or the root itself for
+/// files directly inside it.
+fn top_level_directory(root_path: &Path, path: &Path) -> PathBuf {
+    let Ok(relative_path) = path.strip_prefix(root_path) else {
+        return path.parent().map(Path::to_path_buf).unwrap_or_default();
+    };
+
+    let mut components = relative_path.components();
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => root_path.join(first),
+        _ => root_path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::ActionKind;
+
+    #[test]
+    fn attributes_size_changes_to_recent_agent_actions() {
+        let root = PathBuf::from("/root");
+        let log = root.join("logs").join("app.log");
+        let other = root.join("src").join("main.rs");
+        let now = SystemTime::now();
+        let mut correlator = SizeCorrelator::default();
+
+        let mut action = ActionEvent::new(ActionKind::Write, log.clone());
+        action.timestamp = now - Duration::from_secs(10);
+        correlator.record_action(&action);
+
+        let delta = correlator
+            .observe_size(&log, Some(1_500), Some(500), now)
+            .unwrap();
+        assert_eq!(delta.bytes, 1_000);
+        assert_eq!(delta.agent_id, "openclaw");
+        assert!(correlator
+            .observe_size(&other, Some(10), Some(0), now)
+            .is_none());
+
+        correlator.observe_size(&log, Some(1_200), None, now);
+
+        let summaries = correlator.summaries(&root, now, ATTRIBUTION_WINDOW);
+        assert_eq!(
+            summaries,
+            vec![DeltaSummary {
+                agent_id: "openclaw".to_string(),
+                directory: root.join("logs"),
+                bytes_added: 1_000,
+                bytes_removed: 300,
+                changes: 2,
+            }]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Exclusion rules the scanner skips, one pattern per line, saved per scan
+//! root in `exclusions.json` in the config directory.
+//!
+//! Patterns follow the sensitive-path rules: a path without glob characters
+//! excludes itself and everything beneath it, `~` is the home directory, and
+//! relative patterns match anywhere (`node_modules` is `**/node_modules`).
+
+use crate::model::Node;
+use crate::portable::{config_dir, home_dir};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Example paths kept per rule in a preview.
+const PREVIEW_EXAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Default)]
+pub struct ExcludeRules {
+    patterns: Vec<String>,
+    set: Option<GlobSet>,
+    /// Maps each compiled glob back to its index in `patterns`.
+    glob_owners: Vec<usize>,
+}
+
+/// What one rule would exclude from a scanned tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RulePreview {
+    pub pattern: String,
+    pub files: u64,
+    pub bytes: u64,
+    pub examples: Vec<PathBuf>,
+}
+
+impl ExcludeRules {
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut rules = Self::default();
+        let mut errors = Vec::new();
+        let mut builder = GlobSetBuilder::new();
+
+        for line in text.lines() {
+            let pattern = line.trim();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+
+            let compiled: Result<Vec<_>, _> = expand_pattern(pattern)
+                .iter()
+                .map(|glob_text| {
+                    GlobBuilder::new(glob_text)
+                        .case_insensitive(cfg!(windows))
+                        .literal_separator(true)
+                        .build()
+                })
+                .collect();
+            match compiled {
+                Ok(globs) => {
+                    let owner = rules.patterns.len();
+                    rules.patterns.push(pattern.to_string());
+                    for glob in globs {
+                        builder.add(glob);
+                        rules.glob_owners.push(owner);
+                    }
+                }
+                Err(error) => errors.push(format!("{pattern}: {error}")),
+            }
+        }
+
+        match builder.build() {
+            Ok(set) => rules.set = Some(set),
+            Err(error) => errors.push(error.to_string()),
+        }
+        (rules, errors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.set
+            .as_ref()
+            .is_some_and(|set| set.is_match(normalize(path).as_str()))
+    }
+
+    /// What each rule would exclude from `root`, plus the total (entries
+    /// matched by several rules count once in the total).
+    pub fn preview(&self, root: &Node) -> (Vec<RulePreview>, RulePreview) {
+        let mut previews: Vec<RulePreview> = self
+            .patterns
+            .iter()
+            .map(|pattern| RulePreview {
+                pattern: pattern.clone(),
+                ..RulePreview::default()
+            })
+            .collect();
+        let mut total = RulePreview::default();
+        if let Some(set) = &self.set {
+            for child in &root.children {
+                self.preview_node(set, child, &mut previews, &mut total);
+            }
+        }
+        (previews, total)
+    }
+
+    fn preview_node(
+        &self,
+        set: &GlobSet,
+        node: &Node,
+        previews: &mut [RulePreview],
+        total: &mut RulePreview,
+    ) {
+        let mut owners: Vec<usize> = set
+            .matches(normalize(&node.path).as_str())
+            .into_iter()
+            .map(|index| self.glob_owners[index])
+            .collect();
+        if owners.is_empty() {
+            for child in &node.children {
+                self.preview_node(set, child, previews, total);
+            }
+            return;
+        }
+        owners.dedup();
+        let files = count_files(node);
+        for owner in owners {
+            previews[owner].add(node, files);
+        }
+        total.add(node, files);
+    }
+}
+
+impl RulePreview {
+    fn add(&mut self, node: &Node, files: u64) {
+        self.files += files;
+        self.bytes += node.size;
+        if self.examples.len() < PREVIEW_EXAMPLES {
+            self.examples.push(node.path.clone());
+        }
+    }
+}
+
+fn count_files(node: &Node) -> u64 {
+    if node.children.is_empty() {
+        return 1;
+    }
+    node.children.iter().map(count_files).sum()
+}
+
+fn normalize(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Globs for one rule line; see the module docs.
+pub fn expand_pattern(pattern: &str) -> Vec<String> {
+    let mut expanded = pattern.replace('\\', "/");
+
+    if expanded == "~" || expanded.starts_with("~/") {
+        if let Some(home) = home_dir() {
+            let home = home.to_string_lossy().replace('\\', "/");
+            expanded = format!("{}{}", home.trim_end_matches('/'), &expanded[1..]);
+        }
+    }
+
+    let is_anchored = expanded.starts_with('/')
+        || expanded.starts_with("**")
+        || expanded.as_bytes().get(1) == Some(&b':');
+    if !is_anchored {
+        expanded = format!("**/{expanded}");
+    }
+
+    let expanded = expanded.trim_end_matches('/').to_string();
+    let has_glob = expanded
+        .trim_start_matches("**/")
+        .contains(['*', '?', '[', '{']);
+    if has_glob {
+        vec![expanded]
+    } else {
+        vec![format!("{expanded}/**"), expanded]
+    }
+}
+
+pub fn exclusions_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("exclusions.json"))
+}
+
+fn load_all() -> BTreeMap<PathBuf, String> {
+    exclusions_path()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+/// The rules saved for `root`, or an empty string.
+pub fn load_exclusions(root: &Path) -> String {
+    load_all().remove(root).unwrap_or_default()
+}
+
+/// Saves the rules for `root`, keeping every other root's rules.
+pub fn save_exclusions(root: &Path, text: &str) -> Result<(), String> {
+    let path = exclusions_path().ok_or("No config directory")?;
+    let mut all = load_all();
+    if text.trim().is_empty() {
+        all.remove(root);
+    } else {
+        all.insert(root.to_path_buf(), text.to_string());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Could not create {}: {error}", parent.display()))?;
+    }
+    let json = serde_json::to_vec_pretty(&all).map_err(|error| error.to_string())?;
+    fs::write(&path, json).map_err(|error| format!("Could not write {}: {error}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_what_each_rule_would_exclude() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        for (path, size) in [
+            ("app/node_modules/a/index.js", 100),
+            ("app/node_modules/b.js", 50),
+            ("app/src/main.js", 10),
+            ("build/out.o", 1000),
+            ("build/node_modules/c.js", 5),
+            ("notes.tmp", 1),
+        ] {
+            root.insert_relative(Path::new(path), size);
+        }
+        root.compute_total_size();
+
+        let (rules, errors) = ExcludeRules::parse("node_modules\n/r/build\n*.tmp\n[bad\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(rules.len(), 3);
+        assert!(rules.is_excluded(Path::new("/r/app/node_modules/a/index.js")));
+        assert!(!rules.is_excluded(Path::new("/r/app/src/main.js")));
+
+        let (previews, total) = rules.preview(&root);
+        let summary: Vec<(u64, u64)> = previews
+            .iter()
+            .map(|preview| (preview.files, preview.bytes))
+            .collect();
+        // build/node_modules sits inside the excluded build folder.
+        assert_eq!(summary, vec![(2, 150), (2, 1005), (1, 1)]);
+        assert_eq!((total.files, total.bytes), (5, 1156));
+        assert_eq!(previews[1].examples, vec![PathBuf::from("/r/build")]);
+    }
+}
@@ -0,0 +1,903 @@
+//! Tree exports for other tools. Sizes are always written as exact byte
+//! counts; names and paths come from a labelling function so demo mode can
+//! mask them.
+
+use crate::analysis::{file_type_key, TypeStat};
+use crate::category::CategoryStat;
+use crate::format::{exact_bytes, human_size, percent, Language};
+use crate::model::Node;
+use crate::scanner::ScanResult;
+use arrow_array::builder::{
+    BooleanBuilder, StringBuilder, TimestampSecondBuilder, UInt32Builder, UInt64Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use rusqlite::{params, Connection, Transaction};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Displayed name and path of a node in an export.
+pub struct NodeLabel {
+    pub name: String,
+    pub path: String,
+}
+
+/// Pruning for exports meant to be read by people or graph tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Levels below the root to include; `None` for the whole tree.
+    pub max_depth: Option<usize>,
+    /// Entries smaller than this are folded into one summary entry.
+    pub min_size: u64,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(3),
+            min_size: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeExport {
+    /// Folder hierarchy with sizes and counts, in the XML layout of
+    /// TreeSize reports.
+    TreeSizeXml,
+    /// Graphviz digraph, pruned by [`ExportOptions`].
+    Dot,
+    /// SQLite database with `nodes`, `stats`, `warnings` and
+    /// `warning_counts` tables.
+    Sqlite,
+    /// One zstd-compressed Parquet row per file and folder.
+    Parquet,
+    /// Single HTML page with the tree embedded and a zoomable treemap,
+    /// pruned by [`ExportOptions`].
+    Html,
+}
+
+impl TreeExport {
+    pub const ALL: [TreeExport; 5] = [
+        TreeExport::TreeSizeXml,
+        TreeExport::Dot,
+        TreeExport::Sqlite,
+        TreeExport::Parquet,
+        TreeExport::Html,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TreeExport::TreeSizeXml => "TreeSize XML",
+            TreeExport::Dot => "Graphviz DOT",
+            TreeExport::Sqlite => "SQLite",
+            TreeExport::Parquet => "Parquet",
+            TreeExport::Html => "HTML report",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            TreeExport::TreeSizeXml => "xml",
+            TreeExport::Dot => "dot",
+            TreeExport::Sqlite => "sqlite",
+            TreeExport::Parquet => "parquet",
+            TreeExport::Html => "html",
+        }
+    }
+
+    /// Whether [`ExportOptions`] apply to this format.
+    pub fn is_pruned(self) -> bool {
+        matches!(self, TreeExport::Dot | TreeExport::Html)
+    }
+
+    pub fn default_file_name(self) -> String {
+        format!("scan.{}", self.extension())
+    }
+
+    /// `warning_messages` is off for masked exports, since the messages
+    /// carry raw paths; only the per-category counts are written then.
+    pub fn write_file(
+        self,
+        path: &Path,
+        scan: &ScanResult,
+        options: &ExportOptions,
+        labels: &dyn Fn(&Node) -> NodeLabel,
+        warning_messages: bool,
+    ) -> Result<(), String> {
+        if self == TreeExport::Sqlite {
+            return write_sqlite(path, scan, labels, warning_messages)
+                .map_err(|error| format!("Could not write {}: {error}", path.display()));
+        }
+        let file = File::create(path)
+            .map_err(|error| format!("Could not create {}: {error}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        match self {
+            TreeExport::TreeSizeXml => write_treesize_xml(&mut writer, scan, labels),
+            TreeExport::Dot => write_dot(&mut writer, &scan.root, options, labels),
+            TreeExport::Parquet => write_parquet(&mut writer, &scan.root, labels),
+            TreeExport::Html => write_html_report(&mut writer, &scan.root, options, labels),
+            TreeExport::Sqlite => unreachable!("written through a connection above"),
+        }
+        .and_then(|()| writer.flush())
+        .map_err(|error| format!("Could not write {}: {error}", path.display()))
+    }
+}
+
+/// Writes folders only (files are summarized in each folder's counts), as
+/// TreeSize does:
+///
+/// ```xml
+/// <folder name="logs" path="/srv/logs" size="300" allocated="300"
+///         files="2" folders="0" ownsize="300">
+/// ```
+pub fn write_treesize_xml(
+    writer: &mut dyn Write,
+    scan: &ScanResult,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<treesize generator="tree-map-base" files="{}" folders="{}" size="{}">"#,
+        scan.stats.files_scanned, scan.stats.directories_scanned, scan.root.size
+    )?;
+    write_treesize_folder(writer, &scan.root, labels, 1)?;
+    writeln!(writer, "</treesize>")
+}
+
+fn write_treesize_folder(
+    writer: &mut dyn Write,
+    node: &Node,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+    indent: usize,
+) -> io::Result<()> {
+    let label = labels(node);
+    let (files, folders) = count_entries(node);
+    let subfolders: Vec<&Node> = node
+        .children
+        .iter()
+        .filter(|child| !child.children.is_empty())
+        .collect();
+
+    write!(
+        writer,
+        r#"{:indent$}<folder name="{}" path="{}" size="{}" allocated="{}" files="{files}" folders="{folders}" ownsize="{}""#,
+        "",
+        xml_escape(&label.name),
+        xml_escape(&label.path),
+        node.size,
+        node.size,
+        node.own_size,
+        indent = indent * 2
+    )?;
+    if subfolders.is_empty() {
+        return writeln!(writer, "/>");
+    }
+
+    writeln!(writer, ">")?;
+    for child in subfolders {
+        write_treesize_folder(writer, child, labels, indent + 1)?;
+    }
+    writeln!(writer, "{:indent$}</folder>", "", indent = indent * 2)
+}
+
+/// Writes a left-to-right digraph; each label carries the entry's size and
+/// the fill darkens with its share of the root.
+pub fn write_dot(
+    writer: &mut dyn Write,
+    root: &Node,
+    options: &ExportOptions,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+) -> io::Result<()> {
+    writeln!(writer, "digraph tree {{")?;
+    writeln!(writer, "  graph [rankdir=LR];")?;
+    writeln!(
+        writer,
+        r#"  node [shape=box, style="rounded,filled", fontname="Helvetica"];"#
+    )?;
+    let mut next_id = 0_usize;
+    write_dot_node(
+        writer,
+        root,
+        root.size.max(1),
+        options,
+        labels,
+        0,
+        &mut next_id,
+    )?;
+    writeln!(writer, "}}")
+}
+
+fn write_dot_node(
+    writer: &mut dyn Write,
+    node: &Node,
+    root_size: u64,
+    options: &ExportOptions,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+    depth: usize,
+    next_id: &mut usize,
+) -> io::Result<usize> {
+    let id = *next_id;
+    *next_id += 1;
+    let shape = if node.children.is_empty() {
+        ", shape=note"
+    } else {
+        ""
+    };
+    writeln!(
+        writer,
+        r#"  n{id} [label="{}\n{}", fillcolor="{}"{shape}];"#,
+        dot_escape(&labels(node).name),
+        human_size(node.size, Language::English),
+        share_color(node.size, root_size)
+    )?;
+
+    if options
+        .max_depth
+        .is_some_and(|max_depth| depth >= max_depth)
+    {
+        return Ok(id);
+    }
+
+    let (mut folded_count, mut folded_size) = (0_usize, 0_u64);
+    for child in &node.children {
+        if child.size < options.min_size {
+            folded_count += 1;
+            folded_size = folded_size.saturating_add(child.size);
+            continue;
+        }
+        let child_id = write_dot_node(
+            writer,
+            child,
+            root_size,
+            options,
+            labels,
+            depth + 1,
+            next_id,
+        )?;
+        writeln!(writer, "  n{id} -> n{child_id};")?;
+    }
+    if folded_count > 0 {
+        let folded_id = *next_id;
+        *next_id += 1;
+        writeln!(
+            writer,
+            r#"  n{folded_id} [label="({folded_count} smaller)\n{}", fillcolor="{}", style="dashed,filled"];"#,
+            human_size(folded_size, Language::English),
+            share_color(folded_size, root_size)
+        )?;
+        writeln!(writer, "  n{id} -> n{folded_id};")?;
+    }
+    Ok(id)
+}
+
+/// Fills `report.html` with the pruned tree as JSON (`n` name, `s` size,
+/// `c` children). `<` is escaped so names cannot close the script element.
+pub fn write_html_report(
+    writer: &mut dyn Write,
+    root: &Node,
+    options: &ExportOptions,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+) -> io::Result<()> {
+    let tree = report_json(root, options, labels, 0)
+        .to_string()
+        .replace('<', "\\u003c");
+    let title = xml_escape(&format!("Disk usage: {}", labels(root).path));
+    let page = include_str!("report.html")
+        .replace("{{TITLE}}", &title)
+        .replace("{{TREE}}", &tree);
+    writer.write_all(page.as_bytes())
+}
+
+fn report_json(
+    node: &Node,
+    options: &ExportOptions,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+    depth: usize,
+) -> Value {
+    let name = labels(node).name;
+    if node.children.is_empty()
+        || options
+            .max_depth
+            .is_some_and(|max_depth| depth >= max_depth)
+    {
+        return json!({ "n": name, "s": node.size });
+    }
+
+    let (mut folded_count, mut folded_size) = (0_usize, 0_u64);
+    let mut children: Vec<Value> = Vec::new();
+    for child in &node.children {
+        if child.size < options.min_size {
+            folded_count += 1;
+            folded_size = folded_size.saturating_add(child.size);
+        } else {
+            children.push(report_json(child, options, labels, depth + 1));
+        }
+    }
+    if folded_count > 0 {
+        children.push(json!({ "n": format!("({folded_count} smaller)"), "s": folded_size }));
+    }
+    json!({ "n": name, "s": node.size, "c": children })
+}
+
+/// Light to dark blue by the square root of the entry's share of the root.
+fn share_color(size: u64, root_size: u64) -> String {
+    const LIGHT: [f64; 3] = [232.0, 241.0, 251.0];
+    const DARK: [f64; 3] = [66.0, 128.0, 196.0];
+    let t = (size as f64 / root_size as f64).clamp(0.0, 1.0).sqrt();
+    let [r, g, b] =
+        [0, 1, 2].map(|index| (LIGHT[index] + (DARK[index] - LIGHT[index]) * t).round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE nodes (
+    id INTEGER PRIMARY KEY,
+    parent_id INTEGER REFERENCES nodes(id),
+    name TEXT NOT NULL,
+    path TEXT NOT NULL,
+    is_dir INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    own_size INTEGER NOT NULL,
+    type TEXT,
+    modified INTEGER
+);
+CREATE INDEX nodes_parent ON nodes(parent_id);
+CREATE TABLE stats (key TEXT PRIMARY KEY, value);
+CREATE TABLE warnings (id INTEGER PRIMARY KEY, message TEXT NOT NULL);
+CREATE TABLE warning_counts (category TEXT PRIMARY KEY, count INTEGER NOT NULL);
+";
+
+/// Writes a fresh database at `path`, replacing any existing file. Node ids
+/// follow a depth-first walk, so the root is always 1. `type` is the
+/// lowercase extension for files and NULL for folders; `modified` is the
+/// scanned modification time in Unix seconds, NULL when unknown and for
+/// folders. The `warnings` table stays empty unless `warning_messages` is set.
+pub fn write_sqlite(
+    path: &Path,
+    scan: &ScanResult,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+    warning_messages: bool,
+) -> rusqlite::Result<()> {
+    // A leftover database would make CREATE TABLE fail halfway through.
+    let _ = fs::remove_file(path);
+    let mut connection = Connection::open(path)?;
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(SQLITE_SCHEMA)?;
+
+    insert_sqlite_node(&transaction, &scan.root, None, labels, &mut 0)?;
+
+    let stats = &scan.stats;
+    let mut insert_stat = transaction.prepare("INSERT INTO stats (key, value) VALUES (?1, ?2)")?;
+    insert_stat.execute(params!["root", labels(&scan.root).path])?;
+    insert_stat.execute(params!["entries_scanned", stats.entries_scanned])?;
+    insert_stat.execute(params!["files_scanned", stats.files_scanned])?;
+    insert_stat.execute(params!["directories_scanned", stats.directories_scanned])?;
+    insert_stat.execute(params!["warnings", stats.warnings])?;
+    insert_stat.execute(params!["truncated", stats.truncated])?;
+    insert_stat.execute(params!["elapsed_ms", stats.elapsed.as_millis() as u64])?;
+    drop(insert_stat);
+
+    let mut insert_warning = transaction.prepare("INSERT INTO warnings (message) VALUES (?1)")?;
+    for warning in scan.warnings.iter().filter(|_| warning_messages) {
+        insert_warning.execute([warning])?;
+    }
+    drop(insert_warning);
+
+    let mut insert_count =
+        transaction.prepare("INSERT INTO warning_counts (category, count) VALUES (?1, ?2)")?;
+    for (category, count) in &scan.warning_counts {
+        insert_count.execute(params![category.label(), count])?;
+    }
+    drop(insert_count);
+
+    transaction.commit()
+}
+
+fn insert_sqlite_node(
+    transaction: &Transaction,
+    node: &Node,
+    parent_id: Option<i64>,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+    next_id: &mut i64,
+) -> rusqlite::Result<()> {
+    *next_id += 1;
+    let id = *next_id;
+    let is_dir = !node.children.is_empty() || parent_id.is_none();
+    let modified = modified_unix_secs(node);
+    let label = labels(node);
+    transaction
+        .prepare_cached(
+            "INSERT INTO nodes (id, parent_id, name, path, is_dir, size, own_size, type, modified)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?
+        .execute(params![
+            id,
+            parent_id,
+            label.name,
+            label.path,
+            is_dir,
+            node.size,
+            node.own_size,
+            (!is_dir).then(|| file_type_key(&node.path)),
+            modified,
+        ])?;
+    for child in &node.children {
+        insert_sqlite_node(transaction, child, Some(id), labels, next_id)?;
+    }
+    Ok(())
+}
+
+/// Rows per record batch, so multi-million-entry trees are never held as
+/// one set of columns.
+const PARQUET_BATCH_ROWS: usize = 64 * 1024;
+
+fn parquet_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new("parent", DataType::Utf8, true),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("is_dir", DataType::Boolean, false),
+        Field::new("size", DataType::UInt64, false),
+        Field::new("extension", DataType::Utf8, true),
+        Field::new("depth", DataType::UInt32, false),
+        Field::new(
+            "mtime",
+            DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+            true,
+        ),
+    ]))
+}
+
+#[derive(Default)]
+struct ParquetColumns {
+    path: StringBuilder,
+    parent: StringBuilder,
+    name: StringBuilder,
+    is_dir: BooleanBuilder,
+    size: UInt64Builder,
+    extension: StringBuilder,
+    depth: UInt32Builder,
+    mtime: TimestampSecondBuilder,
+    rows: usize,
+}
+
+impl ParquetColumns {
+    fn finish(&mut self, schema: &SchemaRef) -> Result<RecordBatch, arrow_schema::ArrowError> {
+        self.rows = 0;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.path.finish()),
+            Arc::new(self.parent.finish()),
+            Arc::new(self.name.finish()),
+            Arc::new(self.is_dir.finish()),
+            Arc::new(self.size.finish()),
+            Arc::new(self.extension.finish()),
+            Arc::new(self.depth.finish()),
+            Arc::new(self.mtime.finish().with_timezone("UTC")),
+        ];
+        RecordBatch::try_new(schema.clone(), columns)
+    }
+}
+
+/// Writes every file and folder depth-first; `parent` is the labelled path
+/// of the containing folder (null for the root) and `mtime` is the scanned
+/// modification time (null when unknown and for folders).
+pub fn write_parquet<W: Write + Send>(
+    writer: W,
+    root: &Node,
+    labels: &dyn Fn(&Node) -> NodeLabel,
+) -> io::Result<()> {
+    let schema = parquet_schema();
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(ZstdLevel::default()))
+        .build();
+    let mut writer =
+        ArrowWriter::try_new(writer, schema.clone(), Some(properties)).map_err(io::Error::other)?;
+    let mut columns = ParquetColumns::default();
+
+    let mut stack: Vec<(&Node, Option<Arc<str>>, u32)> = vec![(root, None, 0)];
+    while let Some((node, parent, depth)) = stack.pop() {
+        let label = labels(node);
+        let is_dir = !node.children.is_empty() || depth == 0;
+        columns.path.append_value(&label.path);
+        columns.parent.append_option(parent.as_deref());
+        columns.name.append_value(&label.name);
+        columns.is_dir.append_value(is_dir);
+        columns.size.append_value(node.size);
+        columns
+            .extension
+            .append_option((!is_dir).then(|| file_type_key(&node.path)));
+        columns.depth.append_value(depth);
+        columns.mtime.append_option(modified_unix_secs(node));
+        columns.rows += 1;
+
+        let path: Arc<str> = label.path.into();
+        for child in node.children.iter().rev() {
+            stack.push((child, Some(path.clone()), depth + 1));
+        }
+
+        if columns.rows >= PARQUET_BATCH_ROWS {
+            let batch = columns.finish(&schema).map_err(io::Error::other)?;
+            writer.write(&batch).map_err(io::Error::other)?;
+        }
+    }
+    if columns.rows > 0 {
+        let batch = columns.finish(&schema).map_err(io::Error::other)?;
+        writer.write(&batch).map_err(io::Error::other)?;
+    }
+    writer.close().map_err(io::Error::other)?;
+    Ok(())
+}
+
+fn modified_unix_secs(node: &Node) -> Option<i64> {
+    (node.modified > 0).then_some(node.modified as i64)
+}
+
+/// GitHub-flavoured Markdown overview: totals, the 20 largest files, the 10
+/// largest folders, the type breakdown and warning counts by category.
+/// Warning messages themselves are left out since they carry raw paths.
+pub fn markdown_summary(
+    scan: &ScanResult,
+    type_stats: &[TypeStat],
+    categories: &[CategoryStat],
+    labels: &dyn Fn(&Node) -> NodeLabel,
+) -> String {
+    let root = &scan.root;
+    let stats = &scan.stats;
+    let size = |bytes: u64| human_size(bytes, Language::English);
+    let share = |bytes: u64| percent(bytes as f64 / root.size.max(1) as f64);
+    let cell = |text: &str| text.replace('|', "\\|");
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Disk usage: `{}`\n", labels(root).path);
+    let _ = writeln!(
+        out,
+        "- **Total size:** {} ({})",
+        size(root.size),
+        exact_bytes(root.size, Language::English)
+    );
+    let _ = writeln!(out, "- **Files:** {}", stats.files_scanned);
+    let _ = writeln!(out, "- **Folders:** {}", stats.directories_scanned);
+    if stats.truncated {
+        let _ = writeln!(
+            out,
+            "- **Note:** the scan hit its entry limit, so totals are partial"
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "\n## Largest files\n\n| File | Size | Share |\n| --- | ---: | ---: |"
+    );
+    for node in root.largest(20, false) {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} |",
+            cell(&labels(node).path),
+            size(node.size),
+            share(node.size)
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "\n## Largest folders\n\n| Folder | Size | Share |\n| --- | ---: | ---: |"
+    );
+    for node in root.largest(10, true) {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} |",
+            cell(&labels(node).path),
+            size(node.size),
+            share(node.size)
+        );
+    }
+
+    if !categories.is_empty() {
+        let _ = writeln!(
+            out,
+            "\n## Categories\n\n| Category | Files | Size | Share |\n| --- | ---: | ---: | ---: |"
+        );
+        for stat in categories {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                cell(&stat.name),
+                stat.files,
+                size(stat.bytes),
+                share(stat.bytes)
+            );
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "\n## File types\n\n| Type | Files | Size | Share |\n| --- | ---: | ---: | ---: |"
+    );
+    for stat in type_stats {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            cell(&stat.key),
+            stat.files,
+            size(stat.bytes),
+            share(stat.bytes)
+        );
+    }
+
+    let _ = writeln!(out, "\n## Warnings\n");
+    if stats.warnings == 0 {
+        let _ = writeln!(out, "None.");
+    } else {
+        let _ = writeln!(out, "{} in total:\n", stats.warnings);
+        for (category, count) in &scan.warning_counts {
+            let _ = writeln!(out, "- {}: {count}", category.label());
+        }
+    }
+    out
+}
+
+/// Files and folders anywhere below `node`.
+fn count_entries(node: &Node) -> (u64, u64) {
+    node.children
+        .iter()
+        .fold((0, 0), |(files, folders), child| {
+            if child.children.is_empty() {
+                (files + 1, folders)
+            } else {
+                let (child_files, child_folders) = count_entries(child);
+                (files + child_files, folders + child_folders + 1)
+            }
+        })
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::ScanStats;
+    use std::path::PathBuf;
+
+    #[test]
+    fn treesize_xml_nests_folders_with_counts() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("a&b/x.bin"), 10);
+        root.insert_relative(Path::new("a&b/deep/y.bin"), 5);
+        root.insert_relative(Path::new("top.txt"), 1);
+        root.compute_total_size();
+        let scan = ScanResult {
+            root,
+            stats: ScanStats::default(),
+            warnings: Vec::new(),
+            warning_counts: Vec::new(),
+            permission_findings: Vec::new(),
+            symlinks: Vec::new(),
+            owners: Vec::new(),
+        };
+
+        let mut output = Vec::new();
+        let labels = |node: &Node| NodeLabel {
+            name: node.name.clone(),
+            path: node.path.display().to_string(),
+        };
+        write_treesize_xml(&mut output, &scan, &labels).unwrap();
+        let xml = String::from_utf8(output).unwrap();
+
+        assert!(xml.contains(r#"<folder name="r" path="/r" size="16" allocated="16" files="3" folders="2" ownsize="1">"#));
+        assert!(xml.contains(r#"<folder name="a&amp;b" path="/r/a&amp;b" size="15""#));
+        assert!(xml.contains(r#"<folder name="deep" path="/r/a&amp;b/deep" size="5" allocated="5" files="1" folders="0" ownsize="5"/>"#));
+        assert!(roxmltree::Document::parse(&xml).is_ok());
+    }
+
+    #[test]
+    fn dot_prunes_by_depth_and_size() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("big/inner/x.bin"), 1000);
+        root.insert_relative(Path::new("tiny1.txt"), 1);
+        root.insert_relative(Path::new("tiny2.txt"), 2);
+        root.compute_total_size();
+        root.sort_children_by_size_desc();
+
+        let mut output = Vec::new();
+        let options = ExportOptions {
+            max_depth: Some(2),
+            min_size: 10,
+        };
+        let labels = |node: &Node| NodeLabel {
+            name: format!("say \"{}\"", node.name),
+            path: String::new(),
+        };
+        write_dot(&mut output, &root, &options, &labels).unwrap();
+        let dot = String::from_utf8(output).unwrap();
+
+        assert!(dot.contains(r##"n0 [label="say \"r\"\n1003 B", fillcolor="#4280c4"];"##));
+        assert!(dot.contains(r#"label="say \"inner\"\n1000 B""#));
+        assert!(!dot.contains("x.bin"));
+        assert!(dot.contains(r#"label="(2 smaller)\n3 B""#));
+        assert_eq!(dot.matches("->").count(), 3);
+    }
+
+    #[test]
+    fn html_report_embeds_escaped_tree() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("evil/x.bin"), 10);
+        root.compute_total_size();
+
+        let mut output = Vec::new();
+        let labels = |node: &Node| NodeLabel {
+            name: node.name.replace("evil", "</script>"),
+            path: node.path.display().to_string(),
+        };
+        write_html_report(&mut output, &root, &ExportOptions::default(), &labels).unwrap();
+        let html = String::from_utf8(output).unwrap();
+
+        assert!(html.contains(r#"const TREE = {"c":[{"c":[{"n":"x.bin","s":10}],"n":"\u003c/script>","s":10}],"n":"r","s":10};"#));
+        assert_eq!(html.matches("</script>").count(), 1);
+        assert!(!html.contains("{{"));
+    }
+
+    #[test]
+    fn markdown_summary_lists_tables() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("a|b/x.bin"), 30);
+        root.insert_relative(Path::new("top.txt"), 10);
+        root.compute_total_size();
+        let scan = ScanResult {
+            root,
+            stats: ScanStats::default(),
+            warnings: Vec::new(),
+            warning_counts: Vec::new(),
+            permission_findings: Vec::new(),
+            symlinks: Vec::new(),
+            owners: Vec::new(),
+        };
+        let labels = |node: &Node| NodeLabel {
+            name: node.name.clone(),
+            path: node.path.display().to_string(),
+        };
+
+        let markdown = markdown_summary(&scan, &[], &[], &labels);
+
+        assert!(markdown.contains("- **Total size:** 40 B (40 bytes)"));
+        assert!(markdown.contains("| /r/a\\|b/x.bin | 30 B | 75% |\n| /r/top.txt | 10 B | 25% |"));
+        assert!(markdown.contains("## Largest folders\n\n| Folder | Size | Share |\n| --- | ---: | ---: |\n| /r/a\\|b | 30 B | 75% |\n\n"));
+        assert!(markdown.ends_with("## Warnings\n\nNone.\n"));
+    }
+
+    #[test]
+    fn sqlite_links_nodes_to_parents() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("a/x.BIN"), 10)
+            .unwrap()
+            .modified = 1_700_000_000;
+        root.insert_relative(Path::new("top.txt"), 1);
+        root.compute_total_size();
+        let scan = ScanResult {
+            root,
+            stats: ScanStats::default(),
+            warnings: vec!["denied".to_string()],
+            warning_counts: Vec::new(),
+            permission_findings: Vec::new(),
+            symlinks: Vec::new(),
+            owners: Vec::new(),
+        };
+        let path = std::env::temp_dir().join(format!(
+            "tree-map-base-export-{}.sqlite",
+            std::process::id()
+        ));
+        let labels = |node: &Node| NodeLabel {
+            name: node.name.clone(),
+            path: node.path.display().to_string(),
+        };
+        // Masked exports keep the counts but not the messages.
+        write_sqlite(&path, &scan, &labels, false).unwrap();
+        let connection = Connection::open(&path).unwrap();
+        let masked_warnings: i64 = connection
+            .query_row("SELECT COUNT(*) FROM warnings", [], |row| row.get(0))
+            .unwrap();
+        drop(connection);
+        assert_eq!(masked_warnings, 0);
+        // A second export replaces the database instead of failing.
+        write_sqlite(&path, &scan, &labels, true).unwrap();
+
+        let connection = Connection::open(&path).unwrap();
+        let (parent, kind, size, modified): (String, String, i64, Option<i64>) = connection
+            .query_row(
+                "SELECT p.name, c.type, c.size, c.modified FROM nodes c
+                 JOIN nodes p ON c.parent_id = p.id WHERE c.name = 'x.BIN'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        let warnings: i64 = connection
+            .query_row("SELECT COUNT(*) FROM warnings", [], |row| row.get(0))
+            .unwrap();
+        drop(connection);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!((parent.as_str(), kind.as_str(), size), ("a", "bin", 10));
+        // Taken from the scan, not from disk where /r does not exist.
+        assert_eq!(modified, Some(1_700_000_000));
+        assert_eq!(warnings, 1);
+    }
+
+    #[test]
+    fn parquet_rows_carry_parent_and_depth() {
+        use arrow_array::Array;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("a/x.bin"), 10);
+        root.insert_relative(Path::new("top.txt"), 1);
+        root.compute_total_size();
+        root.sort_children_by_size_desc();
+
+        let path = std::env::temp_dir().join(format!(
+            "tree-map-base-export-{}.parquet",
+            std::process::id()
+        ));
+        let labels = |node: &Node| NodeLabel {
+            name: node.name.clone(),
+            path: node.path.display().to_string(),
+        };
+        write_parquet(File::create(&path).unwrap(), &root, &labels).unwrap();
+
+        let batches: Vec<RecordBatch> =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+                .unwrap()
+                .build()
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+        let _ = fs::remove_file(&path);
+        let batch = &batches[0];
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+        let paths = column("path");
+        let paths = paths
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        let parents = column("parent");
+        let parents = parents
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        let depths = column("depth");
+        let depths = depths
+            .as_any()
+            .downcast_ref::<arrow_array::UInt32Array>()
+            .unwrap();
+
+        assert_eq!(batch.num_rows(), 4);
+        assert_eq!(paths.value(2), "/r/a/x.bin");
+        assert_eq!(parents.value(2), "/r/a");
+        assert!(parents.is_null(0));
+        assert_eq!(depths.value(2), 2);
+    }
+}
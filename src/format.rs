@@ -1,21 +1,476 @@
-pub fn human_size(bytes: u64) -> String {
-    const UNITS: [&str; 6] = ["B", "KB", "MB", "GB", "TB", "PB"];
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 
-    if bytes < 1024 {
-        return format!("{bytes} B");
+/// UI language that localized formatting follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    Chinese,
+}
+
+impl Language {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            Self::English => Self::Chinese,
+            Self::Chinese => Self::English,
+        };
+    }
+
+    /// Digit grouping conventional for this language.
+    pub fn count_grouping(self) -> CountGrouping {
+        match self {
+            Self::English => CountGrouping::Thousands(','),
+            Self::Chinese => CountGrouping::Myriad,
+        }
+    }
+
+    fn pick(self, english: &'static str, chinese: &'static str) -> &'static str {
+        match self {
+            Self::English => english,
+            Self::Chinese => chinese,
+        }
+    }
+}
+
+/// Unit convention for byte sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeUnits {
+    /// 1024-based with the traditional "KB"/"MB" labels.
+    #[default]
+    Binary,
+    /// 1024-based with IEC labels ("KiB"/"MiB").
+    Iec,
+    /// 1000-based SI units ("kB"/"MB").
+    Si,
+}
+
+impl SizeUnits {
+    pub const ALL: [SizeUnits; 3] = [SizeUnits::Binary, SizeUnits::Iec, SizeUnits::Si];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeUnits::Binary => "KB (1024)",
+            SizeUnits::Iec => "KiB (IEC)",
+            SizeUnits::Si => "kB (SI)",
+        }
+    }
+}
+
+pub fn human_size(bytes: u64, language: Language) -> String {
+    let byte_label = language.pick("B", "字节");
+    scaled_size(
+        bytes,
+        1024.0,
+        [byte_label, "KB", "MB", "GB", "TB", "PB", "EB"],
+    )
+}
+
+/// 1000-based size, e.g. `1.50 MB` for 1,500,000 bytes.
+pub fn human_size_si(bytes: u64, language: Language) -> String {
+    let byte_label = language.pick("B", "字节");
+    scaled_size(
+        bytes,
+        1000.0,
+        [byte_label, "kB", "MB", "GB", "TB", "PB", "EB"],
+    )
+}
+
+/// 1024-based size with IEC labels, e.g. `1.50 MiB`.
+pub fn human_size_iec(bytes: u64, language: Language) -> String {
+    let byte_label = language.pick("B", "字节");
+    scaled_size(
+        bytes,
+        1024.0,
+        [byte_label, "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"],
+    )
+}
+
+/// Size in the chosen units; plain byte counts read "字节" in Chinese.
+pub fn human_size_in(bytes: u64, units: SizeUnits, language: Language) -> String {
+    match units {
+        SizeUnits::Binary => human_size(bytes, language),
+        SizeUnits::Iec => human_size_iec(bytes, language),
+        SizeUnits::Si => human_size_si(bytes, language),
+    }
+}
+
+/// Unrounded size with digit grouping, e.g. "1,234,567,890 bytes".
+pub fn exact_bytes(bytes: u64, language: Language) -> String {
+    let count = human_count(bytes, language.count_grouping());
+    match language {
+        Language::English if bytes == 1 => "1 byte".to_string(),
+        Language::English => format!("{count} bytes"),
+        Language::Chinese => format!("{count} 字节"),
+    }
+}
+
+/// Bytes per unit in a size literal, case-insensitive. Units follow the size
+/// display: `KB` and `KiB` alike are 1024 bytes; empty and `B` are bytes.
+pub fn size_unit_multiplier(unit: &str) -> Option<u64> {
+    match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => Some(1),
+        "kb" | "kib" => Some(1 << 10),
+        "mb" | "mib" => Some(1 << 20),
+        "gb" | "gib" => Some(1 << 30),
+        "tb" | "tib" => Some(1 << 40),
+        "pb" | "pib" => Some(1 << 50),
+        "eb" | "eib" => Some(1 << 60),
+        _ => None,
+    }
+}
+
+/// Parses a size literal such as `500 GB`, `1.5KiB` or `200`; `None` when
+/// it does not fit in 64 bits.
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier = size_unit_multiplier(unit.trim())?;
+    if let Ok(whole) = number.parse::<u64>() {
+        return whole.checked_mul(multiplier);
+    }
+    let bytes = number.parse::<f64>().ok()? * multiplier as f64;
+    (bytes < u64::MAX as f64).then(|| bytes.round() as u64)
+}
+
+fn scaled_size(bytes: u64, base: f64, units: [&str; 7]) -> String {
+    if (bytes as f64) < base {
+        return format!("{bytes} {}", units[0]);
     }
 
     let mut value = bytes as f64;
     let mut unit_index = 0_usize;
 
-    while value >= 1024.0 && unit_index + 1 < UNITS.len() {
-        value /= 1024.0;
+    while value >= base && unit_index + 1 < units.len() {
+        value /= base;
         unit_index += 1;
     }
 
     if value >= 10.0 {
-        format!("{value:.1} {}", UNITS[unit_index])
+        format!("{value:.1} {}", units[unit_index])
+    } else {
+        format!("{value:.2} {}", units[unit_index])
+    }
+}
+
+/// Share of a whole as a percentage: "<0.1%" for tiny shares, one decimal
+/// below 10% and none above, e.g. "0.4%", "7.3%", "42%".
+pub fn percent(ratio: f64) -> String {
+    let value = ratio * 100.0;
+    if value <= 0.0 {
+        "0%".to_string()
+    } else if value < 0.1 {
+        "<0.1%".to_string()
+    } else if value < 9.95 {
+        format!("{value:.1}%")
     } else {
-        format!("{value:.2} {}", UNITS[unit_index])
+        format!("{value:.0}%")
+    }
+}
+
+/// Short duration: "450 ms" below a second, "12.3s", "2m 05s", then
+/// "1h 02m" from an hour on ("1小时02分" in Chinese).
+pub fn format_duration_compact(duration: Duration, language: Language) -> String {
+    let (ms, h, m, s) = match language {
+        Language::English => (" ms", "h ", "m ", "s"),
+        Language::Chinese => (" 毫秒", "小时", "分", "秒"),
+    };
+
+    if duration < Duration::from_secs(1) {
+        return format!("{}{ms}", duration.as_millis());
+    }
+
+    let total_seconds = duration.as_secs();
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+
+    if hours > 0 {
+        return format!("{hours}{h}{minutes:02}{}", m.trim_end());
+    }
+
+    if minutes > 0 {
+        return format!("{minutes}{m}{seconds:02}{s}");
+    }
+
+    format!("{:.1}{s}", duration.as_secs_f64())
+}
+
+/// Average per-second rate of `amount` over `elapsed`; `None` before any
+/// time has passed.
+pub fn per_second(amount: u64, elapsed: Duration) -> Option<f64> {
+    (!elapsed.is_zero()).then(|| amount as f64 / elapsed.as_secs_f64())
+}
+
+/// Grouped items per second without a unit, e.g. "45,210"; "-" if unknown.
+pub fn count_rate(count: u64, elapsed: Duration, grouping: CountGrouping) -> String {
+    per_second(count, elapsed).map_or_else(
+        || "-".to_string(),
+        |rate| human_count(rate.round() as u64, grouping),
+    )
+}
+
+/// Bytes per second, e.g. "118 MB/s"; "-" if unknown.
+pub fn byte_rate(bytes: u64, elapsed: Duration, units: SizeUnits, language: Language) -> String {
+    per_second(bytes, elapsed).map_or_else(
+        || "-".to_string(),
+        |rate| {
+            format!(
+                "{}{}",
+                human_size_in(rate.round() as u64, units, language),
+                language.pick("/s", "/秒")
+            )
+        },
+    )
+}
+
+/// Extension key from `analysis::file_type_key` as shown to the user.
+pub fn format_type_key(key: &str, language: Language) -> String {
+    if key == "(no_ext)" {
+        return language.pick("(no extension)", "（无扩展名）").to_string();
+    }
+
+    format!(".{key}")
+}
+
+/// Age of `time` relative to now, e.g. "3 minutes ago" or "2 天前".
+pub fn relative_time(time: SystemTime, language: Language) -> String {
+    let age = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or(Duration::ZERO);
+    relative_age(age, language)
+}
+
+fn relative_age(age: Duration, language: Language) -> String {
+    const UNITS: [(u64, &str, &str); 6] = [
+        (365 * 86_400, "year", "年"),
+        (30 * 86_400, "month", "个月"),
+        (86_400, "day", "天"),
+        (3_600, "hour", "小时"),
+        (60, "minute", "分钟"),
+        (1, "second", "秒"),
+    ];
+
+    let seconds = age.as_secs();
+    if seconds < 5 {
+        return language.pick("just now", "刚刚").to_string();
+    }
+
+    let (unit_seconds, english, chinese) = UNITS
+        .into_iter()
+        .find(|(unit_seconds, _, _)| seconds >= *unit_seconds)
+        .unwrap_or(UNITS[UNITS.len() - 1]);
+    let count = seconds / unit_seconds;
+    match language {
+        Language::English if count == 1 => format!("1 {english} ago"),
+        Language::English => format!("{count} {english}s ago"),
+        Language::Chinese => format!("{count} {chinese}前"),
+    }
+}
+
+/// How [`human_count`] groups digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountGrouping {
+    /// Groups of three separated by the given character: `1,834,211`.
+    Thousands(char),
+    /// Chinese myriad grouping: `183万4211`.
+    Myriad,
+}
+
+/// Formats `value` with digit grouping, e.g. `1,834,211` or `183万4211`.
+pub fn human_count(value: u64, grouping: CountGrouping) -> String {
+    match grouping {
+        CountGrouping::Thousands(separator) => format_int(value, separator),
+        CountGrouping::Myriad => format_myriad(value),
+    }
+}
+
+/// Groups decimal digits in threes with `separator`.
+pub fn format_int(value: u64, separator: char) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+fn format_myriad(value: u64) -> String {
+    const UNITS: [&str; 5] = ["", "万", "亿", "万亿", "亿亿"];
+
+    let mut groups = Vec::new();
+    let mut rest = value;
+    loop {
+        groups.push(rest % 10_000);
+        rest /= 10_000;
+        if rest == 0 {
+            break;
+        }
+    }
+
+    let mut text = String::new();
+    for (index, group) in groups.iter().enumerate().rev() {
+        if index + 1 == groups.len() {
+            text.push_str(&group.to_string());
+        } else {
+            text.push_str(&format!("{group:04}"));
+        }
+        text.push_str(UNITS[index]);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_follow_unit_mode() {
+        assert_eq!(human_size(1023, Language::English), "1023 B");
+        assert_eq!(human_size(1536, Language::English), "1.50 KB");
+        assert_eq!(human_size_iec(1536, Language::English), "1.50 KiB");
+        assert_eq!(human_size_si(1536, Language::English), "1.54 kB");
+        assert_eq!(human_size_si(999, Language::English), "999 B");
+        assert_eq!(human_size_si(25_000_000, Language::English), "25.0 MB");
+        assert_eq!(human_size_iec(u64::MAX, Language::English), "16.0 EiB");
+        assert_eq!(human_size_si(u64::MAX, Language::English), "18.4 EB");
+        assert_eq!(
+            human_size_in(12, SizeUnits::Si, Language::Chinese),
+            "12 字节"
+        );
+    }
+
+    #[test]
+    fn parses_size_literals() {
+        assert_eq!(parse_size("200"), Some(200));
+        assert_eq!(parse_size(" 1.5 KB "), Some(1536));
+        assert_eq!(parse_size("2gib"), Some(2 << 30));
+        assert_eq!(parse_size("lots"), None);
+        assert_eq!(parse_size("3 PB"), Some(3 << 50));
+        assert_eq!(parse_size("15 EiB"), Some(15 << 60));
+        assert_eq!(parse_size("16 EiB"), None);
+        assert_eq!(parse_size("17.5 EB"), None);
+        assert_eq!(parse_size("3 ZB"), None);
+        for bytes in [5 << 50, 3 << 60] {
+            assert_eq!(
+                parse_size(&human_size(bytes, Language::English)),
+                Some(bytes)
+            );
+            assert_eq!(
+                parse_size(&human_size_iec(bytes, Language::English)),
+                Some(bytes)
+            );
+        }
+    }
+
+    #[test]
+    fn relative_ages_pick_the_largest_unit() {
+        let age = |seconds| Duration::from_secs(seconds);
+        assert_eq!(relative_age(age(2), Language::English), "just now");
+        assert_eq!(relative_age(age(59), Language::English), "59 seconds ago");
+        assert_eq!(relative_age(age(60), Language::English), "1 minute ago");
+        assert_eq!(
+            relative_age(age(3 * 86_400 + 5), Language::English),
+            "3 days ago"
+        );
+        assert_eq!(relative_age(age(2 * 3_600), Language::Chinese), "2 小时前");
+        assert_eq!(relative_age(age(400 * 86_400), Language::Chinese), "1 年前");
+    }
+
+    #[test]
+    fn percent_precision_depends_on_magnitude() {
+        assert_eq!(percent(0.0), "0%");
+        assert_eq!(percent(0.0004), "<0.1%");
+        assert_eq!(percent(0.073), "7.3%");
+        assert_eq!(percent(0.0996), "10%");
+        assert_eq!(percent(0.4213), "42%");
+        assert_eq!(percent(1.0), "100%");
+    }
+
+    #[test]
+    fn durations_scale_their_precision() {
+        assert_eq!(
+            format_duration_compact(Duration::ZERO, Language::English),
+            "0 ms"
+        );
+        assert_eq!(
+            format_duration_compact(Duration::from_millis(450), Language::English),
+            "450 ms"
+        );
+        assert_eq!(
+            format_duration_compact(Duration::from_millis(12_340), Language::English),
+            "12.3s"
+        );
+        assert_eq!(
+            format_duration_compact(Duration::from_secs(125), Language::English),
+            "2m 05s"
+        );
+        assert_eq!(
+            format_duration_compact(Duration::from_secs(3_725), Language::English),
+            "1h 02m"
+        );
+        assert_eq!(
+            format_duration_compact(Duration::from_secs(3_725), Language::Chinese),
+            "1小时02分"
+        );
+        assert_eq!(
+            format_duration_compact(Duration::from_secs(125), Language::Chinese),
+            "2分05秒"
+        );
+    }
+
+    #[test]
+    fn rates_need_elapsed_time() {
+        let grouping = CountGrouping::Thousands(',');
+        assert_eq!(count_rate(10, Duration::ZERO, grouping), "-");
+        assert_eq!(
+            count_rate(90_000, Duration::from_secs(2), grouping),
+            "45,000"
+        );
+        assert_eq!(
+            byte_rate(
+                3 * 1024 * 1024,
+                Duration::from_secs(2),
+                SizeUnits::Iec,
+                Language::English
+            ),
+            "1.50 MiB/s"
+        );
+    }
+
+    #[test]
+    fn exact_bytes_are_grouped() {
+        assert_eq!(exact_bytes(1, Language::English), "1 byte");
+        assert_eq!(
+            exact_bytes(1_234_567_890, Language::English),
+            "1,234,567,890 bytes"
+        );
+        assert_eq!(exact_bytes(1_834_211, Language::Chinese), "183万4211 字节");
+    }
+
+    #[test]
+    fn groups_counts() {
+        assert_eq!(format_int(0, ','), "0");
+        assert_eq!(format_int(999, ','), "999");
+        assert_eq!(format_int(1_834_211, ','), "1,834,211");
+        assert_eq!(
+            human_count(1_000_000, CountGrouping::Thousands(' ')),
+            "1 000 000"
+        );
+        assert_eq!(human_count(9_999, CountGrouping::Myriad), "9999");
+        assert_eq!(human_count(1_834_211, CountGrouping::Myriad), "183万4211");
+        assert_eq!(
+            human_count(100_000_005, CountGrouping::Myriad),
+            "1亿0000万0005"
+        );
+        assert_eq!(
+            human_count(u64::MAX, CountGrouping::Myriad),
+            "1844亿亿6744万亿0737亿0955万1615"
+        );
     }
 }
@@ -0,0 +1,368 @@
+//! The single path through which the app touches scanned files.
+//!
+//! Everything here is read-only: files are opened with read access only,
+//! metadata is read without following links, and each access is counted by
+//! kind. Removing an entry is the one exception; it is refused unless
+//! cleanup mode was switched on, entries only ever go to the platform trash
+//! (never deleted permanently), and every removal request, granted or not,
+//! goes into the audit log together with scans and cleanup mode changes.
+//!
+//! The app's own files (settings, snapshots, exports to a chosen path) are
+//! written directly and are not part of this guarantee.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+/// Audit records kept in memory; the oldest are dropped first.
+pub const AUDIT_LOG_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessKind {
+    Metadata,
+    DirectoryListing,
+    ContentRead,
+    Removal,
+}
+
+impl AccessKind {
+    pub const ALL: [AccessKind; 4] = [
+        AccessKind::Metadata,
+        AccessKind::DirectoryListing,
+        AccessKind::ContentRead,
+        AccessKind::Removal,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Metadata => "metadata",
+            Self::DirectoryListing => "directory listing",
+            Self::ContentRead => "content read",
+            Self::Removal => "removal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    Scan,
+    CleanupEnabled,
+    CleanupDisabled,
+    /// Moved to the platform trash.
+    Removed,
+    RemovalDenied,
+    RemovalFailed(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// Unix seconds.
+    pub timestamp: u64,
+    pub event: AuditEvent,
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessStats {
+    pub metadata: u64,
+    pub directory_listings: u64,
+    pub content_reads: u64,
+    pub removals: u64,
+    /// Removals refused because cleanup mode was off.
+    pub denied: u64,
+}
+
+impl AccessStats {
+    pub fn count(&self, kind: AccessKind) -> u64 {
+        match kind {
+            AccessKind::Metadata => self.metadata,
+            AccessKind::DirectoryListing => self.directory_listings,
+            AccessKind::ContentRead => self.content_reads,
+            AccessKind::Removal => self.removals,
+        }
+    }
+}
+
+static COUNTS: [AtomicU64; 4] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+static DENIED: AtomicU64 = AtomicU64::new(0);
+static CLEANUP_MODE: AtomicBool = AtomicBool::new(false);
+static AUDIT_LOG: Mutex<VecDeque<AuditRecord>> = Mutex::new(VecDeque::new());
+
+/// Counts one access made outside the helpers below, like a directory
+/// listing done by a walker.
+pub fn record(kind: AccessKind) {
+    COUNTS[kind as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn stats() -> AccessStats {
+    let count = |kind: AccessKind| COUNTS[kind as usize].load(Ordering::Relaxed);
+    AccessStats {
+        metadata: count(AccessKind::Metadata),
+        directory_listings: count(AccessKind::DirectoryListing),
+        content_reads: count(AccessKind::ContentRead),
+        removals: count(AccessKind::Removal),
+        denied: DENIED.load(Ordering::Relaxed),
+    }
+}
+
+/// Audit records, oldest first.
+pub fn audit_log() -> Vec<AuditRecord> {
+    AUDIT_LOG
+        .lock()
+        .map(|log| log.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn audit(event: AuditEvent, path: Option<&Path>) {
+    let Ok(mut log) = AUDIT_LOG.lock() else {
+        return;
+    };
+    if log.len() >= AUDIT_LOG_CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(AuditRecord {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs()),
+        event,
+        path: path.map(Path::to_path_buf),
+    });
+}
+
+/// A walker over `root` that never follows links; the scan is logged.
+pub fn walk(root: &Path) -> WalkDir {
+    audit(AuditEvent::Scan, Some(root));
+    WalkDir::new(root).follow_links(false)
+}
+
+/// Metadata of the entry itself, not a link target.
+pub fn metadata(path: &Path) -> io::Result<Metadata> {
+    record(AccessKind::Metadata);
+    fs::symlink_metadata(path)
+}
+
+/// Disk space a file takes as the file system reports it; `None` where
+/// the platform has no such figure.
+#[cfg(unix)]
+pub fn allocated_size(_path: &Path, metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    // `st_blocks` counts 512-byte units whatever the file system block size.
+    Some(metadata.blocks().saturating_mul(512))
+}
+
+/// Disk space a file takes as the file system reports it, compressed and
+/// sparse files included; `None` for links and on failure.
+#[cfg(windows)]
+pub fn allocated_size(path: &Path, metadata: &Metadata) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    const INVALID_FILE_SIZE: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCompressedFileSizeW(file_name: *const u16, file_size_high: *mut u32) -> u32;
+    }
+
+    // The call would measure the link target rather than the link.
+    if metadata.file_type().is_symlink() {
+        return None;
+    }
+    record(AccessKind::Metadata);
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high = 0_u32;
+    // SAFETY: `wide` is NUL-terminated and both pointers outlive the call.
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    if low == INVALID_FILE_SIZE && io::Error::last_os_error().raw_os_error() != Some(0) {
+        return None;
+    }
+    Some((u64::from(high) << 32) | u64::from(low))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn allocated_size(_path: &Path, _metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+/// The entries of a folder, unsorted.
+pub fn read_dir(path: &Path) -> io::Result<fs::ReadDir> {
+    record(AccessKind::DirectoryListing);
+    fs::read_dir(path)
+}
+
+/// Where a symbolic link points, as stored in it.
+pub fn read_link(path: &Path) -> io::Result<PathBuf> {
+    record(AccessKind::Metadata);
+    fs::read_link(path)
+}
+
+/// The absolute path with every link resolved.
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    record(AccessKind::Metadata);
+    fs::canonicalize(path)
+}
+
+/// Opens a file for reading only.
+pub fn open(path: &Path) -> io::Result<File> {
+    record(AccessKind::ContentRead);
+    OpenOptions::new().read(true).open(path)
+}
+
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open(path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let mut text = String::new();
+    open(path)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+pub fn cleanup_mode() -> bool {
+    CLEANUP_MODE.load(Ordering::Relaxed)
+}
+
+pub fn set_cleanup_mode(enabled: bool) {
+    if CLEANUP_MODE.swap(enabled, Ordering::Relaxed) != enabled {
+        let event = if enabled {
+            AuditEvent::CleanupEnabled
+        } else {
+            AuditEvent::CleanupDisabled
+        };
+        audit(event, None);
+    }
+}
+
+/// Moves a file, or a folder with everything in it, to the platform trash
+/// (Recycle Bin, macOS Trash, freedesktop trash), where it can be restored;
+/// refused unless cleanup mode is on. Succeeds only once the entry is gone.
+#[cfg(feature = "app")]
+pub fn remove(path: &Path) -> Result<(), String> {
+    remove_with(path, cleanup_mode(), |path| {
+        trash::delete(path).map_err(|error| error.to_string())
+    })
+}
+
+/// [`remove`] with the cleanup mode and the trash passed in.
+#[cfg(feature = "app")]
+fn remove_with(
+    path: &Path,
+    allowed: bool,
+    move_to_trash: impl FnOnce(&Path) -> Result<(), String>,
+) -> Result<(), String> {
+    if !allowed {
+        DENIED.fetch_add(1, Ordering::Relaxed);
+        audit(AuditEvent::RemovalDenied, Some(path));
+        return Err(format!(
+            "Refused to remove {}: cleanup mode is off",
+            path.display()
+        ));
+    }
+    record(AccessKind::Removal);
+    let result = move_to_trash(path).and_then(|()| match fs::symlink_metadata(path) {
+        Ok(_) => Err("it is still there after moving it to the trash".to_string()),
+        Err(_) => Ok(()),
+    });
+    match result {
+        Ok(()) => {
+            audit(AuditEvent::Removed, Some(path));
+            Ok(())
+        }
+        Err(error) => {
+            audit(AuditEvent::RemovalFailed(error.clone()), Some(path));
+            Err(format!(
+                "Could not move {} to the trash: {error}",
+                path.display()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_are_counted_by_kind() {
+        let dir = std::env::temp_dir().join(format!("tmb-gateway-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("old.log");
+        fs::write(&file, b"log").unwrap();
+
+        let before = stats();
+        assert_eq!(read_to_string(&file).unwrap(), "log");
+        assert!(metadata(&file).unwrap().is_file());
+        let after = stats();
+        assert!(after.content_reads > before.content_reads);
+        assert!(after.metadata > before.metadata);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn removal_moves_to_trash_only_in_cleanup_mode() {
+        let dir = std::env::temp_dir().join(format!("tmb-gateway-trash-{}", std::process::id()));
+        let trash_dir = dir.join("trash");
+        fs::create_dir_all(&trash_dir).unwrap();
+        let file = dir.join("old.log");
+        fs::write(&file, b"log").unwrap();
+        let fake_trash = |path: &Path| {
+            fs::rename(path, trash_dir.join(path.file_name().unwrap()))
+                .map_err(|error| error.to_string())
+        };
+
+        let before = stats();
+        assert_eq!(read_to_string(&file).unwrap(), "log");
+        assert!(remove_with(&file, false, fake_trash).is_err());
+        assert!(file.exists());
+        let after = stats();
+        assert!(after.content_reads > before.content_reads);
+        assert!(after.denied > before.denied);
+
+        assert!(remove_with(&file, true, fake_trash).is_ok());
+        assert!(!file.exists());
+        assert!(trash_dir.join("old.log").exists());
+
+        // A trash that reports success but leaves the entry in place failed.
+        let kept = dir.join("kept.log");
+        fs::write(&kept, b"log").unwrap();
+        assert!(remove_with(&kept, true, |_| Ok(())).is_err());
+        assert!(kept.exists());
+
+        let events: Vec<AuditEvent> = audit_log()
+            .into_iter()
+            .filter(|record| {
+                record
+                    .path
+                    .as_deref()
+                    .is_some_and(|path| path.starts_with(&dir))
+            })
+            .map(|record| record.event)
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                AuditEvent::RemovalDenied,
+                AuditEvent::Removed,
+                AuditEvent::RemovalFailed(
+                    "it is still there after moving it to the trash".to_string()
+                ),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,240 @@
+//! Git repository awareness: finds repositories in the scanned tree, splits
+//! each into its `.git` object store and working tree, and marks what the
+//! repository's ignore rules exclude.
+//!
+//! Ignore rules come from every `.gitignore` in the tree plus
+//! `.git/info/exclude`, with git's semantics for anchoring, `dir/`-only
+//! patterns, `**` and `!` negation (the last matching rule wins, and nothing
+//! below an ignored folder can be re-included). Git itself is never run.
+
+use crate::gateway;
+use crate::model::Node;
+use crate::wake::{self, Waker};
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+#[derive(Debug, Clone)]
+pub struct GitRepo {
+    pub root: PathBuf,
+    /// Size of the `.git` folder (objects, packs, index, ...).
+    pub git_dir_bytes: u64,
+    /// Everything else below the repository root, ignored files included.
+    pub working_bytes: u64,
+    pub ignored_bytes: u64,
+}
+
+#[derive(Debug)]
+pub struct GitScan {
+    /// The scan generation the analyzed tree belonged to.
+    pub scan_generation: u64,
+    pub repos: Vec<GitRepo>,
+    /// Topmost ignored entries; everything below them is ignored too.
+    pub ignored: HashSet<PathBuf>,
+}
+
+impl GitScan {
+    /// Whether `path` or one of its ancestors is ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        !self.ignored.is_empty() && path.ancestors().any(|path| self.ignored.contains(path))
+    }
+}
+
+/// Analyzes every repository below `root` on a background thread.
+pub fn spawn_git_scan(root: &Node, scan_generation: u64, waker: Waker) -> Receiver<GitScan> {
+    let mut repos = Vec::new();
+    find_repos(root, &mut repos);
+    let repos: Vec<Node> = repos.into_iter().cloned().collect();
+    let (tx, rx) = wake::channel(waker);
+
+    thread::spawn(move || {
+        let mut scan = GitScan {
+            scan_generation,
+            repos: Vec::with_capacity(repos.len()),
+            ignored: HashSet::new(),
+        };
+        for repo in &repos {
+            scan.repos.push(analyze_repo(repo, &mut scan.ignored));
+        }
+        let _ = tx.send(scan);
+    });
+
+    rx
+}
+
+fn find_repos<'a>(node: &'a Node, repos: &mut Vec<&'a Node>) {
+    if is_repo_root(node) {
+        repos.push(node);
+    }
+    for child in &node.children {
+        if child.name != ".git" {
+            find_repos(child, repos);
+        }
+    }
+}
+
+/// A folder with a `.git` folder, or a `.git` file for worktrees and
+/// submodules.
+fn is_repo_root(node: &Node) -> bool {
+    node.children.iter().any(|child| child.name == ".git")
+}
+
+fn analyze_repo(repo: &Node, ignored: &mut HashSet<PathBuf>) -> GitRepo {
+    let git_dir_bytes = repo
+        .children
+        .iter()
+        .filter(|child| child.name == ".git")
+        .map(|child| child.size)
+        .sum();
+    let mut rules = Vec::new();
+    if let Ok(text) = gateway::read_to_string(&repo.path.join(".git").join("info").join("exclude"))
+    {
+        rules.push(IgnoreFile::parse(&repo.path, &text));
+    }
+    let mut ignored_bytes = 0;
+    mark_ignored(repo, &mut rules, ignored, &mut ignored_bytes);
+
+    GitRepo {
+        root: repo.path.clone(),
+        git_dir_bytes,
+        working_bytes: repo.size.saturating_sub(git_dir_bytes),
+        ignored_bytes,
+    }
+}
+
+fn mark_ignored(
+    dir: &Node,
+    rules: &mut Vec<IgnoreFile>,
+    ignored: &mut HashSet<PathBuf>,
+    ignored_bytes: &mut u64,
+) {
+    let local_rules = dir
+        .children
+        .iter()
+        .any(|child| child.name == ".gitignore" && child.children.is_empty())
+        .then(|| gateway::read_to_string(&dir.path.join(".gitignore")).ok())
+        .flatten();
+    if let Some(text) = &local_rules {
+        rules.push(IgnoreFile::parse(&dir.path, text));
+    }
+
+    for child in &dir.children {
+        let is_dir = !child.children.is_empty();
+        // Nested repositories are analyzed on their own.
+        if child.name == ".git" || (is_dir && is_repo_root(child)) {
+            continue;
+        }
+        if is_ignored(rules, &child.path, is_dir) {
+            ignored.insert(child.path.clone());
+            *ignored_bytes += child.size;
+        } else if is_dir {
+            mark_ignored(child, rules, ignored, ignored_bytes);
+        }
+    }
+
+    if local_rules.is_some() {
+        rules.pop();
+    }
+}
+
+fn is_ignored(rules: &[IgnoreFile], path: &Path, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for file in rules {
+        let Ok(relative) = path.strip_prefix(&file.base) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        for rule in &file.rules {
+            if (is_dir || !rule.dir_only) && rule.glob.is_match(&relative) {
+                ignored = !rule.negated;
+            }
+        }
+    }
+    ignored
+}
+
+struct IgnoreFile {
+    base: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+struct IgnoreRule {
+    glob: GlobMatcher,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreFile {
+    /// Parses one ignore file whose patterns are relative to `base`; invalid
+    /// patterns are skipped, as git does.
+    fn parse(base: &Path, text: &str) -> Self {
+        let rules = text
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let (negated, pattern) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, pattern) = match pattern.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, pattern),
+                };
+                // A slash anywhere but the end anchors the pattern to `base`.
+                let glob = if pattern.contains('/') {
+                    pattern.trim_start_matches('/').to_string()
+                } else {
+                    format!("**/{pattern}")
+                };
+                let glob = GlobBuilder::new(&glob)
+                    .literal_separator(true)
+                    .backslash_escape(true)
+                    .build()
+                    .ok()?
+                    .compile_matcher();
+                Some(IgnoreRule {
+                    glob,
+                    negated,
+                    dir_only,
+                })
+            })
+            .collect();
+        Self {
+            base: base.to_path_buf(),
+            rules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_gitignore_semantics() {
+        let base = Path::new("/repo");
+        let rules = [
+            IgnoreFile::parse(
+                base,
+                "# build output\ntarget/\n*.log\n!keep.log\n/docs/*.pdf\n",
+            ),
+            IgnoreFile::parse(&base.join("web"), "dist\n"),
+        ];
+        let ignored = |path: &str, is_dir| is_ignored(&rules, &base.join(path), is_dir);
+
+        assert!(ignored("target", true));
+        assert!(ignored("crates/a/target", true));
+        assert!(!ignored("target", false));
+        assert!(ignored("logs/app.log", false));
+        assert!(!ignored("logs/keep.log", false));
+        assert!(ignored("docs/manual.pdf", false));
+        assert!(!ignored("docs/api/manual.pdf", false));
+        assert!(ignored("web/dist", true));
+        assert!(!ignored("dist", true));
+    }
+}
@@ -0,0 +1,150 @@
+//! Growth across repeated scans of the same root within a session: the last
+//! few scans' folder totals, for a growth chart and a "fastest growing
+//! folders" list.
+
+use crate::model::Node;
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Scans kept per root.
+const GROWTH_HISTORY_LEN: usize = 48;
+/// Folders deeper than this below the root are not tracked individually.
+const TRACKED_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct GrowthSample {
+    pub at: SystemTime,
+    pub total: u64,
+    folders: HashMap<PathBuf, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderGrowth {
+    pub path: PathBuf,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl FolderGrowth {
+    pub fn delta(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GrowthHistory {
+    root: Option<PathBuf>,
+    samples: VecDeque<GrowthSample>,
+}
+
+impl GrowthHistory {
+    /// Adds a finished scan; scanning a different root starts a new history.
+    pub fn record(&mut self, root: &Node, at: SystemTime) {
+        if self.root.as_deref() != Some(root.path.as_path()) {
+            self.root = Some(root.path.clone());
+            self.samples.clear();
+        }
+        let mut folders = HashMap::new();
+        collect_folders(root, 0, &mut folders);
+        if self.samples.len() == GROWTH_HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(GrowthSample {
+            at,
+            total: root.size,
+            folders,
+        });
+    }
+
+    pub fn samples(&self) -> &VecDeque<GrowthSample> {
+        &self.samples
+    }
+
+    /// Folders below the root that grew most between the oldest and newest
+    /// scan, largest growth first.
+    pub fn fastest_growing(&self, count: usize) -> Vec<FolderGrowth> {
+        let (Some(first), Some(last)) = (self.samples.front(), self.samples.back()) else {
+            return Vec::new();
+        };
+        let root = self.root.as_deref();
+        let mut growth: Vec<FolderGrowth> = last
+            .folders
+            .iter()
+            .filter(|(path, _)| Some(path.as_path()) != root)
+            .map(|(path, &after)| FolderGrowth {
+                path: path.clone(),
+                before: first.folders.get(path).copied().unwrap_or(0),
+                after,
+            })
+            .filter(|growth| growth.after > growth.before)
+            .collect();
+        growth.sort_by_key(|growth| (Reverse(growth.delta()), growth.path.clone()));
+        growth.truncate(count);
+        growth
+    }
+}
+
+fn collect_folders(node: &Node, depth: usize, folders: &mut HashMap<PathBuf, u64>) {
+    folders.insert(node.path.clone(), node.size);
+    if depth == TRACKED_DEPTH {
+        return;
+    }
+    for child in node
+        .children
+        .iter()
+        .filter(|child| !child.children.is_empty())
+    {
+        collect_folders(child, depth + 1, folders);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn tree(files: &[(&str, u64)]) -> Node {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        for (path, size) in files {
+            root.insert_relative(Path::new(path), *size);
+        }
+        root.compute_total_size();
+        root
+    }
+
+    #[test]
+    fn ranks_folders_by_growth_since_first_scan() {
+        let mut history = GrowthHistory::default();
+        let start = SystemTime::UNIX_EPOCH;
+        history.record(&tree(&[("logs/a.log", 10), ("src/main.rs", 5)]), start);
+        history.record(
+            &tree(&[("logs/a.log", 50), ("src/main.rs", 5), ("cache/x", 20)]),
+            start + Duration::from_secs(60),
+        );
+
+        let growth = history.fastest_growing(5);
+        assert_eq!(
+            growth
+                .iter()
+                .map(|growth| (growth.path.to_str().unwrap(), growth.delta()))
+                .collect::<Vec<_>>(),
+            vec![("/r/logs", 40), ("/r/cache", 20)]
+        );
+        assert_eq!(
+            history
+                .samples()
+                .iter()
+                .map(|sample| sample.total)
+                .collect::<Vec<_>>(),
+            vec![15, 75]
+        );
+
+        let mut other = tree(&[("a", 1)]);
+        other.path = PathBuf::from("/other");
+        history.record(&other, start);
+        assert_eq!(history.samples().len(), 1);
+    }
+}
@@ -0,0 +1,477 @@
+//! Importers for disk-usage reports written by other tools, so trees scanned
+//! elsewhere (e.g. on a headless server) can be opened like a snapshot.
+//!
+//! Supported: XML tree reports in the Baobab/gdmap style, where directory
+//! elements (`folder`, `directory`, `dir`) nest file elements (`file`) and
+//! each carries a `name` (or `path`) and a byte `size` attribute. Any other
+//! wrapper element around the top directory is ignored.
+//!
+//! Also: plain `du -a` listings (`<size><TAB><path>` per line), in KiB
+//! blocks, bytes (`-b`) or human units (`-h`); see [`parse_du_listing`];
+//! and WizTree CSV exports; see [`parse_wiztree_csv`].
+
+use crate::model::Node;
+use crate::scanner::{ScanResult, ScanStats};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File extensions offered when picking a report to import.
+pub const IMPORT_EXTENSIONS: &[&str] = &["xml", "csv", "txt", "du"];
+
+pub fn import_report(path: &Path) -> Result<ScanResult, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("Could not read {}: {error}", path.display()))?;
+    let text = text.trim_start_matches('\u{feff}');
+    let first_line = text.lines().next().unwrap_or_default();
+    if text.trim_start().starts_with('<') {
+        parse_baobab_xml(text)
+    } else if first_line.starts_with("Generated by WizTree")
+        || first_line.starts_with("\"File Name\"")
+    {
+        parse_wiztree_csv(text)
+    } else {
+        parse_du_listing(text)
+    }
+}
+
+/// Parses a WizTree "Export to CSV" file: an optional `Generated by WizTree`
+/// line, a header with `File Name` and `Size` columns (and `Allocated`, when
+/// present, for the disk space taken), then one row per entry with its full
+/// path. Folder rows end in a path separator and carry
+/// totals, so only file rows are inserted; the first folder row is the root.
+pub fn parse_wiztree_csv(text: &str) -> Result<ScanResult, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.starts_with("Generated by WizTree"));
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| "WizTree CSV is empty".to_string())?;
+    let header = split_csv_line(header);
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|field| field.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("WizTree CSV has no {name:?} column"))
+    };
+    let (name_column, size_column) = (column("File Name")?, column("Size")?);
+    let allocated_column = column("Allocated").ok();
+
+    let mut root: Option<(Node, String)> = None;
+    let mut stats = ScanStats::default();
+    let mut warnings = Vec::new();
+    for (index, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        let (Some(path), Some(size)) = (fields.get(name_column), fields.get(size_column)) else {
+            warnings.push(format!("Line {}: missing columns", index + 1));
+            continue;
+        };
+        let is_dir = path.ends_with(['\\', '/']);
+        stats.entries_scanned += 1;
+        if is_dir {
+            stats.directories_scanned += 1;
+        }
+        let Some((root, prefix)) = &mut root else {
+            if is_dir {
+                let name = path.trim_end_matches(['\\', '/']);
+                let name = name.rsplit(['\\', '/']).next().unwrap_or(name);
+                let mut folder = Node::new(name.to_string(), PathBuf::from(path), 0);
+                folder.is_dir = true;
+                root = Some((folder, path.clone()));
+            } else {
+                warnings.push(format!("Line {}: file listed before any folder", index + 1));
+            }
+            continue;
+        };
+        if is_dir {
+            continue;
+        }
+        let Some(relative) = path.strip_prefix(prefix.as_str()) else {
+            warnings.push(format!("Line {}: {path} is outside {prefix}", index + 1));
+            continue;
+        };
+        let Ok(size) = size.trim().parse::<u64>() else {
+            warnings.push(format!("Line {}: invalid size {size:?}", index + 1));
+            continue;
+        };
+        let relative: PathBuf = relative
+            .split(['\\', '/'])
+            .filter(|part| !part.is_empty())
+            .collect();
+        let allocated = allocated_column
+            .and_then(|column| fields.get(column))
+            .and_then(|allocated| allocated.trim().parse::<u64>().ok());
+        if let Some(file) = root.insert_relative(&relative, size) {
+            file.allocated = allocated;
+        }
+        stats.files_scanned += 1;
+    }
+
+    let (mut root, _) = root.ok_or_else(|| "WizTree CSV contains no folder rows".to_string())?;
+    // The root row itself is not below the root.
+    stats.directories_scanned = stats.directories_scanned.saturating_sub(1);
+    root.compute_total_size();
+    root.sort_children_by_size_desc();
+    stats.warnings = warnings.len() as u64;
+
+    Ok(ScanResult {
+        root,
+        stats,
+        warnings,
+        warning_counts: Vec::new(),
+        permission_findings: Vec::new(),
+        symlinks: Vec::new(),
+        owners: Vec::new(),
+    })
+}
+
+/// Splits one CSV record with `"`-quoted fields and `""` escapes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            ch => current.push(ch),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+pub fn parse_baobab_xml(text: &str) -> Result<ScanResult, String> {
+    let document =
+        roxmltree::Document::parse(text).map_err(|error| format!("Invalid XML report: {error}"))?;
+    let top = document
+        .root_element()
+        .descendants()
+        .find(|element| element_kind(element) == Some(ElementKind::Directory))
+        .ok_or_else(|| "XML report contains no directory element".to_string())?;
+
+    let root_path = attribute(&top, &["path"])
+        .or_else(|| attribute(&top, &["name"]))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/"));
+    let mut stats = ScanStats::default();
+    let mut warnings = Vec::new();
+    let mut root = import_element(&top, root_path, &mut stats, &mut warnings);
+    root.compute_total_size();
+    root.sort_children_by_size_desc();
+    stats.warnings = warnings.len() as u64;
+
+    Ok(ScanResult {
+        root,
+        stats,
+        warnings,
+        warning_counts: Vec::new(),
+        permission_findings: Vec::new(),
+        symlinks: Vec::new(),
+        owners: Vec::new(),
+    })
+}
+
+/// Parses `du -a` output. Plain numbers are taken as bytes when folders
+/// exceed the sum of their listed children by at least 512 on average
+/// (the folder's own inode, as `du -ab` reports it), otherwise as KiB
+/// blocks, `du`'s default. The root is the deepest folder containing every
+/// listed path, so listings of `.` and of absolute paths both work.
+pub fn parse_du_listing(text: &str) -> Result<ScanResult, String> {
+    let mut warnings = Vec::new();
+    // (path, size, size already in bytes)
+    let mut entries: Vec<(String, u64, bool)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((size, path)) = line
+            .split_once('\t')
+            .or_else(|| line.trim_start().split_once(char::is_whitespace))
+        else {
+            warnings.push(format!("Line {}: expected <size> <path>", index + 1));
+            continue;
+        };
+        let Some((size, in_bytes)) = parse_du_size(size.trim()) else {
+            warnings.push(format!(
+                "Line {}: invalid size {:?}",
+                index + 1,
+                size.trim()
+            ));
+            continue;
+        };
+        let path = path.trim_start();
+        let path = if path.len() > 1 {
+            path.trim_end_matches('/')
+        } else {
+            path
+        };
+        entries.push((path.to_string(), size, in_bytes));
+    }
+    if entries.is_empty() {
+        return Err("No `du` entries found in report".to_string());
+    }
+
+    let block_size = du_block_size(&entries);
+    let split = |path: &str| -> Vec<String> {
+        let mut components: Vec<String> = path
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .map(str::to_string)
+            .collect();
+        if path.starts_with('/') {
+            components.insert(0, "/".to_string());
+        }
+        components
+    };
+    let paths: Vec<Vec<String>> = entries.iter().map(|(path, _, _)| split(path)).collect();
+    let mut root_len = paths[0].len();
+    for components in &paths[1..] {
+        root_len = root_len.min(components.len());
+        root_len = (0..root_len)
+            .find(|&index| components[index] != paths[0][index])
+            .unwrap_or(root_len);
+    }
+    // A single listed file is shown inside its folder.
+    if paths.len() == 1 && root_len > 0 {
+        root_len -= 1;
+    }
+
+    let root_components = &paths[0][..root_len];
+    let root_path = match root_components.split_first() {
+        Some((first, rest)) => rest
+            .iter()
+            .fold(PathBuf::from(first), |path, part| path.join(part)),
+        None => PathBuf::from("."),
+    };
+    let root_name = root_components
+        .last()
+        .cloned()
+        .unwrap_or_else(|| ".".to_string());
+    let mut root = Node::new(root_name, root_path, 0);
+    root.is_dir = true;
+    for ((_, size, in_bytes), components) in entries.iter().zip(&paths) {
+        let relative: PathBuf = components[root_len..].iter().collect();
+        let bytes = if *in_bytes {
+            *size
+        } else {
+            size.saturating_mul(block_size)
+        };
+        root.insert_relative(&relative, bytes);
+    }
+    root.compute_total_size();
+    root.sort_children_by_size_desc();
+
+    let mut stats = ScanStats {
+        entries_scanned: entries.len() as u64,
+        warnings: warnings.len() as u64,
+        ..ScanStats::default()
+    };
+    let mut stack: Vec<&Node> = root.children.iter().collect();
+    while let Some(node) = stack.pop() {
+        if node.children.is_empty() {
+            stats.files_scanned += 1;
+        } else {
+            stats.directories_scanned += 1;
+            stack.extend(&node.children);
+        }
+    }
+
+    Ok(ScanResult {
+        root,
+        stats,
+        warnings,
+        warning_counts: Vec::new(),
+        permission_findings: Vec::new(),
+        symlinks: Vec::new(),
+        owners: Vec::new(),
+    })
+}
+
+/// `123`, or `du -h` style `1.5K` / `2M` / `0` (already in bytes).
+fn parse_du_size(text: &str) -> Option<(u64, bool)> {
+    if let Ok(value) = text.parse() {
+        return Some((value, false));
+    }
+    let unit = text.chars().last()?.to_ascii_uppercase();
+    let exponent = "BKMGTP".find(unit)? as i32;
+    let value: f64 = text[..text.len() - 1].replace(',', ".").parse().ok()?;
+    (value >= 0.0).then(|| ((value * 1024_f64.powi(exponent)) as u64, true))
+}
+
+fn du_block_size(entries: &[(String, u64, bool)]) -> u64 {
+    let mut child_sums: HashMap<&str, u64> = HashMap::new();
+    for (path, size, _) in entries {
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            let parent = if parent.is_empty() { "/" } else { parent };
+            *child_sums.entry(parent).or_default() += size;
+        }
+    }
+    let mut overheads: Vec<u64> = entries
+        .iter()
+        .filter_map(|(path, size, _)| {
+            child_sums
+                .get(path.as_str())
+                .map(|children| size.saturating_sub(*children))
+        })
+        .collect();
+    if overheads.is_empty() {
+        return 1024;
+    }
+    let middle = overheads.len() / 2;
+    let (_, median, _) = overheads.select_nth_unstable(middle);
+    if *median >= 512 {
+        1
+    } else {
+        1024
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElementKind {
+    Directory,
+    File,
+}
+
+fn element_kind(node: &roxmltree::Node<'_, '_>) -> Option<ElementKind> {
+    if !node.is_element() {
+        return None;
+    }
+    match node.tag_name().name().to_ascii_lowercase().as_str() {
+        "folder" | "directory" | "dir" => Some(ElementKind::Directory),
+        "file" => Some(ElementKind::File),
+        _ => None,
+    }
+}
+
+fn attribute<'a>(node: &roxmltree::Node<'a, '_>, names: &[&str]) -> Option<&'a str> {
+    names.iter().find_map(|name| node.attribute(*name))
+}
+
+fn import_element(
+    element: &roxmltree::Node<'_, '_>,
+    path: PathBuf,
+    stats: &mut ScanStats,
+    warnings: &mut Vec<String>,
+) -> Node {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let size = match attribute(element, &["size", "bytes"]) {
+        Some(text) => text.trim().parse().unwrap_or_else(|_| {
+            warnings.push(format!("Invalid size {text:?} for {}", path.display()));
+            0
+        }),
+        None => 0,
+    };
+    stats.entries_scanned += 1;
+
+    let mut node = Node::new(name, path, size);
+    node.is_dir = element_kind(element) == Some(ElementKind::Directory);
+    for child in element.children() {
+        let Some(kind) = element_kind(&child) else {
+            continue;
+        };
+        let Some(child_name) = attribute(&child, &["name"]).or_else(|| {
+            attribute(&child, &["path"]).and_then(|path| path.rsplit(['/', '\\']).next())
+        }) else {
+            warnings.push(format!("Unnamed entry inside {}", node.path.display()));
+            continue;
+        };
+        let child_path = node.path.join(child_name);
+        match kind {
+            ElementKind::Directory => stats.directories_scanned += 1,
+            ElementKind::File => stats.files_scanned += 1,
+        }
+        node.children
+            .push(import_element(&child, child_path, stats, warnings));
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_nested_xml_report() {
+        let report = r#"<?xml version="1.0"?>
+            <baobab version="2">
+              <folder path="/srv/data" size="0">
+                <folder name="logs">
+                  <file name="app.log" size="300"/>
+                  <file name="old.log" size="nope"/>
+                </folder>
+                <file name="db.bin" size="1000"/>
+                <folder name="empty" size="4096"/>
+              </folder>
+            </baobab>"#;
+
+        let result = parse_baobab_xml(report).unwrap();
+
+        assert_eq!(result.root.path, PathBuf::from("/srv/data"));
+        assert_eq!(result.root.size, 1000 + 300 + 4096);
+        assert_eq!(result.root.children[0].name, "empty");
+        assert!(result.root.children[0].is_dir);
+        let logs = result.root.find(Path::new("/srv/data/logs")).unwrap();
+        assert_eq!(logs.size, 300);
+        assert_eq!(result.stats.files_scanned, 3);
+        assert_eq!(result.stats.directories_scanned, 2);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(parse_baobab_xml("<report/>").is_err());
+    }
+
+    #[test]
+    fn imports_du_listings_in_blocks_and_bytes() {
+        let blocks =
+            "8\t./logs/app.log\n4\t./logs/old log.txt\n16\t./logs\n100\t./db.bin\n120\t.\n";
+        let result = parse_du_listing(blocks).unwrap();
+        assert_eq!(result.root.path, PathBuf::from("."));
+        assert_eq!(result.root.size, (8 + 4 + 100) * 1024);
+        let logs = result.root.find(Path::new("./logs/old log.txt")).unwrap();
+        assert_eq!(logs.size, 4096);
+
+        let bytes = "300\t/srv/data/logs/app.log\n4396\t/srv/data/logs\n1000\t/srv/data/db.bin\n9492\t/srv/data\nbad line\n";
+        let result = parse_du_listing(bytes).unwrap();
+        assert_eq!(result.root.path, PathBuf::from("/srv/data"));
+        assert_eq!(result.root.size, 1300);
+        assert_eq!(result.stats.files_scanned, 2);
+        assert_eq!(result.stats.directories_scanned, 1);
+        assert_eq!(result.warnings.len(), 1);
+
+        let human = parse_du_listing("1.5K\tdir/a\n2M\tdir/b\n").unwrap();
+        assert_eq!(human.root.size, 1536 + 2 * 1024 * 1024);
+    }
+
+    #[test]
+    fn imports_wiztree_csv() {
+        let csv = "Generated by WizTree 4.20 16/10/2026 (You can disable this line)\r\n\
+            \"File Name\",\"Size\",\"Allocated\",\"Modified\",\"Attributes\",\"Files\",\"Folders\"\r\n\
+            \"C:\\\",1300,4096,2026/10/16 09:00:00,0,2,1\r\n\
+            \"C:\\Users\\\",300,4096,2026/10/16 09:00:00,16,1,0\r\n\
+            \"C:\\Users\\a, \"\"b\"\".txt\",300,4096,2026/10/16 09:00:00,32,0,0\r\n\
+            \"C:\\pagefile.sys\",1000,4096,2026/10/16 09:00:00,38,0,0\r\n";
+
+        let result = parse_wiztree_csv(csv).unwrap();
+
+        assert_eq!(result.root.name, "C:");
+        assert_eq!(result.root.size, 1300);
+        assert_eq!(result.root.children[0].name, "pagefile.sys");
+        assert_eq!(result.root.children[0].allocated, Some(4096));
+        assert_eq!(result.root.children[1].children[0].name, "a, \"b\".txt");
+        assert_eq!(result.stats.files_scanned, 2);
+        assert_eq!(result.stats.directories_scanned, 1);
+        assert!(result.warnings.is_empty());
+    }
+}
@@ -0,0 +1,649 @@
+use crate::action::ActionEvent;
+use crate::ipc::{describe_endpoint, endpoint_name};
+use crate::wake::{self, Waker, WakingSender};
+use interprocess::local_socket::prelude::*;
+use interprocess::local_socket::{ListenerNonblockingMode, ListenerOptions};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:7979";
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const CLIENT_READ_TIMEOUT: Duration = Duration::from_millis(200);
+/// Longest accepted line from a client or stream; a peer sending more
+/// without a newline is disconnected.
+const MAX_LINE_BYTES: usize = 64 * 1024;
+/// Most data one Server-Sent Event may buffer across its `data:` lines
+/// before a blank line ends it; a stream going past it is dropped.
+const MAX_EVENT_BYTES: usize = 4 * MAX_LINE_BYTES;
+const STREAM_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// A stream silent for this long is treated as dead and reconnected.
+const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const STREAM_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const STREAM_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenerStatus {
+    Listening {
+        address: String,
+        clients: usize,
+    },
+    /// A remote stream is open and delivering events.
+    Connected {
+        address: String,
+    },
+    /// A remote stream dropped; the next attempt starts after `retry_in`.
+    Reconnecting {
+        address: String,
+        retry_in: Duration,
+        error: String,
+    },
+    Failed(String),
+    Stopped,
+}
+
+#[derive(Debug)]
+pub enum IngestMessage {
+    Action(ActionEvent),
+    Rejected(String),
+    Status(ListenerStatus),
+}
+
+/// Background listener accepting newline-delimited action events, over TCP
+/// or the local IPC endpoint.
+///
+/// See [`crate::action`] for the wire schema.
+pub struct ActionListener {
+    stop: Arc<AtomicBool>,
+    receiver: Receiver<IngestMessage>,
+}
+
+impl ActionListener {
+    pub fn spawn(address: &str, waker: Waker) -> Self {
+        let (tx, rx) = wake::channel::<IngestMessage>(waker);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let address = address.trim().to_string();
+        let thread_stop = Arc::clone(&stop);
+        thread::spawn(move || run_listener(&address, &thread_stop, &tx));
+
+        Self { stop, receiver: rx }
+    }
+
+    /// Listens on the named pipe / Unix domain socket called `name`.
+    pub fn spawn_local(name: &str, waker: Waker) -> Self {
+        let (tx, rx) = wake::channel::<IngestMessage>(waker);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let name = name.trim().to_string();
+        let thread_stop = Arc::clone(&stop);
+        thread::spawn(move || run_local_listener(&name, &thread_stop, &tx));
+
+        Self { stop, receiver: rx }
+    }
+
+    /// Follows a remote HTTP stream of action events, reconnecting with backoff.
+    ///
+    /// `text/event-stream` responses are parsed as Server-Sent Events whose
+    /// `data` carries one event; any other response is read as
+    /// newline-delimited events (chunked long-poll).
+    pub fn spawn_stream(url: &str, waker: Waker) -> Self {
+        let (tx, rx) = wake::channel::<IngestMessage>(waker);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let url = url.trim().to_string();
+        let thread_stop = Arc::clone(&stop);
+        thread::spawn(move || run_stream_client(&url, &thread_stop, &tx));
+
+        Self { stop, receiver: rx }
+    }
+
+    pub fn receiver(&self) -> &Receiver<IngestMessage> {
+        &self.receiver
+    }
+}
+
+impl Drop for ActionListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Reads newline-delimited action events from stdin until EOF.
+///
+/// The receiver disconnects once stdin is closed.
+pub fn spawn_stdin_reader(waker: Waker) -> Receiver<IngestMessage> {
+    let (tx, rx) = wake::channel::<IngestMessage>(waker);
+
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let message = match ActionEvent::parse_line(&line) {
+                Ok(event) => IngestMessage::Action(event),
+                Err(error) => IngestMessage::Rejected(error),
+            };
+            if tx.send(message).is_err() {
+                return;
+            }
+        }
+    });
+
+    rx
+}
+
+fn run_listener(address: &str, stop: &Arc<AtomicBool>, tx: &WakingSender<IngestMessage>) {
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            let _ = tx.send(IngestMessage::Status(ListenerStatus::Failed(format!(
+                "Could not listen on {address}: {error}"
+            ))));
+            return;
+        }
+    };
+
+    let local_address = match listener.local_addr() {
+        Ok(local_address) => local_address.to_string(),
+        Err(error) => {
+            let _ = tx.send(IngestMessage::Status(ListenerStatus::Failed(format!(
+                "Could not resolve listener address: {error}"
+            ))));
+            return;
+        }
+    };
+
+    if let Err(error) = listener.set_nonblocking(true) {
+        let _ = tx.send(IngestMessage::Status(ListenerStatus::Failed(format!(
+            "Could not configure listener: {error}"
+        ))));
+        return;
+    }
+
+    let clients = Arc::new(AtomicUsize::new(0));
+    send_status(tx, &local_address, 0);
+
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if stream.set_nonblocking(false).is_err()
+                    || stream.set_read_timeout(Some(CLIENT_READ_TIMEOUT)).is_err()
+                {
+                    continue;
+                }
+                spawn_client(stream, &local_address, &clients, stop, tx);
+            }
+            // Non-blocking accept: `WouldBlock` just means no pending client.
+            Err(_) => thread::sleep(ACCEPT_POLL_INTERVAL),
+        }
+    }
+
+    let _ = tx.send(IngestMessage::Status(ListenerStatus::Stopped));
+}
+
+fn run_local_listener(name: &str, stop: &Arc<AtomicBool>, tx: &WakingSender<IngestMessage>) {
+    let endpoint = describe_endpoint(name);
+    let listener = match endpoint_name(name).and_then(|socket_name| {
+        ListenerOptions::new()
+            .name(socket_name)
+            .nonblocking(ListenerNonblockingMode::Accept)
+            .try_overwrite(true)
+            .create_sync()
+    }) {
+        Ok(listener) => listener,
+        Err(error) => {
+            let _ = tx.send(IngestMessage::Status(ListenerStatus::Failed(format!(
+                "Could not listen on {endpoint}: {error}"
+            ))));
+            return;
+        }
+    };
+
+    let clients = Arc::new(AtomicUsize::new(0));
+    send_status(tx, &endpoint, 0);
+
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok(stream) => {
+                if stream.set_recv_timeout(Some(CLIENT_READ_TIMEOUT)).is_err() {
+                    continue;
+                }
+                spawn_client(stream, &endpoint, &clients, stop, tx);
+            }
+            Err(_) => thread::sleep(ACCEPT_POLL_INTERVAL),
+        }
+    }
+
+    let _ = tx.send(IngestMessage::Status(ListenerStatus::Stopped));
+}
+
+fn send_status(tx: &WakingSender<IngestMessage>, address: &str, clients: usize) {
+    let _ = tx.send(IngestMessage::Status(ListenerStatus::Listening {
+        address: address.to_string(),
+        clients,
+    }));
+}
+
+/// Reads one client on its own thread, keeping the listener's client count current.
+fn spawn_client<R: Read + Send + 'static>(
+    stream: R,
+    address: &str,
+    clients: &Arc<AtomicUsize>,
+    stop: &Arc<AtomicBool>,
+    tx: &WakingSender<IngestMessage>,
+) {
+    let count = clients.fetch_add(1, Ordering::Relaxed) + 1;
+    send_status(tx, address, count);
+
+    let address = address.to_string();
+    let client_stop = Arc::clone(stop);
+    let client_count = Arc::clone(clients);
+    let client_tx = tx.clone();
+    thread::spawn(move || {
+        read_client(stream, &client_stop, &client_tx);
+        let remaining = client_count.fetch_sub(1, Ordering::Relaxed) - 1;
+        if !client_stop.load(Ordering::Relaxed) {
+            send_status(&client_tx, &address, remaining);
+        }
+    });
+}
+
+fn run_stream_client(url: &str, stop: &Arc<AtomicBool>, tx: &WakingSender<IngestMessage>) {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(STREAM_CONNECT_TIMEOUT)
+        .timeout_read(STREAM_IDLE_TIMEOUT)
+        .build();
+    let mut backoff = STREAM_INITIAL_BACKOFF;
+    let mut last_event_id: Option<String> = None;
+
+    while !stop.load(Ordering::Relaxed) {
+        let mut request = agent
+            .get(url)
+            .set("Accept", "text/event-stream, application/x-ndjson");
+        if let Some(id) = &last_event_id {
+            request = request.set("Last-Event-ID", id);
+        }
+
+        let error = match request.call() {
+            Ok(response) => {
+                backoff = STREAM_INITIAL_BACKOFF;
+                let _ = tx.send(IngestMessage::Status(ListenerStatus::Connected {
+                    address: url.to_string(),
+                }));
+                let is_sse = response.content_type() == "text/event-stream";
+                let reader = BufReader::new(response.into_reader());
+                match read_stream(reader, is_sse, &mut last_event_id, &mut backoff, stop, tx) {
+                    Ok(()) => "Stream closed by server".to_string(),
+                    Err(error) => error.to_string(),
+                }
+            }
+            Err(ureq::Error::Status(code, _)) => format!("HTTP status {code}"),
+            Err(error) => error.to_string(),
+        };
+
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let _ = tx.send(IngestMessage::Status(ListenerStatus::Reconnecting {
+            address: url.to_string(),
+            retry_in: backoff,
+            error,
+        }));
+
+        let resume_at = Instant::now() + backoff;
+        while Instant::now() < resume_at {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            thread::sleep(ACCEPT_POLL_INTERVAL);
+        }
+        backoff = (backoff * 2).min(STREAM_MAX_BACKOFF);
+    }
+
+    let _ = tx.send(IngestMessage::Status(ListenerStatus::Stopped));
+}
+
+fn read_stream<R: BufRead>(
+    mut reader: R,
+    is_sse: bool,
+    last_event_id: &mut Option<String>,
+    backoff: &mut Duration,
+    stop: &Arc<AtomicBool>,
+    tx: &WakingSender<IngestMessage>,
+) -> io::Result<()> {
+    let mut parser = SseParser::default();
+    let mut buffer = Vec::new();
+
+    loop {
+        buffer.clear();
+        if read_bounded_line(&mut reader, &mut buffer)? == 0 {
+            return Ok(());
+        }
+        if stop.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&buffer);
+        let line = text.strip_suffix('\n').unwrap_or(&text).to_string();
+
+        let data = if is_sse {
+            let data = parser.push_line(&line);
+            if parser.data_bytes > MAX_EVENT_BYTES {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("event data longer than {MAX_EVENT_BYTES} bytes"),
+                ));
+            }
+            let Some(data) = data else {
+                continue;
+            };
+            if let Some(id) = &parser.last_event_id {
+                *last_event_id = Some(id.clone());
+            }
+            if let Some(retry) = parser.retry {
+                *backoff = retry.clamp(STREAM_INITIAL_BACKOFF, STREAM_MAX_BACKOFF);
+            }
+            data
+        } else if line.trim().is_empty() {
+            continue;
+        } else {
+            line
+        };
+
+        let message = match ActionEvent::parse_line(&data) {
+            Ok(event) => IngestMessage::Action(event),
+            Err(error) => IngestMessage::Rejected(error),
+        };
+        if tx.send(message).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Appends the rest of the current line, newline included, to `line`,
+/// which may already hold its start; fails once the line is longer than
+/// [`MAX_LINE_BYTES`].
+fn read_bounded_line<R: BufRead>(reader: &mut R, line: &mut Vec<u8>) -> io::Result<usize> {
+    // One byte past the limit tells an oversized line from a full one.
+    let limit = (MAX_LINE_BYTES + 1).saturating_sub(line.len()) as u64;
+    let read = reader.take(limit).read_until(b'\n', line)?;
+    if line.len() > MAX_LINE_BYTES && !line.ends_with(b"\n") {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("line longer than {MAX_LINE_BYTES} bytes"),
+        ));
+    }
+    Ok(read)
+}
+
+/// Incremental Server-Sent Events parser.
+#[derive(Debug, Default)]
+struct SseParser {
+    data: Vec<String>,
+    /// Bytes in `data`.
+    data_bytes: usize,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseParser {
+    /// Feeds one line; returns the event data when a blank line completes an event.
+    fn push_line(&mut self, line: &str) -> Option<String> {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            if self.data.is_empty() {
+                return None;
+            }
+            let data = self.data.join("\n");
+            self.data.clear();
+            self.data_bytes = 0;
+            return Some(data);
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => {
+                self.data_bytes += value.len();
+                self.data.push(value.to_string());
+            }
+            "id" => self.last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(millis) = value.parse::<u64>() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Reads lines until EOF or stop; `stream` must already have a read timeout.
+fn read_client<R: Read>(stream: R, stop: &Arc<AtomicBool>, tx: &WakingSender<IngestMessage>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        match read_bounded_line(&mut reader, &mut line) {
+            Ok(0) => return,
+            Err(error) if error.kind() == ErrorKind::InvalidData => {
+                let _ = tx.send(IngestMessage::Rejected(format!(
+                    "Action {error}; client disconnected"
+                )));
+                return;
+            }
+            Ok(_) => {
+                let text = String::from_utf8_lossy(&line);
+                let message = if text.trim().is_empty() {
+                    None
+                } else {
+                    Some(match ActionEvent::parse_line(&text) {
+                        Ok(event) => IngestMessage::Action(event),
+                        Err(error) => IngestMessage::Rejected(error),
+                    })
+                };
+                if let Some(message) = message {
+                    if tx.send(message).is_err() {
+                        return;
+                    }
+                }
+                line.clear();
+            }
+            // A timeout keeps any partial line buffered; keep reading.
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::ActionKind;
+    use crate::ipc::IpcClient;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    #[test]
+    fn sse_parser_joins_data_lines_and_tracks_fields() {
+        let mut parser = SseParser::default();
+        let lines = [
+            ": keep-alive",
+            "id: 7",
+            "retry: 2500",
+            "data: {\"kind\":\"read\",",
+            "data: \"target_path\":\"a\"}",
+            "",
+        ];
+
+        let mut dispatched = Vec::new();
+        for line in lines {
+            dispatched.extend(parser.push_line(line));
+        }
+
+        assert_eq!(
+            dispatched,
+            vec!["{\"kind\":\"read\",\n\"target_path\":\"a\"}"]
+        );
+        assert_eq!(parser.last_event_id.as_deref(), Some("7"));
+        assert_eq!(parser.retry, Some(Duration::from_millis(2500)));
+        assert!(ActionEvent::parse_line(&dispatched[0]).is_ok());
+    }
+
+    #[test]
+    fn client_sending_an_endless_line_is_disconnected() {
+        let mut input = b"Write\ta.txt\n".to_vec();
+        input.extend(std::iter::repeat_n(b'x', MAX_LINE_BYTES + 10));
+        input.extend(b"\nWrite\tb.txt\n");
+        let (tx, rx) = wake::channel(Waker::default());
+
+        read_client(
+            io::Cursor::new(input),
+            &Arc::new(AtomicBool::new(false)),
+            &tx,
+        );
+        drop(tx);
+
+        let messages: Vec<IngestMessage> = rx.iter().collect();
+        assert_eq!(messages.len(), 2);
+        let IngestMessage::Action(event) = &messages[0] else {
+            panic!("first line was not accepted");
+        };
+        assert_eq!(event.target_path, PathBuf::from("a.txt"));
+        assert!(matches!(messages[1], IngestMessage::Rejected(_)));
+    }
+
+    #[test]
+    fn stream_with_an_endless_line_or_event_is_dropped() {
+        let run = |input: Vec<u8>| {
+            let (tx, rx) = wake::channel(Waker::default());
+            let mut backoff = STREAM_INITIAL_BACKOFF;
+            let result = read_stream(
+                io::Cursor::new(input),
+                true,
+                &mut None,
+                &mut backoff,
+                &Arc::new(AtomicBool::new(false)),
+                &tx,
+            );
+            drop(tx);
+            (result, rx.iter().count())
+        };
+
+        let mut endless_line = b"data: Write\ta.txt\n\ndata: ".to_vec();
+        endless_line.extend(std::iter::repeat_n(b'x', MAX_LINE_BYTES + 10));
+        endless_line.extend(b"\n\n");
+        let (result, messages) = run(endless_line);
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(messages, 1);
+
+        let data_line = format!("data: {}\n", "x".repeat(1000));
+        let endless_event = data_line.repeat(MAX_EVENT_BYTES / 1000 + 1).into_bytes();
+        let (result, messages) = run(endless_event);
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(messages, 0);
+    }
+
+    #[test]
+    fn server_retry_is_clamped_to_the_backoff_range() {
+        let backoff_after = |input: &str| {
+            let (tx, _rx) = wake::channel(Waker::default());
+            let mut backoff = STREAM_INITIAL_BACKOFF;
+            read_stream(
+                io::Cursor::new(input.as_bytes()),
+                true,
+                &mut None,
+                &mut backoff,
+                &Arc::new(AtomicBool::new(false)),
+                &tx,
+            )
+            .unwrap();
+            backoff
+        };
+
+        assert_eq!(
+            backoff_after("retry: 0\ndata: Write\ta.txt\n\n"),
+            STREAM_INITIAL_BACKOFF
+        );
+        assert_eq!(
+            backoff_after("retry: 86400000\ndata: Write\ta.txt\n\n"),
+            STREAM_MAX_BACKOFF
+        );
+        assert_eq!(
+            backoff_after("retry: 5000\ndata: Write\ta.txt\n\n"),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn stream_client_reads_server_sent_events() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/events", server.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = server.accept().unwrap();
+            let mut request = [0_u8; 1024];
+            let _ = stream.read(&mut request);
+            let body = "data: {\"kind\":\"delete\",\"target_path\":\"x\"}\n\n";
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+        });
+
+        let listener = ActionListener::spawn_stream(&url, Waker::default());
+        let event = loop {
+            match listener.receiver().recv_timeout(Duration::from_secs(5)) {
+                Ok(IngestMessage::Action(event)) => break event,
+                Ok(_) => continue,
+                Err(error) => panic!("no action received: {error}"),
+            }
+        };
+        assert_eq!(event.kind, ActionKind::Delete);
+    }
+
+    #[test]
+    fn local_listener_receives_events_from_ipc_client() {
+        let name = format!("tree-map-base-test-{}", std::process::id());
+        let listener = ActionListener::spawn_local(&name, Waker::default());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut client = loop {
+            match IpcClient::connect(&name) {
+                Ok(client) => break client,
+                Err(error) if Instant::now() > deadline => panic!("{error}"),
+                Err(_) => thread::sleep(Duration::from_millis(20)),
+            }
+        };
+        client
+            .send(&ActionEvent::new(
+                ActionKind::Write,
+                PathBuf::from("a/b.txt"),
+            ))
+            .unwrap();
+
+        let event = loop {
+            match listener.receiver().recv_timeout(Duration::from_secs(5)) {
+                Ok(IngestMessage::Action(event)) => break event,
+                Ok(_) => continue,
+                Err(error) => panic!("no action received: {error}"),
+            }
+        };
+        assert_eq!(event.kind, ActionKind::Write);
+        assert_eq!(event.target_path, PathBuf::from("a/b.txt"));
+    }
+}
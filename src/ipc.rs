@@ -0,0 +1,77 @@
+//! Local IPC endpoint naming and a small client for sending action events.
+//!
+//! The endpoint is a named pipe (`\\.\pipe\<name>`) on Windows and a Unix
+//! domain socket elsewhere. A bare name on Unix resolves to
+//! `<temp dir>/<name>.sock`; a name containing a path separator is used as-is.
+
+use crate::action::ActionEvent;
+use interprocess::local_socket::prelude::*;
+use interprocess::local_socket::{GenericFilePath, GenericNamespaced, Name, Stream};
+use std::env;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+pub const DEFAULT_IPC_NAME: &str = "tree-map-base";
+
+/// Resolves an endpoint name to the platform socket name.
+pub fn endpoint_name(name: &str) -> io::Result<Name<'static>> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "IPC endpoint name is empty",
+        ));
+    }
+
+    if cfg!(windows) {
+        return name.to_string().to_ns_name::<GenericNamespaced>();
+    }
+
+    socket_path(name).to_fs_name::<GenericFilePath>()
+}
+
+/// Human-readable endpoint location for status labels.
+pub fn describe_endpoint(name: &str) -> String {
+    let name = name.trim();
+    if cfg!(windows) {
+        format!(r"\\.\pipe\{name}")
+    } else {
+        socket_path(name).display().to_string()
+    }
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    if name.contains('/') {
+        PathBuf::from(name)
+    } else {
+        env::temp_dir().join(format!("{name}.sock"))
+    }
+}
+
+/// Client side of the local IPC endpoint.
+pub struct IpcClient {
+    stream: Stream,
+}
+
+impl IpcClient {
+    pub fn connect(name: &str) -> Result<Self, String> {
+        let socket_name =
+            endpoint_name(name).map_err(|error| format!("Invalid IPC endpoint {name}: {error}"))?;
+        let stream = Stream::connect(socket_name).map_err(|error| {
+            format!("Could not connect to {}: {error}", describe_endpoint(name))
+        })?;
+
+        Ok(Self { stream })
+    }
+
+    pub fn send(&mut self, event: &ActionEvent) -> Result<(), String> {
+        let mut line = serde_json::to_string(event)
+            .map_err(|error| format!("Could not encode action event: {error}"))?;
+        line.push('\n');
+
+        self.stream
+            .write_all(line.as_bytes())
+            .and_then(|()| self.stream.flush())
+            .map_err(|error| format!("Could not send action event: {error}"))
+    }
+}
@@ -0,0 +1,424 @@
+//! User-defined external commands ("Open in VS Code", "Open terminal here")
+//! offered in the treemap's context menu.
+//!
+//! One command per line as `Name = program args...`. Arguments are split on
+//! whitespace, with double quotes grouping; `{path}` is the clicked entry,
+//! `{dir}` the folder itself or the file's folder, and `{name}` its file
+//! name. Placeholders are filled in one pass, so a path that itself contains
+//! `{name}` is passed on unchanged. With several entries selected the command
+//! runs once per entry, unless it has a `{paths}` argument, which expands to
+//! all of them.
+//!
+//! Commands are started directly (no shell) and not waited for. On Windows a
+//! program named without an extension is looked up with `PATHEXT`, so `code`
+//! finds `code.cmd`, which then runs through `cmd /C` with its arguments
+//! escaped by the standard library. Lines
+//! starting with `>` are actions instead: they are waited for on a background
+//! thread and their exit status and output go to the command log.
+
+use crate::wake::{self, Waker};
+use std::borrow::Cow;
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+#[cfg(windows)]
+pub const DEFAULT_EXTERNAL_COMMANDS: &str = "\
+# Name = program arguments; {path}, {dir}, {name} and {paths} are replaced
+# Lines starting with > show their output in the command log
+Open in VS Code = code {path}
+Open terminal here = wt -d {dir}
+Show in Explorer = explorer /select,{path}
+> Compute SHA-256 = certutil -hashfile {path} SHA256
+";
+
+#[cfg(target_os = "macos")]
+pub const DEFAULT_EXTERNAL_COMMANDS: &str = "\
+# Name = program arguments; {path}, {dir}, {name} and {paths} are replaced
+# Lines starting with > show their output in the command log
+Open in VS Code = code {path}
+Open terminal here = open -a Terminal {dir}
+Reveal in Finder = open -R {path}
+> Compute SHA-256 = shasum -a 256 {paths}
+";
+
+#[cfg(not(any(windows, target_os = "macos")))]
+pub const DEFAULT_EXTERNAL_COMMANDS: &str = "\
+# Name = program arguments; {path}, {dir}, {name} and {paths} are replaced
+# Lines starting with > show their output in the command log
+Open in VS Code = code {path}
+Open terminal here = x-terminal-emulator --working-directory {dir}
+Open folder = xdg-open {dir}
+> Compute SHA-256 = sha256sum {paths}
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCommand {
+    pub name: String,
+    /// Waited for, with its output shown in the command log.
+    pub logged: bool,
+    program: String,
+    args: Vec<String>,
+}
+
+/// How one run of a logged command ended.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub name: String,
+    pub command_line: Vec<String>,
+    /// The exit code (`None` when killed by a signal), or why the command
+    /// could not be started.
+    pub status: Result<Option<i32>, String>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ExternalCommand {
+    /// Parses the command list, returning the valid commands and one error
+    /// per rejected line.
+    pub fn parse_list(text: &str) -> (Vec<Self>, Vec<String>) {
+        let mut commands = Vec::new();
+        let mut errors = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (logged, line) = match line.strip_prefix('>') {
+                Some(rest) => (true, rest.trim_start()),
+                None => (false, line),
+            };
+            let Some((name, command)) = line.split_once('=') else {
+                errors.push(format!("Line {}: expected `Name = command`", index + 1));
+                continue;
+            };
+            let mut words = split_words(command);
+            if name.trim().is_empty() || words.is_empty() {
+                errors.push(format!("Line {}: missing name or command", index + 1));
+                continue;
+            }
+            commands.push(Self {
+                name: name.trim().to_string(),
+                logged,
+                program: words.remove(0),
+                args: words,
+            });
+        }
+        (commands, errors)
+    }
+
+    /// The program and arguments with placeholders filled in for `path`.
+    pub fn command_line(&self, path: &Path, is_dir: bool) -> Vec<String> {
+        let dir = if is_dir {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let values = [
+            ("path", path.to_string_lossy()),
+            ("dir", dir.to_string_lossy()),
+            ("name", name),
+        ];
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|word| expand_placeholders(word, &values))
+            .collect()
+    }
+
+    /// One command line per `(path, is_dir)` target, or a single one for all
+    /// of them when the command has a `{paths}` argument.
+    pub fn command_lines(&self, targets: &[(PathBuf, bool)]) -> Vec<Vec<String>> {
+        let takes_all = self.args.iter().any(|word| word == "{paths}");
+        match targets.first() {
+            Some((path, is_dir)) if takes_all => vec![self
+                .command_line(path, *is_dir)
+                .into_iter()
+                .flat_map(|word| {
+                    if word == "{paths}" {
+                        targets
+                            .iter()
+                            .map(|(path, _)| path.to_string_lossy().into_owned())
+                            .collect()
+                    } else {
+                        vec![word]
+                    }
+                })
+                .collect()],
+            _ => targets
+                .iter()
+                .map(|(path, is_dir)| self.command_line(path, *is_dir))
+                .collect(),
+        }
+    }
+
+    /// Starts the command for every target without waiting for it.
+    pub fn run(&self, targets: &[(PathBuf, bool)]) -> Result<(), String> {
+        for command_line in self.command_lines(targets) {
+            process(&command_line)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|error| format!("Could not start {}: {error}", command_line[0]))?;
+        }
+        Ok(())
+    }
+
+    /// Runs the command for every target one after another on a background
+    /// thread, sending each run's output as it finishes.
+    pub fn spawn_logged(
+        &self,
+        targets: &[(PathBuf, bool)],
+        waker: Waker,
+    ) -> Receiver<CommandOutput> {
+        let command_lines = self.command_lines(targets);
+        let name = self.name.clone();
+        let (tx, rx) = wake::channel(waker);
+        thread::spawn(move || {
+            for command_line in command_lines {
+                let output = process(&command_line).stdin(Stdio::null()).output();
+                let (status, stdout, stderr) = match output {
+                    Ok(output) => (
+                        Ok(output.status.code()),
+                        String::from_utf8_lossy(&output.stdout).into_owned(),
+                        String::from_utf8_lossy(&output.stderr).into_owned(),
+                    ),
+                    Err(error) => (
+                        Err(format!("Could not start {}: {error}", command_line[0])),
+                        String::new(),
+                        String::new(),
+                    ),
+                };
+                let sent = tx.send(CommandOutput {
+                    name: name.clone(),
+                    command_line,
+                    status,
+                    stdout,
+                    stderr,
+                });
+                if sent.is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+}
+
+/// Shows `path` in the system file manager: a folder is opened, a file is
+/// selected in its folder where the platform supports it (Windows, macOS)
+/// and otherwise its folder is opened.
+pub fn reveal_in_file_manager(path: &Path, is_dir: bool) -> Result<(), String> {
+    if is_dir {
+        return open_folder(path);
+    }
+    if cfg!(windows) {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        start_detached("explorer", &[select.as_os_str()])
+    } else if cfg!(target_os = "macos") {
+        start_detached("open", &["-R".as_ref(), path.as_os_str()])
+    } else {
+        open_folder(path.parent().unwrap_or(path))
+    }
+}
+
+/// Opens `folder` in the system file manager.
+pub fn open_folder(folder: &Path) -> Result<(), String> {
+    let program = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    start_detached(program, &[folder.as_os_str()])
+}
+
+fn start_detached(program: &str, args: &[&std::ffi::OsStr]) -> Result<(), String> {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+        .map_err(|error| format!("Could not start {program}: {error}"))
+}
+
+/// Replaces each `{key}` in `word` with its value, scanning the word once so
+/// values are never expanded again. Unknown placeholders are kept as typed.
+fn expand_placeholders(word: &str, values: &[(&str, Cow<'_, str>)]) -> String {
+    let mut expanded = String::with_capacity(word.len());
+    let mut rest = word;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find('}').and_then(|end| {
+            let key = &placeholder[1..end];
+            values
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                expanded.push_str(value);
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                expanded.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// The process for a command line, with the program resolved as described
+/// in the module docs.
+fn process(command_line: &[String]) -> Command {
+    let program = &command_line[0];
+    let resolved = if cfg!(windows) && Path::new(program).extension().is_none() {
+        let extensions = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into());
+        env::var_os("PATH").and_then(|paths| find_on_path(program, &paths, &extensions))
+    } else {
+        None
+    };
+    let mut command = Command::new(resolved.unwrap_or_else(|| PathBuf::from(program)));
+    command.args(&command_line[1..]);
+    command
+}
+
+/// The first `PATH` entry holding `program` with one of the `;`-separated
+/// `extensions`, for bare program names only.
+fn find_on_path(program: &str, paths: &OsStr, extensions: &str) -> Option<PathBuf> {
+    if Path::new(program).components().count() != 1 {
+        return None;
+    }
+    env::split_paths(paths).find_map(|dir| {
+        extensions
+            .split(';')
+            .filter(|extension| !extension.is_empty())
+            .map(|extension| dir.join(format!("{program}{}", extension.to_ascii_lowercase())))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_word = false;
+    for ch in text.chars() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_word = true;
+            }
+            ch if ch.is_whitespace() && !in_quotes => {
+                if has_word {
+                    words.push(std::mem::take(&mut current));
+                    has_word = false;
+                }
+            }
+            ch => {
+                current.push(ch);
+                has_word = true;
+            }
+        }
+    }
+    if has_word {
+        words.push(current);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_fills_placeholders() {
+        let (commands, errors) = ExternalCommand::parse_list(
+            "# comment\nEdit = \"/opt/My Editor/edit\" --goto {path}\nbroken line\nShell = term -d {dir} -t \"{name}\"\n",
+        );
+
+        assert_eq!(errors, vec!["Line 3: expected `Name = command`"]);
+        assert_eq!(commands[0].name, "Edit");
+        assert_eq!(
+            commands[0].command_line(Path::new("/srv/a b.txt"), false),
+            vec!["/opt/My Editor/edit", "--goto", "/srv/a b.txt"]
+        );
+        assert_eq!(
+            commands[1].command_line(Path::new("/srv/a b.txt"), false),
+            vec!["term", "-d", "/srv", "-t", "a b.txt"]
+        );
+        assert_eq!(
+            commands[1].command_line(Path::new("/srv/logs"), true)[2],
+            "/srv/logs"
+        );
+    }
+
+    #[test]
+    fn logged_commands_and_multiple_targets() {
+        let (commands, errors) =
+            ExternalCommand::parse_list("> Hash = sha256sum {paths}\nOpen = open {path}\n");
+        assert!(errors.is_empty());
+        assert!(commands[0].logged && !commands[1].logged);
+
+        let targets = [
+            (PathBuf::from("/a.txt"), false),
+            (PathBuf::from("/b.txt"), false),
+        ];
+        assert_eq!(
+            commands[0].command_lines(&targets),
+            vec![vec!["sha256sum", "/a.txt", "/b.txt"]]
+        );
+        assert_eq!(
+            commands[1].command_lines(&targets),
+            vec![vec!["open", "/a.txt"], vec!["open", "/b.txt"]]
+        );
+    }
+
+    #[test]
+    fn placeholders_expand_in_one_pass() {
+        let (commands, _) = ExternalCommand::parse_list(
+            "Open = open {path} {dir}/{name} {other}
+",
+        );
+        assert_eq!(
+            commands[0].command_line(Path::new("/srv/{name}/{dir}.txt"), false),
+            vec![
+                "open",
+                "/srv/{name}/{dir}.txt",
+                "/srv/{name}/{dir}.txt",
+                "{other}"
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_programs_are_found_with_path_extensions() {
+        let dir = env::temp_dir().join(format!("tmb-launch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("code.cmd"), b"").unwrap();
+        let paths = env::join_paths([Path::new("/nonexistent"), &dir]).unwrap();
+
+        assert_eq!(
+            find_on_path("code", &paths, ".EXE;.CMD"),
+            Some(dir.join("code.cmd"))
+        );
+        assert_eq!(find_on_path("code", &paths, ".EXE"), None);
+        assert_eq!(find_on_path("bin/code", &paths, ".CMD"), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
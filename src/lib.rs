@@ -0,0 +1,22 @@
+//! Scanning, tree model, treemap layout and formatting behind the
+//! `tree-map-base` desktop app, usable without the GUI: build with
+//! `default-features = false` to leave out eframe and the app's integrations.
+//! `tmb-scan` is a small command-line front end over the same code.
+
+pub mod action;
+pub mod analysis;
+pub mod audit;
+pub mod category;
+pub mod exclude;
+pub mod format;
+pub mod gateway;
+pub mod links;
+pub mod memory;
+pub mod model;
+pub mod openclaw;
+pub mod owners;
+pub mod portable;
+pub mod scanner;
+pub mod snapshot;
+pub mod treemap;
+pub mod wake;
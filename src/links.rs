@@ -0,0 +1,114 @@
+//! Symbolic links found during a scan, with where they point.
+//!
+//! The scanner never follows links, so a link is a small leaf in the tree
+//! and its target's bytes are counted where the target lives (or not at all
+//! when it is outside the root). Windows junctions report as symlinks and
+//! are listed the same way.
+
+use crate::gateway;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Links kept per scan.
+pub const MAX_SYMLINKS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkStatus {
+    /// The target exists inside the scanned root.
+    Inside,
+    /// The target exists outside the scanned root.
+    Outside,
+    /// Nothing exists at the target.
+    Broken,
+}
+
+impl LinkStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Inside => "inside root",
+            Self::Outside => "outside root",
+            Self::Broken => "broken",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymlinkEntry {
+    pub path: PathBuf,
+    /// The target as stored in the link, possibly relative.
+    pub target: PathBuf,
+    /// The absolute target; below the root path as scanned when inside it,
+    /// so it matches paths in the tree.
+    pub resolved: PathBuf,
+    pub status: LinkStatus,
+}
+
+/// Reads the link at `path`; `canonical_root` is the canonicalized scan
+/// root, used to recognize targets reached through other links.
+pub fn inspect_link(path: &Path, root: &Path, canonical_root: &Path) -> Option<SymlinkEntry> {
+    let target = gateway::read_link(path).ok()?;
+    let joined = normalize(&path.parent().unwrap_or(path).join(&target));
+    let (resolved, status) = match gateway::canonicalize(path) {
+        Ok(canonical) => match canonical.strip_prefix(canonical_root) {
+            Ok(relative) => (root.join(relative), LinkStatus::Inside),
+            Err(_) if joined.starts_with(root) => (joined, LinkStatus::Inside),
+            Err(_) => (canonical, LinkStatus::Outside),
+        },
+        Err(_) => (joined, LinkStatus::Broken),
+    };
+    Some(SymlinkEntry {
+        path: path.to_path_buf(),
+        target,
+        resolved,
+        status,
+    })
+}
+
+/// Removes `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn classifies_link_targets() {
+        let dir = std::env::temp_dir().join(format!("tmb-links-{}", std::process::id()));
+        let root = dir.join("root");
+        fs::create_dir_all(root.join("data")).unwrap();
+        fs::write(root.join("data/file"), b"x").unwrap();
+        symlink("data/file", root.join("inside")).unwrap();
+        symlink(&dir, root.join("outside")).unwrap();
+        symlink("../missing", root.join("broken")).unwrap();
+        let canonical_root = fs::canonicalize(&root).unwrap();
+
+        let inside = inspect_link(&root.join("inside"), &root, &canonical_root).unwrap();
+        assert_eq!(inside.status, LinkStatus::Inside);
+        assert_eq!(inside.target, PathBuf::from("data/file"));
+        assert_eq!(inside.resolved, root.join("data/file"));
+
+        let outside = inspect_link(&root.join("outside"), &root, &canonical_root).unwrap();
+        assert_eq!(outside.status, LinkStatus::Outside);
+
+        let broken = inspect_link(&root.join("broken"), &root, &canonical_root).unwrap();
+        assert_eq!(broken.status, LinkStatus::Broken);
+        assert_eq!(broken.resolved, dir.join("missing"));
+
+        assert!(inspect_link(&root.join("data/file"), &root, &canonical_root).is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
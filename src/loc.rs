@@ -0,0 +1,342 @@
+//! Optional lines-of-code count for source trees: every file with a known
+//! source extension is read on worker threads and split into code, comment
+//! and blank lines, so the treemap can be sized by code instead of bytes.
+//!
+//! Counting is line based, like tokei's default mode: a line holding both
+//! code and a comment counts as code, and comment markers inside string
+//! literals are not recognized.
+
+use crate::analysis::collect_files;
+use crate::gateway;
+use crate::model::Node;
+use crate::treemap::SizeMetric;
+use crate::wake::{self, Waker};
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+/// Files larger than this are assumed to be generated or data and skipped.
+const MAX_SOURCE_BYTES: u64 = 8 * 1024 * 1024;
+
+pub struct SourceLanguage {
+    pub name: &'static str,
+    extensions: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+}
+
+const C_BLOCK: Option<(&str, &str)> = Some(("/*", "*/"));
+
+pub const LANGUAGES: &[SourceLanguage] = &[
+    SourceLanguage {
+        name: "Rust",
+        extensions: &["rs"],
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "C/C++",
+        extensions: &["c", "h", "cc", "cpp", "cxx", "hpp", "hh", "hxx"],
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "C#",
+        extensions: &["cs"],
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "Go",
+        extensions: &["go"],
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "Java/Kotlin",
+        extensions: &["java", "kt", "kts", "scala"],
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "JavaScript/TypeScript",
+        extensions: &["js", "jsx", "mjs", "cjs", "ts", "tsx"],
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "Swift",
+        extensions: &["swift"],
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "CSS",
+        extensions: &["css", "scss", "less"],
+        line_comments: &[],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "Python",
+        extensions: &["py", "pyi"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+    SourceLanguage {
+        name: "Ruby",
+        extensions: &["rb"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+    SourceLanguage {
+        name: "Shell",
+        extensions: &["sh", "bash", "zsh", "fish", "ps1"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+    SourceLanguage {
+        name: "PHP",
+        extensions: &["php"],
+        line_comments: &["//", "#"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "Lua",
+        extensions: &["lua"],
+        line_comments: &["--"],
+        block_comment: Some(("--[[", "]]")),
+    },
+    SourceLanguage {
+        name: "SQL",
+        extensions: &["sql"],
+        line_comments: &["--"],
+        block_comment: C_BLOCK,
+    },
+    SourceLanguage {
+        name: "HTML/XML",
+        extensions: &["html", "htm", "xml", "svg", "vue"],
+        line_comments: &[],
+        block_comment: Some(("<!--", "-->")),
+    },
+    SourceLanguage {
+        name: "TOML/YAML",
+        extensions: &["toml", "yaml", "yml"],
+        line_comments: &["#"],
+        block_comment: None,
+    },
+];
+
+pub fn language_of(path: &Path) -> Option<&'static SourceLanguage> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|language| language.extensions.contains(&extension.as_str()))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCounts {
+    pub code: u64,
+    pub comments: u64,
+    pub blanks: u64,
+}
+
+impl LineCounts {
+    fn add(&mut self, other: LineCounts) {
+        self.code += other.code;
+        self.comments += other.comments;
+        self.blanks += other.blanks;
+    }
+}
+
+pub fn count_lines(text: &str, language: &SourceLanguage) -> LineCounts {
+    let mut counts = LineCounts::default();
+    let mut in_block = false;
+    for line in text.lines() {
+        let mut rest = line.trim();
+        if rest.is_empty() && !in_block {
+            counts.blanks += 1;
+            continue;
+        }
+        let mut has_code = false;
+        while !rest.is_empty() {
+            if in_block {
+                let (_, end) = language.block_comment.unwrap_or(("", ""));
+                match rest.find(end) {
+                    Some(index) => {
+                        in_block = false;
+                        rest = rest[index + end.len()..].trim_start();
+                    }
+                    None => rest = "",
+                }
+                continue;
+            }
+            if language
+                .line_comments
+                .iter()
+                .any(|marker| rest.starts_with(marker))
+                && !language
+                    .block_comment
+                    .is_some_and(|(start, _)| rest.starts_with(start))
+            {
+                break;
+            }
+            match language.block_comment {
+                Some((start, _)) if rest.starts_with(start) => {
+                    in_block = true;
+                    rest = &rest[start.len()..];
+                }
+                Some((start, _)) => match rest.find(start) {
+                    Some(index) => {
+                        has_code = true;
+                        in_block = true;
+                        rest = &rest[index + start.len()..];
+                    }
+                    None => {
+                        has_code = true;
+                        rest = "";
+                    }
+                },
+                None => {
+                    has_code = true;
+                    rest = "";
+                }
+            }
+        }
+        if has_code {
+            counts.code += 1;
+        } else {
+            counts.comments += 1;
+        }
+    }
+    counts
+}
+
+#[derive(Debug)]
+pub struct LocReport {
+    /// The scan generation the counted tree belonged to.
+    pub scan_generation: u64,
+    pub files: HashMap<PathBuf, LineCounts>,
+    /// Code lines per folder, including everything below it.
+    pub folders: HashMap<PathBuf, u64>,
+    /// Files and line counts per language, most code first.
+    pub languages: Vec<(&'static str, u64, LineCounts)>,
+    /// Code lines of the largest file.
+    pub max_file_code: u64,
+}
+
+impl LocReport {
+    /// Code lines of a file or folder, if it holds any counted source.
+    pub fn code_lines(&self, path: &Path) -> Option<u64> {
+        self.files
+            .get(path)
+            .map(|counts| counts.code)
+            .or_else(|| self.folders.get(path).copied())
+    }
+
+    /// A file's code lines from 0 to 1 on a log scale, relative to the
+    /// largest file.
+    pub fn heat(&self, path: &Path) -> Option<f32> {
+        let code = self.files.get(path)?.code;
+        Some((code as f32).ln_1p() / (self.max_file_code.max(1) as f32).ln_1p())
+    }
+}
+
+impl SizeMetric for LocReport {
+    fn file_weight(&self, file: &Node, _depth: usize) -> u64 {
+        self.files.get(&file.path).map_or(0, |counts| counts.code)
+    }
+}
+
+/// Counts every source file of `root` on a background thread.
+pub fn spawn_loc_count(root: &Node, scan_generation: u64, waker: Waker) -> Receiver<LocReport> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    let files: Vec<(PathBuf, &'static SourceLanguage)> = files
+        .into_iter()
+        .filter(|node| node.size > 0 && node.size <= MAX_SOURCE_BYTES)
+        .filter_map(|node| Some((node.path.clone(), language_of(&node.path)?)))
+        .collect();
+    let root_path = root.path.clone();
+    let (tx, rx) = wake::channel(waker);
+
+    thread::spawn(move || {
+        let counted: Vec<(PathBuf, &'static SourceLanguage, LineCounts)> = files
+            .into_par_iter()
+            .filter_map(|(path, language)| {
+                let bytes = gateway::read(&path).ok()?;
+                let counts = count_lines(&String::from_utf8_lossy(&bytes), language);
+                Some((path, language, counts))
+            })
+            .collect();
+
+        let mut folders: HashMap<PathBuf, u64> = HashMap::new();
+        let mut languages: HashMap<&'static str, (u64, LineCounts)> = HashMap::new();
+        for (path, language, counts) in &counted {
+            let entry = languages.entry(language.name).or_default();
+            entry.0 += 1;
+            entry.1.add(*counts);
+            for folder in path.ancestors().skip(1) {
+                *folders.entry(folder.to_path_buf()).or_default() += counts.code;
+                if folder == root_path {
+                    break;
+                }
+            }
+        }
+        let mut languages: Vec<_> = languages
+            .into_iter()
+            .map(|(name, (files, counts))| (name, files, counts))
+            .collect();
+        languages.sort_by_key(|(name, _, counts)| (Reverse(counts.code), *name));
+
+        let max_file_code = counted
+            .iter()
+            .map(|(_, _, counts)| counts.code)
+            .max()
+            .unwrap_or(0);
+        let _ = tx.send(LocReport {
+            scan_generation,
+            max_file_code,
+            files: counted
+                .into_iter()
+                .map(|(path, _, counts)| (path, counts))
+                .collect(),
+            folders,
+            languages,
+        });
+    });
+
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_code_comments_and_blanks() {
+        let rust = language_of(Path::new("src/Main.RS")).unwrap();
+        let text = "// header\n\nfn main() { /* inline */\n    /* block\n       still */ let x = 1;\n    /*\n    */\n}\n";
+        assert_eq!(
+            count_lines(text, rust),
+            LineCounts {
+                code: 3,
+                comments: 4,
+                blanks: 1,
+            }
+        );
+
+        let python = language_of(Path::new("tool.py")).unwrap();
+        assert_eq!(
+            count_lines("# comment\nimport os\n\n", python),
+            LineCounts {
+                code: 1,
+                comments: 1,
+                blanks: 1,
+            }
+        );
+        assert!(language_of(Path::new("photo.jpg")).is_none());
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+
+/// A small least-recently-used cache bounded by the total weight of its
+/// values. Lookups are linear, so it is meant for a handful of large entries.
+#[derive(Debug)]
+pub struct LruCache<K, V> {
+    /// Most recently used last.
+    entries: VecDeque<(K, V, usize)>,
+    max_weight: usize,
+    weight: usize,
+}
+
+impl<K: PartialEq, V> LruCache<K, V> {
+    pub fn new(max_weight: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            max_weight,
+            weight: 0,
+        }
+    }
+
+    /// Removes and returns the entry for `key`, if cached.
+    pub fn take(&mut self, key: &K) -> Option<V> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(entry_key, _, _)| entry_key == key)?;
+        let (_, value, weight) = self.entries.remove(index)?;
+        self.weight -= weight;
+        Some(value)
+    }
+
+    /// Stores `value` as the most recent entry, evicting the least recently
+    /// used ones until the total weight fits. A value heavier than the whole
+    /// budget is not kept.
+    pub fn insert(&mut self, key: K, value: V, weight: usize) {
+        let _ = self.take(&key);
+        if weight > self.max_weight {
+            return;
+        }
+
+        while self.weight + weight > self.max_weight {
+            let Some((_, _, evicted)) = self.entries.pop_front() else {
+                break;
+            };
+            self.weight -= evicted;
+        }
+        self.entries.push_back((key, value, weight));
+        self.weight += weight;
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, value, _)| value)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.weight = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used_by_weight() {
+        let mut cache = LruCache::new(10);
+        cache.insert("a", 1, 4);
+        cache.insert("b", 2, 4);
+        cache.insert("a", 1, 4);
+        cache.insert("c", 3, 4);
+
+        assert_eq!(cache.take(&"b"), None);
+        assert_eq!(cache.take(&"a"), Some(1));
+        assert_eq!(cache.take(&"a"), None);
+
+        cache.insert("huge", 9, 11);
+        assert_eq!(cache.take(&"huge"), None);
+        assert_eq!(cache.take(&"c"), Some(3));
+    }
+}
@@ -1,20 +1,99 @@
+mod alerts;
+mod anomaly;
+mod api;
 mod app;
-mod format;
-mod model;
-mod scanner;
-mod treemap;
+mod cli;
+mod compress;
+mod correlate;
+mod export;
+mod git;
+mod growth;
+mod import;
+mod ingest;
+mod ipc;
+mod launch;
+mod loc;
+mod lru;
+mod plugin;
+mod reclaim;
+mod sandbox;
+mod script;
+mod shape;
+mod shell;
+mod simulate;
+mod sniff;
+mod spatial;
+mod threshold;
+mod tokens;
+mod update;
+mod watch;
+
+use tree_map_base::{
+    action, analysis, audit, category, exclude, format, gateway, links, memory, model, openclaw,
+    owners, portable, scanner, snapshot, treemap, wake,
+};
+
+use action::ActionEvent;
+use cli::{CliOptions, ContextMenuAction, USAGE};
+use ipc::IpcClient;
 
 fn main() -> eframe::Result<()> {
+    let cli_options = match CliOptions::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    if cli_options.show_help {
+        print!("{USAGE}");
+        return Ok(());
+    }
+    portable::init(cli_options.portable);
+
+    if let Some(action) = cli_options.context_menu {
+        let result = match action {
+            ContextMenuAction::Install => shell::install_context_menu(),
+            ContextMenuAction::Uninstall => shell::uninstall_context_menu(),
+        };
+        match result {
+            Ok(message) => println!("{message}"),
+            Err(error) => {
+                eprintln!("{error}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some((kind, path)) = cli_options.send_action.clone() {
+        let result = IpcClient::connect(&cli_options.ipc_name)
+            .and_then(|mut client| client.send(&ActionEvent::new(kind, path)));
+        if let Err(error) = result {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let options = eframe::NativeOptions {
         viewport: eframe::egui::ViewportBuilder::default()
             .with_inner_size([1200.0, 780.0])
             .with_min_inner_size([900.0, 620.0]),
+        // Settings and window state go with the portable data folder.
+        persistence_path: portable::data_dir().map(|dir| dir.join("app.ron")),
         ..Default::default()
     };
 
     eframe::run_native(
         "tree-map-base",
         options,
-        Box::new(|creation_context| Ok(Box::new(app::TreeMapApp::new(creation_context)))),
+        Box::new(move |creation_context| {
+            Ok(Box::new(app::TreeMapApp::new(
+                creation_context,
+                &cli_options,
+            )))
+        }),
     )
 }
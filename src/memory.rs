@@ -0,0 +1,123 @@
+//! Approximate heap accounting for the diagnostics panel.
+//!
+//! Figures count allocated capacity plus the heap data each value owns. They
+//! ignore allocator overhead, so treat them as lower bounds.
+
+use crate::action::ActionEvent;
+use crate::model::Node;
+use std::collections::{HashMap, VecDeque};
+use std::mem::size_of;
+use std::path::PathBuf;
+
+/// Bytes a value owns on the heap, excluding its own inline size.
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for PathBuf {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_size)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize, S> HeapSize for HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        // One control byte per bucket on top of the key/value slot.
+        let table = self.capacity() * (size_of::<(K, V)>() + 1);
+        table
+            + self
+                .iter()
+                .map(|(key, value)| key.heap_size() + value.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl HeapSize for Node {
+    fn heap_size(&self) -> usize {
+        self.name.heap_size() + self.path.heap_size() + self.children.heap_size()
+    }
+}
+
+impl HeapSize for serde_json::Value {
+    fn heap_size(&self) -> usize {
+        match self {
+            Self::String(text) => text.heap_size(),
+            Self::Array(values) => values.heap_size(),
+            Self::Object(map) => map
+                .iter()
+                .map(|(key, value)| {
+                    size_of::<(String, Self)>() + key.heap_size() + value.heap_size()
+                })
+                .sum(),
+            Self::Null | Self::Bool(_) | Self::Number(_) => 0,
+        }
+    }
+}
+
+impl HeapSize for ActionEvent {
+    fn heap_size(&self) -> usize {
+        self.agent_id.heap_size() + self.target_path.heap_size() + self.payload.heap_size()
+    }
+}
+
+macro_rules! no_heap {
+    ($($type:ty),*) => {
+        $(impl HeapSize for $type {
+            fn heap_size(&self) -> usize {
+                0
+            }
+        })*
+    };
+}
+
+no_heap!(u32, u64, usize, f32, emath::Pos2);
+
+/// Total size of a value including its inline part.
+pub fn total_size<T: HeapSize>(value: &T) -> usize {
+    size_of::<T>() + value.heap_size()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_nested_tree_allocations() {
+        let mut root = Node::new("root".to_string(), PathBuf::from("/r"), 0);
+        let empty = root.heap_size();
+        root.insert_relative(std::path::Path::new("a/b.txt"), 1);
+
+        let child = &root.children[0];
+        let expected_growth = root.children.capacity() * size_of::<Node>()
+            + child.name.capacity()
+            + child.path.capacity()
+            + child.children.capacity() * size_of::<Node>()
+            + child.children[0].name.capacity()
+            + child.children[0].path.capacity();
+        assert_eq!(root.heap_size() - empty, expected_growth);
+    }
+}
@@ -1,10 +1,27 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
 use std::path::{Component, Path, PathBuf};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node {
     pub name: String,
     pub path: PathBuf,
     pub size: u64,
+    /// Bytes of files directly inside this directory, excluding subdirectories.
+    pub own_size: u64,
+    /// Disk space a file takes as reported by the file system, less than
+    /// `size` for sparse and compressed files; `None` for directories and
+    /// where unknown (imports, sizes changed since the scan).
+    pub allocated: Option<u64>,
+    /// Last modification of a file in Unix seconds; 0 when unknown and for
+    /// directories.
+    pub modified: u64,
+    /// User ID owning a file; only meaningful where the scan recorded
+    /// owners (see [`ScanResult::owners`](crate::scanner::ScanResult)).
+    pub owner: u32,
+    /// Whether the entry is a directory; an empty directory has no
+    /// children either, so this is what tells it from a file.
+    pub is_dir: bool,
     pub children: Vec<Node>,
 }
 
@@ -14,30 +31,37 @@ impl Node {
             name,
             path,
             size,
+            own_size: 0,
+            allocated: None,
+            modified: 0,
+            owner: 0,
+            is_dir: false,
             children: Vec::new(),
         }
     }
 
-    pub fn insert_relative(&mut self, relative_path: &Path, leaf_size: u64) {
+    /// Adds (or resizes) the entry at `relative_path`, creating missing
+    /// folders on the way, and returns it.
+    pub fn insert_relative(&mut self, relative_path: &Path, leaf_size: u64) -> Option<&mut Node> {
         let components: Vec<Component<'_>> = relative_path.components().collect();
-        if components.is_empty() {
-            return;
-        }
-
-        self.insert_components(&components, 0, leaf_size);
+        self.insert_components(&components, 0, leaf_size)
     }
 
-    fn insert_components(&mut self, components: &[Component<'_>], index: usize, leaf_size: u64) {
+    fn insert_components(
+        &mut self,
+        components: &[Component<'_>],
+        index: usize,
+        leaf_size: u64,
+    ) -> Option<&mut Node> {
         if index >= components.len() {
-            return;
+            return None;
         }
 
         let component = components[index];
         let component_name = component.as_os_str().to_string_lossy().to_string();
 
         if component_name.is_empty() || component_name == "." {
-            self.insert_components(components, index + 1, leaf_size);
-            return;
+            return self.insert_components(components, index + 1, leaf_size);
         }
 
         let child_index = match self
@@ -59,10 +83,82 @@ impl Node {
 
         if is_leaf {
             child.size = leaf_size;
-            return;
+            return Some(child);
+        }
+
+        child.is_dir = true;
+        child.insert_components(components, index + 1, leaf_size)
+    }
+
+    /// Looks up the node for an absolute `path` below (or equal to) this node.
+    pub fn find(&self, path: &Path) -> Option<&Node> {
+        let relative_path = path.strip_prefix(&self.path).ok()?;
+        let mut node = self;
+        for component in relative_path.components() {
+            let name = component.as_os_str().to_string_lossy();
+            node = node.children.iter().find(|child| child.name == name)?;
         }
+        Some(node)
+    }
+
+    /// Sets the size of the file at `path` and adjusts every ancestor by the
+    /// difference. Returns the previous size, or `None` if `path` is not a
+    /// leaf of this tree.
+    pub fn update_file_size(&mut self, path: &Path, new_size: u64) -> Option<u64> {
+        let relative_path = path.strip_prefix(&self.path).ok()?;
+        let names: Vec<String> = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        self.update_descendant_size(&names, new_size)
+    }
+
+    /// Removes the entry at `path` with everything beneath it and shrinks
+    /// every ancestor by its size. Returns the removed entry.
+    pub fn remove(&mut self, path: &Path) -> Option<Node> {
+        let relative_path = path.strip_prefix(&self.path).ok()?;
+        let names: Vec<String> = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        self.remove_descendant(&names)
+    }
 
-        child.insert_components(components, index + 1, leaf_size);
+    fn remove_descendant(&mut self, names: &[String]) -> Option<Node> {
+        let (name, rest) = names.split_first()?;
+        let index = self.children.iter().position(|child| child.name == *name)?;
+        let removed = if rest.is_empty() {
+            let removed = self.children.remove(index);
+            if removed.children.is_empty() {
+                self.own_size = self.own_size.saturating_sub(removed.size);
+            }
+            removed
+        } else {
+            self.children[index].remove_descendant(rest)?
+        };
+        self.size = self.size.saturating_sub(removed.size);
+        Some(removed)
+    }
+
+    fn update_descendant_size(&mut self, names: &[String], new_size: u64) -> Option<u64> {
+        let (name, rest) = names.split_first()?;
+        let child = self.children.iter_mut().find(|child| child.name == *name)?;
+        let old_size = if rest.is_empty() {
+            if !child.children.is_empty() {
+                return None;
+            }
+            let old_size = std::mem::replace(&mut child.size, new_size);
+            child.allocated = None;
+            self.own_size = self
+                .own_size
+                .saturating_sub(old_size)
+                .saturating_add(new_size);
+            old_size
+        } else {
+            child.update_descendant_size(rest, new_size)?
+        };
+        self.size = self.size.saturating_sub(old_size).saturating_add(new_size);
+        Some(old_size)
     }
 
     pub fn compute_total_size(&mut self) -> u64 {
@@ -71,18 +167,184 @@ impl Node {
         }
 
         let mut total = 0_u64;
+        let mut own = 0_u64;
         for child in &mut self.children {
-            total = total.saturating_add(child.compute_total_size());
+            let child_size = child.compute_total_size();
+            total = total.saturating_add(child_size);
+            if child.children.is_empty() {
+                own = own.saturating_add(child_size);
+            }
         }
 
         self.size = total;
+        self.own_size = own;
         total
     }
 
+    /// Copies the tree without the entries `hidden` rejects, with folder
+    /// sizes reduced to what remains.
+    pub fn without(&self, hidden: &dyn Fn(&Node) -> bool) -> Node {
+        let mut copy = Node::new(self.name.clone(), self.path.clone(), self.size);
+        copy.allocated = self.allocated;
+        copy.modified = self.modified;
+        copy.owner = self.owner;
+        copy.is_dir = self.is_dir;
+        if self.children.is_empty() {
+            return copy;
+        }
+        copy.children = self
+            .children
+            .iter()
+            .filter(|child| !hidden(child))
+            .map(|child| child.without(hidden))
+            .collect();
+        copy.size = 0;
+        copy.compute_total_size();
+        copy
+    }
+
+    /// Copies the top `depth` levels of a tree that is still being built, with
+    /// every node sized by its current subtree total and children sorted by
+    /// size. Deeper levels are folded into their ancestor at the cut-off.
+    pub fn summarize(&self, depth: usize) -> Node {
+        let mut summary = Node::new(self.name.clone(), self.path.clone(), 0);
+        summary.allocated = self.allocated;
+        summary.modified = self.modified;
+        summary.owner = self.owner;
+        summary.is_dir = self.is_dir;
+        if depth == 0 || self.children.is_empty() {
+            summary.size = self.subtree_size();
+            return summary;
+        }
+
+        summary.children = self
+            .children
+            .iter()
+            .map(|child| child.summarize(depth - 1))
+            .collect();
+        summary.size = summary
+            .children
+            .iter()
+            .map(|child| child.size)
+            .fold(0_u64, u64::saturating_add);
+        summary.own_size = self
+            .children
+            .iter()
+            .filter(|child| child.children.is_empty())
+            .map(|child| child.size)
+            .fold(0_u64, u64::saturating_add);
+        summary.children.sort_by_key(|child| Reverse(child.size));
+        summary
+    }
+
+    fn subtree_size(&self) -> u64 {
+        if self.children.is_empty() {
+            return self.size;
+        }
+        self.children
+            .iter()
+            .map(Node::subtree_size)
+            .fold(0_u64, u64::saturating_add)
+    }
+
+    /// The `count` largest files, or folders below this one when
+    /// `directories` is set, biggest first.
+    pub fn largest(&self, count: usize, directories: bool) -> Vec<&Node> {
+        let mut matches = Vec::new();
+        let mut stack: Vec<&Node> = self.children.iter().collect();
+        while let Some(node) = stack.pop() {
+            if node.children.is_empty() != directories {
+                matches.push(node);
+            }
+            stack.extend(&node.children);
+        }
+
+        if matches.len() > count {
+            matches.select_nth_unstable_by_key(count, |node| Reverse(node.size));
+            matches.truncate(count);
+        }
+        matches.sort_unstable_by_key(|node| Reverse(node.size));
+        matches
+    }
+
     pub fn sort_children_by_size_desc(&mut self) {
-        self.children.sort_by(|a, b| b.size.cmp(&a.size));
+        self.children.sort_by_key(|child| Reverse(child.size));
         for child in &mut self.children {
             child.sort_children_by_size_desc();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_folds_deep_levels_into_ancestors() {
+        let mut root = Node::new("root".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("a/b/c/deep.bin"), 40);
+        root.insert_relative(Path::new("a/top.txt"), 2);
+        root.insert_relative(Path::new("z.log"), 100);
+
+        let summary = root.summarize(2);
+
+        assert_eq!(summary.size, 142);
+        assert_eq!(summary.children[0].name, "z.log");
+        let a = &summary.children[1];
+        assert_eq!((a.size, a.own_size), (42, 2));
+        let b = a.children.iter().find(|child| child.name == "b").unwrap();
+        assert_eq!(b.size, 40);
+        assert!(b.children.is_empty());
+    }
+
+    #[test]
+    fn copies_keep_owners() {
+        let mut root = Node::new("root".to_string(), PathBuf::from("/r"), 0);
+        root.owner = 1000;
+        root.insert_relative(Path::new("a/b.txt"), 3).unwrap().owner = 1001;
+        root.insert_relative(Path::new("c.log"), 4);
+        root.compute_total_size();
+
+        let kept = root.without(&|node| node.name == "c.log");
+        assert_eq!(kept.owner, 1000);
+        assert_eq!(kept.find(Path::new("/r/a/b.txt")).unwrap().owner, 1001);
+
+        let summary = root.summarize(1);
+        assert_eq!(summary.owner, 1000);
+        assert_eq!(
+            root.summarize(3)
+                .find(Path::new("/r/a/b.txt"))
+                .unwrap()
+                .owner,
+            1001
+        );
+    }
+
+    #[test]
+    fn update_file_size_adjusts_ancestors() {
+        let mut root = Node::new("root".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("a/b/log.txt"), 10);
+        root.insert_relative(Path::new("a/c.bin"), 5);
+        root.compute_total_size();
+
+        assert_eq!(
+            root.update_file_size(Path::new("/r/a/b/log.txt"), 25),
+            Some(10)
+        );
+        assert_eq!(root.size, 30);
+        let a = root.find(Path::new("/r/a")).unwrap();
+        assert_eq!((a.size, a.own_size), (30, 5));
+        let b = root.find(Path::new("/r/a/b")).unwrap();
+        assert_eq!((b.size, b.own_size), (25, 25));
+
+        assert_eq!(root.update_file_size(Path::new("/r/a"), 1), None);
+        assert_eq!(root.update_file_size(Path::new("/r/missing"), 1), None);
+
+        let removed = root.remove(Path::new("/r/a/b")).unwrap();
+        assert_eq!(removed.size, 25);
+        assert_eq!(root.size, 5);
+        let a = root.find(Path::new("/r/a")).unwrap();
+        assert_eq!((a.size, a.own_size), (5, 5));
+        assert!(root.remove(Path::new("/r/a/b")).is_none());
+    }
+}
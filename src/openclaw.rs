@@ -0,0 +1,66 @@
+use crate::portable::home_dir;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Environment variables that may point directly at an OpenCLAW installation.
+const ENV_VARIABLES: [&str; 3] = ["OPENCLAW_HOME", "OPENCLAW_PATH", "OPENCLAW_DIR"];
+
+/// Returns existing OpenCLAW installation directories, most specific first.
+///
+/// Environment variables win over well-known install locations; duplicates
+/// and paths that are not directories are dropped.
+pub fn detect_install_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+
+    for variable in ENV_VARIABLES {
+        if let Some(value) = env::var_os(variable).filter(|value| !value.is_empty()) {
+            push_if_dir(&mut candidates, PathBuf::from(value));
+        }
+    }
+
+    for location in well_known_locations() {
+        push_if_dir(&mut candidates, location);
+    }
+
+    candidates
+}
+
+fn well_known_locations() -> Vec<PathBuf> {
+    let mut locations = Vec::new();
+
+    if let Some(home) = home_dir() {
+        locations.push(home.join(".openclaw"));
+        locations.push(home.join("openclaw"));
+        locations.push(home.join("OpenCLAW"));
+        locations.push(home.join(".local").join("share").join("openclaw"));
+        locations.push(
+            home.join("Library")
+                .join("Application Support")
+                .join("OpenCLAW"),
+        );
+    }
+
+    for variable in [
+        "LOCALAPPDATA",
+        "APPDATA",
+        "ProgramFiles",
+        "ProgramFiles(x86)",
+    ] {
+        if let Some(base) = env::var_os(variable).filter(|value| !value.is_empty()) {
+            locations.push(Path::new(&base).join("OpenCLAW"));
+        }
+    }
+
+    locations.push(PathBuf::from("/opt/openclaw"));
+    locations.push(PathBuf::from("/usr/local/share/openclaw"));
+    locations.push(PathBuf::from("/usr/share/openclaw"));
+    locations.push(PathBuf::from("/Applications/OpenCLAW.app"));
+
+    locations
+}
+
+fn push_if_dir(candidates: &mut Vec<PathBuf>, path: PathBuf) {
+    if path.is_dir() && !candidates.contains(&path) {
+        candidates.push(path);
+    }
+}
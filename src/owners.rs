@@ -0,0 +1,159 @@
+//! Bytes and files per owning user, from the owner IDs the scanner records.
+//!
+//! Owners are Unix user IDs, named from `/etc/passwd`; users only known to
+//! a directory service show as their ID. Windows scans record no owners.
+
+use crate::analysis::collect_files;
+use crate::model::Node;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Largest files and folders kept per owner for drill-down.
+pub const OWNER_TOP_ENTRIES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerStat {
+    pub uid: u32,
+    /// The user name, or `None` when the ID has no local account.
+    pub name: Option<String>,
+    pub bytes: u64,
+    pub files: u64,
+    /// The owner's largest files, largest first.
+    pub largest_files: Vec<(PathBuf, u64)>,
+    /// Folders by the bytes of the owner's files directly inside them.
+    pub top_folders: Vec<(PathBuf, u64)>,
+}
+
+impl OwnerStat {
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("uid {}", self.uid),
+        }
+    }
+}
+
+/// Per-owner totals over the files of `root`, most bytes first.
+pub fn summarize_owners(root: &Node, names: &HashMap<u32, String>) -> Vec<OwnerStat> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+
+    let mut owners: HashMap<u32, (u64, u64, Vec<&Node>)> = HashMap::new();
+    let mut folders: HashMap<(u32, &Path), u64> = HashMap::new();
+    // Folders and unreadable entries have no modification time or owner.
+    for file in files.into_iter().filter(|file| file.modified != 0) {
+        let (bytes, count, owned) = owners.entry(file.owner).or_default();
+        *bytes += file.size;
+        *count += 1;
+        owned.push(file);
+        if let Some(parent) = file.path.parent() {
+            *folders.entry((file.owner, parent)).or_default() += file.size;
+        }
+    }
+
+    let mut folders_by_owner: HashMap<u32, Vec<(PathBuf, u64)>> = HashMap::new();
+    for ((uid, folder), bytes) in folders {
+        folders_by_owner
+            .entry(uid)
+            .or_default()
+            .push((folder.to_path_buf(), bytes));
+    }
+
+    let mut stats: Vec<OwnerStat> = owners
+        .into_iter()
+        .map(|(uid, (bytes, files, mut owned))| {
+            owned.sort_by_key(|file| Reverse(file.size));
+            let mut top_folders = folders_by_owner.remove(&uid).unwrap_or_default();
+            top_folders.sort_by_key(|(_, bytes)| Reverse(*bytes));
+            top_folders.truncate(OWNER_TOP_ENTRIES);
+            OwnerStat {
+                uid,
+                name: names.get(&uid).cloned(),
+                bytes,
+                files,
+                largest_files: owned
+                    .iter()
+                    .take(OWNER_TOP_ENTRIES)
+                    .map(|file| (file.path.clone(), file.size))
+                    .collect(),
+                top_folders,
+            }
+        })
+        .collect();
+    stats.sort_by_key(|stat| (Reverse(stat.bytes), stat.uid));
+    stats
+}
+
+/// User names by ID from `/etc/passwd`.
+#[cfg(unix)]
+pub fn owner_names() -> HashMap<u32, String> {
+    std::fs::read_to_string("/etc/passwd")
+        .map(|text| parse_passwd(&text))
+        .unwrap_or_default()
+}
+
+#[cfg(not(unix))]
+pub fn owner_names() -> HashMap<u32, String> {
+    HashMap::new()
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+fn parse_passwd(text: &str) -> HashMap<u32, String> {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_owners_by_bytes() {
+        let mut root = Node::new("home".to_string(), PathBuf::from("/home"), 0);
+        for (path, size, owner) in [
+            ("alice/video.mp4", 900, 1000),
+            ("alice/notes.txt", 10, 1000),
+            ("bob/build/out.o", 500, 1001),
+            ("bob/build/lib.a", 400, 1001),
+            ("shared/alice.log", 50, 1000),
+        ] {
+            let node = root.insert_relative(Path::new(path), size).unwrap();
+            node.modified = 1;
+            node.owner = owner;
+        }
+        root.insert_relative(Path::new("empty"), 0);
+        root.compute_total_size();
+
+        let names =
+            parse_passwd("root:x:0:0::/root:/bin/sh\nalice:x:1000:1000::/home/alice:/bin/sh\n");
+        let owners = summarize_owners(&root, &names);
+        let summary: Vec<(String, u64, u64)> = owners
+            .iter()
+            .map(|owner| (owner.display_name(), owner.bytes, owner.files))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("alice".to_string(), 960, 3),
+                ("uid 1001".to_string(), 900, 2),
+            ]
+        );
+        assert_eq!(
+            owners[0].largest_files[0],
+            (PathBuf::from("/home/alice/video.mp4"), 900)
+        );
+        assert_eq!(
+            owners[1].top_folders,
+            vec![(PathBuf::from("/home/bob/build"), 900)]
+        );
+    }
+}
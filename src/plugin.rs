@@ -0,0 +1,299 @@
+//! Native plugins loaded at startup from the `plugins` folder in the config
+//! directory (`.so`, `.dylib` or `.dll`), so niche analyzers and colorings
+//! can live outside this crate. WASM modules are not supported.
+//!
+//! Plugins use a C ABI, so they can be written in any language:
+//!
+//! ```c
+//! // Required.
+//! uint32_t tmb_plugin_api_version(void);       // must return 1
+//! const char *tmb_plugin_name(void);            // UTF-8, static
+//!
+//! // Optional: a cell color as 0xAARRGGBB, or 0 to keep the normal color.
+//! uint32_t tmb_plugin_color(const TmbEntry *entry);
+//!
+//! // Optional: a file's weight when the plugin is the layout's size metric.
+//! uint64_t tmb_plugin_weight(const TmbEntry *entry);
+//!
+//! // Optional: analyze every entry after a scan and return a UTF-8 JSON
+//! // panel, freed afterwards with tmb_plugin_free:
+//! // {"title": "...", "rows": [{"label": "...", "value": "...", "path": "..."}]}
+//! char *tmb_plugin_analyze(const TmbEntry *entries, size_t count);
+//! void tmb_plugin_free(char *text);
+//!
+//! typedef struct {
+//!     const char *path;   // UTF-8, NUL-terminated, valid during the call
+//!     uint64_t size;      // bytes, including everything below a folder
+//!     uint64_t modified;  // Unix seconds, 0 when unknown
+//!     uint32_t depth;     // 0 for the scanned folder
+//!     uint8_t is_dir;     // approximate: 1 for the scanned folder and folders
+//!                         // with entries; empty folders are reported as 0
+//! } TmbEntry;
+//! ```
+//!
+//! Plugins run inside the app process with its permissions; only install
+//! ones you trust.
+
+use crate::model::Node;
+use crate::portable::config_dir;
+use crate::treemap::SizeMetric;
+use crate::wake::{self, Waker};
+use libloading::Library;
+use serde::Deserialize;
+use std::ffi::{c_char, CStr, CString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::thread;
+
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+#[repr(C)]
+pub struct PluginEntry {
+    path: *const c_char,
+    size: u64,
+    modified: u64,
+    depth: u32,
+    is_dir: u8,
+}
+
+type VersionFn = unsafe extern "C" fn() -> u32;
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+type ColorFn = unsafe extern "C" fn(*const PluginEntry) -> u32;
+type WeightFn = unsafe extern "C" fn(*const PluginEntry) -> u64;
+type AnalyzeFn = unsafe extern "C" fn(*const PluginEntry, usize) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    color: Option<ColorFn>,
+    weight: Option<WeightFn>,
+    analyze: Option<(AnalyzeFn, FreeFn)>,
+    // Keeps the function pointers above valid; dropped last.
+    _library: Library,
+}
+
+/// A plugin's panel, as returned by `tmb_plugin_analyze`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginPanel {
+    pub title: String,
+    #[serde(default)]
+    pub rows: Vec<PluginRow>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginRow {
+    pub label: String,
+    #[serde(default)]
+    pub value: String,
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct PluginReport {
+    pub scan_generation: u64,
+    pub plugin: String,
+    pub panel: Result<PluginPanel, String>,
+}
+
+pub fn plugins_dir() -> Option<PathBuf> {
+    Some(config_dir()?.join("plugins"))
+}
+
+/// Loads every library in `dir`, returning the plugins and one error per
+/// library that could not be loaded. A missing folder is not an error.
+pub fn load_plugins(dir: &Path) -> (Vec<Arc<Plugin>>, Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == std::env::consts::DLL_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+
+    let mut plugins = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match Plugin::load(&path) {
+            Ok(plugin) => plugins.push(Arc::new(plugin)),
+            Err(error) => errors.push(format!("{}: {error}", path.display())),
+        }
+    }
+    (plugins, errors)
+}
+
+impl Plugin {
+    fn load(path: &Path) -> Result<Self, String> {
+        // SAFETY: loading runs the library's initializers; plugins are
+        // trusted code the user installed, as documented above.
+        let library = unsafe { Library::new(path) }.map_err(|error| error.to_string())?;
+        // SAFETY: the symbol types match the documented C signatures, and the
+        // copied function pointers are kept next to the library that owns them.
+        unsafe {
+            let version = *library
+                .get::<VersionFn>(b"tmb_plugin_api_version\0")
+                .map_err(|error| error.to_string())?;
+            let version = version();
+            if version != PLUGIN_API_VERSION {
+                return Err(format!(
+                    "plugin API version {version} (expected {PLUGIN_API_VERSION})"
+                ));
+            }
+            let name = *library
+                .get::<NameFn>(b"tmb_plugin_name\0")
+                .map_err(|error| error.to_string())?;
+            let name = name();
+            if name.is_null() {
+                return Err("tmb_plugin_name returned null".to_string());
+            }
+            let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+            let color = library
+                .get::<ColorFn>(b"tmb_plugin_color\0")
+                .ok()
+                .map(|f| *f);
+            let weight = library
+                .get::<WeightFn>(b"tmb_plugin_weight\0")
+                .ok()
+                .map(|f| *f);
+            let analyze = library
+                .get::<AnalyzeFn>(b"tmb_plugin_analyze\0")
+                .ok()
+                .map(|f| *f)
+                .zip(library.get::<FreeFn>(b"tmb_plugin_free\0").ok().map(|f| *f));
+            Ok(Self {
+                name,
+                path: path.to_path_buf(),
+                color,
+                weight,
+                analyze,
+                _library: library,
+            })
+        }
+    }
+
+    pub fn has_colors(&self) -> bool {
+        self.color.is_some()
+    }
+
+    /// The plugin's color for `node`, if it has an opinion.
+    pub fn color(&self, node: &Node, depth: usize) -> Option<[u8; 4]> {
+        let color = self.color?;
+        let path = CString::new(node.path.to_string_lossy().as_bytes()).ok()?;
+        let entry = entry_for(node, &path, depth);
+        // SAFETY: `entry` and the path it points to outlive the call.
+        let argb = unsafe { color(&entry) };
+        (argb != 0).then(|| {
+            let [a, r, g, b] = argb.to_be_bytes();
+            [r, g, b, a]
+        })
+    }
+
+    pub fn has_weights(&self) -> bool {
+        self.weight.is_some()
+    }
+}
+
+impl SizeMetric for Plugin {
+    fn file_weight(&self, file: &Node, depth: usize) -> u64 {
+        let Some(weight) = self.weight else {
+            return 0;
+        };
+        let Ok(path) = CString::new(file.path.to_string_lossy().as_bytes()) else {
+            return 0;
+        };
+        let entry = entry_for(file, &path, depth);
+        // SAFETY: `entry` and the path it points to outlive the call.
+        unsafe { weight(&entry) }
+    }
+}
+
+/// Runs every analyzing plugin over a copy of `root` on a background thread,
+/// sending one report per plugin.
+pub fn spawn_plugin_analysis(
+    plugins: &[Arc<Plugin>],
+    root: &Node,
+    scan_generation: u64,
+    waker: Waker,
+) -> Option<Receiver<PluginReport>> {
+    let plugins: Vec<Arc<Plugin>> = plugins
+        .iter()
+        .filter(|plugin| plugin.analyze.is_some())
+        .cloned()
+        .collect();
+    if plugins.is_empty() {
+        return None;
+    }
+    let root = root.clone();
+    let (tx, rx) = wake::channel(waker);
+
+    thread::spawn(move || {
+        let mut nodes = Vec::new();
+        collect_entries(&root, 0, &mut nodes);
+        let paths: Vec<CString> = nodes
+            .iter()
+            .map(|(node, _)| {
+                CString::new(node.path.to_string_lossy().as_bytes()).unwrap_or_default()
+            })
+            .collect();
+        let entries: Vec<PluginEntry> = nodes
+            .iter()
+            .zip(&paths)
+            .map(|((node, depth), path)| entry_for(node, path, *depth))
+            .collect();
+
+        for plugin in plugins {
+            let Some((analyze, free)) = plugin.analyze else {
+                continue;
+            };
+            // SAFETY: `entries` and the paths they point to outlive the call;
+            // the returned text is released with the plugin's own free.
+            let panel = unsafe {
+                let text = analyze(entries.as_ptr(), entries.len());
+                if text.is_null() {
+                    continue;
+                }
+                let parsed = serde_json::from_slice::<PluginPanel>(CStr::from_ptr(text).to_bytes())
+                    .map_err(|error| format!("Invalid panel JSON: {error}"));
+                free(text);
+                parsed
+            };
+            let report = PluginReport {
+                scan_generation,
+                plugin: plugin.name.clone(),
+                panel,
+            };
+            if tx.send(report).is_err() {
+                break;
+            }
+        }
+    });
+
+    Some(rx)
+}
+
+fn collect_entries<'a>(node: &'a Node, depth: usize, entries: &mut Vec<(&'a Node, usize)>) {
+    entries.push((node, depth));
+    for child in &node.children {
+        collect_entries(child, depth + 1, entries);
+    }
+}
+
+/// The tree does not record whether an entry is a folder, so `is_dir` is
+/// inferred from children, like everywhere else in the app: empty folders
+/// read as files.
+fn entry_for(node: &Node, path: &CStr, depth: usize) -> PluginEntry {
+    PluginEntry {
+        path: path.as_ptr(),
+        size: node.size,
+        modified: node.modified,
+        depth: depth as u32,
+        is_dir: u8::from(depth == 0 || !node.children.is_empty()),
+    }
+}
@@ -0,0 +1,109 @@
+//! Portable mode: settings, caches and snapshots are kept in a
+//! `tree-map-base-data` folder next to the executable instead of the user
+//! profile, so the app can run from a USB stick without leaving traces.
+//!
+//! It is switched on with `--portable` or by a `tree-map-base.portable`
+//! marker file beside the executable, and must be decided before anything
+//! looks up [`config_dir`]. The user profile paths live here too, so every
+//! module finds them in one place.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub const PORTABLE_MARKER: &str = "tree-map-base.portable";
+pub const PORTABLE_DATA_DIR: &str = "tree-map-base-data";
+
+static DATA_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Decides once per process whether to run portable; later calls return the
+/// first decision.
+pub fn init(forced: bool) -> Option<&'static Path> {
+    DATA_DIR
+        .get_or_init(|| {
+            let executable = env::current_exe().ok()?;
+            data_dir_for(&executable, forced)
+        })
+        .as_deref()
+}
+
+/// The portable data folder, or `None` when using the user profile.
+pub fn data_dir() -> Option<&'static Path> {
+    DATA_DIR.get().and_then(|dir| dir.as_deref())
+}
+
+/// The user's home directory (`HOME`, or `USERPROFILE` on Windows).
+pub fn home_dir() -> Option<PathBuf> {
+    ["HOME", "USERPROFILE"]
+        .into_iter()
+        .filter_map(env::var_os)
+        .find(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The app's folder in the user's config directory, or in the portable
+/// data folder.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(data_dir) = data_dir() {
+        return Some(data_dir.join("config"));
+    }
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .or_else(|| env::var_os("APPDATA"))
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))?;
+    Some(config_dir.join("tree-map-base"))
+}
+
+/// The app's folder in the user's cache directory, or in the portable data
+/// folder.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(data_dir) = data_dir() {
+        return Some(data_dir.join("cache"));
+    }
+    let cache_dir = env::var_os("XDG_CACHE_HOME")
+        .or_else(|| env::var_os("LOCALAPPDATA"))
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".cache")))?;
+    Some(cache_dir.join("tree-map-base"))
+}
+
+/// The user's data directory (`XDG_DATA_HOME`, or `~/.local/share`), where
+/// desktop integrations are installed; portable mode does not move it.
+pub fn data_home() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".local").join("share")))
+}
+
+fn data_dir_for(executable: &Path, forced: bool) -> Option<PathBuf> {
+    let exe_dir = executable.parent()?;
+    (forced || exe_dir.join(PORTABLE_MARKER).is_file()).then(|| exe_dir.join(PORTABLE_DATA_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn marker_or_flag_enables_portable_mode() {
+        let dir = env::temp_dir().join(format!("tmb-portable-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let executable = dir.join("tree-map-base");
+
+        assert_eq!(data_dir_for(&executable, false), None);
+        assert_eq!(
+            data_dir_for(&executable, true),
+            Some(dir.join(PORTABLE_DATA_DIR))
+        );
+        fs::write(dir.join(PORTABLE_MARKER), b"").unwrap();
+        assert_eq!(
+            data_dir_for(&executable, false),
+            Some(dir.join(PORTABLE_DATA_DIR))
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
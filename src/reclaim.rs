@@ -0,0 +1,196 @@
+//! Well-known reclaimable folders: build output, dependency folders and
+//! caches that their tools recreate on demand.
+//!
+//! Matches are by folder name, usually confirmed by a marker next to or
+//! inside the folder (`target` only counts beside a `Cargo.toml`). The
+//! outermost match wins, so nested caches are not counted twice. Nothing is
+//! ever deleted; the report only says what is probably safe to remove.
+
+use crate::model::Node;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// What confirms a folder name match.
+enum Marker {
+    None,
+    /// A file or folder with this name next to the match.
+    Sibling(&'static str),
+    /// A file or folder with this name inside the match.
+    Child(&'static str),
+    /// An ancestor folder with one of these names (case-insensitive).
+    Within(&'static [&'static str]),
+}
+
+const BROWSERS: &[&str] = &["chrome", "chromium", "edge", "bravesoftware", "vivaldi"];
+
+/// Folder name, confirming marker and label.
+const RECLAIM_RULES: &[(&str, Marker, &str)] = &[
+    ("node_modules", Marker::None, "npm dependencies"),
+    (
+        "target",
+        Marker::Sibling("Cargo.toml"),
+        "Cargo build output",
+    ),
+    (".venv", Marker::Child("pyvenv.cfg"), "Python virtualenv"),
+    ("venv", Marker::Child("pyvenv.cfg"), "Python virtualenv"),
+    ("__pycache__", Marker::None, "Python bytecode"),
+    (".pytest_cache", Marker::None, "pytest cache"),
+    (".mypy_cache", Marker::None, "mypy cache"),
+    (".ruff_cache", Marker::None, "Ruff cache"),
+    (".tox", Marker::None, "tox environments"),
+    (".gradle", Marker::None, "Gradle cache"),
+    (
+        "build",
+        Marker::Sibling("build.gradle"),
+        "Gradle build output",
+    ),
+    (
+        "build",
+        Marker::Sibling("build.gradle.kts"),
+        "Gradle build output",
+    ),
+    (".next", Marker::None, "Next.js build cache"),
+    (".nuxt", Marker::None, "Nuxt build cache"),
+    (".parcel-cache", Marker::None, "Parcel cache"),
+    (".turbo", Marker::None, "Turborepo cache"),
+    (
+        "DerivedData",
+        Marker::Within(&["xcode"]),
+        "Xcode build data",
+    ),
+    (
+        "overlay2",
+        Marker::Within(&["docker"]),
+        "Docker image layers",
+    ),
+    ("Cache", Marker::Within(BROWSERS), "Browser cache"),
+    ("Code Cache", Marker::Within(BROWSERS), "Browser cache"),
+    (
+        "cache2",
+        Marker::Within(&["mozilla", "firefox"]),
+        "Firefox cache",
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub struct Reclaimable {
+    pub path: PathBuf,
+    pub label: &'static str,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReclaimReport {
+    /// Matches, largest first.
+    pub items: Vec<Reclaimable>,
+    pub total_bytes: u64,
+    by_path: HashMap<PathBuf, usize>,
+}
+
+impl ReclaimReport {
+    pub fn of(root: &Node) -> Self {
+        let mut items = Vec::new();
+        let mut ancestors = Vec::new();
+        find_reclaimable(root, &mut ancestors, &mut items);
+        items.sort_by_key(|item| Reverse(item.size));
+        let by_path = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (item.path.clone(), index))
+            .collect();
+        Self {
+            total_bytes: items.iter().map(|item| item.size).sum(),
+            items,
+            by_path,
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Reclaimable> {
+        self.by_path.get(path).map(|&index| &self.items[index])
+    }
+}
+
+fn find_reclaimable<'a>(
+    node: &'a Node,
+    ancestors: &mut Vec<&'a str>,
+    items: &mut Vec<Reclaimable>,
+) {
+    ancestors.push(&node.name);
+    for child in node
+        .children
+        .iter()
+        .filter(|child| !child.children.is_empty())
+    {
+        match RECLAIM_RULES.iter().find(|(name, marker, _)| {
+            *name == child.name && marker.confirmed(node, child, ancestors)
+        }) {
+            Some((_, _, label)) => items.push(Reclaimable {
+                path: child.path.clone(),
+                label,
+                size: child.size,
+            }),
+            None => find_reclaimable(child, ancestors, items),
+        }
+    }
+    ancestors.pop();
+}
+
+impl Marker {
+    fn confirmed(&self, parent: &Node, node: &Node, ancestors: &[&str]) -> bool {
+        let has = |node: &Node, name: &str| node.children.iter().any(|child| child.name == name);
+        match *self {
+            Marker::None => true,
+            Marker::Sibling(name) => has(parent, name),
+            Marker::Child(name) => has(node, name),
+            Marker::Within(names) => ancestors
+                .iter()
+                .any(|ancestor| names.iter().any(|name| ancestor.eq_ignore_ascii_case(name))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_outermost_confirmed_matches() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        for (path, size) in [
+            ("app/node_modules/a/node_modules/b.js", 5),
+            ("app/node_modules/a.js", 10),
+            ("crate/Cargo.toml", 1),
+            ("crate/target/debug/app", 40),
+            ("notes/target/plan.txt", 3),
+            ("Google/Chrome/Default/Cache/data_1", 7),
+            ("photos/Cache/thumb", 2),
+        ] {
+            root.insert_relative(Path::new(path), size);
+        }
+        root.compute_total_size();
+
+        let report = ReclaimReport::of(&root);
+        let found: Vec<_> = report
+            .items
+            .iter()
+            .map(|item| (item.path.to_string_lossy().into_owned(), item.size))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                ("/r/crate/target".to_string(), 40),
+                ("/r/app/node_modules".to_string(), 15),
+                ("/r/Google/Chrome/Default/Cache".to_string(), 7),
+            ]
+        );
+        assert_eq!(report.total_bytes, 62);
+        assert_eq!(
+            report
+                .get(Path::new("/r/crate/target"))
+                .map(|item| item.label),
+            Some("Cargo build output")
+        );
+    }
+}
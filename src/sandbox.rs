@@ -0,0 +1,118 @@
+//! The OpenCLAW agent's sandbox boundary, loaded from its allowed-directory
+//! configuration, a JSON file of path patterns:
+//!
+//! ```json
+//! { "allow": ["~/projects/**", "/tmp"], "deny": ["**/.env", "~/projects/secrets"] }
+//! ```
+//!
+//! `allowlist`/`allowed_paths` and `denylist`/`denied_paths` are accepted as
+//! well. Patterns use the sensitive-path rule syntax. A denied path is
+//! forbidden even inside an allowed one; with an allowlist, anything it does
+//! not cover is forbidden too.
+
+use crate::alerts::SensitiveRules;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+struct SandboxFile {
+    #[serde(default, alias = "allowlist", alias = "allowed_paths")]
+    allow: Vec<String>,
+    #[serde(default, alias = "denylist", alias = "denied_paths")]
+    deny: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access<'a> {
+    Allowed,
+    /// Matched this deny pattern.
+    Denied(&'a str),
+    /// Not covered by any allow pattern.
+    OutsideAllowlist,
+}
+
+#[derive(Debug, Clone)]
+pub struct SandboxPolicy {
+    /// The file the policy was loaded from.
+    pub source: PathBuf,
+    allow: SensitiveRules,
+    deny: SensitiveRules,
+}
+
+impl SandboxPolicy {
+    /// Loads the policy at `path`, with one error per unusable pattern.
+    pub fn load(path: &Path) -> Result<(Self, Vec<String>), String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Could not read {}: {error}", path.display()))?;
+        let (mut policy, errors) = Self::parse(&text)?;
+        policy.source = path.to_path_buf();
+        Ok((policy, errors))
+    }
+
+    pub fn parse(json: &str) -> Result<(Self, Vec<String>), String> {
+        let file: SandboxFile =
+            serde_json::from_str(json).map_err(|error| format!("Invalid sandbox file: {error}"))?;
+        let (allow, mut errors) = SensitiveRules::parse(&file.allow.join("\n"));
+        let (deny, deny_errors) = SensitiveRules::parse(&file.deny.join("\n"));
+        errors.extend(deny_errors);
+        Ok((
+            Self {
+                source: PathBuf::new(),
+                allow,
+                deny,
+            },
+            errors,
+        ))
+    }
+
+    pub fn rule_counts(&self) -> (usize, usize) {
+        (self.allow.len(), self.deny.len())
+    }
+
+    pub fn access(&self, path: &Path) -> Access<'_> {
+        if let Some(rule) = self.deny.matching_rule(path) {
+            return Access::Denied(rule);
+        }
+        if self.allow.len() > 0 && self.allow.matching_rule(path).is_none() {
+            return Access::OutsideAllowlist;
+        }
+        Access::Allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deny_rules_win_over_the_allowlist() {
+        let (policy, errors) = SandboxPolicy::parse(
+            r#"{"allowlist": ["/work"], "denied_paths": ["**/.env", "/work/secrets"]}"#,
+        )
+        .unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(policy.rule_counts(), (1, 2));
+
+        assert_eq!(
+            policy.access(Path::new("/work/src/main.rs")),
+            Access::Allowed
+        );
+        assert_eq!(
+            policy.access(Path::new("/work/app/.env")),
+            Access::Denied("**/.env")
+        );
+        assert_eq!(
+            policy.access(Path::new("/work/secrets/key")),
+            Access::Denied("/work/secrets")
+        );
+        assert_eq!(
+            policy.access(Path::new("/home/me/notes")),
+            Access::OutsideAllowlist
+        );
+
+        let (open, _) = SandboxPolicy::parse(r#"{"deny": ["/etc"]}"#).unwrap();
+        assert_eq!(open.access(Path::new("/home/me/notes")), Access::Allowed);
+        assert!(SandboxPolicy::parse("not json").is_err());
+    }
+}
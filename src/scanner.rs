@@ -1,16 +1,58 @@
+use crate::analysis::ScanAnalysis;
+use crate::audit::{permission_issues, PermissionFinding, MAX_PERMISSION_FINDINGS};
+use crate::exclude::ExcludeRules;
+use crate::gateway::{self, AccessKind};
+use crate::links::{inspect_link, SymlinkEntry, MAX_SYMLINKS};
 use crate::model::Node;
-use std::fs;
+use crate::owners::{owner_names, summarize_owners, OwnerStat};
+use crate::snapshot::replace_snapshot;
+use crate::wake::{self, Waker, WakingSender};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
-use walkdir::WalkDir;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// Levels included in the provisional trees sent while phase 2 runs.
+const PARTIAL_TREE_DEPTH: usize = 3;
+/// Minimum time between two provisional trees.
+const PARTIAL_TREE_INTERVAL: Duration = Duration::from_secs(1);
+/// Minimum time between two UI wake-ups for progress alone.
+const PROGRESS_WAKE_INTERVAL: Duration = Duration::from_millis(80);
+/// How often a parallel scan publishes its counters.
+const PARALLEL_PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+/// Error of a scan stopped through its cancel flag.
+const SCAN_CANCELLED: &str = "Scan cancelled";
+
+/// Detailed warning messages kept in memory per scan; further warnings are
+/// only counted (and written to the warning log, if one is configured).
+pub const DEFAULT_MAX_STORED_WARNINGS: usize = 1_000;
 
 #[derive(Debug, Clone)]
 pub struct ScanConfig {
     pub max_depth: usize,
     pub max_files: Option<usize>,
     pub progress_interval: usize,
+    pub max_stored_warnings: usize,
+    /// Every warning is appended here, one per line, regardless of the cap.
+    pub warning_log: Option<PathBuf>,
+    /// A completed scan is also saved here, after it has been delivered.
+    pub snapshot_path: Option<PathBuf>,
+    /// Every file is written here as one JSON line as soon as it is scanned.
+    pub jsonl_export: Option<PathBuf>,
+    /// Record risky permission bits (see [`crate::audit`]).
+    pub audit_permissions: bool,
+    /// Entries skipped, with everything beneath them.
+    pub exclude: ExcludeRules,
+    /// Worker threads listing folders; 1 walks serially, which is kinder to
+    /// spinning disks and network shares.
+    pub threads: usize,
 }
 
 impl Default for ScanConfig {
@@ -19,28 +61,83 @@ impl Default for ScanConfig {
             max_depth: 64,
             max_files: Some(250_000),
             progress_interval: 400,
+            max_stored_warnings: DEFAULT_MAX_STORED_WARNINGS,
+            warning_log: None,
+            snapshot_path: None,
+            jsonl_export: None,
+            audit_permissions: false,
+            exclude: ExcludeRules::default(),
+            threads: 1,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ScanPhase {
-    Counting,
-    Scanning,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarningCategory {
+    PermissionDenied,
+    NotFound,
+    SymlinkLoop,
+    Metadata,
+    Other,
 }
 
-impl Default for ScanPhase {
-    fn default() -> Self {
-        Self::Counting
+impl WarningCategory {
+    pub const ALL: [WarningCategory; 5] = [
+        Self::PermissionDenied,
+        Self::NotFound,
+        Self::SymlinkLoop,
+        Self::Metadata,
+        Self::Other,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::PermissionDenied => "permission denied",
+            Self::NotFound => "not found",
+            Self::SymlinkLoop => "symlink loop",
+            Self::Metadata => "metadata",
+            Self::Other => "other",
+        }
+    }
+
+    fn from_io(kind: io::ErrorKind, fallback: Self) -> Self {
+        match kind {
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            io::ErrorKind::NotFound => Self::NotFound,
+            _ => fallback,
+        }
+    }
+
+    fn from_walkdir(error: &walkdir::Error) -> Self {
+        if error.loop_ancestor().is_some() {
+            return Self::SymlinkLoop;
+        }
+        error
+            .io_error()
+            .map(|error| Self::from_io(error.kind(), Self::Other))
+            .unwrap_or(Self::Other)
+    }
+
+    fn index(self) -> usize {
+        self as usize
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScanPhase {
+    #[default]
+    Counting,
+    Scanning,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ScanProgress {
     pub phase: ScanPhase,
     pub entries_scanned: u64,
     pub files_scanned: u64,
     pub directories_scanned: u64,
+    /// Sum of file sizes read from metadata so far.
+    pub bytes_scanned: u64,
     pub warnings: u64,
     pub truncated: bool,
     pub current_path: Option<PathBuf>,
@@ -50,7 +147,7 @@ pub struct ScanProgress {
     pub eta: Option<Duration>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanStats {
     pub entries_scanned: u64,
     pub files_scanned: u64,
@@ -75,39 +172,422 @@ impl Default for ScanStats {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanResult {
     pub root: Node,
     pub stats: ScanStats,
+    /// The first [`ScanConfig::max_stored_warnings`] warning messages.
     pub warnings: Vec<String>,
+    /// Count of every warning by category, including those not stored.
+    pub warning_counts: Vec<(WarningCategory, u64)>,
+    /// Empty unless the scan audited permissions.
+    pub permission_findings: Vec<PermissionFinding>,
+    /// The first [`MAX_SYMLINKS`] symbolic links.
+    pub symlinks: Vec<SymlinkEntry>,
+    /// Bytes per owning user, most first; empty where owners are unknown.
+    pub owners: Vec<OwnerStat>,
 }
 
 #[derive(Debug)]
 pub enum ScanMessage {
-    Progress(ScanProgress),
-    Finished(Result<ScanResult, String>),
+    /// The top [`PARTIAL_TREE_DEPTH`] levels with the sizes known so far.
+    Partial { root: Node, analysis: ScanAnalysis },
+    /// The complete tree, analyzed on the scan thread.
+    Finished(Result<(ScanResult, ScanAnalysis), String>),
+    /// The scan stopped after [`ScanHandle::cancel`]; nothing was kept.
+    Cancelled,
+}
+
+/// A running scan: the newest progress snapshot plus the final result.
+///
+/// Progress is published into a single slot that the worker overwrites, so a
+/// stalled UI never builds up a backlog of stale progress updates.
+pub struct ScanHandle {
+    progress: Arc<Mutex<Option<ScanProgress>>>,
+    receiver: Receiver<ScanMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ScanHandle {
+    /// Asks the scan to stop; it answers with [`ScanMessage::Cancelled`].
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Takes the newest progress published since the last call, if any.
+    pub fn take_progress(&self) -> Option<ScanProgress> {
+        self.progress.lock().ok()?.take()
+    }
+
+    pub fn receiver(&self) -> &Receiver<ScanMessage> {
+        &self.receiver
+    }
+}
+
+struct ProgressReporter {
+    slot: Arc<Mutex<Option<ScanProgress>>>,
+    sender: WakingSender<ScanMessage>,
+    last_wake: Cell<Option<Instant>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ProgressReporter {
+    /// Checked for every entry by both walks.
+    fn check_cancelled(&self) -> Result<(), String> {
+        if self.cancel.load(Ordering::Relaxed) {
+            Err(SCAN_CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn publish(&self, progress: &ScanProgress) {
+        if let Ok(mut slot) = self.slot.lock() {
+            *slot = Some(progress.clone());
+        }
+
+        let due = self
+            .last_wake
+            .get()
+            .is_none_or(|woken| woken.elapsed() >= PROGRESS_WAKE_INTERVAL);
+        if due {
+            self.last_wake.set(Some(Instant::now()));
+            self.sender.waker().wake();
+        }
+    }
+
+    fn publish_partial(&self, root: &Node) {
+        let root = root.summarize(PARTIAL_TREE_DEPTH);
+        let analysis = ScanAnalysis::of(&root);
+        let _ = self.sender.send(ScanMessage::Partial { root, analysis });
+    }
+}
+
+/// Collects scan warnings with a bounded memory footprint.
+struct WarningCollector {
+    details: Vec<String>,
+    counts: [u64; WarningCategory::ALL.len()],
+    max_stored: usize,
+    log: Option<BufWriter<File>>,
+}
+
+impl WarningCollector {
+    fn new(config: &ScanConfig) -> Self {
+        let mut collector = Self {
+            details: Vec::new(),
+            counts: [0; WarningCategory::ALL.len()],
+            max_stored: config.max_stored_warnings,
+            log: None,
+        };
+
+        if let Some(path) = &config.warning_log {
+            match File::create(path) {
+                Ok(file) => collector.log = Some(BufWriter::new(file)),
+                Err(error) => collector.record(
+                    WarningCategory::from_io(error.kind(), WarningCategory::Other),
+                    format!("Could not create warning log {}: {error}", path.display()),
+                ),
+            }
+        }
+
+        collector
+    }
+
+    fn record(&mut self, category: WarningCategory, message: String) {
+        self.counts[category.index()] = self.counts[category.index()].saturating_add(1);
+
+        if let Some(log) = &mut self.log {
+            if let Err(error) = writeln!(log, "{message}") {
+                self.log = None;
+                self.record(
+                    WarningCategory::Other,
+                    format!("Warning log disabled after write error: {error}"),
+                );
+            }
+        }
+
+        if self.details.len() < self.max_stored {
+            self.details.push(message);
+        }
+    }
+
+    fn finish(mut self) -> (Vec<String>, Vec<(WarningCategory, u64)>) {
+        if let Some(mut log) = self.log.take() {
+            if let Err(error) = log.flush() {
+                self.record(
+                    WarningCategory::Other,
+                    format!("Could not flush warning log: {error}"),
+                );
+            }
+        }
+
+        let counts = WarningCategory::ALL
+            .into_iter()
+            .map(|category| (category, self.counts[category.index()]))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        (self.details, counts)
+    }
+}
+
+/// Streams `{"path":…,"size":…,"modified":…}` lines for
+/// [`ScanConfig::jsonl_export`]. The file is truncated when a scan starts and
+/// flushed with every progress update, so consumers can follow it while the
+/// scan runs; `modified` is Unix seconds or null.
+struct EntryExporter {
+    writer: Option<BufWriter<File>>,
 }
 
-pub fn spawn_scan(root_path: PathBuf, config: ScanConfig) -> Receiver<ScanMessage> {
-    let (tx, rx) = mpsc::channel::<ScanMessage>();
+impl EntryExporter {
+    fn new(config: &ScanConfig, warnings: &mut WarningCollector) -> Self {
+        let writer = config
+            .jsonl_export
+            .as_ref()
+            .and_then(|path| match File::create(path) {
+                Ok(file) => Some(BufWriter::new(file)),
+                Err(error) => {
+                    warnings.record(
+                        WarningCategory::from_io(error.kind(), WarningCategory::Other),
+                        format!("Could not create JSONL export {}: {error}", path.display()),
+                    );
+                    None
+                }
+            });
+        Self { writer }
+    }
+
+    fn write(
+        &mut self,
+        path: &Path,
+        size: u64,
+        metadata: Option<&fs::Metadata>,
+        warnings: &mut WarningCollector,
+    ) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+        let modified = metadata.and_then(unix_modified);
+        let line = serde_json::json!({
+            "path": path.to_string_lossy(),
+            "size": size,
+            "modified": modified,
+        });
+        if let Err(error) = writeln!(writer, "{line}") {
+            self.disable(warnings, error);
+        }
+    }
+
+    fn flush(&mut self, warnings: &mut WarningCollector) {
+        if let Some(Err(error)) = self.writer.as_mut().map(BufWriter::flush) {
+            self.disable(warnings, error);
+        }
+    }
+
+    fn disable(&mut self, warnings: &mut WarningCollector, error: io::Error) {
+        self.writer = None;
+        warnings.record(
+            WarningCategory::Other,
+            format!("JSONL export disabled after write error: {error}"),
+        );
+    }
+}
+
+/// Depth of the shallow walk used by [`spawn_probe`].
+pub const PROBE_DEPTH: usize = 2;
+
+/// Entry count and timing from a previous full scan of the same root.
+#[derive(Debug, Clone, Copy)]
+pub struct ProbeHint {
+    pub entries: u64,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    pub entries_probed: u64,
+    pub files_probed: u64,
+    pub directories_probed: u64,
+    pub unexplored_directories: u64,
+    pub warnings: u64,
+    pub estimated_total_entries: u64,
+    pub estimated_duration: Option<Duration>,
+    pub from_previous_scan: bool,
+    pub elapsed: Duration,
+}
+
+pub fn spawn_probe(
+    root_path: PathBuf,
+    config: ScanConfig,
+    hint: Option<ProbeHint>,
+    waker: Waker,
+) -> Receiver<Result<ProbeResult, String>> {
+    let (tx, rx) = wake::channel(waker);
+
+    thread::spawn(move || {
+        let _ = tx.send(probe_directory(&root_path, &config, hint));
+    });
+
+    rx
+}
+
+fn probe_directory(
+    root_path: &Path,
+    config: &ScanConfig,
+    hint: Option<ProbeHint>,
+) -> Result<ProbeResult, String> {
+    if !root_path.exists() {
+        return Err(format!("Directory does not exist: {}", root_path.display()));
+    }
+
+    if !root_path.is_dir() {
+        return Err(format!("Path is not a directory: {}", root_path.display()));
+    }
+
+    let started = Instant::now();
+    let probe_depth = PROBE_DEPTH.min(config.max_depth.max(1));
+    let mut result = ProbeResult {
+        entries_probed: 0,
+        files_probed: 0,
+        directories_probed: 0,
+        unexplored_directories: 0,
+        warnings: 0,
+        estimated_total_entries: 0,
+        estimated_duration: None,
+        from_previous_scan: false,
+        elapsed: Duration::ZERO,
+    };
+    let mut first_level_directories = 0_u64;
+    let mut deepest_level_entries = 0_u64;
+
+    let walker = gateway::walk(root_path)
+        .max_depth(probe_depth)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !config.exclude.is_excluded(entry.path()));
+
+    for entry_result in walker {
+        let entry = match entry_result {
+            Ok(entry) => entry,
+            Err(_) => {
+                result.warnings = result.warnings.saturating_add(1);
+                continue;
+            }
+        };
+
+        result.entries_probed = result.entries_probed.saturating_add(1);
+        if entry.depth() == 0 {
+            continue;
+        }
+
+        if entry.depth() == probe_depth {
+            deepest_level_entries = deepest_level_entries.saturating_add(1);
+        }
+
+        if entry.file_type().is_dir() {
+            gateway::record(AccessKind::DirectoryListing);
+            result.directories_probed = result.directories_probed.saturating_add(1);
+            if entry.depth() == 1 {
+                first_level_directories = first_level_directories.saturating_add(1);
+            }
+            if entry.depth() == probe_depth && config.max_depth > probe_depth {
+                result.unexplored_directories = result.unexplored_directories.saturating_add(1);
+            }
+        } else {
+            result.files_probed = result.files_probed.saturating_add(1);
+        }
+    }
+
+    result.elapsed = started.elapsed();
+
+    if let Some(hint) = hint.filter(|hint| hint.entries > 0) {
+        result.estimated_total_entries = hint.entries;
+        result.estimated_duration = Some(hint.elapsed);
+        result.from_previous_scan = true;
+        return Ok(result);
+    }
+
+    // Assume every unexplored directory holds as many entries as an average
+    // directory on the deepest probed level.
+    let entries_per_directory = if probe_depth > 1 {
+        deepest_level_entries as f64 / first_level_directories.max(1) as f64
+    } else {
+        0.0
+    };
+    let extrapolated = result.unexplored_directories as f64 * entries_per_directory;
+    let mut estimated = result
+        .entries_probed
+        .saturating_add(extrapolated.round() as u64);
+
+    if let Some(max_files) = config.max_files {
+        estimated = estimated.min(
+            (max_files as u64)
+                .saturating_add(result.directories_probed)
+                .saturating_add(1),
+        );
+    }
+
+    result.estimated_total_entries = estimated.max(result.entries_probed).max(1);
+
+    let probe_seconds = result.elapsed.as_secs_f64();
+    if probe_seconds > 0.0 && result.entries_probed > 0 {
+        let entries_per_second = result.entries_probed as f64 / probe_seconds;
+        // A full scan walks the tree twice (counting + scanning with metadata).
+        let eta_seconds = result.estimated_total_entries as f64 * 2.0 / entries_per_second;
+        result.estimated_duration = Some(Duration::from_secs_f64(eta_seconds.max(0.0)));
+    }
+
+    Ok(result)
+}
+
+pub fn spawn_scan(root_path: PathBuf, config: ScanConfig, waker: Waker) -> ScanHandle {
+    let (tx, rx) = wake::channel::<ScanMessage>(waker);
+    let progress = Arc::new(Mutex::new(None));
+    let cancel = Arc::new(AtomicBool::new(false));
+    let reporter = ProgressReporter {
+        slot: Arc::clone(&progress),
+        sender: tx.clone(),
+        last_wake: Cell::new(None),
+        cancel: Arc::clone(&cancel),
+    };
 
     thread::spawn(move || {
         let started = Instant::now();
-        let result = run_scan_pipeline(&root_path, &config, &tx).map(|mut result| {
+        let result = run_scan_pipeline(&root_path, &config, &reporter).map(|mut result| {
             result.stats.elapsed = started.elapsed();
-            result
+            let analysis = ScanAnalysis::of(&result.root);
+            (result, analysis)
         });
+        if reporter.check_cancelled().is_err() {
+            let _ = tx.send(ScanMessage::Cancelled);
+            return;
+        }
 
+        let snapshot = config
+            .snapshot_path
+            .as_ref()
+            .zip(result.as_ref().ok())
+            .map(|(path, (result, _))| (path.clone(), result.clone()));
         let _ = tx.send(ScanMessage::Finished(result));
+        if let Some((path, result)) = snapshot {
+            // Best effort: a missing snapshot only costs the next fast start.
+            let _ = replace_snapshot(&path, &result);
+        }
     });
 
-    rx
+    ScanHandle {
+        progress,
+        receiver: rx,
+        cancel,
+    }
 }
 
 fn run_scan_pipeline(
     root_path: &Path,
     config: &ScanConfig,
-    tx: &Sender<ScanMessage>,
+    reporter: &ProgressReporter,
 ) -> Result<ScanResult, String> {
     if !root_path.exists() {
         return Err(format!("Directory does not exist: {}", root_path.display()));
@@ -117,25 +597,30 @@ fn run_scan_pipeline(
         return Err(format!("Path is not a directory: {}", root_path.display()));
     }
 
-    let estimated_total_entries = estimate_total_entries(root_path, config, tx)?;
-    scan_directory(root_path, config, tx, estimated_total_entries)
+    if config.threads > 1 {
+        return scan_parallel(root_path, config, reporter);
+    }
+    let estimated_total_entries = estimate_total_entries(root_path, config, reporter)?;
+    scan_directory(root_path, config, reporter, estimated_total_entries)
 }
 
 fn estimate_total_entries(
     root_path: &Path,
     config: &ScanConfig,
-    tx: &Sender<ScanMessage>,
+    reporter: &ProgressReporter,
 ) -> Result<u64, String> {
     let mut progress = ScanProgress {
         phase: ScanPhase::Counting,
         ..Default::default()
     };
 
-    let walker = WalkDir::new(root_path)
-        .follow_links(false)
-        .max_depth(config.max_depth.max(1));
+    let walker = gateway::walk(root_path)
+        .max_depth(config.max_depth.max(1))
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !config.exclude.is_excluded(entry.path()));
 
     for entry_result in walker {
+        reporter.check_cancelled()?;
         match entry_result {
             Ok(entry) => {
                 progress.entries_scanned = progress.entries_scanned.saturating_add(1);
@@ -146,6 +631,7 @@ fn estimate_total_entries(
                 }
 
                 if entry.file_type().is_dir() {
+                    gateway::record(AccessKind::DirectoryListing);
                     progress.directories_scanned = progress.directories_scanned.saturating_add(1);
                 } else {
                     if let Some(max_files) = config.max_files {
@@ -163,15 +649,18 @@ fn estimate_total_entries(
             }
         }
 
-        if progress.entries_scanned % config.progress_interval.max(1) as u64 == 0 {
-            let _ = tx.send(ScanMessage::Progress(progress.clone()));
+        if progress
+            .entries_scanned
+            .is_multiple_of(config.progress_interval.max(1) as u64)
+        {
+            reporter.publish(&progress);
         }
     }
 
     let estimated_total_entries = progress.entries_scanned.max(1);
     progress.total_estimated_entries = Some(estimated_total_entries);
 
-    let _ = tx.send(ScanMessage::Progress(progress));
+    reporter.publish(&progress);
 
     Ok(estimated_total_entries)
 }
@@ -179,7 +668,7 @@ fn estimate_total_entries(
 fn scan_directory(
     root_path: &Path,
     config: &ScanConfig,
-    tx: &Sender<ScanMessage>,
+    reporter: &ProgressReporter,
     estimated_total_entries: u64,
 ) -> Result<ScanResult, String> {
     let root_name = root_path
@@ -188,7 +677,13 @@ fn scan_directory(
         .unwrap_or_else(|| root_path.display().to_string());
 
     let mut root = Node::new(root_name, root_path.to_path_buf(), 0);
-    let mut warnings = Vec::new();
+    root.is_dir = true;
+    let mut warnings = WarningCollector::new(config);
+    let mut exporter = EntryExporter::new(config, &mut warnings);
+    let mut permission_findings = Vec::new();
+    let mut symlinks = Vec::new();
+    let canonical_root =
+        gateway::canonicalize(root_path).unwrap_or_else(|_| root_path.to_path_buf());
     let mut progress = ScanProgress {
         phase: ScanPhase::Scanning,
         total_estimated_entries: Some(estimated_total_entries.max(1)),
@@ -197,12 +692,15 @@ fn scan_directory(
     };
 
     let phase_started = Instant::now();
+    let mut last_partial = phase_started;
 
-    let walker = WalkDir::new(root_path)
-        .follow_links(false)
-        .max_depth(config.max_depth.max(1));
+    let walker = gateway::walk(root_path)
+        .max_depth(config.max_depth.max(1))
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !config.exclude.is_excluded(entry.path()));
 
     for entry_result in walker {
+        reporter.check_cancelled()?;
         match entry_result {
             Ok(entry) => {
                 progress.entries_scanned = progress.entries_scanned.saturating_add(1);
@@ -213,6 +711,7 @@ fn scan_directory(
                 }
 
                 if entry.file_type().is_dir() {
+                    gateway::record(AccessKind::DirectoryListing);
                     progress.directories_scanned = progress.directories_scanned.saturating_add(1);
                 } else {
                     if let Some(max_files) = config.max_files {
@@ -234,44 +733,119 @@ fn scan_directory(
                     continue;
                 }
 
-                let size = if entry.file_type().is_dir() {
-                    0
+                if config.audit_permissions && permission_findings.len() < MAX_PERMISSION_FINDINGS {
+                    if let Ok(metadata) = gateway::metadata(entry.path()) {
+                        let issues = permission_issues(&metadata);
+                        if !issues.is_empty() {
+                            permission_findings.push(PermissionFinding {
+                                path: entry.path().to_path_buf(),
+                                is_dir: entry.file_type().is_dir(),
+                                issues,
+                            });
+                        }
+                    }
+                }
+
+                if entry.path_is_symlink() && symlinks.len() < MAX_SYMLINKS {
+                    symlinks.extend(inspect_link(entry.path(), root_path, &canonical_root));
+                }
+
+                let (size, allocated, modified, owner) = if entry.file_type().is_dir() {
+                    (0, None, None, 0)
                 } else {
-                    match fs::symlink_metadata(entry.path()) {
-                        Ok(metadata) => metadata.len(),
+                    match gateway::metadata(entry.path()) {
+                        Ok(metadata) => {
+                            exporter.write(
+                                entry.path(),
+                                metadata.len(),
+                                Some(&metadata),
+                                &mut warnings,
+                            );
+                            (
+                                metadata.len(),
+                                gateway::allocated_size(entry.path(), &metadata),
+                                unix_modified(&metadata),
+                                owner_id(&metadata),
+                            )
+                        }
                         Err(error) => {
                             progress.warnings = progress.warnings.saturating_add(1);
-                            warnings.push(format!(
-                                "Could not read metadata for {}: {}",
-                                entry.path().display(),
-                                error
-                            ));
-                            0
+                            warnings.record(
+                                WarningCategory::from_io(error.kind(), WarningCategory::Metadata),
+                                format!(
+                                    "Could not read metadata for {}: {}",
+                                    entry.path().display(),
+                                    error
+                                ),
+                            );
+                            exporter.write(entry.path(), 0, None, &mut warnings);
+                            (0, None, None, 0)
                         }
                     }
                 };
 
-                root.insert_relative(relative_path, size);
+                progress.bytes_scanned = progress.bytes_scanned.saturating_add(size);
+                if let Some(node) = root.insert_relative(relative_path, size) {
+                    node.allocated = allocated;
+                    node.modified = modified.unwrap_or(0);
+                    node.owner = owner;
+                    node.is_dir = entry.file_type().is_dir();
+                }
             }
             Err(error) => {
                 progress.warnings = progress.warnings.saturating_add(1);
-                warnings.push(format_walkdir_error(&error));
+                warnings.record(
+                    WarningCategory::from_walkdir(&error),
+                    format_walkdir_error(&error),
+                );
             }
         }
 
-        if progress.entries_scanned % config.progress_interval.max(1) as u64 == 0 {
+        if progress
+            .entries_scanned
+            .is_multiple_of(config.progress_interval.max(1) as u64)
+        {
             update_scan_progress_metrics(&mut progress, phase_started, false);
-            let _ = tx.send(ScanMessage::Progress(progress.clone()));
+            reporter.publish(&progress);
+            exporter.flush(&mut warnings);
+
+            if last_partial.elapsed() >= PARTIAL_TREE_INTERVAL {
+                reporter.publish_partial(&root);
+                last_partial = Instant::now();
+            }
         }
     }
 
+    exporter.flush(&mut warnings);
     root.compute_total_size();
     root.sort_children_by_size_desc();
 
     update_scan_progress_metrics(&mut progress, phase_started, true);
-    let _ = tx.send(ScanMessage::Progress(progress.clone()));
+    reporter.publish(&progress);
 
-    Ok(ScanResult {
+    Ok(finish_scan(
+        root,
+        &progress,
+        warnings,
+        permission_findings,
+        symlinks,
+    ))
+}
+
+fn finish_scan(
+    root: Node,
+    progress: &ScanProgress,
+    warnings: WarningCollector,
+    permission_findings: Vec<PermissionFinding>,
+    symlinks: Vec<SymlinkEntry>,
+) -> ScanResult {
+    let (warnings, warning_counts) = warnings.finish();
+    let owners = if cfg!(unix) {
+        summarize_owners(&root, &owner_names())
+    } else {
+        Vec::new()
+    };
+    ScanResult {
         root,
         stats: ScanStats {
             entries_scanned: progress.entries_scanned,
@@ -283,7 +857,364 @@ fn scan_directory(
             elapsed: Duration::ZERO,
         },
         warnings,
-    })
+        warning_counts,
+        permission_findings,
+        symlinks,
+        owners,
+    }
+}
+
+/// Both phases of a scan with [`ScanConfig::threads`] workers. Folders are
+/// listed concurrently, so the result is the same as a serial scan except
+/// which files are left out when the file limit is reached; no partial
+/// trees are streamed.
+fn scan_parallel(
+    root_path: &Path,
+    config: &ScanConfig,
+    reporter: &ProgressReporter,
+) -> Result<ScanResult, String> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.threads)
+        .build()
+        .map_err(|error| format!("Could not start scan threads: {error}"))?;
+    let root_name = root_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| root_path.display().to_string());
+
+    let counting = ParallelWalk::new(root_path, config, reporter, true);
+    let mut progress = ScanProgress {
+        phase: ScanPhase::Counting,
+        ..Default::default()
+    };
+    let mut tree = Node::new(root_name.clone(), root_path.to_path_buf(), 0);
+    counting.run(&pool, &mut tree, &mut progress, reporter, None)?;
+    let estimated_total_entries = progress.entries_scanned.max(1);
+    progress.total_estimated_entries = Some(estimated_total_entries);
+    reporter.publish(&progress);
+    drop(tree);
+
+    let scanning = ParallelWalk::new(root_path, config, reporter, false);
+    let mut progress = ScanProgress {
+        phase: ScanPhase::Scanning,
+        total_estimated_entries: Some(estimated_total_entries),
+        progress_percent: Some(0.0),
+        ..Default::default()
+    };
+    let phase_started = Instant::now();
+    let mut root = Node::new(root_name, root_path.to_path_buf(), 0);
+    root.is_dir = true;
+    scanning.run(
+        &pool,
+        &mut root,
+        &mut progress,
+        reporter,
+        Some(phase_started),
+    )?;
+    root.compute_total_size();
+    root.sort_children_by_size_desc();
+    update_scan_progress_metrics(&mut progress, phase_started, true);
+    reporter.publish(&progress);
+
+    let mut sinks = scanning
+        .sinks
+        .into_inner()
+        .map_err(|error| error.to_string())?;
+    sinks.exporter.flush(&mut sinks.warnings);
+    drop(sinks.exporter);
+    Ok(finish_scan(
+        root,
+        &progress,
+        sinks.warnings,
+        sinks.permission_findings,
+        sinks.symlinks,
+    ))
+}
+
+/// What the serial walk keeps in locals, behind one lock.
+struct ScanSinks {
+    warnings: WarningCollector,
+    exporter: EntryExporter,
+    permission_findings: Vec<PermissionFinding>,
+    symlinks: Vec<SymlinkEntry>,
+}
+
+/// State shared by the workers of a parallel walk. Counters are read by
+/// the scan thread, which publishes progress while the workers run.
+struct ParallelWalk<'a> {
+    root_path: &'a Path,
+    config: &'a ScanConfig,
+    cancel: &'a AtomicBool,
+    /// Only count entries; no metadata is read and no files are kept.
+    counting: bool,
+    canonical_root: PathBuf,
+    entries: AtomicU64,
+    files: AtomicU64,
+    directories: AtomicU64,
+    bytes: AtomicU64,
+    warning_count: AtomicU64,
+    truncated: AtomicBool,
+    current_path: Mutex<Option<PathBuf>>,
+    sinks: Mutex<ScanSinks>,
+}
+
+impl<'a> ParallelWalk<'a> {
+    fn new(
+        root_path: &'a Path,
+        config: &'a ScanConfig,
+        reporter: &'a ProgressReporter,
+        counting: bool,
+    ) -> Self {
+        let mut warnings = if counting {
+            WarningCollector::new(&ScanConfig::default())
+        } else {
+            WarningCollector::new(config)
+        };
+        let exporter = if counting {
+            EntryExporter { writer: None }
+        } else {
+            EntryExporter::new(config, &mut warnings)
+        };
+        Self {
+            root_path,
+            config,
+            cancel: &reporter.cancel,
+            counting,
+            canonical_root: gateway::canonicalize(root_path)
+                .unwrap_or_else(|_| root_path.to_path_buf()),
+            // The root itself counts as an entry, as in the serial walk.
+            entries: AtomicU64::new(1),
+            files: AtomicU64::new(0),
+            directories: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            warning_count: AtomicU64::new(0),
+            truncated: AtomicBool::new(false),
+            current_path: Mutex::new(None),
+            sinks: Mutex::new(ScanSinks {
+                warnings,
+                exporter,
+                permission_findings: Vec::new(),
+                symlinks: Vec::new(),
+            }),
+        }
+    }
+
+    /// Walks below `root` on `pool`, publishing progress until it is done.
+    /// `phase_started` is set for the scanning phase, whose progress has a
+    /// percentage.
+    fn run(
+        &self,
+        pool: &rayon::ThreadPool,
+        root: &mut Node,
+        progress: &mut ScanProgress,
+        reporter: &ProgressReporter,
+        phase_started: Option<Instant>,
+    ) -> Result<(), String> {
+        thread::scope(|scope| {
+            let scan_thread = thread::current();
+            let worker = scope.spawn(move || {
+                pool.install(|| self.visit(root, 0));
+                scan_thread.unpark();
+            });
+            while !worker.is_finished() {
+                thread::park_timeout(PARALLEL_PROGRESS_INTERVAL);
+                self.fill_progress(progress);
+                if let Some(started) = phase_started {
+                    update_scan_progress_metrics(progress, started, false);
+                }
+                reporter.publish(progress);
+                if let Ok(mut sinks) = self.sinks.lock() {
+                    let sinks = &mut *sinks;
+                    sinks.exporter.flush(&mut sinks.warnings);
+                }
+            }
+            worker
+                .join()
+                .map_err(|_| "Scan worker panicked".to_string())
+        })?;
+        reporter.check_cancelled()?;
+        self.fill_progress(progress);
+        Ok(())
+    }
+
+    fn fill_progress(&self, progress: &mut ScanProgress) {
+        progress.entries_scanned = self.entries.load(Ordering::Relaxed);
+        progress.files_scanned = self.files.load(Ordering::Relaxed);
+        progress.directories_scanned = self.directories.load(Ordering::Relaxed);
+        progress.bytes_scanned = self.bytes.load(Ordering::Relaxed);
+        progress.warnings = self.warning_count.load(Ordering::Relaxed);
+        progress.truncated = self.truncated.load(Ordering::Relaxed);
+        if let Ok(current_path) = self.current_path.lock() {
+            progress.current_path.clone_from(&current_path);
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed) || self.truncated.load(Ordering::Relaxed)
+    }
+
+    fn warn(&self, category: WarningCategory, message: String) {
+        self.warning_count.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut sinks) = self.sinks.lock() {
+            sinks.warnings.record(category, message);
+        }
+    }
+
+    /// Lists `folder`, which is `depth` levels below the root, then its
+    /// subfolders in parallel.
+    fn visit(&self, folder: &mut Node, depth: usize) {
+        if depth >= self.config.max_depth.max(1) || self.stopped() {
+            return;
+        }
+        if let Ok(mut current_path) = self.current_path.lock() {
+            *current_path = Some(folder.path.clone());
+        }
+        let entries = match gateway::read_dir(&folder.path) {
+            Ok(entries) => entries,
+            Err(error) => {
+                self.warn(
+                    WarningCategory::from_io(error.kind(), WarningCategory::Other),
+                    format!("Could not access {}: {error}", folder.path.display()),
+                );
+                return;
+            }
+        };
+
+        let mut subfolders = Vec::new();
+        for entry in entries {
+            if self.stopped() {
+                break;
+            }
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    self.warn(
+                        WarningCategory::from_io(error.kind(), WarningCategory::Other),
+                        format!("Could not access {}: {error}", folder.path.display()),
+                    );
+                    continue;
+                }
+            };
+            let path = entry.path();
+            if self.config.exclude.is_excluded(&path) {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                self.warn(
+                    WarningCategory::Metadata,
+                    format!("Could not read the type of {}", path.display()),
+                );
+                continue;
+            };
+            self.entries.fetch_add(1, Ordering::Relaxed);
+
+            if file_type.is_dir() {
+                self.directories.fetch_add(1, Ordering::Relaxed);
+            } else {
+                let files = self.files.fetch_add(1, Ordering::Relaxed);
+                if self
+                    .config
+                    .max_files
+                    .is_some_and(|max_files| files as usize >= max_files)
+                {
+                    self.files.fetch_sub(1, Ordering::Relaxed);
+                    self.entries.fetch_sub(1, Ordering::Relaxed);
+                    self.truncated.store(true, Ordering::Relaxed);
+                    break;
+                }
+            }
+            if self.counting && !file_type.is_dir() {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().to_string();
+            let mut node = Node::new(name, path, 0);
+            node.is_dir = file_type.is_dir();
+            if !self.counting {
+                self.inspect(&mut node, file_type);
+            }
+            if file_type.is_dir() {
+                subfolders.push(node);
+            } else {
+                folder.children.push(node);
+            }
+        }
+
+        subfolders
+            .par_iter_mut()
+            .for_each(|subfolder| self.visit(subfolder, depth + 1));
+        folder.children.append(&mut subfolders);
+    }
+
+    /// Everything the serial walk records for one entry besides counting it.
+    fn inspect(&self, node: &mut Node, file_type: fs::FileType) {
+        let path = node.path.as_path();
+        if self.config.audit_permissions {
+            if let Ok(metadata) = gateway::metadata(path) {
+                let issues = permission_issues(&metadata);
+                if let (false, Ok(mut sinks)) = (issues.is_empty(), self.sinks.lock()) {
+                    if sinks.permission_findings.len() < MAX_PERMISSION_FINDINGS {
+                        sinks.permission_findings.push(PermissionFinding {
+                            path: path.to_path_buf(),
+                            is_dir: file_type.is_dir(),
+                            issues,
+                        });
+                    }
+                }
+            }
+        }
+
+        if file_type.is_symlink() {
+            let link = inspect_link(path, self.root_path, &self.canonical_root);
+            if let (Some(link), Ok(mut sinks)) = (link, self.sinks.lock()) {
+                if sinks.symlinks.len() < MAX_SYMLINKS {
+                    sinks.symlinks.push(link);
+                }
+            }
+        }
+
+        if file_type.is_dir() {
+            return;
+        }
+        let metadata = gateway::metadata(path);
+        let exporting = self.config.jsonl_export.is_some();
+        match &metadata {
+            Ok(metadata) => {
+                node.size = metadata.len();
+                node.allocated = gateway::allocated_size(path, metadata);
+                node.modified = unix_modified(metadata).unwrap_or(0);
+                node.owner = owner_id(metadata);
+                self.bytes.fetch_add(metadata.len(), Ordering::Relaxed);
+            }
+            Err(error) => self.warn(
+                WarningCategory::from_io(error.kind(), WarningCategory::Metadata),
+                format!("Could not read metadata for {}: {error}", path.display()),
+            ),
+        }
+        if let (true, Ok(mut sinks)) = (exporting, self.sinks.lock()) {
+            let sinks = &mut *sinks;
+            sinks
+                .exporter
+                .write(path, node.size, metadata.as_ref().ok(), &mut sinks.warnings);
+        }
+    }
+}
+
+fn unix_modified(metadata: &fs::Metadata) -> Option<u64> {
+    let modified = metadata.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+#[cfg(unix)]
+fn owner_id(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.uid()
+}
+
+#[cfg(not(unix))]
+fn owner_id(_metadata: &fs::Metadata) -> u32 {
+    0
 }
 
 fn update_scan_progress_metrics(progress: &mut ScanProgress, started: Instant, finished: bool) {
@@ -346,3 +1277,139 @@ fn format_walkdir_error(error: &walkdir::Error) -> String {
 
     format!("Walkdir error: {error}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warning_collector_caps_details_but_counts_everything() {
+        let log_path =
+            std::env::temp_dir().join(format!("tree-map-base-warnings-{}.log", std::process::id()));
+        let mut collector = WarningCollector::new(&ScanConfig {
+            max_stored_warnings: 2,
+            warning_log: Some(log_path.clone()),
+            ..ScanConfig::default()
+        });
+
+        for index in 0..5 {
+            collector.record(WarningCategory::PermissionDenied, format!("denied {index}"));
+        }
+        collector.record(WarningCategory::Metadata, "metadata".to_string());
+        let (details, counts) = collector.finish();
+
+        assert_eq!(details, vec!["denied 0", "denied 1"]);
+        assert_eq!(
+            counts,
+            vec![
+                (WarningCategory::PermissionDenied, 5),
+                (WarningCategory::Metadata, 1)
+            ]
+        );
+        let logged = fs::read_to_string(&log_path).unwrap();
+        let _ = fs::remove_file(&log_path);
+        assert_eq!(logged.lines().count(), 6);
+    }
+
+    #[test]
+    fn entry_exporter_streams_json_lines() {
+        let export_path = std::env::temp_dir().join(format!(
+            "tree-map-base-entries-{}.jsonl",
+            std::process::id()
+        ));
+        let config = ScanConfig {
+            jsonl_export: Some(export_path.clone()),
+            ..ScanConfig::default()
+        };
+        let mut warnings = WarningCollector::new(&config);
+        let mut exporter = EntryExporter::new(&config, &mut warnings);
+
+        exporter.write(Path::new("/r/a \"b\".txt"), 12, None, &mut warnings);
+        exporter.flush(&mut warnings);
+        let written = fs::read_to_string(&export_path).unwrap();
+        exporter.write(Path::new("/r/c"), 3, None, &mut warnings);
+        drop(exporter);
+        let finished = fs::read_to_string(&export_path).unwrap();
+        let _ = fs::remove_file(&export_path);
+
+        assert_eq!(
+            written,
+            "{\"modified\":null,\"path\":\"/r/a \\\"b\\\".txt\",\"size\":12}\n"
+        );
+        assert_eq!(finished.lines().count(), 2);
+        assert!(warnings.finish().0.is_empty());
+    }
+
+    fn test_reporter() -> ProgressReporter {
+        let (sender, _receiver) = wake::channel(Waker::default());
+        ProgressReporter {
+            slot: Arc::new(Mutex::new(None)),
+            sender,
+            last_wake: Cell::new(None),
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    #[test]
+    fn parallel_scan_matches_serial_scan() {
+        let root = std::env::temp_dir().join(format!("tmb-parallel-{}", std::process::id()));
+        for (index, folder) in ["a", "a/b", "a/b/c", "d", "e/f"].iter().enumerate() {
+            fs::create_dir_all(root.join(folder)).unwrap();
+            fs::write(root.join(folder).join("file"), vec![0; index * 10 + 1]).unwrap();
+        }
+        fs::create_dir_all(root.join("empty")).unwrap();
+        let reporter = test_reporter();
+
+        let serial = run_scan_pipeline(&root, &ScanConfig::default(), &reporter).unwrap();
+        let parallel_config = ScanConfig {
+            threads: 4,
+            ..ScanConfig::default()
+        };
+        let parallel = run_scan_pipeline(&root, &parallel_config, &reporter).unwrap();
+        let _ = fs::remove_dir_all(&root);
+
+        let summary = |result: &ScanResult| {
+            let mut nodes = Vec::new();
+            let mut stack = vec![&result.root];
+            while let Some(node) = stack.pop() {
+                nodes.push((
+                    node.path.clone(),
+                    node.size,
+                    node.own_size,
+                    node.allocated,
+                    node.is_dir,
+                ));
+                stack.extend(&node.children);
+            }
+            nodes.sort();
+            let stats = &result.stats;
+            (
+                nodes,
+                stats.entries_scanned,
+                stats.files_scanned,
+                stats.directories_scanned,
+            )
+        };
+        assert_eq!(summary(&parallel), summary(&serial));
+        assert_eq!(serial.root.size, 1 + 11 + 21 + 31 + 41);
+        let file = serial.root.find(&root.join("d/file")).unwrap();
+        assert_eq!(file.allocated.is_some(), cfg!(any(unix, windows)));
+        assert!(!file.is_dir);
+        assert!(serial.root.find(&root.join("empty")).unwrap().is_dir);
+    }
+
+    #[test]
+    fn cancelled_scan_stops_before_finishing() {
+        let reporter = test_reporter();
+        let root = std::env::temp_dir().join(format!("tmb-cancel-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("file"), b"x").unwrap();
+
+        let finished = run_scan_pipeline(&root, &ScanConfig::default(), &reporter);
+        assert_eq!(finished.map(|result| result.root.size), Ok(1));
+        reporter.cancel.store(true, Ordering::Relaxed);
+        let cancelled = run_scan_pipeline(&root, &ScanConfig::default(), &reporter);
+        let _ = fs::remove_dir_all(&root);
+        assert_eq!(cancelled.err(), Some(SCAN_CANCELLED.to_string()));
+    }
+}
@@ -0,0 +1,134 @@
+//! The shape of a scanned tree rather than its size: how deep it goes, how
+//! wide its folders are, and how big its files are on average.
+
+use crate::model::Node;
+use std::path::PathBuf;
+
+/// Upper bounds of the fan-out buckets; folders with more children than the
+/// last bound fall into one final bucket.
+pub const FAN_OUT_BOUNDS: &[usize] = &[1, 4, 16, 64, 256, 1024];
+
+#[derive(Debug, Clone, Default)]
+pub struct TreeShape {
+    /// Entries at each depth below the root (index 0 = the root's children).
+    pub depth_histogram: Vec<u64>,
+    /// Folders per [`FAN_OUT_BOUNDS`] bucket, plus one bucket for larger ones.
+    pub fan_out_histogram: Vec<u64>,
+    pub files: u64,
+    pub folders: u64,
+    pub file_bytes: u64,
+    /// The entry furthest below the root and its depth.
+    pub deepest: Option<(PathBuf, usize)>,
+    /// The folder with the most direct children and their count.
+    pub widest: Option<(PathBuf, usize)>,
+}
+
+impl TreeShape {
+    pub fn of(root: &Node) -> Self {
+        let mut shape = Self {
+            fan_out_histogram: vec![0; FAN_OUT_BOUNDS.len() + 1],
+            ..Self::default()
+        };
+        shape.visit(root, 0);
+        shape
+    }
+
+    pub fn average_file_size(&self) -> Option<u64> {
+        (self.files > 0).then(|| self.file_bytes / self.files)
+    }
+
+    fn visit(&mut self, node: &Node, depth: usize) {
+        if depth > 0 {
+            if self.depth_histogram.len() < depth {
+                self.depth_histogram.resize(depth, 0);
+            }
+            self.depth_histogram[depth - 1] += 1;
+            if self
+                .deepest
+                .as_ref()
+                .is_none_or(|(_, deepest)| depth > *deepest)
+            {
+                self.deepest = Some((node.path.clone(), depth));
+            }
+        }
+
+        if node.children.is_empty() && depth > 0 {
+            self.files += 1;
+            self.file_bytes += node.size;
+            return;
+        }
+
+        self.folders += 1;
+        let fan_out = node.children.len();
+        if fan_out == 0 {
+            return;
+        }
+        let bucket = FAN_OUT_BOUNDS
+            .iter()
+            .position(|&bound| fan_out <= bound)
+            .unwrap_or(FAN_OUT_BOUNDS.len());
+        self.fan_out_histogram[bucket] += 1;
+        if self
+            .widest
+            .as_ref()
+            .is_none_or(|(_, widest)| fan_out > *widest)
+        {
+            self.widest = Some((node.path.clone(), fan_out));
+        }
+        for child in &node.children {
+            self.visit(child, depth + 1);
+        }
+    }
+}
+
+/// "1", "2-4", ..., "1025+" for each fan-out bucket.
+pub fn fan_out_label(bucket: usize) -> String {
+    let low = match bucket {
+        0 => 1,
+        _ => FAN_OUT_BOUNDS[bucket - 1] + 1,
+    };
+    match FAN_OUT_BOUNDS.get(bucket) {
+        Some(&high) if high == low => low.to_string(),
+        Some(&high) => format!("{low}-{high}"),
+        None => format!("{low}+"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn measures_depth_fan_out_and_file_sizes() {
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        for (path, size) in [
+            ("a.txt", 10),
+            ("src/lib.rs", 20),
+            ("src/main.rs", 30),
+            ("src/bin/tool.rs", 40),
+        ] {
+            root.insert_relative(Path::new(path), size);
+        }
+        root.compute_total_size();
+
+        let shape = TreeShape::of(&root);
+        assert_eq!(shape.depth_histogram, vec![2, 3, 1]);
+        assert_eq!(shape.files, 4);
+        assert_eq!(shape.folders, 3);
+        assert_eq!(shape.average_file_size(), Some(25));
+        assert_eq!(
+            shape.deepest,
+            Some((PathBuf::from("/r/src/bin/tool.rs"), 3))
+        );
+        assert_eq!(shape.widest, Some((PathBuf::from("/r/src"), 3)));
+        // Fan-outs 2, 3 and 1.
+        assert_eq!(shape.fan_out_histogram, vec![1, 2, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            (0..=FAN_OUT_BOUNDS.len())
+                .map(fan_out_label)
+                .collect::<Vec<_>>(),
+            vec!["1", "2-4", "5-16", "17-64", "65-256", "257-1024", "1025+"]
+        );
+    }
+}
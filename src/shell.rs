@@ -0,0 +1,146 @@
+//! Opt-in file manager integration: a "Scan with tree-map-base" entry on
+//! folders that launches the app with `--root <folder>`.
+//!
+//! Windows registers Explorer verbs under `HKEY_CURRENT_USER` (no admin
+//! rights needed); Linux installs a Nautilus script. Nothing is registered
+//! unless `--install-context-menu` is run, and `--uninstall-context-menu`
+//! removes exactly what was added.
+
+#[cfg(not(windows))]
+use crate::portable;
+use std::env;
+#[cfg(not(windows))]
+use std::fs;
+use std::path::Path;
+#[cfg(not(windows))]
+use std::path::PathBuf;
+
+pub const MENU_LABEL: &str = "Scan with tree-map-base";
+
+/// Registers the entry for the running executable and describes what was
+/// installed.
+pub fn install_context_menu() -> Result<String, String> {
+    let exe = env::current_exe()
+        .map_err(|error| format!("Could not locate the tree-map-base executable: {error}"))?;
+    install_for(&exe)
+}
+
+#[cfg(windows)]
+const REGISTRY_KEYS: [(&str, &str); 2] = [
+    (r"HKCU\Software\Classes\Directory\shell\tree-map-base", "%1"),
+    (
+        r"HKCU\Software\Classes\Directory\Background\shell\tree-map-base",
+        "%V",
+    ),
+];
+
+#[cfg(windows)]
+fn install_for(exe: &Path) -> Result<String, String> {
+    for (key, placeholder) in REGISTRY_KEYS {
+        let exe = exe.display();
+        reg(&["add", key, "/ve", "/d", MENU_LABEL, "/f"])?;
+        reg(&["add", key, "/v", "Icon", "/d", &exe.to_string(), "/f"])?;
+        let command = format!("\"{exe}\" --root \"{placeholder}\"");
+        reg(&[
+            "add",
+            &format!(r"{key}\command"),
+            "/ve",
+            "/d",
+            &command,
+            "/f",
+        ])?;
+    }
+    Ok(format!(
+        "Added \"{MENU_LABEL}\" to the Explorer folder menu"
+    ))
+}
+
+#[cfg(windows)]
+pub fn uninstall_context_menu() -> Result<String, String> {
+    for (key, _) in REGISTRY_KEYS {
+        reg(&["delete", key, "/f"])?;
+    }
+    Ok(format!(
+        "Removed \"{MENU_LABEL}\" from the Explorer folder menu"
+    ))
+}
+
+#[cfg(windows)]
+fn reg(args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|error| format!("Could not run reg.exe: {error}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "reg {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(not(windows))]
+fn install_for(exe: &Path) -> Result<String, String> {
+    let path = nautilus_script_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Could not create {}: {error}", parent.display()))?;
+    }
+    fs::write(&path, nautilus_script(exe))
+        .map_err(|error| format!("Could not write {}: {error}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .map_err(|error| format!("Could not make {} executable: {error}", path.display()))?;
+    }
+    Ok(format!("Installed Nautilus script {}", path.display()))
+}
+
+#[cfg(not(windows))]
+pub fn uninstall_context_menu() -> Result<String, String> {
+    let path = nautilus_script_path()?;
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(format!("Removed Nautilus script {}", path.display())),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            Ok("No context menu entry was installed".to_string())
+        }
+        Err(error) => Err(format!("Could not remove {}: {error}", path.display())),
+    }
+}
+
+#[cfg(not(windows))]
+fn nautilus_script_path() -> Result<PathBuf, String> {
+    let data_dir = portable::data_home()
+        .ok_or_else(|| "Could not determine the home directory".to_string())?;
+    Ok(data_dir.join("nautilus").join("scripts").join(MENU_LABEL))
+}
+
+/// Scans the first selected folder, or the folder being viewed when the
+/// script is run on a file or the background.
+#[cfg(not(windows))]
+fn nautilus_script(exe: &Path) -> String {
+    let exe = exe.display().to_string().replace('\'', r"'\''");
+    format!(
+        "#!/bin/sh\n\
+         # Installed by tree-map-base --install-context-menu\n\
+         dir=$(printf '%s\\n' \"$NAUTILUS_SCRIPT_SELECTED_FILE_PATHS\" | head -n 1)\n\
+         [ -d \"$dir\" ] || dir=$(pwd)\n\
+         exec '{exe}' --root \"$dir\"\n"
+    )
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nautilus_script_quotes_executable() {
+        let script = nautilus_script(Path::new("/opt/it's here/tree-map-base"));
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains(r#"exec '/opt/it'\''s here/tree-map-base' --root "$dir""#));
+    }
+}
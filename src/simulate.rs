@@ -0,0 +1,261 @@
+use crate::action::{ActionEvent, ActionKind};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of events fired by a single "Simulate" click.
+pub const BURST_SIZE: usize = 6;
+/// Upper bound on events emitted by one continuous-run tick, so a long stall
+/// (window hidden, debugger pause) does not release a flood at once.
+const MAX_EVENTS_PER_TICK: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetBias {
+    Uniform,
+    LargeFiles,
+    SmallFiles,
+}
+
+impl TargetBias {
+    pub const ALL: [TargetBias; 3] = [Self::Uniform, Self::LargeFiles, Self::SmallFiles];
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub events_per_second: f32,
+    /// Relative weight per action kind; kinds with weight 0 are never chosen.
+    pub kind_weights: Vec<(ActionKind, u32)>,
+    pub target_bias: TargetBias,
+    /// Fixed seed for reproducible runs; `None` seeds from the clock.
+    pub seed: Option<u64>,
+    pub continuous: bool,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        let default_kinds = [
+            ActionKind::Inspect,
+            ActionKind::Classify,
+            ActionKind::Correlate,
+            ActionKind::Trace,
+            ActionKind::Verify,
+            ActionKind::Highlight,
+        ];
+
+        Self {
+            events_per_second: 4.0,
+            kind_weights: ActionKind::ALL
+                .into_iter()
+                .map(|kind| (kind, u32::from(default_kinds.contains(&kind))))
+                .collect(),
+            target_bias: TargetBias::Uniform,
+            seed: None,
+            continuous: false,
+        }
+    }
+}
+
+/// A candidate path for simulated actions.
+#[derive(Debug, Clone)]
+pub struct SimulationTarget {
+    pub path: PathBuf,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Generates synthetic agent actions from a [`SimulationConfig`].
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    pub config: SimulationConfig,
+    rng_state: u64,
+    pending_events: f32,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new(SimulationConfig::default())
+    }
+}
+
+impl Simulator {
+    pub fn new(config: SimulationConfig) -> Self {
+        let mut simulator = Self {
+            config,
+            rng_state: 0,
+            pending_events: 0.0,
+        };
+        simulator.reset();
+        simulator
+    }
+
+    /// Restarts the random sequence from the configured seed.
+    pub fn reset(&mut self) {
+        self.rng_state = self.config.seed.unwrap_or_else(time_seed);
+        self.pending_events = 0.0;
+    }
+
+    pub fn burst(&mut self, targets: &[SimulationTarget]) -> Vec<ActionEvent> {
+        (0..BURST_SIZE.min(targets.len()))
+            .filter_map(|_| self.next_event(targets))
+            .collect()
+    }
+
+    /// Advances a continuous run by `delta_seconds`, returning the events due.
+    pub fn tick(&mut self, delta_seconds: f32, targets: &[SimulationTarget]) -> Vec<ActionEvent> {
+        if !self.config.continuous || targets.is_empty() {
+            self.pending_events = 0.0;
+            return Vec::new();
+        }
+
+        self.pending_events += self.config.events_per_second.max(0.0) * delta_seconds.max(0.0);
+        let due = (self.pending_events.floor() as usize).min(MAX_EVENTS_PER_TICK);
+        self.pending_events = (self.pending_events - due as f32).min(1.0);
+
+        (0..due).filter_map(|_| self.next_event(targets)).collect()
+    }
+
+    fn next_event(&mut self, targets: &[SimulationTarget]) -> Option<ActionEvent> {
+        let kind = self.pick_kind()?;
+        let target = self.pick_target(targets)?;
+        Some(ActionEvent::new(kind, target.path.clone()))
+    }
+
+    fn pick_kind(&mut self) -> Option<ActionKind> {
+        let total: u64 = self
+            .config
+            .kind_weights
+            .iter()
+            .map(|(_, weight)| u64::from(*weight))
+            .sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = self.next_u64() % total;
+        for (kind, weight) in &self.config.kind_weights {
+            let weight = u64::from(*weight);
+            if roll < weight {
+                return Some(*kind);
+            }
+            roll -= weight;
+        }
+        None
+    }
+
+    fn pick_target<'a>(&mut self, targets: &'a [SimulationTarget]) -> Option<&'a SimulationTarget> {
+        if targets.is_empty() {
+            return None;
+        }
+
+        let bias = self.config.target_bias;
+        let weight_of = |target: &SimulationTarget| -> f64 {
+            match bias {
+                TargetBias::Uniform => 1.0,
+                // Folders are covered by their children, so biased modes aim at files.
+                _ if target.is_dir => 0.0,
+                TargetBias::LargeFiles => target.size.max(1) as f64,
+                TargetBias::SmallFiles => 1.0 / target.size.max(1) as f64,
+            }
+        };
+
+        let total: f64 = targets.iter().map(weight_of).sum();
+        if total <= 0.0 {
+            let index = (self.next_u64() % targets.len() as u64) as usize;
+            return targets.get(index);
+        }
+
+        let mut roll = self.next_f64() * total;
+        for target in targets {
+            let weight = weight_of(target);
+            if roll < weight {
+                return Some(target);
+            }
+            roll -= weight;
+        }
+        targets.iter().rev().find(|target| weight_of(target) > 0.0)
+    }
+
+    /// SplitMix64.
+    fn next_u64(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.rng_state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+fn time_seed() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_nanos() as u64,
+        Err(_) => 0xA5A5_5A5A_1234_5678,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn targets() -> Vec<SimulationTarget> {
+        [
+            ("big.bin", 1_000_000),
+            ("small.txt", 10),
+            ("dir", 2_000_000),
+        ]
+        .into_iter()
+        .map(|(name, size)| SimulationTarget {
+            path: PathBuf::from(name),
+            size,
+            is_dir: name == "dir",
+        })
+        .collect()
+    }
+
+    #[test]
+    fn fixed_seed_reproduces_the_same_sequence() {
+        let config = SimulationConfig {
+            seed: Some(42),
+            ..SimulationConfig::default()
+        };
+        let summarize = |events: Vec<ActionEvent>| {
+            events
+                .into_iter()
+                .map(|event| (event.kind, event.target_path))
+                .collect::<Vec<_>>()
+        };
+
+        let first = summarize(Simulator::new(config.clone()).burst(&targets()));
+        let second = summarize(Simulator::new(config).burst(&targets()));
+
+        assert_eq!(first.len(), 3);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn weights_and_bias_constrain_choices() {
+        let mut simulator = Simulator::new(SimulationConfig {
+            kind_weights: vec![(ActionKind::Read, 0), (ActionKind::Write, 1)],
+            target_bias: TargetBias::LargeFiles,
+            events_per_second: 100.0,
+            continuous: true,
+            seed: Some(7),
+        });
+
+        let events = simulator.tick(1.0, &targets());
+
+        assert_eq!(events.len(), 100);
+        assert!(events.iter().all(|event| event.kind == ActionKind::Write));
+        assert!(events
+            .iter()
+            .all(|event| event.target_path != Path::new("dir")));
+        let big_hits = events
+            .iter()
+            .filter(|event| event.target_path == Path::new("big.bin"))
+            .count();
+        assert!(big_hits > 90);
+    }
+}
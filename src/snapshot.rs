@@ -0,0 +1,400 @@
+//! Compact on-disk snapshots of scan results.
+//!
+//! Layout: the 8-byte magic `TMBSNAP\0`, a little-endian `u16` format
+//! version, then a zstd-compressed postcard encoding of [`ScanResult`].
+//!
+//! Snapshots saved under a `.json` name are plain JSON instead,
+//! `{"format": "tree-map-base", "version": …, "scan": {…}}`, for reading
+//! with other tools; they are several times larger and slower to load.
+
+use crate::analysis::{AliasEntry, AliasKind};
+use crate::model::Node;
+use crate::owners::OwnerStat;
+use crate::portable;
+use crate::scanner::ScanResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+pub const SNAPSHOT_EXTENSION: &str = "tmbsnap";
+pub const JSON_SNAPSHOT_EXTENSION: &str = "json";
+const JSON_FORMAT_NAME: &str = "tree-map-base";
+const MAGIC: &[u8; 8] = b"TMBSNAP\0";
+const FORMAT_VERSION: u16 = 8;
+const COMPRESSION_LEVEL: i32 = 3;
+
+#[derive(Serialize)]
+struct JsonSnapshotRef<'a> {
+    format: &'a str,
+    version: u16,
+    scan: &'a ScanResult,
+}
+
+#[derive(Deserialize)]
+struct JsonSnapshot {
+    format: String,
+    version: u16,
+    scan: ScanResult,
+}
+
+/// Saves a compact snapshot, or JSON when `path` ends in `.json`.
+pub fn save_snapshot(path: &Path, result: &ScanResult) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|error| format!("Could not create {}: {error}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(JSON_SNAPSHOT_EXTENSION));
+    if is_json {
+        write_json_snapshot(&mut writer, result)?;
+    } else {
+        write_snapshot(&mut writer, result)?;
+    }
+    writer
+        .flush()
+        .map_err(|error| format!("Could not write {}: {error}", path.display()))
+}
+
+/// Snapshot of the most recent completed scan, opened on the next launch.
+pub fn last_snapshot_path() -> Option<PathBuf> {
+    Some(portable::cache_dir()?.join(format!("last.{SNAPSHOT_EXTENSION}")))
+}
+
+/// Writes through a temporary file so an interrupted save never leaves a
+/// truncated snapshot behind.
+pub fn replace_snapshot(path: &Path, result: &ScanResult) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Could not create {}: {error}", parent.display()))?;
+    }
+    let temporary = path.with_extension(format!("{SNAPSHOT_EXTENSION}.tmp"));
+    save_snapshot(&temporary, result)?;
+    fs::rename(&temporary, path)
+        .map_err(|error| format!("Could not replace {}: {error}", path.display()))
+}
+
+/// Loads either kind of snapshot, told apart by content.
+pub fn load_snapshot(path: &Path) -> Result<ScanResult, String> {
+    let file =
+        File::open(path).map_err(|error| format!("Could not open {}: {error}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let is_json = reader
+        .fill_buf()
+        .map_err(|error| format!("Could not read {}: {error}", path.display()))?
+        .iter()
+        .find(|byte| !byte.is_ascii_whitespace())
+        == Some(&b'{');
+    if is_json {
+        read_json_snapshot(reader)
+    } else {
+        read_snapshot(reader)
+    }
+}
+
+/// Copy of `result` safe to hand to someone else: every name becomes its
+/// demo mode alias ("Folder C", "File AB") and paths are rebuilt from those
+/// aliases, while sizes and structure are kept. Warning messages quote real
+/// paths and are dropped; their per-category counts are kept.
+pub fn anonymized(result: &ScanResult, aliases: &HashMap<PathBuf, AliasEntry>) -> ScanResult {
+    let mut renamed = HashMap::new();
+    let root_name = alias_name(aliases, &result.root);
+    let root = anonymize_node(
+        &result.root,
+        root_name.clone(),
+        PathBuf::from(root_name),
+        aliases,
+        &mut renamed,
+    );
+    let permission_findings = result
+        .permission_findings
+        .iter()
+        .filter_map(|finding| {
+            let mut finding = finding.clone();
+            finding.path = renamed.get(&finding.path)?.clone();
+            Some(finding)
+        })
+        .collect();
+    // Targets outside the tree have no alias, so only the status is kept.
+    let symlinks = result
+        .symlinks
+        .iter()
+        .filter_map(|link| {
+            let mut link = link.clone();
+            link.path = renamed.get(&link.path)?.clone();
+            link.resolved = renamed.get(&link.resolved).cloned().unwrap_or_default();
+            link.target = link.resolved.clone();
+            Some(link)
+        })
+        .collect();
+
+    // User names identify people; owners become "Owner 1", "Owner 2", ...
+    let owners = result
+        .owners
+        .iter()
+        .enumerate()
+        .map(|(index, owner)| {
+            let rename = |entries: &[(PathBuf, u64)]| {
+                entries
+                    .iter()
+                    .filter_map(|(path, bytes)| Some((renamed.get(path)?.clone(), *bytes)))
+                    .collect()
+            };
+            OwnerStat {
+                uid: owner.uid,
+                name: Some(format!("Owner {}", index + 1)),
+                bytes: owner.bytes,
+                files: owner.files,
+                largest_files: rename(&owner.largest_files),
+                top_folders: rename(&owner.top_folders),
+            }
+        })
+        .collect();
+
+    ScanResult {
+        root,
+        stats: result.stats.clone(),
+        warnings: Vec::new(),
+        warning_counts: result.warning_counts.clone(),
+        permission_findings,
+        symlinks,
+        owners,
+    }
+}
+
+fn anonymize_node(
+    node: &Node,
+    name: String,
+    path: PathBuf,
+    aliases: &HashMap<PathBuf, AliasEntry>,
+    renamed: &mut HashMap<PathBuf, PathBuf>,
+) -> Node {
+    let mut copy = Node::new(name, path, node.size);
+    copy.own_size = node.own_size;
+    copy.allocated = node.allocated;
+    copy.modified = node.modified;
+    copy.owner = node.owner;
+    copy.is_dir = node.is_dir;
+    copy.children = node
+        .children
+        .iter()
+        .map(|child| {
+            let child_name = alias_name(aliases, child);
+            let child_path = copy.path.join(&child_name);
+            anonymize_node(child, child_name, child_path, aliases, renamed)
+        })
+        .collect();
+    renamed.insert(node.path.clone(), copy.path.clone());
+    copy
+}
+
+fn alias_name(aliases: &HashMap<PathBuf, AliasEntry>, node: &Node) -> String {
+    match aliases.get(&node.path) {
+        Some(AliasEntry {
+            code,
+            kind: AliasKind::Folder,
+        }) => format!("Folder {code}"),
+        Some(AliasEntry {
+            code,
+            kind: AliasKind::File,
+        }) => format!("File {code}"),
+        None if node.children.is_empty() => "File ?".to_string(),
+        None => "Folder ?".to_string(),
+    }
+}
+
+pub fn write_snapshot<W: Write>(writer: &mut W, result: &ScanResult) -> Result<(), String> {
+    let encoded = postcard::to_stdvec(result)
+        .map_err(|error| format!("Could not encode snapshot: {error}"))?;
+
+    writer
+        .write_all(MAGIC)
+        .and_then(|()| writer.write_all(&FORMAT_VERSION.to_le_bytes()))
+        .and_then(|()| {
+            zstd::stream::copy_encode(encoded.as_slice(), &mut *writer, COMPRESSION_LEVEL)
+        })
+        .map_err(|error| format!("Could not write snapshot: {error}"))
+}
+
+pub fn write_json_snapshot<W: Write>(writer: &mut W, result: &ScanResult) -> Result<(), String> {
+    let snapshot = JsonSnapshotRef {
+        format: JSON_FORMAT_NAME,
+        version: FORMAT_VERSION,
+        scan: result,
+    };
+    serde_json::to_writer(writer, &snapshot)
+        .map_err(|error| format!("Could not write JSON snapshot: {error}"))
+}
+
+pub fn read_json_snapshot<R: Read>(reader: R) -> Result<ScanResult, String> {
+    let snapshot: JsonSnapshot = serde_json::from_reader(reader)
+        .map_err(|error| format!("Not a tree-map-base JSON snapshot: {error}"))?;
+    if snapshot.format != JSON_FORMAT_NAME {
+        return Err(format!(
+            "Unknown JSON snapshot format {:?}",
+            snapshot.format
+        ));
+    }
+    if snapshot.version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported snapshot version {} (expected {FORMAT_VERSION})",
+            snapshot.version
+        ));
+    }
+    Ok(snapshot.scan)
+}
+
+pub fn read_snapshot<R: Read>(mut reader: R) -> Result<ScanResult, String> {
+    let mut header = [0_u8; 10];
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| "Not a tree-map-base snapshot (file too short)".to_string())?;
+    if &header[..8] != MAGIC {
+        return Err("Not a tree-map-base snapshot (bad magic)".to_string());
+    }
+
+    let version = u16::from_le_bytes([header[8], header[9]]);
+    if version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported snapshot version {version} (expected {FORMAT_VERSION})"
+        ));
+    }
+
+    let encoded = zstd::stream::decode_all(reader)
+        .map_err(|error| format!("Could not decompress snapshot: {error}"))?;
+    postcard::from_bytes(&encoded).map_err(|error| format!("Corrupt snapshot: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Node;
+    use crate::scanner::{ScanStats, WarningCategory};
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    fn sample_result(files: usize) -> ScanResult {
+        let mut root = Node::new("root".to_string(), PathBuf::from("/data/root"), 0);
+        for index in 0..files {
+            let relative = PathBuf::from(format!(
+                "dir_{}/sub_{}/file_{index}.bin",
+                index % 50,
+                index % 7
+            ));
+            root.insert_relative(&relative, index as u64 * 31 + 7);
+        }
+        root.compute_total_size();
+        root.sort_children_by_size_desc();
+
+        ScanResult {
+            root,
+            stats: ScanStats {
+                entries_scanned: files as u64,
+                elapsed: Duration::from_millis(1234),
+                ..ScanStats::default()
+            },
+            warnings: vec!["Could not read /data/root/locked".to_string()],
+            warning_counts: vec![(WarningCategory::PermissionDenied, 1)],
+            permission_findings: Vec::new(),
+            symlinks: Vec::new(),
+            owners: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn round_trips_and_rejects_foreign_files() {
+        let result = sample_result(500);
+        let mut bytes = Vec::new();
+        write_snapshot(&mut bytes, &result).unwrap();
+
+        let loaded = read_snapshot(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.root.size, result.root.size);
+        assert_eq!(loaded.root.own_size, result.root.own_size);
+        assert_eq!(loaded.root.children.len(), result.root.children.len());
+        assert_eq!(loaded.stats.elapsed, result.stats.elapsed);
+        assert_eq!(loaded.warnings, result.warnings);
+        assert_eq!(loaded.warning_counts, result.warning_counts);
+
+        assert!(read_snapshot(&b"{\"root\":{}}"[..]).is_err());
+        bytes[8] = 99;
+        assert!(read_snapshot(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn json_snapshots_load_like_compact_ones() {
+        let result = sample_result(20);
+        let path = std::env::temp_dir().join(format!("tmb-scan-{}.json", std::process::id()));
+        save_snapshot(&path, &result).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        let loaded = load_snapshot(&path);
+        let _ = fs::remove_file(&path);
+
+        assert!(text.starts_with("{\"format\":\"tree-map-base\",\"version\":"));
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.root.size, result.root.size);
+        assert_eq!(loaded.stats.elapsed, result.stats.elapsed);
+        assert_eq!(loaded.warning_counts, result.warning_counts);
+
+        let foreign =
+            format!("{{\"format\":\"other\",\"version\":{FORMAT_VERSION},\"scan\":null}}");
+        assert!(read_json_snapshot(foreign.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn anonymized_snapshot_keeps_shape_but_no_names() {
+        let result = sample_result(40);
+        let aliases = crate::analysis::ScanAnalysis::of(&result.root).alias_map;
+        let anonymous = anonymized(&result, &aliases);
+
+        assert_eq!(anonymous.root.name, "Folder A");
+        assert_eq!(anonymous.root.path, PathBuf::from("Folder A"));
+        assert_eq!(anonymous.root.size, result.root.size);
+        assert_eq!(anonymous.root.children.len(), result.root.children.len());
+        assert!(anonymous.warnings.is_empty());
+        assert_eq!(anonymous.warning_counts, result.warning_counts);
+
+        let mut stack = vec![&anonymous.root];
+        while let Some(node) = stack.pop() {
+            let text = node.path.to_string_lossy();
+            assert!(!text.contains("data") && !text.contains("file_"), "{text}");
+            for child in &node.children {
+                assert_eq!(child.path, node.path.join(&child.name));
+                stack.push(child);
+            }
+        }
+    }
+
+    /// Size/speed comparison against JSON:
+    /// `cargo test --release snapshot_vs_json -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn snapshot_vs_json_benchmark() {
+        let result = sample_result(1_000_000);
+
+        let started = Instant::now();
+        let mut binary = Vec::new();
+        write_snapshot(&mut binary, &result).unwrap();
+        let binary_write = started.elapsed();
+        let started = Instant::now();
+        read_snapshot(binary.as_slice()).unwrap();
+        let binary_read = started.elapsed();
+
+        let started = Instant::now();
+        let json = serde_json::to_vec(&result).unwrap();
+        let json_write = started.elapsed();
+        let started = Instant::now();
+        serde_json::from_slice::<ScanResult>(&json).unwrap();
+        let json_read = started.elapsed();
+
+        println!(
+            "snapshot: {} bytes, write {binary_write:?}, read {binary_read:?}",
+            binary.len()
+        );
+        println!(
+            "json:     {} bytes, write {json_write:?}, read {json_read:?}",
+            json.len()
+        );
+        assert!(binary.len() < json.len());
+    }
+}
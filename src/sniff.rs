@@ -0,0 +1,131 @@
+//! Optional content-based typing: reads the first bytes of every file and
+//! records the type found by its magic number wherever that disagrees with
+//! the extension (extension-less blobs, renamed files).
+//!
+//! Runs on a background thread after a scan; the treemap colors, the type
+//! legend and the tooltip then use the detected type instead.
+
+use crate::analysis::{collect_files, file_type_key, summarize_types, TypeStat};
+use crate::gateway;
+use crate::model::Node;
+use crate::wake::{self, Waker};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+/// Enough for every signature `infer` checks (tar's sits at offset 257).
+const SNIFF_BYTES: usize = 8 * 1024;
+
+/// Extensions that name the same format, or a format stored in the container
+/// `infer` reports (Office documents and Java archives are zip files).
+const EQUIVALENT_TYPES: &[&[&str]] = &[
+    &["jpg", "jpeg", "jpe", "jfif"],
+    &["tif", "tiff"],
+    &["htm", "html"],
+    &["mpg", "mpeg"],
+    &["gz", "tgz"],
+    &["mp4", "m4v", "m4a"],
+    &["ogg", "oga", "ogv", "opus"],
+    &["exe", "dll", "sys", "msi"],
+    &["elf", "so", "o"],
+    &[
+        "zip", "jar", "apk", "aab", "epub", "docx", "xlsx", "pptx", "odt", "ods", "odp", "whl",
+        "nupkg", "vsix", "xpi", "ipa", "kmz", "3mf",
+    ],
+    &["doc", "xls", "ppt", "msg"],
+];
+
+#[derive(Debug)]
+pub struct SniffResult {
+    /// The scan generation the sniffed tree belonged to.
+    pub scan_generation: u64,
+    /// Detected type key per file whose content disagrees with its extension.
+    pub content_types: HashMap<PathBuf, String>,
+    pub type_stats: Vec<TypeStat>,
+    pub files_read: u64,
+}
+
+/// Sniffs every file of `root` on a background thread.
+pub fn spawn_sniff(root: &Node, scan_generation: u64, waker: Waker) -> Receiver<SniffResult> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    let files: Vec<(PathBuf, u64)> = files
+        .into_iter()
+        .filter(|node| !std::ptr::eq(*node, root))
+        .map(|node| (node.path.clone(), node.size))
+        .collect();
+    let (tx, rx) = wake::channel(waker);
+
+    thread::spawn(move || {
+        let detected: Vec<Option<String>> = files
+            .par_iter()
+            .map(|(path, _)| {
+                content_type_key(path).filter(|key| !same_type(key, &file_type_key(path)))
+            })
+            .collect();
+        let files_read = files.len() as u64;
+        let (type_stats, _) =
+            summarize_types(files.iter().zip(&detected).map(|((path, size), key)| {
+                (key.clone().unwrap_or_else(|| file_type_key(path)), *size)
+            }));
+        let content_types = files
+            .into_iter()
+            .zip(detected)
+            .filter_map(|((path, _), key)| Some((path, key?)))
+            .collect();
+        let _ = tx.send(SniffResult {
+            scan_generation,
+            content_types,
+            type_stats,
+            files_read,
+        });
+    });
+
+    rx
+}
+
+/// The type key (lowercase extension) matching the file's magic bytes.
+fn content_type_key(path: &Path) -> Option<String> {
+    let mut buffer = Vec::with_capacity(SNIFF_BYTES);
+    gateway::open(path)
+        .ok()?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut buffer)
+        .ok()?;
+    infer::get(&buffer).map(|kind| kind.extension().to_ascii_lowercase())
+}
+
+fn same_type(detected: &str, extension: &str) -> bool {
+    detected == extension
+        || EQUIVALENT_TYPES
+            .iter()
+            .any(|group| group.contains(&detected) && group.contains(&extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_renamed_files_only() {
+        let dir = std::env::temp_dir().join(format!("tree-map-base-sniff-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        std::fs::write(dir.join("blob"), png).unwrap();
+        std::fs::write(dir.join("real.png"), png).unwrap();
+        std::fs::write(dir.join("photo.jpeg"), b"\xFF\xD8\xFF\xE0\0\x10JFIF").unwrap();
+
+        let detected: Vec<_> = ["blob", "real.png", "photo.jpeg"]
+            .map(|name| {
+                let path = dir.join(name);
+                content_type_key(&path).filter(|key| !same_type(key, &file_type_key(&path)))
+            })
+            .into();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(detected, vec![Some("png".to_string()), None, None]);
+    }
+}
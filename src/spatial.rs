@@ -0,0 +1,140 @@
+use crate::memory::HeapSize;
+use eframe::egui::{Pos2, Rect};
+
+/// Average number of cells per grid bucket the grid is sized for.
+const TARGET_CELLS_PER_BUCKET: usize = 8;
+const MAX_BUCKETS_PER_AXIS: usize = 256;
+
+/// Uniform grid over world-space rects for visibility and hit queries.
+///
+/// Indices refer to the slice the grid was built from and are returned in
+/// ascending order, so callers keep the original paint order.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    bounds: Rect,
+    columns: usize,
+    rows: usize,
+    buckets: Vec<Vec<u32>>,
+    len: usize,
+}
+
+impl SpatialGrid {
+    pub fn build(bounds: Rect, rects: &[Rect]) -> Self {
+        let per_axis = ((rects.len() / TARGET_CELLS_PER_BUCKET) as f32)
+            .sqrt()
+            .ceil()
+            .clamp(1.0, MAX_BUCKETS_PER_AXIS as f32) as usize;
+        let mut grid = Self {
+            bounds,
+            columns: per_axis,
+            rows: per_axis,
+            buckets: vec![Vec::new(); per_axis * per_axis],
+            len: rects.len(),
+        };
+
+        for (index, rect) in rects.iter().enumerate() {
+            let Some((columns, rows)) = grid.bucket_span(*rect) else {
+                continue;
+            };
+            for row in rows.0..=rows.1 {
+                for column in columns.0..=columns.1 {
+                    grid.buckets[row * grid.columns + column].push(index as u32);
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// Indices of rects that may intersect `rect`, ascending.
+    pub fn query_rect(&self, rect: Rect) -> Vec<usize> {
+        if rect.contains_rect(self.bounds) {
+            return (0..self.len).collect();
+        }
+
+        let Some((columns, rows)) = self.bucket_span(rect) else {
+            return Vec::new();
+        };
+        let mut indices = Vec::new();
+        for row in rows.0..=rows.1 {
+            for column in columns.0..=columns.1 {
+                indices.extend(
+                    self.buckets[row * self.columns + column]
+                        .iter()
+                        .map(|&index| index as usize),
+                );
+            }
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Indices of rects that may contain `position`, ascending.
+    pub fn query_point(&self, position: Pos2) -> &[u32] {
+        if !self.bounds.contains(position) {
+            return &[];
+        }
+
+        let column = self.column_of(position.x);
+        let row = self.row_of(position.y);
+        &self.buckets[row * self.columns + column]
+    }
+
+    /// Inclusive (column, row) bucket ranges overlapped by `rect`.
+    fn bucket_span(&self, rect: Rect) -> Option<((usize, usize), (usize, usize))> {
+        if !rect.intersects(self.bounds) {
+            return None;
+        }
+        let clipped = rect.intersect(self.bounds);
+
+        Some((
+            (self.column_of(clipped.min.x), self.column_of(clipped.max.x)),
+            (self.row_of(clipped.min.y), self.row_of(clipped.max.y)),
+        ))
+    }
+
+    fn column_of(&self, x: f32) -> usize {
+        let fraction = (x - self.bounds.min.x) / self.bounds.width().max(f32::EPSILON);
+        ((fraction * self.columns as f32) as usize).min(self.columns - 1)
+    }
+
+    fn row_of(&self, y: f32) -> usize {
+        let fraction = (y - self.bounds.min.y) / self.bounds.height().max(f32::EPSILON);
+        ((fraction * self.rows as f32) as usize).min(self.rows - 1)
+    }
+}
+
+impl HeapSize for SpatialGrid {
+    fn heap_size(&self) -> usize {
+        self.buckets.heap_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::pos2;
+
+    #[test]
+    fn queries_return_only_nearby_cells_in_order() {
+        let bounds = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 100.0));
+        let rects: Vec<Rect> = (0..100)
+            .map(|index| {
+                let x = (index % 10) as f32 * 10.0;
+                let y = (index / 10) as f32 * 10.0;
+                Rect::from_min_max(pos2(x, y), pos2(x + 10.0, y + 10.0))
+            })
+            .collect();
+        let grid = SpatialGrid::build(bounds, &rects);
+
+        let visible = grid.query_rect(Rect::from_min_max(pos2(1.0, 1.0), pos2(9.0, 9.0)));
+        assert!(visible.contains(&0));
+        assert!(visible.len() < rects.len());
+        assert!(visible.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(grid.query_rect(bounds.expand(5.0)).len(), rects.len());
+        assert!(grid.query_point(pos2(55.0, 55.0)).contains(&55));
+        assert!(grid.query_point(pos2(500.0, 5.0)).is_empty());
+    }
+}
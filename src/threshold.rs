@@ -0,0 +1,173 @@
+//! Folder size limits checked after every scan, one rule per line:
+//!
+//! ```text
+//! D:\backups > 500 GB
+//! ~/Downloads > 20 GiB
+//! ```
+//!
+//! Units follow the size display: `KB` through `EB` and the IEC spellings
+//! `KiB` through `EiB` are all powers of 1024. The rules are kept in
+//! `thresholds.txt` in the config directory.
+
+use crate::format::parse_size;
+use crate::model::Node;
+use crate::portable::{config_dir, home_dir};
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdRule {
+    pub path: PathBuf,
+    pub limit: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdBreach {
+    pub path: PathBuf,
+    pub limit: u64,
+    pub size: u64,
+}
+
+/// Parses one rule per line; blank lines and `#` comments are skipped.
+pub fn parse_thresholds(text: &str) -> (Vec<ThresholdRule>, Vec<String>) {
+    let mut rules = Vec::new();
+    let mut errors = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((path, limit)) = line.rsplit_once('>') else {
+            errors.push(format!("{line}: expected \"<folder> > <size>\""));
+            continue;
+        };
+        let Some(limit) = parse_size(limit) else {
+            errors.push(format!("{line}: unrecognized size {:?}", limit.trim()));
+            continue;
+        };
+        rules.push(ThresholdRule {
+            path: expand_home(path.trim()),
+            limit,
+        });
+    }
+    (rules, errors)
+}
+
+/// Rules whose folder is in the scanned tree and larger than its limit.
+pub fn check_thresholds(rules: &[ThresholdRule], root: &Node) -> Vec<ThresholdBreach> {
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let node = root.find(&rule.path)?;
+            (node.size > rule.limit).then(|| ThresholdBreach {
+                path: rule.path.clone(),
+                limit: rule.limit,
+                size: node.size,
+            })
+        })
+        .collect()
+}
+
+pub fn thresholds_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("thresholds.txt"))
+}
+
+pub fn load_thresholds() -> String {
+    thresholds_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_thresholds(text: &str) -> Result<(), String> {
+    let path = thresholds_path().ok_or("No config directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Could not create {}: {error}", parent.display()))?;
+    }
+    fs::write(&path, text).map_err(|error| format!("Could not write {}: {error}", path.display()))
+}
+
+/// Shows a desktop notification through the platform's own tool
+/// (`notify-send`, `osascript` or PowerShell).
+pub fn notify_desktop(title: &str, body: &str) -> Result<(), String> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            apple_script_string(body),
+            apple_script_string(title)
+        );
+        let mut command = Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(windows) {
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+             $icon = New-Object System.Windows.Forms.NotifyIcon; \
+             $icon.Icon = [System.Drawing.SystemIcons]::Warning; $icon.Visible = $true; \
+             $icon.ShowBalloonTip(10000, '{}', '{}', 'Warning'); Start-Sleep -Seconds 10; \
+             $icon.Dispose()",
+            title.replace('\'', "''"),
+            body.replace('\'', "''")
+        );
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-WindowStyle", "Hidden", "-Command", &script]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args([title, body]);
+        command
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+        .map_err(|error| format!("Could not show a desktop notification: {error}"))
+}
+
+fn apple_script_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~').zip(home_dir()) {
+        Some((rest, home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn parses_rules_and_reports_folders_over_their_limit() {
+        let (rules, errors) = parse_thresholds(
+            "# limits\n/r/backups > 1.5 KB\n/r/logs > 1 KiB\n/r/missing > 1 B\nbad line\n/r > lots\n",
+        );
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            rules.iter().map(|rule| rule.limit).collect::<Vec<_>>(),
+            vec![1536, 1024, 1]
+        );
+
+        let mut root = Node::new("r".to_string(), PathBuf::from("/r"), 0);
+        root.insert_relative(Path::new("backups/a.tar"), 2000);
+        root.insert_relative(Path::new("logs/a.log"), 1000);
+        root.compute_total_size();
+
+        assert_eq!(
+            check_thresholds(&rules, &root),
+            vec![ThresholdBreach {
+                path: PathBuf::from("/r/backups"),
+                limit: 1536,
+                size: 2000,
+            }]
+        );
+    }
+}
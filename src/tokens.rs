@@ -0,0 +1,185 @@
+//! Optional LLM token estimate for text files, to see which parts of a
+//! workspace would not fit an agent's context budget.
+//!
+//! No real tokenizer is bundled; counts are approximations chosen in the UI.
+//! Binary files (a NUL byte in their first block) count as zero tokens, and
+//! large text files are sampled from the start and extrapolated by size.
+
+use crate::analysis::collect_files;
+use crate::gateway;
+use crate::model::Node;
+use crate::treemap::SizeMetric;
+use crate::wake::{self, Waker};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+/// Bytes read per file; longer files are extrapolated from this sample.
+const SAMPLE_BYTES: u64 = 1024 * 1024;
+/// Bytes checked for a NUL to tell binary files apart.
+const BINARY_CHECK_BYTES: usize = 8 * 1024;
+
+pub const DEFAULT_CONTEXT_BUDGET: u64 = 200_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenApproximation {
+    /// One token per this many characters (about 4 for English prose and
+    /// code with BPE tokenizers).
+    Characters(f32),
+    /// Four tokens per three words, plus one per punctuation character;
+    /// closer for symbol-heavy code.
+    Words,
+}
+
+impl Default for TokenApproximation {
+    fn default() -> Self {
+        Self::Characters(4.0)
+    }
+}
+
+pub fn estimate_tokens(text: &str, approximation: TokenApproximation) -> u64 {
+    match approximation {
+        TokenApproximation::Characters(per_token) => {
+            (text.chars().count() as f64 / per_token.max(0.5) as f64).ceil() as u64
+        }
+        TokenApproximation::Words => {
+            let mut words = 0_u64;
+            let mut punctuation = 0_u64;
+            let mut in_word = false;
+            for ch in text.chars() {
+                if ch.is_alphanumeric() || ch == '_' {
+                    if !in_word {
+                        words += 1;
+                    }
+                    in_word = true;
+                } else {
+                    in_word = false;
+                    if !ch.is_whitespace() {
+                        punctuation += 1;
+                    }
+                }
+            }
+            (words * 4).div_ceil(3) + punctuation
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TokenReport {
+    /// The scan generation the estimated tree belonged to.
+    pub scan_generation: u64,
+    pub approximation: TokenApproximation,
+    /// Estimated tokens per text file.
+    pub files: HashMap<PathBuf, u64>,
+    /// Estimated tokens per folder, including everything below it.
+    pub folders: HashMap<PathBuf, u64>,
+    pub binary_files: u64,
+}
+
+impl TokenReport {
+    /// Estimated tokens of a file or folder, if it holds any text.
+    pub fn tokens(&self, path: &Path) -> Option<u64> {
+        self.files
+            .get(path)
+            .or_else(|| self.folders.get(path))
+            .copied()
+    }
+}
+
+impl SizeMetric for TokenReport {
+    fn file_weight(&self, file: &Node, _depth: usize) -> u64 {
+        self.files.get(&file.path).copied().unwrap_or(0)
+    }
+}
+
+/// Estimates every file of `root` on a background thread.
+pub fn spawn_token_count(
+    root: &Node,
+    approximation: TokenApproximation,
+    scan_generation: u64,
+    waker: Waker,
+) -> Receiver<TokenReport> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    let files: Vec<(PathBuf, u64)> = files
+        .into_iter()
+        .filter(|node| !std::ptr::eq(*node, root) && node.size > 0)
+        .map(|node| (node.path.clone(), node.size))
+        .collect();
+    let root_path = root.path.clone();
+    let (tx, rx) = wake::channel(waker);
+
+    thread::spawn(move || {
+        let estimates: Vec<(PathBuf, Option<u64>)> = files
+            .into_par_iter()
+            .filter_map(|(path, size)| {
+                let tokens = estimate_file(&path, size, approximation)?;
+                Some((path, tokens))
+            })
+            .collect();
+
+        let mut report = TokenReport {
+            scan_generation,
+            approximation,
+            files: HashMap::new(),
+            folders: HashMap::new(),
+            binary_files: 0,
+        };
+        for (path, tokens) in estimates {
+            let Some(tokens) = tokens else {
+                report.binary_files += 1;
+                continue;
+            };
+            for folder in path.ancestors().skip(1) {
+                *report.folders.entry(folder.to_path_buf()).or_default() += tokens;
+                if folder == root_path {
+                    break;
+                }
+            }
+            report.files.insert(path, tokens);
+        }
+        let _ = tx.send(report);
+    });
+
+    rx
+}
+
+/// Estimated tokens of a text file, `Some(None)` for a binary file and
+/// `None` when it cannot be read.
+fn estimate_file(path: &Path, size: u64, approximation: TokenApproximation) -> Option<Option<u64>> {
+    let mut buffer = Vec::new();
+    gateway::open(path)
+        .ok()?
+        .take(SAMPLE_BYTES)
+        .read_to_end(&mut buffer)
+        .ok()?;
+    if buffer.is_empty() {
+        return None;
+    }
+    if buffer[..buffer.len().min(BINARY_CHECK_BYTES)].contains(&0) {
+        return Some(None);
+    }
+    let tokens = estimate_tokens(&String::from_utf8_lossy(&buffer), approximation);
+    let scale = size.max(buffer.len() as u64) as f64 / buffer.len() as f64;
+    Some(Some((tokens as f64 * scale).round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approximations_count_characters_or_words() {
+        let text = "fn main() { println!(\"hi\"); }";
+        assert_eq!(
+            estimate_tokens(text, TokenApproximation::Characters(4.0)),
+            8
+        );
+        // Four words make 6 tokens, plus 10 punctuation characters.
+        assert_eq!(estimate_tokens(text, TokenApproximation::Words), 16);
+        assert_eq!(estimate_tokens("", TokenApproximation::Words), 0);
+    }
+}
@@ -1,4 +1,81 @@
 use crate::model::Node;
+use rayon::prelude::*;
+use std::cmp::Reverse;
+
+/// Node budgets below this are laid out on the calling thread; spawning
+/// subtree jobs only pays off for large layouts.
+const PARALLEL_LAYOUT_MIN_NODES: usize = 10_000;
+
+/// A per-file quantity cells can be sized by; a folder weighs the sum of
+/// its files.
+pub trait SizeMetric: Sync {
+    /// Weight of `file`, an entry without children, `depth` levels below
+    /// the laid-out root.
+    fn file_weight(&self, file: &Node, depth: usize) -> u64;
+}
+
+/// File sizes in bytes, the default.
+pub struct Bytes;
+
+impl SizeMetric for Bytes {
+    fn file_weight(&self, file: &Node, _depth: usize) -> u64 {
+        file.size
+    }
+}
+
+/// Disk space files take as recorded by the scan, so many small files weigh
+/// more than their contents and sparse or compressed files less; files
+/// without a recorded allocation weigh their size.
+pub struct AllocatedBytes;
+
+impl SizeMetric for AllocatedBytes {
+    fn file_weight(&self, file: &Node, _depth: usize) -> u64 {
+        file.allocated.unwrap_or(file.size)
+    }
+}
+
+/// One per file; empty folders weigh nothing.
+pub struct FileCount;
+
+impl SizeMetric for FileCount {
+    fn file_weight(&self, file: &Node, _depth: usize) -> u64 {
+        u64::from(!file.is_dir)
+    }
+}
+
+/// The weight of every entry in a tree under one metric, with `children` in
+/// the same order as the tree's.
+#[derive(Debug, Clone, Default)]
+pub struct Weights {
+    pub total: u64,
+    pub children: Vec<Weights>,
+}
+
+impl Weights {
+    pub fn of(root: &Node, metric: &dyn SizeMetric) -> Self {
+        Self::at_depth(root, metric, 0)
+    }
+
+    fn at_depth(node: &Node, metric: &dyn SizeMetric, depth: usize) -> Self {
+        if node.children.is_empty() {
+            return Self {
+                total: metric.file_weight(node, depth),
+                children: Vec::new(),
+            };
+        }
+        let children: Vec<Weights> = node
+            .children
+            .iter()
+            .map(|child| Self::at_depth(child, metric, depth + 1))
+            .collect();
+        Self {
+            total: children
+                .iter()
+                .fold(0_u64, |sum, child| sum.saturating_add(child.total)),
+            children,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct LayoutRect {
@@ -42,11 +119,15 @@ pub struct TreemapCell<'a> {
 #[derive(Debug, Clone, Copy)]
 struct RowItem<'a> {
     node: &'a Node,
+    weights: &'a Weights,
     area: f32,
 }
 
+/// Lays out `root` with cell areas proportional to `weights`, which must
+/// have been computed for `root`.
 pub fn squarified_treemap<'a>(
     root: &'a Node,
+    weights: &'a Weights,
     bounds: LayoutRect,
     max_depth: usize,
     max_nodes: usize,
@@ -57,12 +138,79 @@ pub fn squarified_treemap<'a>(
         return cells;
     }
 
-    layout_recursive(root, bounds, 0, max_depth, max_nodes, &mut cells);
+    if max_nodes < PARALLEL_LAYOUT_MIN_NODES {
+        layout_recursive(root, weights, bounds, 0, max_depth, max_nodes, &mut cells);
+    } else {
+        layout_parallel(root, weights, bounds, max_depth, max_nodes, &mut cells);
+    }
     cells
 }
 
+/// Lays out the first level, then the child subtrees in parallel waves.
+///
+/// Each subtree is laid out with the whole remaining budget and the results
+/// are appended in child order and truncated, which yields exactly the cells
+/// of the sequential depth-first layout. Waves of one subtree per thread keep
+/// the work discarded by truncation bounded.
+fn layout_parallel<'a>(
+    root: &'a Node,
+    weights: &'a Weights,
+    bounds: LayoutRect,
+    max_depth: usize,
+    max_nodes: usize,
+    out: &mut Vec<TreemapCell<'a>>,
+) {
+    if max_nodes == 0 || bounds.w <= 0.2 || bounds.h <= 0.2 {
+        return;
+    }
+
+    out.push(TreemapCell {
+        node: root,
+        rect: bounds,
+        depth: 0,
+    });
+
+    if max_depth == 0 {
+        return;
+    }
+
+    let children = child_rects(root, weights, bounds);
+    for wave in children.chunks(rayon::current_num_threads().max(1)) {
+        let remaining = max_nodes.saturating_sub(out.len());
+        if remaining == 0 {
+            break;
+        }
+
+        let subtrees: Vec<Vec<TreemapCell<'a>>> = wave
+            .par_iter()
+            .map(|&(child, child_weights, rect)| {
+                let mut subtree = Vec::new();
+                layout_recursive(
+                    child,
+                    child_weights,
+                    rect,
+                    1,
+                    max_depth,
+                    remaining,
+                    &mut subtree,
+                );
+                subtree
+            })
+            .collect();
+
+        for subtree in subtrees {
+            let room = max_nodes.saturating_sub(out.len());
+            out.extend(subtree.into_iter().take(room));
+            if out.len() >= max_nodes {
+                return;
+            }
+        }
+    }
+}
+
 fn layout_recursive<'a>(
     node: &'a Node,
+    weights: &'a Weights,
     bounds: LayoutRect,
     depth: usize,
     max_depth: usize,
@@ -79,48 +227,75 @@ fn layout_recursive<'a>(
         depth,
     });
 
-    if depth >= max_depth || node.children.is_empty() {
+    if depth >= max_depth {
         return;
     }
 
+    for (child, child_weights, rect) in child_rects(node, weights, bounds) {
+        layout_recursive(
+            child,
+            child_weights,
+            rect,
+            depth + 1,
+            max_depth,
+            max_nodes,
+            out,
+        );
+        if out.len() >= max_nodes {
+            break;
+        }
+    }
+}
+
+/// Squarified rects for the children of `node` with a non-zero weight
+/// inside `bounds`.
+fn child_rects<'a>(
+    node: &'a Node,
+    weights: &'a Weights,
+    bounds: LayoutRect,
+) -> Vec<(&'a Node, &'a Weights, LayoutRect)> {
+    if node.children.is_empty() {
+        return Vec::new();
+    }
+
     let inner_bounds = bounds.shrink(1.0);
     if inner_bounds.w <= 0.2 || inner_bounds.h <= 0.2 {
-        return;
+        return Vec::new();
     }
 
-    let mut children: Vec<&Node> = node
+    let mut children: Vec<(&Node, &Weights)> = node
         .children
         .iter()
-        .filter(|child| child.size > 0)
+        .zip(&weights.children)
+        .filter(|(_, weights)| weights.total > 0)
         .collect();
     if children.is_empty() {
-        return;
+        return Vec::new();
     }
 
-    children.sort_by(|a, b| b.size.cmp(&a.size));
+    children.sort_by_key(|(_, weights)| Reverse(weights.total));
 
-    let total_size: u64 = children
+    let total_weight: u64 = children
         .iter()
-        .fold(0_u64, |sum, node| sum.saturating_add(node.size));
-    if total_size == 0 {
-        return;
+        .fold(0_u64, |sum, (_, weights)| sum.saturating_add(weights.total));
+    if total_weight == 0 {
+        return Vec::new();
     }
 
     let total_area = inner_bounds.area();
     let items: Vec<RowItem<'_>> = children
         .iter()
-        .map(|node| RowItem {
+        .map(|&(node, weights)| RowItem {
             node,
-            area: total_area * (node.size as f32 / total_size as f32),
+            weights,
+            area: total_area * (weights.total as f32 / total_weight as f32),
         })
         .collect();
 
-    for (item, rect) in squarify_items(&items, inner_bounds) {
-        layout_recursive(item.node, rect, depth + 1, max_depth, max_nodes, out);
-        if out.len() >= max_nodes {
-            break;
-        }
-    }
+    squarify_items(&items, inner_bounds)
+        .into_iter()
+        .map(|(item, rect)| (item.node, item.weights, rect))
+        .collect()
 }
 
 fn squarify_items<'a>(items: &[RowItem<'a>], bounds: LayoutRect) -> Vec<(RowItem<'a>, LayoutRect)> {
@@ -251,7 +426,7 @@ fn worst_ratio(row: &[RowItem<'_>], side: f32) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     fn build_root_with_sizes(sizes: &[u64]) -> Node {
         let mut root = Node::new("root".to_string(), PathBuf::from("root"), 0);
@@ -271,7 +446,8 @@ mod tests {
     fn wide_canvas_splits_across_x_axis() {
         let root = build_root_with_sizes(&[500, 250, 125, 64, 32, 16, 8, 4]);
         let bounds = LayoutRect::new(0.0, 0.0, 1200.0, 600.0);
-        let cells = squarified_treemap(&root, bounds, 1, 1024);
+        let weights = Weights::of(&root, &Bytes);
+        let cells = squarified_treemap(&root, &weights, bounds, 1, 1024);
 
         let depth1_cells: Vec<_> = cells.into_iter().filter(|cell| cell.depth == 1).collect();
         assert!(
@@ -292,4 +468,85 @@ mod tests {
             "layout should split along x-axis on a wide canvas"
         );
     }
+
+    #[test]
+    fn parallel_layout_matches_sequential_layout() {
+        let mut root = Node::new("root".to_string(), PathBuf::from("root"), 0);
+        for dir in 0..24 {
+            let dir_path = PathBuf::from(format!("dir_{dir}"));
+            for file in 0..(400 + dir * 37) {
+                root.insert_relative(
+                    &dir_path.join(format!("file_{file}")),
+                    (file * 13 + dir) as u64 + 1,
+                );
+            }
+        }
+        root.compute_total_size();
+        root.sort_children_by_size_desc();
+        let bounds = LayoutRect::new(0.0, 0.0, 4000.0, 3000.0);
+        let weights = Weights::of(&root, &Bytes);
+
+        for max_nodes in [PARALLEL_LAYOUT_MIN_NODES, 12_345] {
+            let parallel = squarified_treemap(&root, &weights, bounds, 8, max_nodes);
+            let mut sequential = Vec::new();
+            layout_recursive(&root, &weights, bounds, 0, 8, max_nodes, &mut sequential);
+
+            assert_eq!(parallel.len(), sequential.len());
+            for (a, b) in parallel.iter().zip(&sequential) {
+                assert_eq!(a.node.path, b.node.path);
+                assert_eq!(a.depth, b.depth);
+                assert_eq!(
+                    (a.rect.x, a.rect.y, a.rect.w, a.rect.h),
+                    (b.rect.x, b.rect.y, b.rect.w, b.rect.h)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn metrics_weigh_folders_by_their_files() {
+        let mut root = Node::new("root".to_string(), PathBuf::from("root"), 0);
+        // A sparse video with a single block on disk.
+        root.insert_relative(Path::new("big/video.mkv"), 1_000_000)
+            .unwrap()
+            .allocated = Some(4096);
+        for index in 0..9 {
+            let file = root
+                .insert_relative(&Path::new("small").join(format!("{index}.txt")), 10)
+                .unwrap();
+            // The last one was imported without an allocation.
+            if index < 8 {
+                file.allocated = Some(4096);
+            }
+        }
+        root.insert_relative(Path::new("empty"), 0).unwrap().is_dir = true;
+        root.compute_total_size();
+        root.sort_children_by_size_desc();
+
+        let bytes = Weights::of(&root, &Bytes);
+        assert_eq!(bytes.total, 1_000_090);
+        assert_eq!(
+            Weights::of(&root, &AllocatedBytes).total,
+            4096 + 8 * 4096 + 10
+        );
+
+        let files = Weights::of(&root, &FileCount);
+        assert_eq!(files.total, 10);
+        assert_eq!(
+            files
+                .children
+                .iter()
+                .map(|child| child.total)
+                .collect::<Vec<_>>(),
+            vec![1, 9, 0]
+        );
+
+        let bounds = LayoutRect::new(0.0, 0.0, 100.0, 100.0);
+        let cells = squarified_treemap(&root, &files, bounds, 1, 16);
+        let area = |name: &str| {
+            let cell = cells.iter().find(|cell| cell.node.name == name).unwrap();
+            cell.rect.w * cell.rect.h
+        };
+        assert!(area("small") > area("big") * 8.0);
+    }
 }
@@ -0,0 +1,187 @@
+//! Opt-in update check: fetches the latest release from a configurable URL
+//! in the background and reports it when it is newer than this build.
+//!
+//! The response is GitHub's release JSON (`/releases/latest` or a list from
+//! `/releases`, where drafts and prereleases are skipped). Other servers can
+//! serve `{"version": ..., "notes": ..., "url": ...}` instead. Settings live
+//! in `update.json` in the config directory; nothing is sent besides the
+//! request itself.
+
+use crate::portable::config_dir;
+use crate::wake::{self, Waker};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_RELEASES_URL: &str =
+    "https://api.github.com/repos/DD-Ching/TreeMapBase/releases/latest";
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateSettings {
+    /// Check once at startup.
+    pub enabled: bool,
+    pub url: String,
+    /// A version whose notice was dismissed; newer ones still show.
+    pub dismissed: Option<String>,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: DEFAULT_RELEASES_URL.to_string(),
+            dismissed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub notes: String,
+    /// Release page, if the server gave one.
+    pub url: Option<String>,
+}
+
+fn settings_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("update.json"))
+}
+
+pub fn load_settings() -> UpdateSettings {
+    settings_path()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_settings(settings: &UpdateSettings) -> Result<(), String> {
+    let path = settings_path().ok_or("No config directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Could not create {}: {error}", parent.display()))?;
+    }
+    let json = serde_json::to_vec_pretty(settings).map_err(|error| error.to_string())?;
+    fs::write(&path, json).map_err(|error| format!("Could not write {}: {error}", path.display()))
+}
+
+/// Numeric components of a version like `v1.2.3-beta`; missing ones are 0.
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    let (left, right) = (version_parts(left), version_parts(right));
+    for index in 0..left.len().max(right.len()) {
+        let order = left
+            .get(index)
+            .unwrap_or(&0)
+            .cmp(right.get(index).unwrap_or(&0));
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    Ordering::Equal
+}
+
+/// The release described by `json`, if it is newer than `current`.
+pub fn parse_release(json: &str, current: &str) -> Result<Option<UpdateInfo>, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|error| format!("Invalid release JSON: {error}"))?;
+    let release = match &value {
+        Value::Array(releases) => releases.iter().find(|release| {
+            !release["draft"].as_bool().unwrap_or(false)
+                && !release["prerelease"].as_bool().unwrap_or(false)
+        }),
+        Value::Object(_) => Some(&value),
+        _ => None,
+    };
+    let Some(release) = release else {
+        return Ok(None);
+    };
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| release[*key].as_str())
+            .map(str::to_string)
+    };
+    let version = text(&["tag_name", "version"]).ok_or("Release has no version")?;
+    if compare_versions(&version, current) != Ordering::Greater {
+        return Ok(None);
+    }
+    Ok(Some(UpdateInfo {
+        version,
+        notes: text(&["body", "notes"]).unwrap_or_default(),
+        url: text(&["html_url", "url"]),
+    }))
+}
+
+/// Fetches `url` on a background thread.
+pub fn spawn_update_check(
+    url: String,
+    waker: Waker,
+) -> Receiver<Result<Option<UpdateInfo>, String>> {
+    let (tx, rx) = wake::channel(waker);
+    thread::spawn(move || {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        let result = match agent
+            .get(&url)
+            .set("User-Agent", &format!("tree-map-base/{CURRENT_VERSION}"))
+            .set("Accept", "application/vnd.github+json, application/json")
+            .call()
+        {
+            Ok(response) => response
+                .into_string()
+                .map_err(|error| error.to_string())
+                .and_then(|body| parse_release(&body, CURRENT_VERSION)),
+            Err(ureq::Error::Status(code, _)) => Err(format!("HTTP status {code}")),
+            Err(error) => Err(error.to_string()),
+        };
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_only_newer_releases() {
+        assert_eq!(compare_versions("v0.10.0", "0.9.3"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.0-rc1", "1.2.1"), Ordering::Less);
+
+        let latest =
+            r#"{"tag_name": "v0.2.0", "body": "Fixes", "html_url": "https://example.com/r"}"#;
+        assert_eq!(
+            parse_release(latest, "0.1.0").unwrap(),
+            Some(UpdateInfo {
+                version: "v0.2.0".to_string(),
+                notes: "Fixes".to_string(),
+                url: Some("https://example.com/r".to_string()),
+            })
+        );
+        assert_eq!(parse_release(latest, "0.2.0").unwrap(), None);
+
+        let list = r#"[{"tag_name": "v0.3.0", "prerelease": true}, {"version": "0.2.1"}]"#;
+        let update = parse_release(list, "0.1.0").unwrap().unwrap();
+        assert_eq!((update.version.as_str(), update.url), ("0.2.1", None));
+        assert!(parse_release("{}", "0.1.0").is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, SendError, Sender};
+use std::sync::Arc;
+
+/// Lets a background thread wake the UI after it queued something, so the UI
+/// does not have to poll its channels on a timer.
+#[derive(Clone, Default)]
+pub struct Waker(Option<Arc<dyn Fn() + Send + Sync>>);
+
+impl Waker {
+    pub fn new(wake: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(wake)))
+    }
+
+    pub fn wake(&self) {
+        if let Some(wake) = &self.0 {
+            wake();
+        }
+    }
+}
+
+impl fmt::Debug for Waker {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_tuple("Waker")
+            .field(&self.0.is_some())
+            .finish()
+    }
+}
+
+/// A channel sender that wakes the receiving side on every message and when
+/// it is dropped, so disconnection is noticed without polling too.
+#[derive(Debug)]
+pub struct WakingSender<T> {
+    sender: Sender<T>,
+    waker: Waker,
+}
+
+impl<T> WakingSender<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.sender.send(value)?;
+        self.waker.wake();
+        Ok(())
+    }
+
+    pub fn waker(&self) -> &Waker {
+        &self.waker
+    }
+}
+
+impl<T> Clone for WakingSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+}
+
+impl<T> Drop for WakingSender<T> {
+    fn drop(&mut self) {
+        self.waker.wake();
+    }
+}
+
+pub fn channel<T>(waker: Waker) -> (WakingSender<T>, Receiver<T>) {
+    let (sender, receiver) = mpsc::channel();
+    (WakingSender { sender, waker }, receiver)
+}
@@ -0,0 +1,124 @@
+use crate::action::{ActionEvent, ActionKind};
+use crate::wake::{self, Waker};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+
+/// Agent id attached to events produced by the filesystem watcher.
+pub const FS_WATCH_AGENT_ID: &str = "filesystem";
+
+#[derive(Debug)]
+pub enum WatchMessage {
+    Change(ActionEvent),
+    Error(String),
+}
+
+/// Observes a directory tree and reports changes as [`ActionEvent`]s.
+///
+/// Watching only subscribes to OS change notifications; it never touches the
+/// files themselves.
+pub struct FsWatcher {
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<WatchMessage>,
+}
+
+impl FsWatcher {
+    pub fn start(root_path: &Path, waker: Waker) -> Result<Self, String> {
+        let (tx, rx) = wake::channel::<WatchMessage>(waker);
+
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+                Ok(event) => {
+                    let Some(kind) = action_kind_for(&event.kind) else {
+                        return;
+                    };
+                    for path in event.paths {
+                        let mut action = ActionEvent::new(kind, path);
+                        action.agent_id = FS_WATCH_AGENT_ID.to_string();
+                        let _ = tx.send(WatchMessage::Change(action));
+                    }
+                }
+                Err(error) => {
+                    let _ = tx.send(WatchMessage::Error(error.to_string()));
+                }
+            })
+            .map_err(|error| format!("Could not create filesystem watcher: {error}"))?;
+
+        watcher
+            .watch(root_path, RecursiveMode::Recursive)
+            .map_err(|error| format!("Could not watch {}: {error}", root_path.display()))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver: rx,
+        })
+    }
+
+    pub fn receiver(&self) -> &Receiver<WatchMessage> {
+        &self.receiver
+    }
+}
+
+/// Watches one file by subscribing to its folder, so editors that save by
+/// replacing the file are noticed too, and wakes the UI on every change.
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<()>,
+}
+
+impl FileWatcher {
+    pub fn start(path: &Path, waker: Waker) -> Result<Self, String> {
+        let folder = path
+            .parent()
+            .ok_or_else(|| format!("Could not watch {}: no parent folder", path.display()))?;
+        let (tx, rx) = wake::channel::<()>(waker);
+        // Compared by name: the folder is watched on its own, and reported
+        // paths may be spelled differently (symlinked or canonical folders).
+        let name = path.file_name().map(|name| name.to_os_string());
+
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+                Ok(event)
+                    if event
+                        .paths
+                        .iter()
+                        .any(|changed| changed.file_name() == name.as_deref()) =>
+                {
+                    let _ = tx.send(());
+                }
+                Ok(_) => {}
+                // A missed event is better reported as a change than lost.
+                Err(_) => {
+                    let _ = tx.send(());
+                }
+            })
+            .map_err(|error| format!("Could not create file watcher: {error}"))?;
+
+        watcher
+            .watch(folder, RecursiveMode::NonRecursive)
+            .map_err(|error| format!("Could not watch {}: {error}", folder.display()))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver: rx,
+        })
+    }
+
+    /// Whether the file changed since the last call.
+    pub fn changed(&self) -> bool {
+        self.receiver.try_iter().count() > 0
+    }
+}
+
+fn action_kind_for(kind: &EventKind) -> Option<ActionKind> {
+    match kind {
+        EventKind::Create(_) => Some(ActionKind::Create),
+        EventKind::Remove(_) => Some(ActionKind::Delete),
+        EventKind::Modify(ModifyKind::Name(_)) => Some(ActionKind::Rename),
+        // Metadata-only changes (atime, permissions) are too noisy to draw.
+        EventKind::Modify(ModifyKind::Metadata(_)) => None,
+        EventKind::Modify(_) => Some(ActionKind::Write),
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => None,
+    }
+}